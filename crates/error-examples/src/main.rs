@@ -30,7 +30,8 @@ fn error_to_snippet<'s>(
 
     let level = match error.kind() {
         toml_spanner::ErrorKind::UnexpectedKey { .. }
-        | toml_spanner::ErrorKind::Deprecated { .. } => Level::WARNING,
+        | toml_spanner::ErrorKind::Deprecated { .. }
+        | toml_spanner::ErrorKind::DeprecatedVariant { .. } => Level::WARNING,
         _ => Level::ERROR,
     };
 
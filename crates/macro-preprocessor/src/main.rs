@@ -285,6 +285,10 @@ const REPLACEMENTS: &[(&str, &str)] = &[
     ),
 ];
 
+/// Marks a statement id that does not fit in one byte of `BLIT_SRC`, the id
+/// being `ESCAPE` plus the byte that follows.
+const ESCAPE: u8 = u8::MAX;
+
 fn export_merged_blocks(files: &[&[u8]]) -> (Vec<u8>, Vec<Vec<u8>>) {
     let merges: Vec<_> = files.iter().map(|data| TTMergeBlocks::new(data)).collect();
     let mut distinct_stmts: HashMap<(Kind, &str), u64> = HashMap::new();
@@ -297,12 +301,20 @@ fn export_merged_blocks(files: &[&[u8]]) -> (Vec<u8>, Vec<Vec<u8>>) {
             *distinct_stmts.entry((stmt.kind, stmt.literal)).or_insert(0) += 1;
         }
     }
+    // Ids from `ESCAPE` up are written as `ESCAPE` followed by a second byte.
+    assert!((distinct_stmts.len()) < 2 * ESCAPE as usize);
     // We sort these to ensure reproduceable results.
-    // assert!((distinct_stmts.len() + (b' ' as usize)) < u8::MAX as usize);
-    assert!((distinct_stmts.len()) < u8::MAX as usize);
     let mut distinct_stmts: Vec<(u64, Kind, &str)> = distinct_stmts
         .into_iter()
-        .map(|((kind, text), count)| (count | ((kind as u64) << 49), kind, text))
+        .map(|((kind, text), count)| {
+            // Common idents come first, leaving the escaped ids to the rarest ones.
+            let rank = if kind == Kind::PushIdent {
+                u32::MAX as u64 - count
+            } else {
+                count
+            };
+            (rank | ((kind as u64) << 49), kind, text)
+        })
         .collect();
     // Try to map most common punctionuation to `\t`, `\n` & `\r`
     {
@@ -331,10 +343,10 @@ fn export_merged_blocks(files: &[&[u8]]) -> (Vec<u8>, Vec<Vec<u8>>) {
         }
     }
     distinct_stmts.sort_unstable();
-    let stmt_id: HashMap<(Kind, &str), u8> = distinct_stmts
+    let stmt_id: HashMap<(Kind, &str), u16> = distinct_stmts
         .iter()
         .enumerate()
-        .map(|(i, (_, kind, text))| ((*kind, *text), (i as u8)))
+        .map(|(i, (_, kind, text))| ((*kind, *text), (i as u16)))
         .collect();
     let mut max_ident = 0usize;
     let mut max_punct = 0usize;
@@ -354,10 +366,11 @@ fn export_merged_blocks(files: &[&[u8]]) -> (Vec<u8>, Vec<Vec<u8>>) {
     // we implement greedy substring compression
     // at the time of implementation brings down the size from 1400 -> 1036
     let mut stmt_slice_buffer: Vec<u8> = Vec::new();
+    // Whether each byte of `stmt_slice_buffer` starts an id, so a match never
+    // begins on the second byte of an escaped one.
+    let mut stmt_starts: Vec<bool> = Vec::new();
 
     let mut current_slice = Vec::<u8>::new();
-    // todo consider remapping the space so that &\x00 never happen and
-    // that the majority of text gets mapped to asscie characters
     let mut g_count = 0;
     let mut og_count = 0;
     let mut outputs: Vec<Vec<u8>> = Vec::new();
@@ -370,22 +383,26 @@ fn export_merged_blocks(files: &[&[u8]]) -> (Vec<u8>, Vec<Vec<u8>>) {
             current_slice.clear();
             for (stmt, _) in group {
                 let id = stmt_id[&(stmt.kind, stmt.literal)];
-                current_slice.push(id);
+                if id < ESCAPE as u16 {
+                    current_slice.push(id as u8);
+                } else {
+                    current_slice.extend_from_slice(&[ESCAPE, (id - ESCAPE as u16) as u8]);
+                }
             }
             output.extend_from_slice(&data[written..group[0].1.start]);
             written = group.last().unwrap().1.end;
             if let [(stmt, _)] = group {
                 match stmt.kind {
                     Kind::PushPunctAlone | Kind::PushPunctJoint => {
-                        write!(output, "{}.blit_punct({});", stmt.buffer, current_slice[0])
-                            .unwrap();
+                        let id = stmt_id[&(stmt.kind, stmt.literal)];
+                        write!(output, "{}.blit_punct({});", stmt.buffer, id).unwrap();
                     }
                     Kind::PushIdent => {
                         write!(
                             output,
                             "{}.blit_ident({});",
                             stmt.buffer,
-                            current_slice[0] - puncts.len() as u8
+                            stmt_id[&(stmt.kind, stmt.literal)] - puncts.len() as u16
                         )
                         .unwrap();
                     }
@@ -394,14 +411,21 @@ fn export_merged_blocks(files: &[&[u8]]) -> (Vec<u8>, Vec<Vec<u8>>) {
                     }
                 }
             } else if group.len() > 0 {
-                let start =
-                    if let Some(start) = memchr::memmem::find(&stmt_slice_buffer, &current_slice) {
-                        start
-                    } else {
-                        let start = stmt_slice_buffer.len();
-                        stmt_slice_buffer.extend_from_slice(&current_slice);
-                        start
-                    };
+                let start = if let Some(start) =
+                    memchr::memmem::find_iter(&stmt_slice_buffer, &current_slice)
+                        .find(|start| stmt_starts[*start])
+                {
+                    start
+                } else {
+                    let start = stmt_slice_buffer.len();
+                    stmt_slice_buffer.extend_from_slice(&current_slice);
+                    let mut escaped = false;
+                    for byte in &current_slice {
+                        stmt_starts.push(!escaped);
+                        escaped = !escaped && *byte == ESCAPE;
+                    }
+                    start
+                };
                 write!(
                     output,
                     "{}.blit({}, {});",
@@ -26,6 +26,7 @@ invalid_de!(
     Boop,
     "s = 'val'\nthis-field-is-not-known = 20"
 );
+invalid_de!(misspelled_field, Boop, "s = 'val'\nOs = 20");
 
 #[derive(Debug)]
 struct Package {
@@ -292,6 +293,30 @@ invalid_de!(
     "name = 'a'\nserver_name = 'b'"
 );
 
+// --- ConflictingFields / MissingRequiredBy: field relations ---
+
+#[derive(Toml, Debug)]
+#[toml(FromToml)]
+struct DependencySource {
+    #[toml(conflicts_with = path)]
+    git: Option<String>,
+    #[toml(requires = git)]
+    branch: Option<String>,
+    path: Option<String>,
+}
+
+invalid_de!(
+    conflicting_fields,
+    DependencySource,
+    "path = 'vendor/a'\ngit = 'https://example.com/a'"
+);
+
+invalid_de!(
+    missing_required_by,
+    DependencySource,
+    "path = 'vendor/a'\nbranch = 'main'"
+);
+
 // --- Deprecated field ---
 
 #[derive(Toml, Debug)]
@@ -327,6 +352,7 @@ impl<'de> FromToml<'de> for Palette {
 }
 
 invalid_de!(unexpected_variant, Palette, "color = 'Purple'");
+invalid_de!(misspelled_variant, Palette, "color = 'Gren'");
 
 // --- Custom error: wrong array size ---
 
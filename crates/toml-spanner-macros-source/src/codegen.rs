@@ -1,7 +1,8 @@
 use crate::ast::{
-    self, DefaultKind, DeriveTargetInner, DeriveTargetKind, EnumKind, EnumVariant, Field,
-    FieldAttrs, Generic, GenericKind, UnknownFieldPolicy, ENUM_CONTAINS_STRUCT_VARIANT,
-    ENUM_CONTAINS_TUPLE_VARIANT, ENUM_CONTAINS_UNIT_VARIANT, FROM_TOML, TO_TOML,
+    self, Constraint, DefaultKind, DeriveTargetInner, DeriveTargetKind, EnumKind, EnumVariant,
+    Field, FieldAttrs, Generic, GenericKind, KeyMatch, SpanSource, UnitRepr, UnknownFieldPolicy,
+    ENUM_CONTAINS_STRUCT_VARIANT, ENUM_CONTAINS_TUPLE_VARIANT, ENUM_CONTAINS_UNIT_VARIANT,
+    FROM_TOML, TO_TOML,
 };
 use crate::case::RenameRule;
 use crate::util::MemoryPool;
@@ -98,6 +99,7 @@ macro_rules! append_tok {
     (-> $d:tt) => {$d.tt_punct_joint('-'); $d.tt_punct_alone('>') };
     (=> $d:tt) => {$d.tt_punct_joint('='); $d.tt_punct_alone('>') };
     (>= $d:tt) => {$d.tt_punct_joint('>'); $d.tt_punct_alone('=') };
+    (.. $d:tt) => {$d.tt_punct_joint('.'); $d.tt_punct_alone('.') };
     (> $d:tt) => { $d.tt_punct_alone('>') };
     (! $d:tt) => { $d.tt_punct_alone('!') };
     (| $d:tt) => { $d.tt_punct_alone('|') };
@@ -221,8 +223,32 @@ fn field_name_lit(ctx: &Ctx, field: &Field, variant: Option<&EnumVariant>) -> Li
     }
 }
 
-fn impl_from_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
+/// Wraps a `from_toml` body so the container `validate` function, if any, runs
+/// on the constructed value of type `ty`.
+fn wrap_container_validate(
+    output: &mut RustWriter,
+    ctx: &Ctx,
+    ty: &[TokenTree],
+    inner: TokenStream,
+) -> TokenStream {
+    if let Some(validate) = &ctx.target.validate {
+        token_stream! {
+            output;
+            ~[[allow(clippy::redundant_closure_call)]]
+            let __result = (|| -> ::std::result::Result<[~ty], [#ctx.crate_path]::Failed>
+                [@TokenTree::Group(Group::new(Delimiter::Brace, inner))])()?;
+            ([~validate])(&__result, __ctx)?;
+            Ok(__result)
+        }
+    } else {
+        inner
+    }
+}
+
+fn impl_from_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream, items: &[TokenTree]) {
     let target = ctx.target;
+    let self_ty = [TokenTree::Ident(Ident::new("Self", Span::call_site()))];
+    let inner = wrap_container_validate(output, ctx, &self_ty, inner);
     let any_generics = !target.generics.is_empty();
     splat! {
         output;
@@ -238,10 +264,31 @@ fn impl_from_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
                 [~ty]: [#ctx.crate_path]::FromFlattened<#[#: &ctx.lifetime]>,
             }] [~&target.where_clauses] ]
         {
-            fn from_toml(
-                __ctx: &mut [#ctx.crate_path]::Context<#[#: &ctx.lifetime]>,
-                __item: &[#ctx.crate_path]::Item<#[#: &ctx.lifetime]>,
-            ) -> ::std::result::Result<Self, [#ctx.crate_path]::Failed> [@TokenTree::Group(Group::new(Delimiter::Brace, inner))]
+            [if target.has_key_span {
+                splat! {
+                    output;
+                    fn from_toml(
+                        __ctx: &mut [#ctx.crate_path]::Context<#[#: &ctx.lifetime]>,
+                        __item: &[#ctx.crate_path]::Item<#[#: &ctx.lifetime]>,
+                    ) -> ::std::result::Result<Self, [#ctx.crate_path]::Failed> {
+                        Self::from_toml_at_key(__ctx, [#ctx.crate_path]::Span::default(), __item)
+                    }
+                    fn from_toml_at_key(
+                        __ctx: &mut [#ctx.crate_path]::Context<#[#: &ctx.lifetime]>,
+                        __key_span: [#ctx.crate_path]::Span,
+                        __item: &[#ctx.crate_path]::Item<#[#: &ctx.lifetime]>,
+                    ) -> ::std::result::Result<Self, [#ctx.crate_path]::Failed> [@TokenTree::Group(Group::new(Delimiter::Brace, inner))]
+                }
+            } else {
+                splat! {
+                    output;
+                    fn from_toml(
+                        __ctx: &mut [#ctx.crate_path]::Context<#[#: &ctx.lifetime]>,
+                        __item: &[#ctx.crate_path]::Item<#[#: &ctx.lifetime]>,
+                    ) -> ::std::result::Result<Self, [#ctx.crate_path]::Failed> [@TokenTree::Group(Group::new(Delimiter::Brace, inner))]
+                }
+            }]
+            [~items]
         }
     };
 }
@@ -254,7 +301,7 @@ fn is_option_type(field: &Field) -> bool {
     }
 }
 
-fn impl_to_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
+fn impl_to_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream, items: &[TokenTree]) {
     let target = ctx.target;
     let any_generics = !target.generics.is_empty();
     let lf = Ident::new("__de", Span::mixed_site());
@@ -269,11 +316,16 @@ fn impl_to_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
                 [~ty]: [#ctx.crate_path]::ToToml,
             }] [for (ty in &target.generic_flatten_field_types) {
                 [~ty]: [#ctx.crate_path]::ToFlattened,
-            }] [~&target.where_clauses] ]
+            }] [?(target.doc_comments) [for (ty in &target.generic_field_types) {
+                [~ty]: [#ctx.crate_path]::schema::TomlSchema,
+            }] [for (ty in &target.generic_flatten_field_types) {
+                [~ty]: [#ctx.crate_path]::schema::TomlSchema,
+            }]] [~&target.where_clauses] ]
         {
             fn to_toml<# [#lf]>(& # [#lf] self, __arena: & # [#lf] [#ctx.crate_path]::Arena)
                 -> ::std::result::Result<[#ctx.crate_path]::Item<# [#lf]>, [#ctx.crate_path]::ToTomlError>
                 [@TokenTree::Group(Group::new(Delimiter::Brace, inner))]
+            [~items]
         }
     };
 }
@@ -306,54 +358,82 @@ fn emit_table_field_deser(
     skip_keys: &[Literal],
 ) {
     let recoverable = ctx.target.recoverable;
-    let mut flatten_field: Option<&Field> = None;
+    let mut flatten_fields: Vec<&Field> = Vec::new();
+    let mut catch_all: Option<&Field> = None;
     for field in fields {
+        if field.has_checks(FROM_TOML)
+            && field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0
+        {
+            throw!("validate and constraints cannot be used on skipped or flattened fields" @ field.name.span())
+        }
         if field.flags & Field::WITH_FLATTEN != 0 {
-            if flatten_field.is_some() {
-                throw!("Only one #[toml(flatten)] field is allowed")
+            if !is_flatten_any(field.with(FROM_TOML)) {
+                if catch_all.is_some() {
+                    throw!("At most one #[toml(flatten)] field can collect the remaining keys, flatten derived structs with `with = flatten_any`" @ field.name.span())
+                }
+                catch_all = Some(field);
             }
-            flatten_field = Some(field);
+            flatten_fields.push(field);
         }
     }
 
+    let key_span_tracked = resolve_field_relations(ctx, fields, variant);
+
     if recoverable {
         splat!(out; let mut __failed = false;);
         splat!(out; let mut __seen : u64 = [@TokenTree::Literal(Literal::u64_suffixed(0))] ;);
     }
 
+    // Aliases and loose key matching let one field be spelled twice, so the
+    // span of its first spelling is kept to report the duplicate.
+    let loose_keys = !ctx.target.key_match.is_exact();
+
     // Declare field variables
     for field in fields {
         if field.flags & Field::WITH_FLATTEN != 0 {
-            splat!(out; let mut __flatten_partial =);
+            splat!(out; let mut [#: &flatten_partial_ident(field)] =);
             emit_flatten_prefix(out, ctx, field, FROM_TOML);
             splat!(out; ::init(););
             continue;
         }
-        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+        if let Some(source) = field.span_source() {
+            match source {
+                SpanSource::Table => {
+                    let table_id = Ident::new(table_ident, Span::mixed_site());
+                    splat!(out; let [#: field.name] = [#table_id].span(););
+                }
+                SpanSource::Key => splat!(out; let [#: field.name] = __key_span;),
+            }
+        } else if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
             emit_field_default(out, field, FROM_TOML, false);
         } else if field.flags & Field::WITH_FROM_TOML_OPTION != 0 {
             splat!(out; let mut [#: field.name] : [~field.ty] = None;);
         } else {
             splat!(out; let mut [#: field.name] = None::< [~field.ty] >;);
         }
-        if field.attr.has_aliases(FROM_TOML) {
-            let span_ident = Ident::new(
-                &{
-                    let mut s = field.name.to_string();
-                    s.push_str("_first_span");
-                    s
-                },
-                Span::mixed_site(),
-            );
+        if field.flags & Field::WITH_FROM_TOML_SKIP == 0
+            && (loose_keys || field.attr.has_aliases(FROM_TOML))
+        {
+            let span_ident = first_span_ident(field);
             let zero = TokenTree::Literal(Literal::u32_suffixed(0));
             splat!(out; let mut [#: &span_ident] = toml_spanner::Span::new([@zero.clone()], [@zero]););
         }
     }
+    for (field, &tracked) in fields.iter().zip(&key_span_tracked) {
+        if tracked {
+            splat!(out; let mut [#: &key_span_ident(field)] : Option<[#ctx.crate_path]::Span> = None;);
+        }
+    }
 
     // Build for loop: for (__key, __value) in table { match __key.name { ... } }
     emit_for_table_header(out, table_ident);
     let for_body_at = out.buf.len();
-    splat!(out; match __key . name);
+    if loose_keys {
+        emit_match_key(out, ctx, fields, variant);
+        splat!(out; match __name);
+    } else {
+        splat!(out; match __key . name);
+    }
     let arms_at = out.buf.len();
 
     // Skip key arms
@@ -363,7 +443,7 @@ fn emit_table_field_deser(
 
     // Field arms
     let mut required_idx: u32 = 0;
-    for field in fields {
+    for (field_idx, field) in fields.iter().enumerate() {
         if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
             continue;
         }
@@ -372,7 +452,7 @@ fn emit_table_field_deser(
         let is_default = field.flags & Field::WITH_FROM_TOML_DEFAULT != 0;
         let is_option = field.flags & Field::WITH_FROM_TOML_OPTION != 0;
         let is_required = !is_option && !is_default;
-        let has_aliases = field.attr.has_aliases(FROM_TOML);
+        let tracks_first_span = loose_keys || field.attr.has_aliases(FROM_TOML);
 
         let has_deprecated_aliases = field.attr.has_deprecated_aliases(FROM_TOML);
 
@@ -389,6 +469,10 @@ fn emit_table_field_deser(
         splat!(out; =>);
         let arm_body_at = out.buf.len();
 
+        if key_span_tracked[field_idx] {
+            splat!(out; [#: &key_span_ident(field)] = Some(__key.span););
+        }
+
         if recoverable && is_required {
             if required_idx < 64 {
                 let mask = TokenTree::Literal(Literal::u64_suffixed(1u64 << required_idx));
@@ -397,15 +481,8 @@ fn emit_table_field_deser(
             required_idx += 1;
         }
 
-        if has_aliases {
-            let span_ident = Ident::new(
-                &{
-                    let mut s = field.name.to_string();
-                    s.push_str("_first_span");
-                    s
-                },
-                Span::mixed_site(),
-            );
+        if tracks_first_span {
+            let span_ident = first_span_ident(field);
             splat!(out;
                 if [#: field.name].is_some() {
                     return Err(__ctx.report_duplicate_field([@name_lit.clone().into()], __key.span, [#: &span_ident], __value));
@@ -419,7 +496,7 @@ fn emit_table_field_deser(
                 .attr
                 .for_each_deprecated_alias(FROM_TOML, &mut |tag, alias| {
                     splat!(out;
-                        if __key . name == [@alias.clone().into()] {
+                        if [?(loose_keys) __name] [?(!loose_keys) __key . name] == [@alias.clone().into()] {
                             __ctx . report_deprecated_field(
                                 [if let Some(tag_tokens) = tag {
                                     out.buf.extend_from_slice(tag_tokens);
@@ -445,57 +522,81 @@ fn emit_table_field_deser(
         };
         emit_from_toml_call(out, ctx, field, ty);
         let match_body_at = out.buf.len();
-        if has_aliases {
-            let span_ident = Ident::new(
-                &{
-                    let mut s = field.name.to_string();
-                    s.push_str("_first_span");
-                    s
-                },
-                Span::mixed_site(),
-            );
-            splat!(out;
-                Ok(__val) => { [#: field.name] = Some(__val); [#: &span_ident] = __key.span; }
-                [?(is_required && !recoverable) Err(__e) => return Err(__e),]
-                [?(is_required && recoverable) Err(_) => { __failed = true; },]
-                [?(!is_required) Err(_) => {},]
-            );
-        } else {
-            splat!(out;
-                Ok(__val) => { [#: field.name] = Some(__val); }
-                [?(is_required && !recoverable) Err(__e) => return Err(__e),]
-                [?(is_required && recoverable) Err(_) => { __failed = true; },]
-                [?(!is_required) Err(_) => {},]
-            );
+        splat!(out; Ok(__val) =>);
+        let ok_at = out.buf.len();
+        emit_field_checks(out, field, ty, is_required, recoverable);
+        let assign_at = out.buf.len();
+        splat!(out; [#: field.name] = Some(__val););
+        if tracks_first_span {
+            let span_ident = first_span_ident(field);
+            splat!(out; [#: &span_ident] = __key.span;);
         }
+        out.tt_group(Delimiter::Brace, assign_at);
+        out.tt_group(Delimiter::Brace, ok_at);
+        splat!(out;
+            [?(is_required && !recoverable) Err(__e) => return Err(__e),]
+            [?(is_required && recoverable) Err(_) => { __failed = true; },]
+            [?(!is_required) Err(_) => {},]
+        );
         out.tt_group(Delimiter::Brace, match_body_at);
 
         out.tt_group(Delimiter::Brace, arm_body_at);
     }
 
     // Wildcard arm
-    if let Some(ff) = flatten_field {
-        splat!(out; _ =>);
-        let wild_at = out.buf.len();
-        splat!(out; let _ =);
-        emit_flatten_prefix(out, ctx, ff, FROM_TOML);
-        splat!(out; ::insert( __ctx, __key, __value, &mut __flatten_partial););
-        out.tt_group(Delimiter::Brace, wild_at);
-    } else {
-        emit_unknown_field_arm(out, ctx);
+    let mut expected = Vec::new();
+    for field in fields {
+        if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
+            expected.push(field_name_lit(ctx, field, variant));
+        }
+    }
+    match flatten_fields.as_slice() {
+        [] => emit_unknown_field_arm(out, ctx, &expected, &[]),
+        [ff] => {
+            splat!(out; _ =>);
+            let wild_at = out.buf.len();
+            emit_flatten_insert(out, ctx, ff);
+            out.tt_group(Delimiter::Brace, wild_at);
+        }
+        _ => {
+            // Each key goes to the first flattened struct claiming it, then
+            // to the catch-all field or the unknown key policy.
+            splat!(out; _ =>);
+            let wild_at = out.buf.len();
+            for ff in &flatten_fields {
+                if catch_all.is_some_and(|ca| std::ptr::eq(ca, *ff)) {
+                    continue;
+                }
+                splat!(out; if < [~ff.ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::claims_key(__key.name));
+                let if_at = out.buf.len();
+                emit_flatten_insert(out, ctx, ff);
+                out.tt_group(Delimiter::Brace, if_at);
+                splat!(out; else);
+            }
+            if let Some(ca) = catch_all {
+                let else_at = out.buf.len();
+                emit_flatten_insert(out, ctx, ca);
+                out.tt_group(Delimiter::Brace, else_at);
+            } else {
+                emit_unknown_field_body(out, ctx, &expected, &flatten_fields);
+            }
+            out.tt_group(Delimiter::Brace, wild_at);
+        }
     }
     out.tt_group(Delimiter::Brace, arms_at);
     out.tt_group(Delimiter::Brace, for_body_at);
 
-    // Finish flatten partial
-    if let Some(ff) = flatten_field {
+    // Finish flatten partials
+    for ff in &flatten_fields {
         let table_id = Ident::new(table_ident, Span::mixed_site());
         splat!(out; let Ok([#: ff.name]) =);
         emit_flatten_prefix(out, ctx, ff, FROM_TOML);
-        splat!(out; ::finish( __ctx, [#table_id], __flatten_partial) else);
+        splat!(out; ::finish( __ctx, [#table_id], [#: &flatten_partial_ident(ff)]) else);
         emit_failed_return(out, ctx);
     }
 
+    emit_field_relation_checks(out, ctx, fields, variant);
+
     if recoverable {
         required_idx = 0;
         for field in fields {
@@ -570,6 +671,357 @@ fn emit_table_field_deser(
     }
 }
 
+/// Emits `let __name = __ctx.match_key(..);`, resolving the key to the field
+/// name it matches under the container's `key_match` rule.
+fn emit_match_key(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    fields: &[Field],
+    variant: Option<&EnumVariant>,
+) {
+    let rule = &ctx.target.key_match;
+    let mut names: Vec<(String, Literal)> = Vec::new();
+    for field in fields {
+        if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+            continue;
+        }
+        let mut push = |lit: Literal| {
+            let crate::lit::InlineKind::String(text) = crate::lit::literal_inline(lit.to_string())
+            else {
+                throw!("Expected a string literal" @ lit.span())
+            };
+            let normalized = rule.normalize(&text);
+            for (other, _) in &names {
+                if *other != text && rule.normalize(other) == normalized {
+                    let mut keys = String::from("keys `");
+                    keys.push_str(other);
+                    keys.push_str("` and `");
+                    keys.push_str(&text);
+                    keys.push('`');
+                    throw!("cannot be told apart under key_match" @ field.name.span(), keys)
+                }
+            }
+            names.push((text, lit));
+        };
+        push(field_name_lit(ctx, field, variant));
+        field
+            .attr
+            .for_each_alias(FROM_TOML, &mut |alias| push(alias.clone()));
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |_, alias| push(alias.clone()));
+    }
+
+    let rule_ident = Ident::new(rule.variant_name(), Span::call_site());
+    splat!(out; let __name = __ctx.match_key);
+    let args_at = out.buf.len();
+    splat!(out; __key, __value, &);
+    let list_at = out.buf.len();
+    for (_, lit) in &names {
+        splat!(out; [@lit.clone().into()],);
+    }
+    out.tt_group(Delimiter::Bracket, list_at);
+    splat!(out; , [#ctx.crate_path]::KeyMatch::[#: &rule_ident]);
+    out.tt_group(Delimiter::Parenthesis, args_at);
+    splat!(out; ;);
+}
+
+fn first_span_ident(field: &Field) -> Ident {
+    let mut name = field.name.to_string();
+    name.push_str("_first_span");
+    Ident::new(&name, Span::mixed_site())
+}
+
+fn key_span_ident(field: &Field) -> Ident {
+    let mut name = field.name.to_string();
+    name.push_str("_key_span");
+    Ident::new(&name, Span::mixed_site())
+}
+
+fn find_related_field(fields: &[Field], ident: &Ident) -> usize {
+    let name = ident.to_string();
+    for (i, field) in fields.iter().enumerate() {
+        if field.name.to_string() == name {
+            if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+                throw!("Cannot refer to a skipped or flattened field" @ ident.span())
+            }
+            return i;
+        }
+    }
+    throw!("Unknown field" @ ident.span(), name)
+}
+
+/// Validates `conflicts_with`, `requires` and `exactly_one_of` and returns which
+/// fields need their key span tracked during deserialization.
+fn resolve_field_relations(
+    ctx: &Ctx,
+    fields: &[Field],
+    variant: Option<&EnumVariant>,
+) -> Vec<bool> {
+    let mut tracked = Vec::new();
+    tracked.resize(fields.len(), false);
+    for (i, field) in fields.iter().enumerate() {
+        let mut mark = |other: &Ident| {
+            if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+                throw!("conflicts_with and requires cannot be used on skipped or flattened fields" @ field.name.span())
+            }
+            let j = find_related_field(fields, other);
+            if i == j {
+                throw!("A field cannot refer to itself" @ other.span())
+            }
+            tracked[i] = true;
+            tracked[j] = true;
+        };
+        field.attr.for_each_conflict(FROM_TOML, &mut mark);
+        field.attr.for_each_requirement(FROM_TOML, &mut mark);
+    }
+    if variant.is_none() {
+        for group in &ctx.target.exactly_one_of {
+            for ident in group {
+                tracked[find_related_field(fields, ident)] = true;
+            }
+        }
+    }
+    tracked
+}
+
+/// Wraps the report emitted since `report_at` in a block that fails
+/// deserialization, returning immediately unless the container is recoverable.
+fn emit_relation_failure(out: &mut RustWriter, ctx: &Ctx, report_at: usize) {
+    let report = TokenTree::Group(Group::new(Delimiter::None, out.split_off_stream(report_at)));
+    let at = out.buf.len();
+    if ctx.target.recoverable {
+        splat!(out; [@report]; __failed = true;);
+    } else {
+        splat!(out; return Err([@report]););
+    }
+    out.tt_group(Delimiter::Brace, at);
+}
+
+fn emit_field_relation_checks(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    fields: &[Field],
+    variant: Option<&EnumVariant>,
+) {
+    let mut conflicts: Vec<(usize, usize)> = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        field.attr.for_each_conflict(FROM_TOML, &mut |other| {
+            let j = find_related_field(fields, other);
+            let pair = (i.min(j), i.max(j));
+            if !conflicts.contains(&pair) {
+                conflicts.push(pair);
+            }
+        });
+    }
+    for (i, j) in conflicts {
+        let (a, b) = (&fields[i], &fields[j]);
+        splat!(out;
+            if let (Some(__a), Some(__b)) = ([#: &key_span_ident(a)], [#: &key_span_ident(b)])
+        );
+        let at = out.buf.len();
+        splat!(out;
+            __ctx.report_conflicting_fields(
+                [@field_name_lit(ctx, a, variant).into()], __a,
+                [@field_name_lit(ctx, b, variant).into()], __b,
+                __item
+            )
+        );
+        emit_relation_failure(out, ctx, at);
+    }
+
+    for field in fields {
+        field.attr.for_each_requirement(FROM_TOML, &mut |other| {
+            let required = &fields[find_related_field(fields, other)];
+            splat!(out;
+                if let (Some(__by), None) = ([#: &key_span_ident(field)], [#: &key_span_ident(required)])
+            );
+            let at = out.buf.len();
+            splat!(out;
+                __ctx.report_missing_required_by(
+                    [@field_name_lit(ctx, required, variant).into()],
+                    [@field_name_lit(ctx, field, variant).into()],
+                    __by,
+                    __item
+                )
+            );
+            emit_relation_failure(out, ctx, at);
+        });
+    }
+
+    if variant.is_some() {
+        return;
+    }
+    for group in &ctx.target.exactly_one_of {
+        let mut candidates: Vec<TokenTree> = Vec::new();
+        let mut names: Vec<TokenTree> = Vec::new();
+        for ident in group {
+            let field = &fields[find_related_field(fields, ident)];
+            let name: TokenTree = field_name_lit(ctx, field, variant).into();
+            let at = out.buf.len();
+            splat!(out; [@name.clone()], [#: &key_span_ident(field)]);
+            out.tt_group(Delimiter::Parenthesis, at);
+            splat!(out; ,);
+            candidates.extend(out.buf.drain(at..));
+            splat!(out; [@name],);
+            names.extend(out.buf.drain(at..));
+        }
+        let candidates = TokenTree::Group(Group::new(
+            Delimiter::Bracket,
+            candidates.into_iter().collect(),
+        ));
+        let names = TokenTree::Group(Group::new(Delimiter::Bracket, names.into_iter().collect()));
+
+        let group_at = out.buf.len();
+        splat!(out;
+            let mut __one_of: Option<(&str, [#ctx.crate_path]::Span)> = None;
+            for (__name, __span) in [@candidates]
+        );
+        let for_at = out.buf.len();
+        splat!(out;
+            let Some(__span) = __span else { continue };
+            if let Some((__first, __first_span)) = __one_of
+        );
+        let at = out.buf.len();
+        splat!(out; __ctx.report_conflicting_fields(__name, __span, __first, __first_span, __item));
+        emit_relation_failure(out, ctx, at);
+        splat!(out; else { __one_of = Some((__name, __span)); });
+        out.tt_group(Delimiter::Brace, for_at);
+        splat!(out; if __one_of.is_none());
+        let at = out.buf.len();
+        splat!(out; __ctx.report_missing_one_of(&[@names], __item));
+        emit_relation_failure(out, ctx, at);
+        out.tt_group(Delimiter::Brace, group_at);
+    }
+}
+
+/// Joins tokens into a compact display string, e.g. `Vec<String>` or `1..=65535`.
+fn compact_tokens(tokens: &[TokenTree]) -> String {
+    let mut out = String::new();
+    let mut prev_word = false;
+    for tok in tokens {
+        let word = matches!(tok, TokenTree::Ident(_) | TokenTree::Literal(_));
+        if word && prev_word {
+            out.push(' ');
+        }
+        out.push_str(&tok.to_string());
+        prev_word = word;
+    }
+    out
+}
+
+/// Emits the `if <violated> { <report> } else` chain checking `__val` against the
+/// field's validator and constraints. The caller follows it with the assignment block.
+fn emit_field_checks(
+    out: &mut RustWriter,
+    field: &Field,
+    ty: &[TokenTree],
+    is_required: bool,
+    recoverable: bool,
+) {
+    fn failure(out: &mut RustWriter, report_at: usize, is_required: bool, recoverable: bool) {
+        let report = TokenTree::Group(Group::new(Delimiter::None, out.split_off_stream(report_at)));
+        let at = out.buf.len();
+        if is_required && !recoverable {
+            splat!(out; return Err([@report]););
+        } else {
+            splat!(out; [@report]; [?(is_required) __failed = true;]);
+        }
+        out.tt_group(Delimiter::Brace, at);
+        splat!(out; else);
+    }
+
+    if let Some(validate) = field.validate(FROM_TOML) {
+        splat!(out; if let Err(__e) = ([~validate])(&__val));
+        let at = out.buf.len();
+        splat!(out; __ctx.report_custom_error(__e, __value));
+        failure(out, at, is_required, recoverable);
+    }
+
+    let mut min_len: Option<&[TokenTree]> = None;
+    let mut max_len: Option<&[TokenTree]> = None;
+    field.for_each_constraint(FROM_TOML, &mut |constraint| match constraint {
+        Constraint::MinLen(tokens) => min_len = Some(tokens),
+        Constraint::MaxLen(tokens) => max_len = Some(tokens),
+        _ => (),
+    });
+    let length = match (min_len, max_len) {
+        (Some(min), Some(max)) => {
+            let mut s = String::from("length ");
+            s.push_str(&compact_tokens(min));
+            s.push_str("..=");
+            s.push_str(&compact_tokens(max));
+            s
+        }
+        (Some(min), None) => {
+            let mut s = String::from("length >= ");
+            s.push_str(&compact_tokens(min));
+            s
+        }
+        (None, Some(max)) => {
+            let mut s = String::from("length <= ");
+            s.push_str(&compact_tokens(max));
+            s
+        }
+        (None, None) => String::new(),
+    };
+    let ty_lit = TokenTree::Literal(Literal::string(&compact_tokens(ty)));
+
+    field.for_each_constraint(FROM_TOML, &mut |constraint| {
+        let range = match constraint {
+            Constraint::Range(tokens) => {
+                splat!(out; if !([~tokens]).contains(&__val));
+                compact_tokens(tokens)
+            }
+            Constraint::MinLen(tokens) => {
+                splat!(out; if __val.len() < ([~tokens]));
+                length.clone()
+            }
+            Constraint::MaxLen(tokens) => {
+                splat!(out; if __val.len() > ([~tokens]));
+                length.clone()
+            }
+            Constraint::NonEmpty => {
+                splat!(out; if __val.is_empty());
+                String::from("non-empty")
+            }
+            Constraint::OneOf(values) => {
+                let mut expected: Vec<TokenTree> = Vec::new();
+                let mut current = String::new();
+                for tok in values {
+                    match tok {
+                        TokenTree::Punct(p) if p.as_char() == ',' => {
+                            expected.push(TokenTree::Literal(Literal::string(&current)));
+                            expected.push(tok.clone());
+                            current.clear();
+                        }
+                        TokenTree::Literal(lit) => match crate::lit::literal_inline(lit.to_string()) {
+                            crate::lit::InlineKind::String(text) => current.push_str(&text),
+                            _ => current.push_str(&lit.to_string()),
+                        },
+                        _ => current.push_str(&tok.to_string()),
+                    }
+                }
+                if !current.is_empty() {
+                    expected.push(TokenTree::Literal(Literal::string(&current)));
+                }
+                splat!(out; if ![@TokenTree::Group(Group::new(Delimiter::Bracket, values.iter().cloned().collect()))]
+                    .iter().any(|__c| *__c == __val));
+                let at = out.buf.len();
+                splat!(out; __ctx.report_unexpected_value(
+                    &[@TokenTree::Group(Group::new(Delimiter::Bracket, expected.into_iter().collect()))],
+                    __value
+                ));
+                failure(out, at, is_required, recoverable);
+                return;
+            }
+        };
+        let at = out.buf.len();
+        splat!(out; __ctx.report_out_of_range(&[@ty_lit.clone()], &[@TokenTree::Literal(Literal::string(&range))], __value));
+        failure(out, at, is_required, recoverable);
+    });
+}
+
 /// Emit a field default: either as initial `let field = default;` or as unwrap `let field = field.unwrap_or...;`
 fn emit_field_default(out: &mut RustWriter, field: &Field, direction: u8, is_unwrap: bool) {
     if is_unwrap {
@@ -686,10 +1138,29 @@ fn emit_table_field_ser(
         if let Some(skip_tokens) = skip_if {
             let emit_body = out.split_off_stream(emit_start);
             splat!(out;
-                if !([~skip_tokens])([@TokenTree::Group(Group::new(Delimiter::None, field_ref))])
+                if !([~skip_tokens])([@TokenTree::Group(Group::new(Delimiter::None, field_ref.clone()))])
                     [@TokenTree::Group(Group::new(Delimiter::Brace, emit_body))]
             );
         }
+
+        let default = field.default(FROM_TOML);
+        if field.is_skip_if_default() && default.is_none() {
+            throw!("skip_if_default requires a default value" @ field.name.span())
+        }
+        if let Some(default) =
+            default.filter(|_| field.is_skip_if_default() || ctx.target.skip_if_default)
+        {
+            let emit_body = out.split_off_stream(emit_start);
+            splat!(out;
+                if [#ctx.crate_path]::Formatting::expands_defaults() || {
+                    let __default: [~field.ty] = [match default {
+                        DefaultKind::Custom(tokens) => splat!(out; [~tokens.as_slice()]),
+                        DefaultKind::Default => splat!(out; Default::default()),
+                    }];
+                    [@TokenTree::Group(Group::new(Delimiter::None, field_ref))] != &__default
+                } [@TokenTree::Group(Group::new(Delimiter::Brace, emit_body))]
+            );
+        }
     }
 
     for field in fields {
@@ -741,6 +1212,11 @@ fn struct_from_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
     let start = out.buf.len();
     splat!(out; let Ok(__table) = __item.require_table(__ctx) else);
     emit_failed_return(out, ctx);
+    if version_field(fields).is_some() {
+        splat!(out;
+            let __table = __ctx.migrated_table::<Self>(__table)?;
+        );
+    }
     emit_table_field_deser(out, ctx, fields, "__table", None, &[]);
     splat!(out;
         Ok(Self {
@@ -748,49 +1224,1011 @@ fn struct_from_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
         })
     );
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    let items: Vec<TokenTree> = emit_claims_key(out, ctx, fields).into_iter().collect();
+    impl_from_toml(out, ctx, body, &items);
+}
+
+/// Emits the `claims_key` and `expected_keys` overrides reporting the keys
+/// read by the struct's fields, so it can share a table with other flattened
+/// structs.
+fn emit_claims_key(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) -> TokenStream {
+    let start = out.buf.len();
+    let mut names: Vec<Literal> = Vec::new();
+    let mut nested: Vec<&Field> = Vec::new();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        if field.flags & Field::WITH_FLATTEN != 0 {
+            if !is_flatten_any(field.with(FROM_TOML)) {
+                // A catch-all field reads every key, keep the default.
+                return TokenStream::new();
+            }
+            nested.push(field);
+            continue;
+        }
+        names.push(field_name_lit(ctx, field, None));
+        field
+            .attr
+            .for_each_alias(FROM_TOML, &mut |alias| names.push(alias.clone()));
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |_, alias| names.push(alias.clone()));
+    }
+    splat!(out; fn claims_key(__key: &str) -> bool);
+    let body_at = out.buf.len();
+    if ctx.target.key_match.is_exact() {
+        // Parenthesized so a following `||` continues the expression.
+        let expr_at = out.buf.len();
+        splat!(out; match __key);
+        let arms_at = out.buf.len();
+        if !names.is_empty() {
+            for (i, name) in names.iter().enumerate() {
+                splat!(out; [?(i > 0) |] [@name.clone().into()]);
+            }
+            splat!(out; => true,);
+        }
+        splat!(out; _ => false);
+        out.tt_group(Delimiter::Brace, arms_at);
+        if !nested.is_empty() {
+            out.tt_group(Delimiter::Parenthesis, expr_at);
+        }
+    } else {
+        let rule_ident = Ident::new(ctx.target.key_match.variant_name(), Span::call_site());
+        let list_at = out.buf.len();
+        for name in &names {
+            splat!(out; [@name.clone().into()],);
+        }
+        out.tt_group(Delimiter::Bracket, list_at);
+        splat!(out; .iter().any(|__name| [#ctx.crate_path]::KeyMatch::[#: &rule_ident].matches(__key, __name)));
+    }
+    for field in &nested {
+        splat!(out; || < [~field.ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::claims_key(__key));
+    }
+    out.tt_group(Delimiter::Brace, body_at);
+
+    splat!(out; fn expected_keys(__keys: &mut ::std::vec::Vec<&#static str>));
+    let body_at = out.buf.len();
+    splat!(out; __keys.extend_from_slice);
+    let args_at = out.buf.len();
+    splat!(out; &);
+    let list_at = out.buf.len();
+    for field in fields {
+        if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
+            splat!(out; [@field_name_lit(ctx, field, None).into()],);
+        }
+    }
+    out.tt_group(Delimiter::Bracket, list_at);
+    out.tt_group(Delimiter::Parenthesis, args_at);
+    splat!(out; ;);
+    for field in &nested {
+        splat!(out; < [~field.ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::expected_keys(__keys););
+    }
+    out.tt_group(Delimiter::Brace, body_at);
+    out.split_off_stream(start)
+}
+
+fn struct_to_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let start = out.buf.len();
+    emit_table_alloc(out, ctx, "__table", count_ser_fields(fields));
+    emit_table_field_ser(out, ctx, fields, "__table", None, true);
+    splat!(out; Ok(__table.into_item()));
+    let body = out.split_off_stream(start);
+    let mut items = Vec::new();
+    if ctx.target.doc_comments {
+        items.extend(token_stream! {out;
+            fn doc_comments(&self) -> ::std::option::Option<[#ctx.crate_path]::schema::Schema> {
+                Some(<Self as [#ctx.crate_path]::schema::TomlSchema>::schema())
+            }
+        });
+    }
+    impl_to_toml(out, ctx, body, &items);
+}
+
+/// Writes tokens the way they are usually spelled in source, for the type
+/// names and default expressions recorded in schemas.
+fn write_source(tokens: TokenStream, out: &mut String) {
+    let mut prev_word = false;
+    for tt in tokens {
+        match &tt {
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                if prev_word {
+                    out.push(' ');
+                }
+                out.push_str(&tt.to_string());
+                prev_word = true;
+            }
+            TokenTree::Punct(punct) => {
+                out.push(punct.as_char());
+                if matches!(punct.as_char(), ',' | ';') {
+                    out.push(' ');
+                }
+                prev_word = false;
+            }
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{ ", " }"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                write_source(group.stream(), out);
+                out.push_str(close);
+                prev_word = false;
+            }
+        }
+    }
+}
+
+fn source_literal(tokens: &[TokenTree]) -> Literal {
+    let mut text = String::new();
+    write_source(tokens.iter().cloned().collect(), &mut text);
+    Literal::string(&text)
+}
+
+fn bool_ident(value: bool) -> Ident {
+    Ident::new(if value { "true" } else { "false" }, Span::call_site())
+}
+
+/// Emits `&[docs...]` for the doc comment literals of an item.
+fn emit_doc_slice(out: &mut RustWriter, docs: &[Literal]) {
+    splat!(out; &);
+    let at = out.buf.len();
+    for doc in docs {
+        splat!(out; [@TokenTree::Literal(doc.clone())],);
+    }
+    out.tt_group(Delimiter::Bracket, at);
+}
+
+/// Emits an expression of type `fn() -> Schema` describing `ty`.
+fn emit_schema_fn(out: &mut RustWriter, ctx: &Ctx, ty: &[TokenTree], with: Option<&[TokenTree]>) {
+    let typed = match with {
+        None => true,
+        Some(path) => {
+            matches!(path.last(), Some(TokenTree::Ident(id)) if id.to_string() == "flatten_any")
+        }
+    };
+    if typed {
+        splat!(out; < [~ty] as [#ctx.crate_path]::schema::TomlSchema >::schema);
+    } else {
+        splat!(out; < [#ctx.crate_path]::Item<# static> as [#ctx.crate_path]::schema::TomlSchema >::schema);
+    }
+    splat!(out; as fn() -> [#ctx.crate_path]::schema::Schema);
+}
+
+fn emit_field_schemas(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    fields: &[Field],
+    variant: Option<&EnumVariant>,
+) {
+    let skip = if ctx.target.from_toml {
+        Field::WITH_FROM_TOML_SKIP
+    } else {
+        Field::WITH_TO_TOML_SKIP
+    };
+    splat!(out; const);
+    let outer = out.buf.len();
+    splat!(out; &);
+    let at = out.buf.len();
+    for field in fields {
+        if field.flags & skip != 0 {
+            continue;
+        }
+        let field_start = out.buf.len();
+        splat!(out; [@TokenTree::Literal(field_name_lit(ctx, field, variant))], &);
+        let aliases = out.buf.len();
+        field.attr.for_each_alias(FROM_TOML, &mut |lit| {
+            splat!(out; [@TokenTree::Literal(lit.clone())],);
+        });
+        out.tt_group(Delimiter::Bracket, aliases);
+        splat!(out; , &);
+        let deprecated = out.buf.len();
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |_, lit| {
+                splat!(out; [@TokenTree::Literal(lit.clone())],);
+            });
+        out.tt_group(Delimiter::Bracket, deprecated);
+        splat!(out; ,);
+        emit_doc_slice(out, &field.attr.docs);
+        splat!(out; , [@TokenTree::Literal(source_literal(field.ty))],);
+        match field.default(FROM_TOML) {
+            None => {
+                splat!(out; [#ctx.crate_path]::schema::FieldDefault::Required);
+            }
+            Some(DefaultKind::Default) => {
+                splat!(out; [#ctx.crate_path]::schema::FieldDefault::Default);
+            }
+            Some(DefaultKind::Custom(expr)) => {
+                splat!(out;
+                    [#ctx.crate_path]::schema::FieldDefault::Expr([@TokenTree::Literal(source_literal(expr))])
+                );
+            }
+        }
+        splat!(out;
+            , [#: &bool_ident(field.flags & Field::WITH_FLATTEN != 0)],
+        );
+        emit_schema_fn(out, ctx, field.ty, field.with(FROM_TOML | TO_TOML));
+        out.tt_group(Delimiter::Parenthesis, field_start);
+        let body = out.buf.pop().unwrap();
+        splat!(out; [#ctx.crate_path]::schema::FieldSchema::new [@body],);
+    }
+    out.tt_group(Delimiter::Bracket, at);
+    out.tt_group(Delimiter::Brace, outer);
+}
+
+/// Emits the `TomlSchema` impl for `#[toml(Schema)]` with the given body.
+fn impl_toml_schema(output: &mut RustWriter, ctx: &Ctx, body: TokenStream) {
+    let target = ctx.target;
+    let any_generics = !target.generics.is_empty();
+    splat! {
+        output;
+        ~[[automatically_derived]]
+        impl [?(any_generics) < [fmt_generics(output, &target.generics, DEF)] >]
+         [#ctx.crate_path]::schema::TomlSchema for [#: &target.name][?(any_generics) <
+            [fmt_generics(output, &target.generics, USE)]
+        >]  [?(!target.where_clauses.is_empty() || !target.generic_field_types.is_empty() || !target.generic_flatten_field_types.is_empty())
+             where [for (ty in &target.generic_field_types) {
+                [~ty]: [#ctx.crate_path]::schema::TomlSchema,
+            }] [for (ty in &target.generic_flatten_field_types) {
+                [~ty]: [#ctx.crate_path]::schema::TomlSchema,
+            }] [~&target.where_clauses] ]
+        {
+            fn schema() -> [#ctx.crate_path]::schema::Schema
+                [@TokenTree::Group(Group::new(Delimiter::Brace, body))]
+        }
+    };
+}
+
+/// Emits the schema of a proxy or transparent container, if it has one.
+fn emit_delegated_schema(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) -> bool {
+    let target = ctx.target;
+    let proxy = target
+        .from_type
+        .as_ref()
+        .or(target.try_from_type.as_ref())
+        .or(target.into_type.as_ref())
+        .or(target.try_into_type.as_ref());
+    if let Some(proxy) = proxy {
+        splat!(out; < [~proxy] as [#ctx.crate_path]::schema::TomlSchema >::schema());
+        return true;
+    }
+    if target.transparent_impl {
+        let [single_field] = fields else {
+            throw!("Struct must contain a single field to use transparent")
+        };
+        let at = out.buf.len();
+        emit_schema_fn(
+            out,
+            ctx,
+            single_field.ty,
+            single_field.with(FROM_TOML | TO_TOML),
+        );
+        out.tt_group(Delimiter::Parenthesis, at);
+        out.tt_group_empty(Delimiter::Parenthesis);
+        return true;
+    }
+    false
+}
+
+fn schema_struct(output: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let target = ctx.target;
+    let start = output.buf.len();
+    if !emit_delegated_schema(output, ctx, fields) {
+        let unknown = match target.unknown_fields {
+            UnknownFieldPolicy::Warn { .. } => "Warn",
+            UnknownFieldPolicy::Ignore => "Ignore",
+            UnknownFieldPolicy::Deny { .. } => "Deny",
+        };
+        let name = Literal::string(&target.name.to_string());
+        let body_start = output.buf.len();
+        splat!(output; [@TokenTree::Literal(name)],);
+        emit_doc_slice(output, &target.docs);
+        splat!(output; ,);
+        emit_field_schemas(output, ctx, fields, None);
+        splat!(output;
+            , [#ctx.crate_path]::schema::UnknownFields::[#: &Ident::new(unknown, Span::call_site())],
+        );
+        output.tt_group(Delimiter::Parenthesis, body_start);
+        let body = output.buf.pop().unwrap();
+        splat!(output;
+            [#ctx.crate_path]::schema::Schema::Struct(const { &[#ctx.crate_path]::schema::StructSchema::new [@body] })
+        );
+    }
+    let body = output.split_off_stream(start);
+    impl_toml_schema(output, ctx, body);
+}
+
+fn schema_tuple_struct(output: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let start = output.buf.len();
+    if !emit_delegated_schema(output, ctx, fields) {
+        // Multi-field tuple structs are rejected by the trait impls.
+        let [single_field] = fields else {
+            return;
+        };
+        let at = output.buf.len();
+        emit_schema_fn(
+            output,
+            ctx,
+            single_field.ty,
+            single_field.with(FROM_TOML | TO_TOML),
+        );
+        output.tt_group(Delimiter::Parenthesis, at);
+        output.tt_group_empty(Delimiter::Parenthesis);
+    }
+    let body = output.split_off_stream(start);
+    impl_toml_schema(output, ctx, body);
+}
+
+fn emit_tuple_schemas(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let at = out.buf.len();
+    for field in fields {
+        emit_schema_fn(out, ctx, field.ty, field.with(FROM_TOML | TO_TOML));
+        splat!(out; ,);
+    }
+    out.tt_group(Delimiter::Bracket, at);
+}
+
+fn schema_enum(output: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], values: &[i64]) {
+    let target = ctx.target;
+    let start = output.buf.len();
+    if !emit_delegated_schema(output, ctx, &[]) {
+        let name = Literal::string(&target.name.to_string());
+        let body_start = output.buf.len();
+        splat!(output; [@TokenTree::Literal(name)],);
+        emit_doc_slice(output, &target.docs);
+        splat!(output; , [#ctx.crate_path]::schema::EnumRepr::);
+        match (&target.tag, &target.content) {
+            _ if target.untagged => {
+                splat!(output; Untagged);
+            }
+            _ if matches!(target.unit_repr, UnitRepr::Int) => {
+                splat!(output; Int);
+            }
+            _ if matches!(target.unit_repr, UnitRepr::StrOrInt) => {
+                splat!(output; StrOrInt);
+            }
+            (Some(tag), Some(content)) => {
+                splat!(output;
+                    Adjacent { tag: [@TokenTree::Literal(tag.clone())], content: [@TokenTree::Literal(content.clone())] }
+                );
+            }
+            (Some(tag), None) => {
+                splat!(output; Internal { tag: [@TokenTree::Literal(tag.clone())] });
+            }
+            _ => {
+                splat!(output; External);
+            }
+        }
+        splat!(output; , const);
+        let variants_outer = output.buf.len();
+        splat!(output; &);
+        let variants_start = output.buf.len();
+        for (i, variant) in variants.iter().enumerate() {
+            let variant_start = output.buf.len();
+            splat!(output; [@TokenTree::Literal(variant_name_literal(ctx, variant))], &);
+            let aliases = output.buf.len();
+            variant.attr.for_each_alias(FROM_TOML, &mut |lit| {
+                splat!(output; [@TokenTree::Literal(lit.clone())],);
+            });
+            output.tt_group(Delimiter::Bracket, aliases);
+            splat!(output; , &);
+            let deprecated = output.buf.len();
+            variant
+                .attr
+                .for_each_deprecated_alias(FROM_TOML, &mut |_, lit| {
+                    splat!(output; [@TokenTree::Literal(lit.clone())],);
+                });
+            output.tt_group(Delimiter::Bracket, deprecated);
+            splat!(output; ,);
+            emit_doc_slice(output, &variant.attr.docs);
+            splat!(output;
+                , [#: &bool_ident(variant.other)],
+                [if let Some(value) = values.get(i) {
+                    splat!(output; Some([int_literal(output, *value)]));
+                } else {
+                    splat!(output; None);
+                }],
+                [#ctx.crate_path]::schema::VariantKind::
+            );
+            match variant.kind {
+                EnumKind::None => {
+                    splat!(output; Unit);
+                }
+                EnumKind::Tuple => {
+                    splat!(output; Tuple);
+                    let at = output.buf.len();
+                    splat!(output; &);
+                    emit_tuple_schemas(output, ctx, variant.fields);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                }
+                EnumKind::Struct => {
+                    splat!(output; Struct);
+                    let at = output.buf.len();
+                    emit_field_schemas(output, ctx, variant.fields, Some(variant));
+                    output.tt_group(Delimiter::Parenthesis, at);
+                }
+            }
+            output.tt_group(Delimiter::Parenthesis, variant_start);
+            let body = output.buf.pop().unwrap();
+            splat!(output; [#ctx.crate_path]::schema::VariantSchema::new [@body],);
+        }
+        output.tt_group(Delimiter::Bracket, variants_start);
+        output.tt_group(Delimiter::Brace, variants_outer);
+        output.tt_group(Delimiter::Parenthesis, body_start);
+        let body = output.buf.pop().unwrap();
+        splat!(output;
+            [#ctx.crate_path]::schema::Schema::Enum(const { &[#ctx.crate_path]::schema::EnumSchema::new [@body] })
+        );
+    }
+    let body = output.split_off_stream(start);
+    impl_toml_schema(output, ctx, body);
+}
+
+fn emit_proxy_from_toml(output: &mut RustWriter, ctx: &Ctx) -> bool {
+    if let Some(from_ty) = &ctx.target.from_type {
+        let body = token_stream! {
+            output;
+            let Ok(__proxy) = < [~from_ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(
+                __ctx, __item
+            ) else [emit_failed_return(output, ctx)]
+            Ok(::std::convert::From::from(__proxy))
+        };
+        impl_from_toml(output, &ctx, body, &[]);
+        true
+    } else if let Some(try_from_ty) = &ctx.target.try_from_type {
+        let body = token_stream! {
+            output;
+            let Ok(__proxy) = < [~try_from_ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(
+                __ctx, __item
+            ) else [emit_failed_return(output, ctx)]
+            match ::std::convert::TryFrom::try_from(__proxy) {
+                Ok(__val) => Ok(__val),
+                Err(__e) => Err(__ctx.report_custom_error(__e, __item)),
+            }
+        };
+        impl_from_toml(output, &ctx, body, &[]);
+        true
+    } else {
+        false
+    }
+}
+
+fn emit_proxy_to_toml(output: &mut RustWriter, ctx: &Ctx) -> bool {
+    if let Some(into_ty) = &ctx.target.into_type {
+        let body = token_stream! {
+            output;
+            let __proxy: [~into_ty] = ::std::convert::Into::into(::std::clone::Clone::clone(self));
+            Ok(< [~into_ty] as [#ctx.crate_path]::ToToml >::to_toml(&__proxy, __arena)?.into_owned_in(__arena))
+        };
+        impl_to_toml(output, ctx, body, &[]);
+        true
+    } else if let Some(try_into_ty) = &ctx.target.try_into_type {
+        let body = token_stream! {
+            output;
+            let __proxy: [~try_into_ty] = match ::std::convert::TryInto::try_into(::std::clone::Clone::clone(self)) {
+                Ok(__val) => __val,
+                Err(__e) => return Err([#ctx.crate_path]::ToTomlError::from(
+                    ::std::borrow::Cow::Owned(::std::string::ToString::to_string(&__e))
+                )),
+            };
+            Ok(< [~try_into_ty] as [#ctx.crate_path]::ToToml >::to_toml(&__proxy, __arena)?.into_owned_in(__arena))
+        };
+        impl_to_toml(output, ctx, body, &[]);
+        true
+    } else {
+        false
+    }
+}
+
+/// Generates inherent `from_toml`/`to_toml` functions on a local mirror of a
+/// foreign struct, for use with `#[toml(with = Mirror)]`.
+///
+/// The bodies construct and destructure the remote type with every field of
+/// the mirror, so a mismatched name, type, or field count fails to compile.
+fn handle_remote_struct(
+    output: &mut RustWriter,
+    ctx: &Ctx,
+    remote: &[TokenTree],
+    fields: &[Field],
+) {
+    let target = ctx.target;
+    if !target.generics.is_empty() {
+        throw!("remote cannot be used on generic types")
+    }
+    if target.transparent_impl
+        || target.from_type.is_some()
+        || target.try_from_type.is_some()
+        || target.into_type.is_some()
+        || target.try_into_type.is_some()
+    {
+        throw!("remote cannot be combined with transparent, from, try_from, into, or try_into")
+    }
+    let start = output.buf.len();
+
+    if target.from_toml {
+        let body_start = output.buf.len();
+        splat!(output; let Ok(__table) = __item.require_table(__ctx) else);
+        emit_failed_return(output, ctx);
+        emit_table_field_deser(output, ctx, fields, "__table", None, &[]);
+        splat!(output;
+            Ok([~remote] {
+                [for field in fields { splat!(output; [#: field.name],); }]
+            })
+        );
+        let body = output.split_off_stream(body_start);
+        let body = wrap_container_validate(output, ctx, remote, body);
+        splat!(output;
+            [~&target.vis] fn from_toml<#[#: &ctx.lifetime]>(
+                __ctx: &mut [#ctx.crate_path]::Context<#[#: &ctx.lifetime]>,
+                __item: &[#ctx.crate_path]::Item<#[#: &ctx.lifetime]>,
+            ) -> ::std::result::Result<[~remote], [#ctx.crate_path]::Failed>
+                [@TokenTree::Group(Group::new(Delimiter::Brace, body))]
+        );
+    }
+
+    if target.to_toml {
+        let body_start = output.buf.len();
+        splat!(output;
+            let [~remote] {
+                [for field in fields {
+                    if field.flags & Field::WITH_TO_TOML_SKIP != 0 {
+                        splat!(output; [#: field.name]: _,);
+                    } else {
+                        splat!(output; [#: field.name],);
+                    }
+                }]
+            } = __value;
+        );
+        emit_table_alloc(output, ctx, "__table", count_ser_fields(fields));
+        emit_table_field_ser(output, ctx, fields, "__table", None, false);
+        splat!(output; Ok(__table.into_item()));
+        let body = output.split_off_stream(body_start);
+        let lf = Ident::new("__de", Span::mixed_site());
+        splat!(output;
+            [~&target.vis] fn to_toml<# [#lf]>(__value: & # [#lf] [~remote], __arena: & # [#lf] [#ctx.crate_path]::Arena)
+                -> ::std::result::Result<[#ctx.crate_path]::Item<# [#lf]>, [#ctx.crate_path]::ToTomlError>
+                [@TokenTree::Group(Group::new(Delimiter::Brace, body))]
+        );
+    }
+
+    // Moves the mirror into the remote type field-for-field, checking that both
+    // agree in every field even when only one direction is derived.
+    splat!(output;
+        ~[[allow(dead_code)]]
+        fn __toml_remote_mirror(__value: Self) -> [~remote] {
+            let Self { [for field in fields { splat!(output; [#: field.name],); }] } = __value;
+            [~remote] { [for field in fields { splat!(output; [#: field.name],); }] }
+        }
+    );
+    let inner = output.split_off_stream(start);
+    splat!(output;
+        ~[[automatically_derived]]
+        impl [#: &target.name] [@TokenTree::Group(Group::new(Delimiter::Brace, inner))]
+    );
+}
+
+/// Returns the `#[toml(version)]` field, rejecting more than one.
+fn version_field<'f>(fields: &'f [Field<'f>]) -> Option<&'f Field<'f>> {
+    let mut found = None;
+    for field in fields {
+        if field.is_version() {
+            if found.is_some() {
+                throw!("Only one #[toml(version)] field is allowed" @ field.name.span())
+            }
+            found = Some(field);
+        }
+    }
+    found
+}
+
+/// Rejects versioning and partial attributes outside of plain structs.
+fn reject_named_struct_attrs(target: &DeriveTargetInner, fields: &[Field]) {
+    if target.migrations.is_some() {
+        throw!("migrations can only be used on structs with a #[toml(version)] field")
+    }
+    if target.partial.is_some() {
+        throw!("partial can only be used on structs with named fields")
+    }
+    for field in fields {
+        if field.is_version() {
+            throw!("version can only be used on fields of structs with named fields" @ field.name.span())
+        }
+        if field.is_partial() {
+            throw!("partial can only be used on fields of structs with named fields" @ field.name.span())
+        }
+    }
+}
+
+/// Implements `Versioned`, naming the version key and the migration chain.
+fn impl_versioned(output: &mut RustWriter, ctx: &Ctx, field: &Field) {
+    let target = ctx.target;
+    if target.remote.is_some()
+        || target.transparent_impl
+        || target.from_type.is_some()
+        || target.try_from_type.is_some()
+    {
+        throw!("version cannot be combined with remote, transparent, from, or try_from" @ field.name.span())
+    }
+    if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+        throw!("version cannot be used on skipped or flattened fields" @ field.name.span())
+    }
+    let any_generics = !target.generics.is_empty();
+    let key = field_name_lit(ctx, field, None);
+    let empty = Vec::new();
+    let migrations = target.migrations.as_ref().unwrap_or(&empty);
+    let list_start = output.buf.len();
+    for migration in migrations {
+        splat!(output; [~migration],);
+    }
+    let list = output.split_off_stream(list_start);
+    let ty_start = output.buf.len();
+    splat!(output; [#ctx.crate_path]::Migration);
+    let slice_ty = output.split_off_stream(ty_start);
+    splat! {
+        output;
+        ~[[automatically_derived]]
+        impl [?(any_generics) < [fmt_generics(output, &target.generics, DEF)] >]
+         [#ctx.crate_path]::Versioned for [#: &target.name][?(any_generics) <
+            [fmt_generics(output, &target.generics, USE)]
+        >] [?(!target.where_clauses.is_empty()) where [~&target.where_clauses]]
+        {
+            const VERSION_KEY: & # static str = [@key.into()];
+            const MIGRATIONS: & # static [@TokenTree::Group(Group::new(Delimiter::Bracket, slice_ty))] =
+                &[@TokenTree::Group(Group::new(Delimiter::Bracket, list))];
+        }
+    };
+}
+
+/// The field type of a `#[toml(partial)]` field's own partial type.
+fn emit_nested_partial_ty(out: &mut RustWriter, ctx: &Ctx, field: &Field) {
+    let ty = if field.flags & Field::WITH_FROM_TOML_OPTION != 0 {
+        option_inner_ty(field.ty)
+    } else {
+        field.ty
+    };
+    splat!(out; < [~ty] as [#ctx.crate_path]::HasPartial >::Partial);
+}
+
+/// Declares the companion type of `#[toml(partial = Name)]`, every field
+/// wrapped in `Option<Layered<_>>`.
+///
+/// The declaration derives `FromToml` itself, forwarding the key spelling
+/// and unknown field policy, and is emitted outside of the `const` block so
+/// it can be named.
+fn partial_struct(output: &mut RustWriter, ctx: &Ctx, partial: &Ident, fields: &[Field]) {
+    let target = ctx.target;
+    let mut doc = String::from(" Partial form of [`");
+    doc.push_str(&target.name.to_string());
+    doc.push_str("`] for merging configuration layers, see [`toml_spanner::Partial`].");
+    output.tt_punct_alone('#');
+    let at = output.buf.len();
+    splat!(output; doc = [@Literal::string(&doc).into()]);
+    output.tt_group(Delimiter::Bracket, at);
+    output.tt_punct_alone('#');
+    let at = output.buf.len();
+    splat!(output; derive(Default, [#ctx.crate_path]::Toml));
+    output.tt_group(Delimiter::Bracket, at);
+
+    output.tt_punct_alone('#');
+    let at = output.buf.len();
+    splat!(output; toml);
+    let args_at = output.buf.len();
+    splat!(output; FromToml);
+    match &target.unknown_fields {
+        UnknownFieldPolicy::Ignore => splat!(output; , ignore_unknown_fields),
+        UnknownFieldPolicy::Deny { tag } => {
+            splat!(output; , deny_unknown_fields);
+            if let Some(tag) = tag {
+                let tag_at = output.buf.len();
+                output.buf.extend_from_slice(tag);
+                output.tt_group(Delimiter::Bracket, tag_at);
+            }
+        }
+        UnknownFieldPolicy::Warn { tag: Some(tag) } => {
+            splat!(output; , warn_unknown_fields);
+            let tag_at = output.buf.len();
+            output.buf.extend_from_slice(tag);
+            output.tt_group(Delimiter::Bracket, tag_at);
+        }
+        UnknownFieldPolicy::Warn { tag: None } => (),
+    }
+    if !target.key_match.is_exact() {
+        splat!(output; , key_match = [@Literal::string(target.key_match.attr_value()).into()]);
+    }
+    output.tt_group(Delimiter::Parenthesis, args_at);
+    output.tt_group(Delimiter::Bracket, at);
+
+    splat!(output; [~&target.vis] struct [#: partial]);
+    let body_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        if field.flags & Field::WITH_FLATTEN != 0 || field.with(FROM_TOML).is_some() {
+            throw!("partial cannot be used with flatten or with fields" @ field.name.span())
+        }
+        for doc in &field.attr.docs {
+            output.tt_punct_alone('#');
+            let at = output.buf.len();
+            splat!(output; doc = [@TokenTree::Literal(doc.clone())]);
+            output.tt_group(Delimiter::Bracket, at);
+        }
+        output.tt_punct_alone('#');
+        let at = output.buf.len();
+        splat!(output; toml);
+        let args_at = output.buf.len();
+        splat!(output; rename = [@field_name_lit(ctx, field, None).into()]);
+        field.attr.for_each_alias(FROM_TOML, &mut |alias| {
+            splat!(output; , alias = [@alias.clone().into()]);
+        });
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |tag, alias| {
+                splat!(output; , deprecated_alias =);
+                if let Some(tag) = tag {
+                    let tag_at = output.buf.len();
+                    output.buf.extend_from_slice(tag);
+                    output.tt_group(Delimiter::Bracket, tag_at);
+                }
+                splat!(output; [@alias.clone().into()]);
+            });
+        output.tt_group(Delimiter::Parenthesis, args_at);
+        output.tt_group(Delimiter::Bracket, at);
+        splat!(output; [~&target.vis] [#: field.name]: Option<[#ctx.crate_path]::Layered<);
+        if field.is_partial() {
+            emit_nested_partial_ty(output, ctx, field);
+        } else if field.flags & Field::WITH_FROM_TOML_OPTION != 0 {
+            splat!(output; [~option_inner_ty(field.ty)]);
+        } else {
+            splat!(output; [~field.ty]);
+        }
+        splat!(output; > >,);
+    }
+    output.tt_group(Delimiter::Brace, body_at);
+}
+
+/// Implements `Partial` for the companion type and `HasPartial` for the
+/// struct.
+fn impl_partial(output: &mut RustWriter, ctx: &Ctx, partial: &Ident, fields: &[Field]) {
+    let target = ctx.target;
+    if !target.generics.is_empty() {
+        throw!("partial cannot be used on generic types")
+    }
+    if target.remote.is_some() || target.transparent_impl {
+        throw!("partial cannot be combined with remote or transparent")
+    }
+    let full = &target.name;
+
+    let merge_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        if field.is_partial() {
+            splat!(output;
+                [#: field.name]: [#ctx.crate_path]::Layered::merge(self.[#: field.name], __upper.[#: field.name]),
+            );
+        } else {
+            splat!(output; [#: field.name]: __upper.[#: field.name].or(self.[#: field.name]),);
+        }
+    }
+    let merge = output.split_off_stream(merge_at);
+
+    let layer_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        splat!(output;
+            if let Some(__value) = &mut self.[#: field.name] {
+                __value.set_layer(__layer);
+                [?(field.is_partial())
+                    [#ctx.crate_path]::Partial::set_layer(&mut __value.value, __layer);
+                ]
+            }
+        );
+    }
+    let set_layer = output.split_off_stream(layer_at);
+
+    let top_layer_at = output.buf.len();
+    splat!(output; let mut __top: Option<u32> = None;);
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        splat!(output;
+            if let Some(__value) = &self.[#: field.name] {
+                __top = __top.max(Some(__value.layer));
+            }
+        );
+    }
+    splat!(output; __top);
+    let top_layer = output.split_off_stream(top_layer_at);
+
+    let resolve_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            emit_field_default(output, field, FROM_TOML, false);
+            continue;
+        }
+        let key = field_name_lit(ctx, field, None);
+        let is_option = field.flags & Field::WITH_FROM_TOML_OPTION != 0;
+        let is_default = field.flags & Field::WITH_FROM_TOML_DEFAULT != 0;
+        if field.is_partial() {
+            splat!(output; let [#: field.name] =);
+            let nested: Vec<TokenTree> = token_stream!(output;
+                [#ctx.crate_path]::Layered::resolve_in(
+                    __value, [@key.into()], __path, __table, __missing
+                )
+            )
+            .into_iter()
+            .collect();
+            if is_option || is_default {
+                splat!(output; match self.[#: field.name]);
+                let arms_at = output.buf.len();
+                splat!(output; None => Some);
+                let value_at = output.buf.len();
+                if is_option {
+                    splat!(output; None);
+                } else {
+                    match field.default(FROM_TOML) {
+                        Some(DefaultKind::Custom(tokens)) => splat!(output; [~tokens.as_slice()]),
+                        _ => splat!(output; Default::default()),
+                    }
+                }
+                output.tt_group(Delimiter::Parenthesis, value_at);
+                splat!(output; , __value => [~&nested] [?(is_option) .map(Some)],);
+                output.tt_group(Delimiter::Brace, arms_at);
+                splat!(output; ;);
+            } else {
+                splat!(output; { let __value = self.[#: field.name]; [~&nested] };);
+            }
+        } else if is_option || is_default {
+            splat!(output;
+                let [#: field.name] = match self.[#: field.name] {
+                    Some(__value) => Some(__value.value),
+                    None => None,
+                };
+            );
+            if !is_option {
+                emit_field_default(output, field, FROM_TOML, true);
+            }
+        } else {
+            splat!(output;
+                let [#: field.name] = [#ctx.crate_path]::Layered::required(
+                    self.[#: field.name], [@key.into()], __path, __table, __missing
+                );
+            );
+        }
+    }
+    splat!(output; Some);
+    let construct_at = output.buf.len();
+    splat!(output; [#: full]);
+    let fields_at = output.buf.len();
+    for field in fields {
+        let fallible = field.flags & Field::WITH_FROM_TOML_SKIP == 0
+            && (field.is_partial()
+                || field.flags & (Field::WITH_FROM_TOML_OPTION | Field::WITH_FROM_TOML_DEFAULT)
+                    == 0);
+        splat!(output; [#: field.name]: [#: field.name] [?(fallible) ?],);
+    }
+    output.tt_group(Delimiter::Brace, fields_at);
+    output.tt_group(Delimiter::Parenthesis, construct_at);
+    let resolve = output.split_off_stream(resolve_at);
+
+    splat! {
+        output;
+        ~[[automatically_derived]]
+        impl [#ctx.crate_path]::HasPartial for [#: full] {
+            type Partial = [#: partial];
+        }
+        ~[[automatically_derived]]
+        impl [#ctx.crate_path]::Partial for [#: partial] {
+            type Full = [#: full];
+            fn merge(self, __upper: Self) -> Self {
+                Self [@TokenTree::Group(Group::new(Delimiter::Brace, merge))]
+            }
+            fn set_layer(&mut self, __layer: u32) [@TokenTree::Group(Group::new(Delimiter::Brace, set_layer))]
+            fn top_layer(&self) -> Option<u32> [@TokenTree::Group(Group::new(Delimiter::Brace, top_layer))]
+            fn resolve_in(
+                self,
+                __path: &mut Vec<& # static str>,
+                __table: Option<[#ctx.crate_path]::Layered<()> >,
+                __missing: &mut Vec<[#ctx.crate_path]::MissingField>,
+            ) -> Option<[#: full]> [@TokenTree::Group(Group::new(Delimiter::Brace, resolve))]
+        }
+    };
+}
+
+/// Implements `UpdateFromToml`, moving each changed field of the new value
+/// into `self` and recording its key.
+fn impl_update(output: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let target = ctx.target;
+    let body_at = output.buf.len();
+    splat!(output; let mut __changes = [#ctx.crate_path]::ChangeSet::new(););
+    for field in fields {
+        if field.flags & Field::WITH_FLATTEN != 0 {
+            throw!("Update cannot be used with flatten fields" @ field.name.span())
+        }
+        if field.span_source().is_some() {
+            // Spans always point into the new document.
+            splat!(output; self.[#: field.name] = __new.[#: field.name];);
+            continue;
+        }
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        let key = field_name_lit(ctx, field, None);
+        if field.is_update() {
+            splat!(output;
+                __changes.nest(
+                    [@key.into()],
+                    [#ctx.crate_path]::UpdateFromToml::update(
+                        &mut self.[#: field.name], __new.[#: field.name]
+                    ),
+                );
+            );
+        } else {
+            splat!(output;
+                if self.[#: field.name] != __new.[#: field.name] {
+                    self.[#: field.name] = __new.[#: field.name];
+                    __changes.nest([@key.into()], [#ctx.crate_path]::ChangeSet::replaced());
+                }
+            );
+        }
+    }
+    splat!(output; __changes);
+    let body = output.split_off_stream(body_at);
+    splat! {
+        output;
+        ~[[automatically_derived]]
+        impl <#[#: &ctx.lifetime]> [#ctx.crate_path]::UpdateFromToml<#[#: &ctx.lifetime]> for [#: &target.name] {
+            fn update(&mut self, __new: Self) -> [#ctx.crate_path]::ChangeSet [@TokenTree::Group(Group::new(Delimiter::Brace, body))]
+        }
+    };
 }
 
-fn struct_to_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
-    let start = out.buf.len();
-    emit_table_alloc(out, ctx, "__table", count_ser_fields(fields));
-    emit_table_field_ser(out, ctx, fields, "__table", None, true);
-    splat!(out; Ok(__table.into_item()));
-    let body = out.split_off_stream(start);
-    impl_to_toml(out, ctx, body);
-}
+fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[Field]) {
+    let ctx = Ctx::new(output, target);
+    if target.schema {
+        schema_struct(output, &ctx, fields);
+    }
 
-fn emit_proxy_from_toml(output: &mut RustWriter, ctx: &Ctx) -> bool {
-    if let Some(from_ty) = &ctx.target.from_type {
-        let body = token_stream! {
-            output;
-            let Ok(__proxy) = < [~from_ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(
-                __ctx, __item
-            ) else [emit_failed_return(output, ctx)]
-            Ok(::std::convert::From::from(__proxy))
-        };
-        impl_from_toml(output, &ctx, body);
-        true
-    } else if let Some(try_from_ty) = &ctx.target.try_from_type {
-        let body = token_stream! {
-            output;
-            let Ok(__proxy) = < [~try_from_ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(
-                __ctx, __item
-            ) else [emit_failed_return(output, ctx)]
-            match ::std::convert::TryFrom::try_from(__proxy) {
-                Ok(__val) => Ok(__val),
-                Err(__e) => Err(__ctx.report_custom_error(__e, __item)),
+    if let Some(partial) = &target.partial {
+        impl_partial(output, &ctx, partial, fields);
+    } else {
+        for field in fields {
+            if field.is_partial() {
+                throw!("partial fields require a partial container attribute" @ field.name.span())
             }
-        };
-        impl_from_toml(output, &ctx, body);
-        true
+        }
+    }
+
+    if target.update {
+        impl_update(output, &ctx, fields);
     } else {
-        false
+        for field in fields {
+            if field.is_update() {
+                throw!("update fields require the Update container attribute" @ field.name.span())
+            }
+        }
     }
-}
 
-fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[Field]) {
-    let ctx = Ctx::new(output, target);
+    if target.from_toml {
+        if let Some(field) = version_field(fields) {
+            impl_versioned(output, &ctx, field);
+        } else if target.migrations.is_some() {
+            throw!("migrations requires a #[toml(version)] field")
+        }
+    }
+
+    if let Some(remote) = &target.remote {
+        handle_remote_struct(output, &ctx, remote, fields);
+        return;
+    }
 
     if target.from_toml && !emit_proxy_from_toml(output, &ctx) {
         if target.transparent_impl {
@@ -803,13 +2241,13 @@ fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[
                     __ctx, __item
                 )
             };
-            impl_from_toml(output, &ctx, body);
+            impl_from_toml(output, &ctx, body, &[]);
         } else {
             struct_from_toml(output, &ctx, fields);
         }
     }
 
-    if target.to_toml {
+    if target.to_toml && !emit_proxy_to_toml(output, &ctx) {
         if target.transparent_impl {
             let [single_field] = fields else {
                 throw!("Struct must contain a single field to use transparent")
@@ -823,7 +2261,7 @@ fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[
                     < [~single_field.ty] as [#ctx.crate_path]::ToToml >::to_toml(&self.[#: single_field.name], __arena)
                 }
             };
-            impl_to_toml(output, &ctx, body);
+            impl_to_toml(output, &ctx, body, &[]);
         } else {
             struct_to_toml(output, &ctx, fields);
         }
@@ -832,6 +2270,13 @@ fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[
 
 fn handle_tuple_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[Field]) {
     let ctx = Ctx::new(output, target);
+    reject_named_struct_attrs(target, fields);
+    if target.remote.is_some() {
+        throw!("remote can only be used on structs with named fields")
+    }
+    if target.schema {
+        schema_tuple_struct(output, &ctx, fields);
+    }
 
     if target.from_toml && !emit_proxy_from_toml(output, &ctx) {
         if let [single_field] = fields {
@@ -842,13 +2287,13 @@ fn handle_tuple_struct(output: &mut RustWriter, target: &DeriveTargetInner, fiel
                 ) else [emit_failed_return(output, &ctx)]
                 Ok([#: &target.name](__val))
             };
-            impl_from_toml(output, &ctx, body);
+            impl_from_toml(output, &ctx, body, &[]);
         } else {
             throw!("FromToml on tuple structs requires exactly one field (transparent delegation)")
         }
     }
 
-    if target.to_toml {
+    if target.to_toml && !emit_proxy_to_toml(output, &ctx) {
         if let [single_field] = fields {
             let body = if let Some(with) = single_field.with(TO_TOML) {
                 token_stream! {output;
@@ -859,7 +2304,7 @@ fn handle_tuple_struct(output: &mut RustWriter, target: &DeriveTargetInner, fiel
                     < [~single_field.ty] as [#ctx.crate_path]::ToToml >::to_toml(&self.[@TokenTree::Literal(Literal::usize_unsuffixed(0))], __arena)
                 }
             };
-            impl_to_toml(output, &ctx, body);
+            impl_to_toml(output, &ctx, body, &[]);
         } else {
             throw!("ToToml on tuple structs requires exactly one field (transparent delegation)")
         }
@@ -912,30 +2357,65 @@ fn find_other_variant<'a>(variants: &'a [EnumVariant]) -> Option<&'a EnumVariant
     None
 }
 
-fn emit_unknown_field_arm(out: &mut RustWriter, ctx: &Ctx) {
-    match &ctx.target.unknown_fields {
-        UnknownFieldPolicy::Ignore => {
-            splat!(out; _ => {});
+/// Emits the `_` arm for keys matching no field. `expected` lists the keys
+/// offered as suggestions.
+fn emit_unknown_field_arm(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    expected: &[Literal],
+    flattened: &[&Field],
+) {
+    splat!(out; _ =>);
+    emit_unknown_field_body(out, ctx, expected, flattened);
+}
+
+/// Emits the block handling a key that matches no field, per the container's
+/// unknown field policy. The keys of `flattened` fields are suggested along
+/// with `expected`.
+fn emit_unknown_field_body(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    expected: &[Literal],
+    flattened: &[&Field],
+) {
+    let expected = {
+        let mut ts = TokenStream::new();
+        for key in expected {
+            ts.extend([
+                key.clone().into(),
+                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+            ]);
         }
-        UnknownFieldPolicy::Warn { tag } => {
-            splat!(out;
-                _ => {
-                    __ctx.report_unexpected_key(
-                        [emit_tag_value(out, tag.as_deref())]
-                        , __value, __key.span);
-                }
-            );
+        TokenTree::Group(Group::new(Delimiter::Bracket, ts))
+    };
+    let (report, deny) = match &ctx.target.unknown_fields {
+        UnknownFieldPolicy::Ignore => {
+            splat!(out; {});
+            return;
         }
-        UnknownFieldPolicy::Deny { tag } => {
-            splat!(out;
-                _ => {
-                    return Err(__ctx.report_unexpected_key(
-                        [emit_tag_value(out, tag.as_deref())]
-                        , __value, __key.span));
-                }
-            );
+        UnknownFieldPolicy::Warn { tag } => (tag, false),
+        UnknownFieldPolicy::Deny { tag } => (tag, true),
+    };
+    let body_at = out.buf.len();
+    let flattened_keys = !flattened.is_empty();
+    if flattened_keys {
+        splat!(out; let mut __expected = ::std::vec::Vec::from(&[@expected.clone()]););
+        for field in flattened {
+            splat!(out; < [~field.ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::expected_keys(&mut __expected););
         }
     }
+    splat!(out; [?(deny) return Err]);
+    let err_at = out.buf.len();
+    splat!(out;
+        __ctx.report_unexpected_key_among(
+            [emit_tag_value(out, report.as_deref())]
+            , __key, __value, [?(flattened_keys) &__expected] [?(!flattened_keys) & [@expected]])
+    );
+    if deny {
+        out.tt_group(Delimiter::Parenthesis, err_at);
+    }
+    splat!(out; ;);
+    out.tt_group(Delimiter::Brace, body_at);
 }
 
 fn emit_tag_value(out: &mut RustWriter, tag: Option<&[TokenTree]>) {
@@ -971,6 +2451,26 @@ fn emit_for_table_header(out: &mut RustWriter, table_var: &str) {
     splat!(out; in [#table_id]);
 }
 
+/// Returns `true` if `with` names `helper::flatten_any`, which reads the field
+/// through its `FromToml` impl rather than collecting arbitrary keys.
+fn is_flatten_any(with: Option<&[TokenTree]>) -> bool {
+    matches!(with.and_then(|path| path.last()), Some(TokenTree::Ident(id)) if id.to_string() == "flatten_any")
+}
+
+fn flatten_partial_ident(field: &Field) -> Ident {
+    let mut name = String::from("__flatten_");
+    name.push_str(&field.name.to_string());
+    Ident::new(&name, Span::mixed_site())
+}
+
+/// Emits `let _ = <prefix>::insert(..);` adding the current entry to the
+/// partial of the flattened `field`.
+fn emit_flatten_insert(out: &mut RustWriter, ctx: &Ctx, field: &Field) {
+    splat!(out; let _ =);
+    emit_flatten_prefix(out, ctx, field, FROM_TOML);
+    splat!(out; ::insert( __ctx, __key, __value, &mut [#: &flatten_partial_ident(field)]););
+}
+
 fn emit_flatten_prefix(out: &mut RustWriter, ctx: &Ctx, field: &Field, direction: u8) {
     if let Some(with) = field.with(direction) {
         out.buf.extend_from_slice(with);
@@ -987,21 +2487,31 @@ fn emit_from_toml_call(out: &mut RustWriter, ctx: &Ctx, field: &Field, ty: &[Tok
     if let Some(with) = field.with(FROM_TOML) {
         splat!(out; [~with]::from_toml(__ctx, __value));
     } else {
-        splat!(out; < [~ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(__ctx, __value));
+        splat!(out; < [~ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml_at_key(__ctx, __key.span, __value));
     }
 }
 
 fn enum_from_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
     let start = out.buf.len();
-    let other_variant = find_other_variant(variants);
     splat!(out; let Ok(s) = __item.require_string(__ctx) else);
     emit_failed_return(out, ctx);
+    emit_unit_name_match(out, ctx, variants);
+    let body = out.split_off_stream(start);
+    impl_from_toml(out, ctx, body, &[]);
+}
+
+/// Emits the `match s` that maps a string `s` to a unit variant by name.
+fn emit_unit_name_match(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
+    let other_variant = find_other_variant(variants);
     splat!(out;
         match s {
             [for variant in variants {
                 if variant.other { continue; }
-                let name_lit = variant_name_literal(ctx, variant);
-                splat!(out; [@name_lit.into()] => Ok(Self::[#: variant.name]),);
+                emit_variant_pattern(out, ctx, variant);
+                splat!(out; => {
+                    [emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Str)]
+                    Ok(Self::[#: variant.name])
+                });
             }]
             [if let Some(ov) = other_variant {
                 splat!(out; _ => Ok(Self::[#: ov.name]),);
@@ -1022,8 +2532,202 @@ fn enum_from_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVarian
             }]
         }
     );
+}
+
+/// Where the name of a variant was read from, for deprecation warnings.
+enum VariantSite {
+    /// A string `s` from `__item`.
+    Str,
+    /// The single `key` of an externally tagged table, holding `value`.
+    Key,
+    /// The string `__tag` from `__tag_item`.
+    Tag,
+}
+
+fn has_deprecated_variant_aliases(variants: &[EnumVariant]) -> bool {
+    variants
+        .iter()
+        .any(|variant| variant.attr.has_deprecated_aliases(FROM_TOML))
+}
+
+/// Emits the pattern matching the name of `variant` or any of its aliases.
+fn emit_variant_pattern(out: &mut RustWriter, ctx: &Ctx, variant: &EnumVariant) {
+    splat!(out; [@variant_name_literal(ctx, variant).into()]);
+    variant.attr.for_each_alias(FROM_TOML, &mut |alias| {
+        splat!(out; | [@alias.clone().into()]);
+    });
+    variant
+        .attr
+        .for_each_deprecated_alias(FROM_TOML, &mut |_, alias| {
+            splat!(out; | [@alias.clone().into()]);
+        });
+}
+
+/// Emits a deprecation warning for each deprecated alias of `variant`,
+/// reported if the name read at `site` is that alias.
+fn emit_deprecated_variant_checks(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    variant: &EnumVariant,
+    site: VariantSite,
+) {
+    let new = variant_name_literal(ctx, variant);
+    variant
+        .attr
+        .for_each_deprecated_alias(FROM_TOML, &mut |tag, alias| {
+            let zero = [TokenTree::Literal(Literal::u32_suffixed(0))];
+            let tag = tag.unwrap_or(&zero);
+            match site {
+                VariantSite::Str => splat!(out;
+                    if s == [@alias.clone().into()] {
+                        __ctx.report_deprecated_variant(
+                            [~tag], &[@alias.clone().into()], &[@new.clone().into()], __item);
+                    }
+                ),
+                VariantSite::Key => splat!(out;
+                    if key.name == [@alias.clone().into()] {
+                        __ctx.report_deprecated_field(
+                            [~tag], &[@alias.clone().into()], &[@new.clone().into()], key.span, value);
+                    }
+                ),
+                VariantSite::Tag => splat!(out;
+                    if __tag == [@alias.clone().into()] {
+                        __ctx.report_deprecated_variant(
+                            [~tag], &[@alias.clone().into()], &[@new.clone().into()], __tag_item);
+                    }
+                ),
+            }
+        });
+}
+
+/// Computes the integer each unit variant is encoded as, following Rust's
+/// rules for implicit discriminants.
+fn unit_discriminants(variants: &[EnumVariant]) -> Vec<i64> {
+    fn parse_int(lit: &Literal) -> Option<i128> {
+        let text = lit.to_string().replace('_', "");
+        let (radix, digits) = match text.get(..2) {
+            Some("0x") => (16, &text[2..]),
+            Some("0o") => (8, &text[2..]),
+            Some("0b") => (2, &text[2..]),
+            _ => (10, &text[..]),
+        };
+        let digits = digits.split(['i', 'u']).next()?;
+        i128::from_str_radix(digits, radix).ok()
+    }
+
+    let mut values = Vec::with_capacity(variants.len());
+    let mut next = 0i128;
+    for variant in variants {
+        let value = match variant.discriminant {
+            [] => Some(next),
+            [TokenTree::Literal(lit)] => parse_int(lit),
+            [TokenTree::Punct(neg), TokenTree::Literal(lit)] if neg.as_char() == '-' => {
+                parse_int(lit).map(|value| -value)
+            }
+            _ => None,
+        };
+        let Some(value) = value else {
+            throw!("repr = \"int\" requires integer literal discriminants" @ variant.name.span())
+        };
+        let Ok(int) = i64::try_from(value) else {
+            throw!("Discriminant does not fit in an i64" @ variant.name.span())
+        };
+        values.push(int);
+        next = value + 1;
+    }
+    values
+}
+
+fn int_literal(out: &mut RustWriter, value: i64) {
+    if value < 0 {
+        out.tt_punct_alone('-');
+    }
+    out.buf.push(TokenTree::Literal(Literal::u64_unsuffixed(
+        value.unsigned_abs(),
+    )));
+}
+
+fn enum_from_toml_int(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], values: &[i64]) {
+    let start = out.buf.len();
+    if matches!(ctx.target.unit_repr, UnitRepr::StrOrInt) {
+        splat!(out;
+            if let Some(s) = __item.as_str() {
+                return [emit_unit_name_match(out, ctx, variants)];
+            }
+            if __item.kind() != [#ctx.crate_path]::Kind::Integer {
+                return Err(__ctx.report_expected_but_found(
+                    &[@TokenTree::Literal(Literal::string("a string or integer"))], __item));
+            }
+        );
+    }
+    splat!(out;
+        let Ok(__value) = <i64 as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(
+            __ctx, __item
+        ) else
+    );
+    emit_failed_return(out, ctx);
+
+    let mut known: Vec<i64> = Vec::new();
+    for (variant, value) in variants.iter().zip(values) {
+        if !variant.other {
+            known.push(*value);
+        }
+    }
+    known.sort_unstable();
+    let contiguous = match (known.first(), known.last()) {
+        (Some(min), Some(max)) => *max as i128 - *min as i128 + 1 == known.len() as i128,
+        _ => false,
+    };
+    splat!(out;
+        match __value {
+            [for (variant, value) in variants.iter().zip(values) {
+                if variant.other { continue; }
+                splat!(out; [int_literal(out, *value)] => Ok(Self::[#: variant.name]),);
+            }]
+            [if let Some(ov) = find_other_variant(variants) {
+                splat!(out; _ => Ok(Self::[#: ov.name]),);
+            } else if contiguous {
+                let name = Literal::string(&ctx.target.name.to_string());
+                let mut range = known[0].to_string();
+                range.push_str("..=");
+                range.push_str(&known[known.len() - 1].to_string());
+                let range = Literal::string(&range);
+                splat!(out;
+                    _ => Err(__ctx.report_out_of_range(&[@name.into()], &[@range.into()], __item)),
+                );
+            } else {
+                let expected_array = {
+                    let mut ts = TokenStream::new();
+                    for value in &known {
+                        ts.extend([
+                            TokenTree::Literal(Literal::string(&value.to_string())),
+                            TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+                        ]);
+                    }
+                    TokenTree::Group(Group::new(Delimiter::Bracket, ts))
+                };
+                splat!(out;
+                    _ => Err(__ctx.report_unexpected_value(&[@expected_array], __item)),
+                );
+            }]
+        }
+    );
+    let body = out.split_off_stream(start);
+    impl_from_toml(out, ctx, body, &[]);
+}
+
+fn enum_to_toml_int(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], values: &[i64]) {
+    let start = out.buf.len();
+    splat!(out;
+        let __value: i64 = match self {
+            [for (variant, value) in variants.iter().zip(values) {
+                splat!(out; Self::[#: variant.name] => [int_literal(out, *value)],);
+            }]
+        };
+        Ok([#ctx.crate_path]::Item::from(__value))
+    );
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_to_toml(out, ctx, body, &[]);
 }
 
 fn enum_to_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
@@ -1037,7 +2741,7 @@ fn enum_to_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]
         }))
     );
     let body = out.split_off_stream(start);
-    impl_to_toml(out, ctx, body);
+    impl_to_toml(out, ctx, body, &[]);
 }
 
 fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode: &TagMode) {
@@ -1177,7 +2881,7 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
 
     out.tt_group(Delimiter::Brace, arms_at);
     let body = out.split_off_stream(start);
-    impl_to_toml(out, ctx, body);
+    impl_to_toml(out, ctx, body, &[]);
 }
 
 fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
@@ -1194,8 +2898,11 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
             return match s {
                 [for variant in variants {
                     if matches!(variant.kind, EnumKind::None) && !variant.other {
-                        let name_lit = variant_name_literal(ctx, variant);
-                        splat!(out; [@name_lit.into()] => Ok(Self::[#: variant.name]),);
+                        emit_variant_pattern(out, ctx, variant);
+                        splat!(out; => {
+                            [emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Str)]
+                            Ok(Self::[#: variant.name])
+                        });
                     }
                 }]
                 [emit_wildcard_arm(out, ctx, other_variant, "a known variant")]
@@ -1235,15 +2942,16 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
             if matches!(variant.kind, EnumKind::None) || variant.other {
                 continue;
             }
-            let name_lit = variant_name_literal(ctx, variant);
             match variant.kind {
                 EnumKind::Tuple => {
                     if variant.fields.len() != 1 {
                         throw!("Only single-field tuple variants are supported in external tagging")
                     }
                     let field = &variant.fields[0];
-                    splat!(out; [@name_lit.into()] =>);
+                    emit_variant_pattern(out, ctx, variant);
+                    splat!(out; =>);
                     let arm_at = out.buf.len();
+                    emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Key);
                     if let Some(with) = field.with(FROM_TOML) {
                         splat!(out; let Ok(__val) = [~with]::from_toml(__ctx, value) else);
                     } else {
@@ -1254,8 +2962,10 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                     out.tt_group(Delimiter::Brace, arm_at);
                 }
                 EnumKind::Struct => {
-                    splat!(out; [@name_lit.into()] =>);
+                    emit_variant_pattern(out, ctx, variant);
+                    splat!(out; =>);
                     let arm_at = out.buf.len();
+                    emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Key);
                     splat!(out; let __item = value;);
                     splat!(out; let Ok(__subtable) = value.require_table(__ctx) else);
                     emit_failed_return(out, ctx);
@@ -1283,7 +2993,7 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
     }
 
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
 }
 
 fn enum_from_toml_internal(
@@ -1302,7 +3012,11 @@ fn enum_from_toml_internal(
     emit_failed_return(out, ctx);
 
     // First pass: find tag
-    splat!(out; let mut __tag: Option<&str> = None;);
+    let deprecated = has_deprecated_variant_aliases(variants);
+    splat!(out;
+        let mut __tag: Option<&str> = None;
+        [?(deprecated) let mut __tag_item = __item;]
+    );
     emit_for_table_header(out, "__table");
     let tag_body_at = out.buf.len();
     splat!(out;
@@ -1310,6 +3024,7 @@ fn enum_from_toml_internal(
             let Ok(__t) = <&str as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(__ctx, __value) else
             [emit_failed_return(out, ctx)]
             __tag = Some(__t);
+            [?(deprecated) __tag_item = __value;]
             break;
         }
     );
@@ -1332,11 +3047,12 @@ fn enum_from_toml_internal(
         if variant.other {
             continue;
         }
-        let name_lit = variant_name_literal(ctx, variant);
         match variant.kind {
             EnumKind::None => {
-                splat!(out; [@name_lit.into()] =>);
+                emit_variant_pattern(out, ctx, variant);
+                splat!(out; =>);
                 let arm_at = out.buf.len();
+                emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag);
                 emit_for_table_header(out, "__table");
                 let check_at = out.buf.len();
                 match &ctx.target.unknown_fields {
@@ -1367,8 +3083,10 @@ fn enum_from_toml_internal(
                 out.tt_group(Delimiter::Brace, arm_at);
             }
             EnumKind::Struct => {
-                splat!(out; [@name_lit.into()] =>);
+                emit_variant_pattern(out, ctx, variant);
+                splat!(out; =>);
                 let arm_at = out.buf.len();
+                emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag);
                 splat!(out; let __subtable = __table;);
                 emit_table_field_deser(
                     out,
@@ -1388,7 +3106,7 @@ fn enum_from_toml_internal(
     out.tt_group(Delimiter::Brace, arms_at);
 
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
 }
 
 fn enum_from_toml_adjacent(
@@ -1402,8 +3120,10 @@ fn enum_from_toml_adjacent(
 
     splat!(out; let Ok(__table) = __item.require_table(__ctx) else);
     emit_failed_return(out, ctx);
+    let deprecated = has_deprecated_variant_aliases(variants);
     splat!(out;
         let mut __tag: Option<&str> = None;
+        [?(deprecated) let mut __tag_item = __item;]
         let mut __content: Option<& [#ctx.crate_path]::Item<#[#: &ctx.lifetime]> > = None;
     );
 
@@ -1417,12 +3137,13 @@ fn enum_from_toml_adjacent(
             let Ok(__t) = <&str as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(__ctx, __value) else
             [emit_failed_return(out, ctx)]
             __tag = Some(__t);
+            [?(deprecated) __tag_item = __value;]
         }
         [@content_lit.clone().into()] => {
             __content = Some(__value);
         }
     );
-    emit_unknown_field_arm(out, ctx);
+    emit_unknown_field_arm(out, ctx, &[tag_lit.clone(), content_lit.clone()], &[]);
     out.tt_group(Delimiter::Brace, extract_arms_at);
     out.tt_group(Delimiter::Brace, for_body_at);
 
@@ -1443,14 +3164,19 @@ fn enum_from_toml_adjacent(
         if variant.other {
             continue;
         }
-        let name_lit = variant_name_literal(ctx, variant);
         match variant.kind {
             EnumKind::None => {
-                splat!(out; [@name_lit.into()] => { Ok(Self::[#: variant.name]) });
+                emit_variant_pattern(out, ctx, variant);
+                splat!(out; => {
+                    [emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag)]
+                    Ok(Self::[#: variant.name])
+                });
             }
             EnumKind::Tuple | EnumKind::Struct => {
-                splat!(out; [@name_lit.into()] =>);
+                emit_variant_pattern(out, ctx, variant);
+                splat!(out; =>);
                 let arm_at = out.buf.len();
+                emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag);
 
                 // Require content
                 splat!(out; let Some(__content) = __content else);
@@ -1500,20 +3226,97 @@ fn enum_from_toml_adjacent(
     out.tt_group(Delimiter::Brace, arms_at);
 
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
+}
+
+/// Describes the shape a variant of an untagged enum accepts, for the error
+/// reported when no variant matches.
+fn untagged_expecting(ctx: &Ctx, variant: &EnumVariant) -> Literal {
+    if let Some(expecting) = &variant.expecting {
+        return expecting.clone();
+    }
+    let mut text = String::new();
+    match variant.kind {
+        EnumKind::None => {
+            let name = variant_name_literal(ctx, variant);
+            let crate::lit::InlineKind::String(name) = crate::lit::literal_inline(name.to_string())
+            else {
+                throw!("Expected a string literal" @ name.span())
+            };
+            text.push('"');
+            text.push_str(&name);
+            text.push('"');
+        }
+        EnumKind::Tuple => {
+            text.push('`');
+            for tok in variant.fields[0].ty {
+                text.push_str(&tok.to_string());
+            }
+            text.push('`');
+        }
+        EnumKind::Struct => {
+            text.push_str("table");
+            let mut keys = Vec::new();
+            for field in variant.fields {
+                if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
+                    keys.push(field_name_lit(ctx, field, Some(variant)));
+                }
+            }
+            for (i, key) in keys.iter().enumerate() {
+                text.push_str(if i == 0 {
+                    " with `"
+                } else if i + 1 == keys.len() {
+                    " or `"
+                } else {
+                    ", `"
+                });
+                let crate::lit::InlineKind::String(key) =
+                    crate::lit::literal_inline(key.to_string())
+                else {
+                    throw!("Expected a string literal" @ key.span())
+                };
+                text.push_str(&key);
+                text.push('`');
+            }
+        }
+    }
+    Literal::string(&text)
 }
 
 fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
     let start = out.buf.len();
 
+    // Errors of each variant that fails stay in `__ctx.errors` from
+    // `__start`, with `__attempts` recording where each attempt began. They
+    // are removed once a variant matches, or reported together as notes if
+    // none does. A variant whose `final_if` predicate holds reports its own
+    // errors instead, as does a lone non-unit variant declared last.
+    let mut expecting = Vec::new();
+    let mut candidates = 0;
+    for variant in variants {
+        expecting.push(untagged_expecting(ctx, variant));
+        if variant.final_if.is_none() && !matches!(variant.kind, EnumKind::None) {
+            candidates += 1;
+        }
+    }
     let last_index = variants.len() - 1;
-    let last_is_unhinted =
-        variants[last_index].try_if.is_none() && variants[last_index].final_if.is_none();
+    let last = &variants[last_index];
+    let single = candidates == 1
+        && last.try_if.is_none()
+        && last.final_if.is_none()
+        && !matches!(last.kind, EnumKind::None);
+    let any_attempts = candidates > 0 && !single;
+    if any_attempts {
+        splat!(out;
+            let __start = __ctx.errors.len();
+            let mut __attempts = ::std::vec::Vec::new();
+        );
+    }
 
-    for (i, variant) in variants.iter().enumerate() {
-        let has_hint = variant.try_if.is_some() || variant.final_if.is_some();
-        let is_last = i == last_index && !has_hint;
-        let propagate = is_last || variant.final_if.is_some();
+    // Whether an earlier variant may have failed, leaving errors to discard.
+    let mut attempted = false;
+    for (i, (variant, expecting)) in variants.iter().zip(&expecting).enumerate() {
+        let propagate = variant.final_if.is_some() || (single && i == last_index);
         let name_lit = variant_name_literal(ctx, variant);
 
         let inner_start = out.buf.len();
@@ -1522,11 +3325,15 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                 splat!(out;
                     if let Some(__s) = __item.as_str() {
                         if __s == [@name_lit.into()] {
+                            [?(attempted) __ctx.errors.truncate(__start);]
                             return Ok(Self::[#: variant.name]);
                         }
                     }
-                    [?(propagate) return Err(__ctx.report_expected_but_found(
-                        &[@TokenTree::Literal(Literal::string("a matching variant"))], __item));]
+                    [?(propagate)
+                        [?(attempted) __ctx.errors.truncate(__start);]
+                        return Err(__ctx.report_expected_but_found(
+                            &[@TokenTree::Literal(Literal::string("a matching variant"))], __item));
+                    ]
                 );
             }
             EnumKind::Tuple => {
@@ -1534,30 +3341,38 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                     throw!("Only single-field tuple variants are supported in untagged enums")
                 }
                 let field = &variant.fields[0];
-                let match_start = out.buf.len();
+                let call_start = out.buf.len();
                 if let Some(with) = field.with(FROM_TOML) {
+                    splat!(out; [~with]::from_toml(__ctx, __item));
+                } else {
+                    splat!(out; < [~field.ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(__ctx, __item));
+                }
+                let call = out.split_off_stream(call_start);
+                if propagate {
                     splat!(out;
-                        match [~with]::from_toml(__ctx, __item) {
+                        [?(attempted) __ctx.errors.truncate(__start);]
+                        match [@TokenTree::Group(Group::new(Delimiter::None, call))] {
                             Ok(__val) => return Ok(Self::[#: variant.name](__val)),
-                            [?(propagate) Err(__e) => return Err(__e),]
-                            [?(!propagate) Err(_) => { __ctx.errors.truncate(__err_len); }]
+                            Err(__e) => return Err(__e),
                         }
                     );
                 } else {
-                    splat!(out;
-                        match < [~field.ty] as [#ctx.crate_path]::FromToml<#[#: &ctx.lifetime]> >::from_toml(__ctx, __item) {
-                            Ok(__val) => return Ok(Self::[#: variant.name](__val)),
-                            [?(propagate) Err(__e) => return Err(__e),]
-                            [?(!propagate) Err(_) => { __ctx.errors.truncate(__err_len); }]
+                    splat!(out; {
+                        let __err_len = __ctx.errors.len();
+                        match [@TokenTree::Group(Group::new(Delimiter::None, call))] {
+                            Ok(__val) => {
+                                [?(attempted) __ctx.errors.drain(__start..__err_len);]
+                                return Ok(Self::[#: variant.name](__val));
+                            }
+                            Err(_) => __attempts.push(([@expecting.clone().into()], __err_len)),
                         }
-                    );
-                }
-                if !propagate {
-                    let match_body = out.split_off_stream(match_start);
-                    splat!(out; { let __err_len = __ctx.errors.len(); [@TokenTree::Group(Group::new(Delimiter::None, match_body))] });
+                    });
                 }
             }
             EnumKind::Struct => {
+                if propagate && attempted {
+                    splat!(out; __ctx.errors.truncate(__start););
+                }
                 let body_start = out.buf.len();
                 splat!(out; let Ok(__subtable) = __item.require_table(__ctx) else);
                 emit_failed_return(out, ctx);
@@ -1579,13 +3394,19 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                         let __result: ::std::result::Result<Self, [#ctx.crate_path]::Failed> =
                             (|| [@closure_body_group]) ();
                         match __result {
-                            Ok(__val) => return Ok(__val),
-                            Err(_) => { __ctx.errors.truncate(__err_len); }
+                            Ok(__val) => {
+                                [?(attempted) __ctx.errors.drain(__start..__err_len);]
+                                return Ok(__val);
+                            }
+                            Err(_) => __attempts.push(([@expecting.clone().into()], __err_len)),
                         }
                     });
                 }
             }
         }
+        if !propagate && !matches!(variant.kind, EnumKind::None) {
+            attempted = true;
+        }
 
         if let Some(predicate) = variant.try_if.as_deref().or(variant.final_if.as_deref()) {
             let inner_code = out.split_off_stream(inner_start);
@@ -1603,30 +3424,62 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
         }
     }
 
-    if !last_is_unhinted {
-        splat!(out;
-            Err(__ctx.report_expected_but_found(
-                &[@TokenTree::Literal(Literal::string("a matching variant"))], __item))
-        );
+    if !single {
+        splat!(out; Err);
+        let err_at = out.buf.len();
+        splat!(out; __ctx.report_no_matching_variant);
+        let args_at = out.buf.len();
+        splat!(out; &);
+        let list_at = out.buf.len();
+        for lit in &expecting {
+            splat!(out; [@lit.clone().into()],);
+        }
+        out.tt_group(Delimiter::Bracket, list_at);
+        splat!(out; , &);
+        if any_attempts {
+            splat!(out; __attempts);
+        } else {
+            out.tt_group_empty(Delimiter::Bracket);
+        }
+        splat!(out; , __item);
+        out.tt_group(Delimiter::Parenthesis, args_at);
+        out.tt_group(Delimiter::Parenthesis, err_at);
     }
-
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
 }
 
 fn handle_enum(output: &mut RustWriter, target: &DeriveTargetInner, variants: &[EnumVariant]) {
     if target.content.is_some() && target.tag.is_none() {
         throw!("content attribute requires tag to also be set")
     }
+    if !target.exactly_one_of.is_empty() {
+        throw!("exactly_one_of can only be used on structs with named fields")
+    }
+    if target.remote.is_some() {
+        throw!("remote can only be used on structs with named fields")
+    }
     if target.untagged && (target.tag.is_some() || target.content.is_some()) {
         throw!("untagged cannot be combined with tag or content attributes")
     }
 
+    if target.untagged || matches!(target.unit_repr, UnitRepr::Int) {
+        for variant in variants {
+            if variant.attr.has_aliases(FROM_TOML) || variant.attr.has_deprecated_aliases(FROM_TOML)
+            {
+                throw!("alias and deprecated_alias cannot be used on untagged or repr = \"int\" enums" @ variant.name.span())
+            }
+        }
+    }
+
     if !target.untagged {
         for variant in variants {
             if variant.try_if.is_some() || variant.final_if.is_some() {
                 throw!("try_if/final_if can only be used on untagged enums" @ variant.name.span())
             }
+            if variant.expecting.is_some() {
+                throw!("expecting can only be used on untagged enums" @ variant.name.span())
+            }
         }
     }
 
@@ -1648,18 +3501,39 @@ fn handle_enum(output: &mut RustWriter, target: &DeriveTargetInner, variants: &[
         }
     }
 
-    let ctx = Ctx::new(output, target);
+    reject_named_struct_attrs(target, &[]);
+    for variant in variants {
+        reject_named_struct_attrs(target, variant.fields);
+    }
 
     let is_string_enum = !target.untagged
         && target.tag.is_none()
         && target.enum_flags & ENUM_CONTAINS_UNIT_VARIANT != 0
         && target.enum_flags & (ENUM_CONTAINS_STRUCT_VARIANT | ENUM_CONTAINS_TUPLE_VARIANT) == 0;
 
+    let values = if target.unit_repr.is_str() {
+        Vec::new()
+    } else if is_string_enum {
+        unit_discriminants(variants)
+    } else {
+        throw!(
+            "repr = \"int\" and \"str_or_int\" require an enum with only unit variants and no tag"
+        )
+    };
+
+    let ctx = Ctx::new(output, target);
+    if target.schema {
+        schema_enum(output, &ctx, variants, &values);
+    }
+
     if target.from_toml && !emit_proxy_from_toml(output, &ctx) {
         if target.untagged {
             enum_from_toml_untagged(output, &ctx, variants);
         } else {
             match (&target.tag, &target.content) {
+                (None, _) if !values.is_empty() => {
+                    enum_from_toml_int(output, &ctx, variants, &values)
+                }
                 (None, _) if is_string_enum => enum_from_toml_string(output, &ctx, variants),
                 (None, _) => enum_from_toml_external(output, &ctx, variants),
                 (Some(tag_lit), None) => enum_from_toml_internal(output, &ctx, variants, tag_lit),
@@ -1670,8 +3544,10 @@ fn handle_enum(output: &mut RustWriter, target: &DeriveTargetInner, variants: &[
         }
     }
 
-    if target.to_toml {
-        if is_string_enum {
+    if target.to_toml && !emit_proxy_to_toml(output, &ctx) {
+        if matches!(target.unit_repr, UnitRepr::Int) {
+            enum_to_toml_int(output, &ctx, variants, &values);
+        } else if is_string_enum {
             enum_to_toml_string(output, &ctx, variants);
         } else {
             let mode = if target.untagged {
@@ -1708,25 +3584,89 @@ pub fn inner_derive(stream: TokenStream) -> TokenStream {
         untagged: false,
         from_type: None,
         try_from_type: None,
+        into_type: None,
+        try_into_type: None,
+        remote: None,
+        vis: Vec::new(),
+        doc_comments: false,
+        schema: false,
+        update: false,
+        skip_if_default: false,
+        has_key_span: false,
+        docs: Vec::new(),
         unknown_fields: UnknownFieldPolicy::Warn { tag: None },
+        key_match: KeyMatch::Exact,
+        unit_repr: UnitRepr::Str,
         recoverable: false,
+        validate: None,
+        exactly_one_of: Vec::new(),
+        migrations: None,
+        partial: None,
     };
     let (kind, body) = ast::extract_derive_target(&mut target, &outer_tokens);
 
+    if !target.unit_repr.is_str() && !matches!(kind, DeriveTargetKind::Enum) {
+        throw!("repr can only be used on enums")
+    }
+
     if !(target.from_toml || target.to_toml) {
         target.from_toml = true;
     }
+    if target.update {
+        if !matches!(kind, DeriveTargetKind::Struct) {
+            throw!("Update can only be used on structs with named fields")
+        }
+        if !target.from_toml {
+            throw!("Update requires FromToml")
+        }
+        if !target.generics.is_empty() {
+            throw!("Update cannot be used on generic types")
+        }
+        if target.remote.is_some()
+            || target.transparent_impl
+            || target.from_type.is_some()
+            || target.try_from_type.is_some()
+        {
+            throw!("Update cannot be combined with remote, transparent, from, or try_from")
+        }
+    }
+    if target.doc_comments {
+        if !matches!(kind, DeriveTargetKind::Struct) {
+            throw!("doc_comments can only be used on structs with named fields")
+        }
+        if !target.to_toml {
+            throw!("doc_comments requires ToToml")
+        }
+        if !target.schema {
+            throw!("doc_comments requires Schema")
+        }
+        if target.remote.is_some()
+            || target.transparent_impl
+            || target.into_type.is_some()
+            || target.try_into_type.is_some()
+        {
+            throw!("doc_comments cannot be combined with remote, transparent, into, or try_into")
+        }
+    }
     let field_toks: Vec<TokenTree> = body.into_iter().collect();
     let mut tt_buf = Vec::<TokenTree>::new();
     let mut field_buf = Vec::<Field>::new();
     let mut pool = MemoryPool::<FieldAttrs>::new();
     let mut attr_buf = pool.allocator();
     let mut rust_writer = RustWriter::new();
+    // Items that must be nameable, emitted outside of the `const` block.
+    let mut outer = TokenStream::new();
     match kind {
         DeriveTargetKind::Struct => {
             ast::parse_struct_fields(&mut field_buf, &field_toks, &mut attr_buf);
             ast::scan_fields(&mut target, &mut field_buf);
             handle_struct(&mut rust_writer, &target, &field_buf);
+            if let Some(partial) = &target.partial {
+                let mut writer = RustWriter::new();
+                let ctx = Ctx::new(&mut writer, &target);
+                partial_struct(&mut writer, &ctx, partial, &field_buf);
+                outer = writer.split_off_stream(0);
+            }
         }
         DeriveTargetKind::TupleStruct => {
             let t = Ident::new("a", Span::call_site());
@@ -1746,11 +3686,13 @@ pub fn inner_derive(stream: TokenStream) -> TokenStream {
         }
     }
     let ts = rust_writer.split_off_stream(0);
-    token_stream!(
-        (&mut rust_writer);
+    rust_writer.buf.extend(outer);
+    let out = &mut rust_writer;
+    splat!(out;
         ~[[allow(clippy::question_mark)]]
         const _: () = [@TokenTree::Group(Group::new(Delimiter::Brace, ts))];
-    )
+    );
+    rust_writer.split_off_stream(0)
 }
 
 pub fn derive(stream: TokenStream) -> TokenStream {
@@ -29,6 +29,74 @@ impl UnknownFieldPolicy {
         }
     }
 }
+/// Mirrors `toml_spanner::KeyMatch`, named by the variant it generates.
+pub enum KeyMatch {
+    Exact,
+    IgnoreCase,
+    IgnoreSeparators,
+    IgnoreCaseAndSeparators,
+}
+impl KeyMatch {
+    pub fn is_exact(&self) -> bool {
+        match self {
+            KeyMatch::Exact => true,
+            _ => false,
+        }
+    }
+    /// The spelling of the rule in `key_match = "..."`.
+    pub fn attr_value(&self) -> &'static str {
+        match self {
+            KeyMatch::Exact => "exact",
+            KeyMatch::IgnoreCase => "ignore_case",
+            KeyMatch::IgnoreSeparators => "ignore_separators",
+            KeyMatch::IgnoreCaseAndSeparators => "ignore_case_and_separators",
+        }
+    }
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            KeyMatch::Exact => "Exact",
+            KeyMatch::IgnoreCase => "IgnoreCase",
+            KeyMatch::IgnoreSeparators => "IgnoreSeparators",
+            KeyMatch::IgnoreCaseAndSeparators => "IgnoreCaseAndSeparators",
+        }
+    }
+    /// The form two keys must share to match, used to detect fields that
+    /// would be ambiguous.
+    pub fn normalize(&self, key: &str) -> String {
+        let mut out = String::with_capacity(key.len());
+        for ch in key.chars() {
+            match self {
+                KeyMatch::IgnoreSeparators | KeyMatch::IgnoreCaseAndSeparators
+                    if ch == '-' || ch == '_' => {}
+                KeyMatch::IgnoreCase | KeyMatch::IgnoreCaseAndSeparators => {
+                    out.push(ch.to_ascii_lowercase())
+                }
+                _ => out.push(ch),
+            }
+        }
+        out
+    }
+}
+/// Encoding of an enum with only unit variants, selected with `repr = "..."`.
+pub enum UnitRepr {
+    Str,
+    Int,
+    StrOrInt,
+}
+impl UnitRepr {
+    pub fn is_str(&self) -> bool {
+        match self {
+            UnitRepr::Str => true,
+            _ => false,
+        }
+    }
+    pub fn reads_str(&self) -> bool {
+        match self {
+            UnitRepr::Str | UnitRepr::StrOrInt => true,
+            _ => false,
+        }
+    }
+}
 pub enum DeriveTargetKind {
     TupleStruct,
     Struct,
@@ -56,10 +124,32 @@ enum FieldAttrInner {
         alias: Literal,
     },
     Style(Ident),
+    Validate(Vec<TokenTree>),
+    Constraint(Constraint),
+    ConflictsWith(Vec<Ident>),
+    Requires(Vec<Ident>),
+    SourceSpan(SpanSource),
+    Version,
+    Partial,
+    Update,
+    SkipIfDefault,
+}
+pub enum SpanSource {
+    Table,
+    Key,
+}
+pub enum Constraint {
+    Range(Vec<TokenTree>),
+    MinLen(Vec<TokenTree>),
+    MaxLen(Vec<TokenTree>),
+    NonEmpty,
+    OneOf(Vec<TokenTree>),
 }
 pub struct FieldAttrs {
     attrs: Vec<FieldAttr>,
     flags: u64,
+    /// Values of the field's `#[doc = "..."]` attributes.
+    pub docs: Vec<Literal>,
 }
 impl FieldAttrs {
     pub fn rename(&self, for_trait: TraitSet) -> Option<&Literal> {
@@ -123,6 +213,28 @@ impl FieldAttrs {
             }
         }
     }
+    pub fn for_each_conflict(&self, for_trait: TraitSet, f: &mut dyn FnMut(&Ident)) {
+        for attr in &self.attrs {
+            if attr.enabled & for_trait != 0 {
+                if let FieldAttrInner::ConflictsWith(others) = &attr.inner {
+                    for other in others {
+                        f(other);
+                    }
+                }
+            }
+        }
+    }
+    pub fn for_each_requirement(&self, for_trait: TraitSet, f: &mut dyn FnMut(&Ident)) {
+        for attr in &self.attrs {
+            if attr.enabled & for_trait != 0 {
+                if let FieldAttrInner::Requires(others) = &attr.inner {
+                    for other in others {
+                        f(other);
+                    }
+                }
+            }
+        }
+    }
 }
 #[allow(clippy::derivable_impls)]
 impl Default for FieldAttrs {
@@ -130,6 +242,7 @@ impl Default for FieldAttrs {
         Self {
             attrs: Default::default(),
             flags: 0,
+            docs: Vec::new(),
         }
     }
 }
@@ -155,8 +268,24 @@ pub struct DeriveTargetInner<'a> {
     pub untagged: bool,
     pub from_type: Option<Vec<TokenTree>>,
     pub try_from_type: Option<Vec<TokenTree>>,
+    pub into_type: Option<Vec<TokenTree>>,
+    pub try_into_type: Option<Vec<TokenTree>>,
+    pub remote: Option<Vec<TokenTree>>,
+    pub vis: Vec<TokenTree>,
+    pub doc_comments: bool,
+    pub schema: bool,
+    pub update: bool,
+    pub skip_if_default: bool,
+    pub has_key_span: bool,
+    pub docs: Vec<Literal>,
     pub unknown_fields: UnknownFieldPolicy,
+    pub key_match: KeyMatch,
+    pub unit_repr: UnitRepr,
     pub recoverable: bool,
+    pub validate: Option<Vec<TokenTree>>,
+    pub exactly_one_of: Vec<Vec<Ident>>,
+    pub migrations: Option<Vec<Vec<TokenTree>>>,
+    pub partial: Option<Ident>,
 }
 impl<'a> DeriveTargetInner<'a> {
     pub fn has_lifetime(&self) -> bool {
@@ -209,6 +338,91 @@ impl<'a> Field<'a> {
         }
         None
     }
+    pub fn validate(&self, for_trait: TraitSet) -> Option<&[TokenTree]> {
+        for attr in &self.attr.attrs {
+            if attr.enabled & for_trait != 0 {
+                if let FieldAttrInner::Validate(validate) = &attr.inner {
+                    return Some(validate);
+                }
+            }
+        }
+        None
+    }
+    pub fn for_each_constraint(&self, for_trait: TraitSet, f: &mut dyn FnMut(&'a Constraint)) {
+        let attrs: &'a FieldAttrs = self.attr;
+        for attr in &attrs.attrs {
+            if attr.enabled & for_trait != 0 {
+                if let FieldAttrInner::Constraint(constraint) = &attr.inner {
+                    f(constraint);
+                }
+            }
+        }
+    }
+    pub fn has_checks(&self, for_trait: TraitSet) -> bool {
+        for attr in &self.attr.attrs {
+            if attr.enabled & for_trait != 0
+                && match attr.inner {
+                    FieldAttrInner::Validate(_) | FieldAttrInner::Constraint(_) => true,
+                    _ => false,
+                }
+            {
+                return true;
+            }
+        }
+        false
+    }
+    pub fn span_source(&self) -> Option<&SpanSource> {
+        for attr in &self.attr.attrs {
+            if let FieldAttrInner::SourceSpan(source) = &attr.inner {
+                return Some(source);
+            }
+        }
+        None
+    }
+    pub fn is_version(&self) -> bool {
+        for attr in &self.attr.attrs {
+            if match attr.inner {
+                FieldAttrInner::Version => true,
+                _ => false,
+            } {
+                return true;
+            }
+        }
+        false
+    }
+    pub fn is_partial(&self) -> bool {
+        for attr in &self.attr.attrs {
+            if match attr.inner {
+                FieldAttrInner::Partial => true,
+                _ => false,
+            } {
+                return true;
+            }
+        }
+        false
+    }
+    pub fn is_skip_if_default(&self) -> bool {
+        for attr in &self.attr.attrs {
+            if match attr.inner {
+                FieldAttrInner::SkipIfDefault => true,
+                _ => false,
+            } {
+                return true;
+            }
+        }
+        false
+    }
+    pub fn is_update(&self) -> bool {
+        for attr in &self.attr.attrs {
+            if match attr.inner {
+                FieldAttrInner::Update => true,
+                _ => false,
+            } {
+                return true;
+            }
+        }
+        false
+    }
     pub fn style(&self, for_trait: TraitSet) -> Option<&Ident> {
         for attr in &self.attr.attrs {
             if attr.enabled & for_trait != 0 {
@@ -246,7 +460,12 @@ pub struct EnumVariant<'a> {
     pub rename_all: RenameRule,
     pub try_if: Option<Vec<TokenTree>>,
     pub final_if: Option<Vec<TokenTree>>,
+    /// Description of the accepted shape used when an untagged enum matches no
+    /// variant, from `#[toml(expecting = "...")]`.
+    pub expecting: Option<Literal>,
     pub other: bool,
+    /// Tokens of an explicit `= discriminant`, empty if there is none.
+    pub discriminant: &'a [TokenTree],
     buf_start: usize,
     buf_end: usize,
 }
@@ -293,6 +512,35 @@ fn parse_unknown_fields_tag(value: &mut [TokenTree]) -> Option<Vec<TokenTree>> {
     }
     None
 }
+/// Parses `field` or `[field_a, field_b]` into a list of field identifiers.
+fn parse_field_ident_list(attr: &Ident, value: &[TokenTree]) -> Vec<Ident> {
+    let mut idents = Vec::new();
+    match value {
+        [TokenTree::Ident(ident)] => idents.push(ident.clone()),
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            let mut expect_ident = true;
+            for tok in group.stream() {
+                match tok {
+                    TokenTree::Ident(ident) if expect_ident => idents.push(ident),
+                    TokenTree::Punct(p) if !expect_ident && p.as_char() == ',' => (),
+                    _ => Error::span_msg(
+                        "Expected a comma separated list of field names",
+                        tok.span(),
+                    ),
+                }
+                expect_ident = !expect_ident;
+            }
+        }
+        _ => Error::span_msg(
+            "Expected a field name or a list of field names",
+            attr.span(),
+        ),
+    }
+    if idents.is_empty() {
+        Error::span_msg("Expected at least one field name", attr.span())
+    }
+    idents
+}
 fn parse_container_attr(
     target: &mut DeriveTargetInner<'_>,
     attr: Ident,
@@ -313,6 +561,18 @@ fn parse_container_attr(
             target.from_toml = true;
             target.to_toml = true;
         }
+        "doc_comments" => {
+            target.doc_comments = true;
+        }
+        "Schema" => {
+            target.schema = true;
+        }
+        "Update" => {
+            target.update = true;
+        }
+        "skip_if_default" => {
+            target.skip_if_default = true;
+        }
         "rename_all" => {
             if target.rename_all != RenameRule::None {
                 Error::span_msg("Duplicate rename_all attribute", attr.span())
@@ -379,6 +639,26 @@ fn parse_container_attr(
             target.try_from_type = Some(value.to_vec());
             value = &mut [];
         }
+        "into" => {
+            if target.into_type.is_some() {
+                Error::span_msg("Duplicate into attribute", attr.span())
+            }
+            if value.is_empty() {
+                Error::span_msg("Expected a type for into", attr.span())
+            }
+            target.into_type = Some(value.to_vec());
+            value = &mut [];
+        }
+        "try_into" => {
+            if target.try_into_type.is_some() {
+                Error::span_msg("Duplicate try_into attribute", attr.span())
+            }
+            if value.is_empty() {
+                Error::span_msg("Expected a type for try_into", attr.span())
+            }
+            target.try_into_type = Some(value.to_vec());
+            value = &mut [];
+        }
         "deny_unknown_fields" => {
             if !target.unknown_fields.is_default() {
                 Error::span_msg("Duplicate unknown fields policy attribute", attr.span())
@@ -401,12 +681,139 @@ fn parse_container_attr(
             }
             target.unknown_fields = UnknownFieldPolicy::Ignore;
         }
+        "key_match" => {
+            if !target.key_match.is_exact() {
+                Error::span_msg("Duplicate key_match attribute", attr.span())
+            }
+            let [TokenTree::Literal(rule), rest @ ..] = value else {
+                Error::span_msg("Expected a string literal for key_match", attr.span())
+            };
+            value = rest;
+            target.key_match =
+                    match rule.to_string().as_str() {
+                        "\"exact\"" => KeyMatch::Exact,
+                        "\"ignore_case\"" => KeyMatch::IgnoreCase,
+                        "\"ignore_separators\"" => KeyMatch::IgnoreSeparators,
+                        "\"ignore_case_and_separators\"" =>
+                            KeyMatch::IgnoreCaseAndSeparators,
+                        _ =>
+                            Error::span_msg("Unknown key_match, expected one of: \"exact\", \"ignore_case\", \"ignore_separators\", \"ignore_case_and_separators\"",
+                                rule.span()),
+                    };
+        }
+        "repr" => {
+            if !target.unit_repr.is_str() {
+                Error::span_msg("Duplicate repr attribute", attr.span())
+            }
+            let [TokenTree::Literal(repr), rest @ ..] = value else {
+                Error::span_msg("Expected a string literal for repr", attr.span())
+            };
+            value = rest;
+            target.unit_repr = match repr.to_string().as_str() {
+                "\"str\"" => UnitRepr::Str,
+                "\"int\"" => UnitRepr::Int,
+                "\"str_or_int\"" => UnitRepr::StrOrInt,
+                _ => Error::span_msg(
+                    "Unknown repr, expected one of: \"str\", \"int\", \"str_or_int\"",
+                    repr.span(),
+                ),
+            };
+        }
         "recoverable" => {
             if target.recoverable {
                 Error::span_msg("Duplicate recoverable attribute", attr.span())
             }
             target.recoverable = true;
         }
+        "validate" => {
+            if target.validate.is_some() {
+                Error::span_msg("Duplicate validate attribute", attr.span())
+            }
+            if value.is_empty() {
+                Error::span_msg("Expected a function for validate", attr.span())
+            }
+            target.validate = Some(value.to_vec());
+            value = &mut [];
+        }
+        "remote" => {
+            if target.remote.is_some() {
+                Error::span_msg("Duplicate remote attribute", attr.span())
+            }
+            let tokens: Vec<TokenTree> = match value {
+                [TokenTree::Literal(lit)] => match crate::lit::literal_inline(lit.to_string()) {
+                    crate::lit::InlineKind::String(path) => match path.parse::<TokenStream>() {
+                        Ok(stream) => stream.into_iter().collect(),
+                        Err(_) => Error::span_msg("Expected a type path for remote", lit.span()),
+                    },
+                    _ => Error::span_msg("Expected a string literal for remote", lit.span()),
+                },
+                _ => value.to_vec(),
+            };
+            if tokens.is_empty() {
+                Error::span_msg("Expected a type for remote", attr.span())
+            }
+            target.remote = Some(tokens);
+            value = &mut [];
+        }
+        "partial" => {
+            if target.partial.is_some() {
+                Error::span_msg("Duplicate partial attribute", attr.span())
+            }
+            let [TokenTree::Ident(name)] = &*value else {
+                Error::span_msg(
+                    "Expected a name for the partial type, e.g. partial = ConfigLayer",
+                    attr.span(),
+                )
+            };
+            target.partial = Some(name.clone());
+            value = &mut [];
+        }
+        "migrations" => {
+            if target.migrations.is_some() {
+                Error::span_msg("Duplicate migrations attribute", attr.span())
+            }
+            let [TokenTree::Group(group)] = &*value else {
+                Error::span_msg(
+                    "Expected a list of migration functions, e.g. migrations = [v1_to_v2]",
+                    attr.span(),
+                )
+            };
+            if group.delimiter() != Delimiter::Bracket {
+                Error::span_msg(
+                    "Expected a list of migration functions, e.g. migrations = [v1_to_v2]",
+                    attr.span(),
+                )
+            }
+            let mut migrations = Vec::new();
+            let mut current = Vec::new();
+            for tok in group.stream() {
+                match tok {
+                    TokenTree::Punct(p) if p.as_char() == ',' => {
+                        if current.is_empty() {
+                            Error::span_msg("Expected a migration function", p.span())
+                        }
+                        migrations.push(std::mem::take(&mut current));
+                    }
+                    tok => current.push(tok),
+                }
+            }
+            if !current.is_empty() {
+                migrations.push(current);
+            }
+            if migrations.is_empty() {
+                Error::span_msg("Expected at least one migration function", attr.span())
+            }
+            target.migrations = Some(migrations);
+            value = &mut [];
+        }
+        "exactly_one_of" => {
+            let fields = parse_field_ident_list(&attr, value);
+            if fields.len() < 2 {
+                Error::span_msg("exactly_one_of requires at least two fields", attr.span())
+            }
+            target.exactly_one_of.push(fields);
+            value = &mut [];
+        }
         _ => Error::span_msg("Unknown attribute", attr.span()),
     }
     if !value.is_empty() {
@@ -414,6 +821,10 @@ fn parse_container_attr(
     }
 }
 fn extract_container_attr(target: &mut DeriveTargetInner<'_>, stream: TokenStream) {
+    if let Some(doc) = extract_doc_attr(&stream) {
+        target.docs.push(doc);
+        return;
+    }
     let mut toks = stream.into_iter();
     let Some(TokenTree::Ident(ident)) = toks.next() else {
         return;
@@ -457,10 +868,24 @@ pub fn extract_derive_target<'a>(
             }
             _ => continue,
         };
-        let ident = ident.to_string();
-        match ident.as_str() {
+        let name = ident.to_string();
+        match name.as_str() {
             "struct" => break DeriveTargetKind::Struct,
             "enum" => break DeriveTargetKind::Enum,
+            "pub" => {
+                target.vis.push(TokenTree::Ident(ident.clone()));
+                if let Some(TokenTree::Group(group)) = toks.as_slice().first() {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        target.vis.push(
+                            match (toks).next() {
+                                Some(t) => t,
+                                None => Error::msg("Unexpected EOF"),
+                            }
+                            .clone(),
+                        );
+                    }
+                }
+            }
             _ => continue,
         }
     };
@@ -703,6 +1128,22 @@ fn parse_single_field_attr(
             });
             2u64 * TRAIT_COUNT
         }
+        "span" | "key_span" => {
+            if !value.is_empty() {
+                Error::span_msg("span and key_span don't take any arguments", ident.span())
+            }
+            trait_set = FROM_TOML | TO_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: FieldAttrInner::SourceSpan(if name == "span" {
+                    SpanSource::Table
+                } else {
+                    SpanSource::Key
+                }),
+            });
+            2u64 * TRAIT_COUNT
+        }
         "skip_if" => {
             if value.is_empty() {
                 Error::span_msg("Expected a function specifying skip criteria", ident.span())
@@ -760,6 +1201,118 @@ fn parse_single_field_attr(
             });
             5u64 * TRAIT_COUNT
         }
+        "validate" => {
+            if value.is_empty() {
+                Error::span_msg("Expected a function for validate", ident.span())
+            }
+            trait_set &= FROM_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: FieldAttrInner::Validate(std::mem::take(value)),
+            });
+            6u64 * TRAIT_COUNT
+        }
+        "range" | "min_len" | "max_len" | "non_empty" | "one_of" => {
+            let (constraint, slot) = match name.as_str() {
+                "non_empty" => {
+                    if !value.is_empty() {
+                        Error::span_msg("non_empty doesn't take any arguments", ident.span())
+                    }
+                    (Constraint::NonEmpty, 8)
+                }
+                "one_of" => {
+                    let [TokenTree::Group(group)] = &value[..] else {
+                        Error::span_msg(
+                            "Expected a list of values, e.g. one_of = [\"a\", \"b\"]",
+                            ident.span(),
+                        )
+                    };
+                    if group.delimiter() != Delimiter::Bracket {
+                        Error::span_msg(
+                            "Expected a list of values, e.g. one_of = [\"a\", \"b\"]",
+                            ident.span(),
+                        )
+                    }
+                    let values: Vec<TokenTree> = group.stream().into_iter().collect();
+                    for tok in &values {
+                        match tok {
+                            TokenTree::Literal(_) => (),
+                            TokenTree::Punct(p) if p.as_char() == ',' || p.as_char() == '-' => (),
+                            _ => Error::span_msg("one_of only accepts literal values", tok.span()),
+                        }
+                    }
+                    (Constraint::OneOf(values), 10)
+                }
+                _ => {
+                    if value.is_empty() {
+                        Error::span_msg("Expected a value", ident.span())
+                    }
+                    let tokens = std::mem::take(value);
+                    match name.as_str() {
+                        "range" => (Constraint::Range(tokens), 7),
+                        "min_len" => (Constraint::MinLen(tokens), 8),
+                        _ => (Constraint::MaxLen(tokens), 9),
+                    }
+                }
+            };
+            value.clear();
+            trait_set &= FROM_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: FieldAttrInner::Constraint(constraint),
+            });
+            slot * TRAIT_COUNT
+        }
+        "version" => {
+            if !value.is_empty() {
+                Error::span_msg("version doesn't take any arguments", ident.span())
+            }
+            trait_set &= FROM_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: FieldAttrInner::Version,
+            });
+            11u64 * TRAIT_COUNT
+        }
+        "partial" => {
+            if !value.is_empty() {
+                Error::span_msg("partial doesn't take any arguments", ident.span())
+            }
+            trait_set &= FROM_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: FieldAttrInner::Partial,
+            });
+            12u64 * TRAIT_COUNT
+        }
+        "update" => {
+            if !value.is_empty() {
+                Error::span_msg("update doesn't take any arguments", ident.span())
+            }
+            trait_set &= FROM_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: FieldAttrInner::Update,
+            });
+            13u64 * TRAIT_COUNT
+        }
+        "skip_if_default" => {
+            if !value.is_empty() {
+                Error::span_msg("skip_if_default doesn't take any arguments", ident.span())
+            }
+            trait_set &= TO_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: FieldAttrInner::SkipIfDefault,
+            });
+            14u64 * TRAIT_COUNT
+        }
         "required" => {
             if !value.is_empty() {
                 Error::span_msg("required doesn't take any arguments", ident.span())
@@ -808,6 +1361,20 @@ fn parse_single_field_attr(
             });
             return;
         }
+        "conflicts_with" | "requires" => {
+            let others = parse_field_ident_list(&ident, value);
+            trait_set &= FROM_TOML;
+            attrs.attrs.push(FieldAttr {
+                enabled: trait_set,
+                span: ident.span(),
+                inner: if name == "requires" {
+                    FieldAttrInner::Requires(others)
+                } else {
+                    FieldAttrInner::ConflictsWith(others)
+                },
+            });
+            return;
+        }
         _ => Error::span_msg("Unknown attr field", ident.span()),
     };
     let mask = (trait_set as u64) << offset;
@@ -816,6 +1383,19 @@ fn parse_single_field_attr(
     }
     attrs.flags |= mask;
 }
+/// Returns the literal of a `doc = "..."` attribute, as produced by `///`.
+fn extract_doc_attr(group: &TokenStream) -> Option<Literal> {
+    let mut toks = group.clone().into_iter();
+    match (toks.next(), toks.next(), toks.next(), toks.next()) {
+        (
+            Some(TokenTree::Ident(ident)),
+            Some(TokenTree::Punct(eq)),
+            Some(TokenTree::Literal(doc)),
+            None,
+        ) if ident_eq(&ident, "doc") && eq.as_char() == '=' => Some(doc),
+        _ => None,
+    }
+}
 fn extract_toml_attr(group: TokenStream) -> Option<TokenStream> {
     let mut toks = group.into_iter();
     {
@@ -910,6 +1490,10 @@ fn parse_field_attr<'a>(
     attr_buf: &mut Allocator<'a, FieldAttrs>,
     toks: TokenStream,
 ) {
+    if let Some(doc) = extract_doc_attr(&toks) {
+        ensure_attr(current, attr_buf).docs.push(doc);
+        return;
+    }
     let Some(attrs) = extract_toml_attr(toks) else {
         return;
     };
@@ -927,6 +1511,11 @@ fn option_inner_ty_ast(ty: &[TokenTree]) -> &[TokenTree] {
     ty
 }
 pub fn scan_fields<'a>(target: &mut DeriveTargetInner<'a>, fields: &mut Vec<Field<'a>>) {
+    for field in fields.iter() {
+        if let Some(SpanSource::Key) = field.span_source() {
+            target.has_key_span = true;
+        }
+    }
     let has_type_generics = target.generics.iter().any(|g| match g.kind {
         GenericKind::Type => true,
         _ => false,
@@ -1026,7 +1615,9 @@ fn parse_inner_enum_variants<'a>(
     let mut next_rename_all = RenameRule::None;
     let mut next_try_if: Option<Vec<TokenTree>> = None;
     let mut next_final_if: Option<Vec<TokenTree>> = None;
+    let mut next_expecting: Option<Literal> = None;
     let mut next_other = false;
+    let mut next_discriminant: &'a [TokenTree] = &[];
     loop {
         let i = if let Some((i, tok)) = f.next() {
             let TokenTree::Punct(punct) = tok else {
@@ -1037,7 +1628,9 @@ fn parse_inner_enum_variants<'a>(
                 let Some((_, TokenTree::Group(group))) = f.next() else {
                     Error::span_msg("Expected attr after", punct.span())
                 };
-                if let Some(attrs) = extract_toml_attr(group.stream()) {
+                if let Some(doc) = extract_doc_attr(&group.stream()) {
+                    ensure_attr(&mut next_attr, attr_buffer).docs.push(doc);
+                } else if let Some(attrs) = extract_toml_attr(group.stream()) {
                     parse_attrs(attrs, &mut |set, ident, buf| {
                         let name = ident.to_string();
                         if name == "rename_all" {
@@ -1079,6 +1672,13 @@ fn parse_inner_enum_variants<'a>(
                             next_final_if = Some(std::mem::take(buf));
                             return;
                         }
+                        if name == "expecting" {
+                            let Some(TokenTree::Literal(lit)) = buf.pop() else {
+                                Error::span_msg("expecting requires a string literal", ident.span())
+                            };
+                            next_expecting = Some(lit);
+                            return;
+                        }
                         if name == "other" {
                             next_other = true;
                             return;
@@ -1092,7 +1692,8 @@ fn parse_inner_enum_variants<'a>(
             if ch == b',' {
             } else if ch == b'=' {
                 let mut colon_stage = 0;
-                while let Some((_, tok)) = f.next() {
+                let mut end = fields.len();
+                while let Some((j, tok)) = f.next() {
                     let TokenTree::Punct(punct) = tok else {
                         continue;
                     };
@@ -1126,11 +1727,15 @@ fn parse_inner_enum_variants<'a>(
                                 }
                             }
                         }
-                        b',' => break,
+                        b',' => {
+                            end = j;
+                            break;
+                        }
                         _ => (),
                     }
                     colon_stage = 0;
                 }
+                next_discriminant = &fields[i + 1..end];
             } else {
                 continue;
             };
@@ -1173,8 +1778,10 @@ fn parse_inner_enum_variants<'a>(
             kind,
             rename_all: std::mem::replace(&mut next_rename_all, RenameRule::None),
             try_if: next_try_if.take(),
+            expecting: next_expecting.take(),
             final_if: next_final_if.take(),
             other: std::mem::replace(&mut next_other, false),
+            discriminant: std::mem::take(&mut next_discriminant),
         });
         if f.len() == 0 {
             break;
@@ -1208,6 +1815,9 @@ fn flags_from_attr(attr: &Option<&mut FieldAttrs>) -> u32 {
                 FieldAttrInner::Flatten => {
                     f |= Field::WITH_FLATTEN;
                 }
+                FieldAttrInner::SourceSpan(_) => {
+                    f |= Field::WITH_FROM_TOML_SKIP | Field::WITH_TO_TOML_SKIP;
+                }
                 _ => (),
             }
         }
@@ -1277,6 +1887,7 @@ static DEFAULT_ATTR: DefaultFieldAttr = const {
     DefaultFieldAttr(FieldAttrs {
         attrs: Vec::new(),
         flags: 0,
+        docs: Vec::new(),
     })
 };
 pub fn parse_struct_fields<'a>(
@@ -1,7 +1,8 @@
 use crate::ast::{
-    self, DefaultKind, DeriveTargetInner, DeriveTargetKind, EnumKind, EnumVariant, Field,
-    FieldAttrs, Generic, GenericKind, UnknownFieldPolicy, ENUM_CONTAINS_STRUCT_VARIANT,
-    ENUM_CONTAINS_TUPLE_VARIANT, ENUM_CONTAINS_UNIT_VARIANT, FROM_TOML, TO_TOML,
+    self, Constraint, DefaultKind, DeriveTargetInner, DeriveTargetKind, EnumKind, EnumVariant,
+    Field, FieldAttrs, Generic, GenericKind, KeyMatch, SpanSource, UnitRepr, UnknownFieldPolicy,
+    ENUM_CONTAINS_STRUCT_VARIANT, ENUM_CONTAINS_TUPLE_VARIANT, ENUM_CONTAINS_UNIT_VARIANT,
+    FROM_TOML, TO_TOML,
 };
 use crate::case::RenameRule;
 use crate::util::MemoryPool;
@@ -53,7 +54,7 @@ fn fmt_generics(buffer: &mut RustWriter, generics: &[Generic], fmt: GenericBound
             }
             GenericKind::Type => (),
             GenericKind::Const => {
-                buffer.blit_ident(41);
+                buffer.blit_ident(48);
             }
         }
         buffer.buf.push(generic.ident.clone().into());
@@ -136,17 +137,77 @@ fn field_name_lit(ctx: &Ctx, field: &Field, variant: Option<&EnumVariant>) -> Li
         Literal::string(&field.name.to_string())
     }
 }
-fn impl_from_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
+/// Wraps a `from_toml` body so the container `validate` function, if any, runs
+/// on the constructed value of type `ty`.
+fn wrap_container_validate(
+    output: &mut RustWriter,
+    ctx: &Ctx,
+    ty: &[TokenTree],
+    inner: TokenStream,
+) -> TokenStream {
+    if let Some(validate) = &ctx.target.validate {
+        {
+            let len = output.buf.len();
+            output.blit_punct(14);
+            {
+                let at = output.buf.len();
+                output.blit_ident(102);
+                {
+                    let at = output.buf.len();
+                    output.blit(3, 4);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.tt_group(Delimiter::Bracket, at);
+            };
+            output.blit(7, 3);
+            {
+                let at = output.buf.len();
+                output.blit(10, 14);
+                output.buf.extend_from_slice(ty);
+                output.blit_punct(13);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(24, 4);
+                output
+                    .buf
+                    .push(TokenTree::Group(Group::new(Delimiter::Brace, inner)));
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(28, 4);
+            {
+                let at = output.buf.len();
+                output.buf.extend_from_slice(validate);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            {
+                let at = output.buf.len();
+                output.blit(32, 4);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(36, 3);
+            {
+                let at = output.buf.len();
+                output.blit_ident(68);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.split_off_stream(len)
+        }
+    } else {
+        inner
+    }
+}
+fn impl_from_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream, items: &[TokenTree]) {
     let target = ctx.target;
+    let self_ty = [TokenTree::Ident(Ident::new("Self", Span::call_site()))];
+    let inner = wrap_container_validate(output, ctx, &self_ty, inner);
     let any_generics = !target.generics.is_empty();
     {
-        output.blit_punct(15);
+        output.blit_punct(14);
         {
             let at = output.buf.len();
-            output.blit_ident(40);
+            output.blit_ident(47);
             output.tt_group(Delimiter::Bracket, at);
         };
-        output.blit(3, 3);
+        output.blit(39, 3);
         output.push_ident(&ctx.lifetime);
         if !ctx.generics.is_empty() {
             output.blit_punct(13);
@@ -154,12 +215,12 @@ fn impl_from_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
         };
         output.blit_punct(0);
         output.buf.push(ctx.crate_path.clone());
-        output.blit(6, 5);
+        output.blit(42, 5);
         output.push_ident(&ctx.lifetime);
-        output.blit(11, 2);
+        output.blit(47, 2);
         output.push_ident(&target.name);
         if any_generics {
-            output.blit_punct(5);
+            output.blit_punct(4);
             fmt_generics(output, &target.generics, USE);
             output.blit_punct(0);
         };
@@ -167,47 +228,108 @@ fn impl_from_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
             || !target.generic_field_types.is_empty()
             || !target.generic_flatten_field_types.is_empty()
         {
-            output.blit_ident(48);
+            output.blit_ident(91);
             for ty in &target.generic_field_types {
                 output.buf.extend_from_slice(ty);
                 output.blit_punct(9);
                 output.buf.push(ctx.crate_path.clone());
-                output.blit(6, 5);
+                output.blit(42, 5);
                 output.push_ident(&ctx.lifetime);
-                output.blit(13, 2);
+                output.blit(49, 2);
             }
             for ty in &target.generic_flatten_field_types {
                 output.buf.extend_from_slice(ty);
                 output.blit_punct(9);
                 output.buf.push(ctx.crate_path.clone());
-                output.blit(15, 5);
+                output.blit(51, 5);
                 output.push_ident(&ctx.lifetime);
-                output.blit(13, 2);
+                output.blit(49, 2);
             }
             output.buf.extend_from_slice(&target.where_clauses);
         };
         {
             let at = output.buf.len();
-            output.blit(20, 2);
             {
-                let at = output.buf.len();
-                output.blit(22, 4);
-                output.buf.push(ctx.crate_path.clone());
-                output.blit(26, 5);
-                output.push_ident(&ctx.lifetime);
-                output.blit(31, 5);
-                output.buf.push(ctx.crate_path.clone());
-                output.blit(36, 5);
-                output.push_ident(&ctx.lifetime);
-                output.blit(13, 2);
-                output.tt_group(Delimiter::Parenthesis, at);
+                if target.has_key_span {
+                    {
+                        output.blit(56, 2);
+                        {
+                            let at = output.buf.len();
+                            output.blit(58, 4);
+                            output.buf.push(ctx.crate_path.clone());
+                            output.blit(62, 5);
+                            output.push_ident(&ctx.lifetime);
+                            output.blit(67, 5);
+                            output.buf.push(ctx.crate_path.clone());
+                            output.blit(72, 5);
+                            output.push_ident(&ctx.lifetime);
+                            output.blit(49, 2);
+                            output.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        output.blit(77, 14);
+                        output.buf.push(ctx.crate_path.clone());
+                        output.blit(24, 4);
+                        {
+                            let at = output.buf.len();
+                            output.blit(91, 4);
+                            {
+                                let at = output.buf.len();
+                                output.blit(95, 2);
+                                output.buf.push(ctx.crate_path.clone());
+                                output.blit(97, 10);
+                                output.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            output.tt_group(Delimiter::Brace, at);
+                        };
+                        output.blit(107, 2);
+                        {
+                            let at = output.buf.len();
+                            output.blit(58, 4);
+                            output.buf.push(ctx.crate_path.clone());
+                            output.blit(62, 5);
+                            output.push_ident(&ctx.lifetime);
+                            output.blit(109, 4);
+                            output.buf.push(ctx.crate_path.clone());
+                            output.blit(113, 7);
+                            output.buf.push(ctx.crate_path.clone());
+                            output.blit(72, 5);
+                            output.push_ident(&ctx.lifetime);
+                            output.blit(49, 2);
+                            output.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        output.blit(77, 14);
+                        output.buf.push(ctx.crate_path.clone());
+                        output.blit(24, 4);
+                        output
+                            .buf
+                            .push(TokenTree::Group(Group::new(Delimiter::Brace, inner)));
+                    }
+                } else {
+                    {
+                        output.blit(56, 2);
+                        {
+                            let at = output.buf.len();
+                            output.blit(58, 4);
+                            output.buf.push(ctx.crate_path.clone());
+                            output.blit(62, 5);
+                            output.push_ident(&ctx.lifetime);
+                            output.blit(67, 5);
+                            output.buf.push(ctx.crate_path.clone());
+                            output.blit(72, 5);
+                            output.push_ident(&ctx.lifetime);
+                            output.blit(49, 2);
+                            output.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        output.blit(77, 14);
+                        output.buf.push(ctx.crate_path.clone());
+                        output.blit(24, 4);
+                        output
+                            .buf
+                            .push(TokenTree::Group(Group::new(Delimiter::Brace, inner)));
+                    }
+                }
             };
-            output.blit(41, 14);
-            output.buf.push(ctx.crate_path.clone());
-            output.blit(55, 4);
-            output
-                .buf
-                .push(TokenTree::Group(Group::new(Delimiter::Brace, inner)));
+            output.buf.extend_from_slice(items);
             output.tt_group(Delimiter::Brace, at);
         };
     };
@@ -219,28 +341,28 @@ fn is_option_type(field: &Field) -> bool {
         false
     }
 }
-fn impl_to_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
+fn impl_to_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream, items: &[TokenTree]) {
     let target = ctx.target;
     let any_generics = !target.generics.is_empty();
     let lf = Ident::new("__de", Span::mixed_site());
     {
-        output.blit_punct(15);
+        output.blit_punct(14);
         {
             let at = output.buf.len();
-            output.blit_ident(40);
+            output.blit_ident(47);
             output.tt_group(Delimiter::Bracket, at);
         };
-        output.blit_ident(45);
+        output.blit_ident(49);
         if !target.generics.is_empty() {
-            output.blit_punct(5);
+            output.blit_punct(4);
             fmt_generics(output, &target.generics, DEF);
             output.blit_punct(0);
         };
         output.buf.push(ctx.crate_path.clone());
-        output.blit(59, 4);
+        output.blit(120, 4);
         output.push_ident(&target.name);
         if any_generics {
-            output.blit_punct(5);
+            output.blit_punct(4);
             fmt_generics(output, &target.generics, USE);
             output.blit_punct(0);
         };
@@ -248,46 +370,61 @@ fn impl_to_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
             || !target.generic_field_types.is_empty()
             || !target.generic_flatten_field_types.is_empty()
         {
-            output.blit_ident(48);
+            output.blit_ident(91);
             for ty in &target.generic_field_types {
                 output.buf.extend_from_slice(ty);
                 output.blit_punct(9);
                 output.buf.push(ctx.crate_path.clone());
-                output.blit(63, 4);
+                output.blit(124, 4);
             }
             for ty in &target.generic_flatten_field_types {
                 output.buf.extend_from_slice(ty);
                 output.blit_punct(9);
                 output.buf.push(ctx.crate_path.clone());
-                output.blit(67, 4);
+                output.blit(128, 4);
             }
+            if target.doc_comments {
+                for ty in &target.generic_field_types {
+                    output.buf.extend_from_slice(ty);
+                    output.blit_punct(9);
+                    output.buf.push(ctx.crate_path.clone());
+                    output.blit(132, 7);
+                }
+                for ty in &target.generic_flatten_field_types {
+                    output.buf.extend_from_slice(ty);
+                    output.blit_punct(9);
+                    output.buf.push(ctx.crate_path.clone());
+                    output.blit(132, 7);
+                }
+            };
             output.buf.extend_from_slice(&target.where_clauses);
         };
         {
             let at = output.buf.len();
-            output.blit(71, 4);
+            output.blit(139, 4);
             output.buf.push(TokenTree::from(lf.clone()));
             output.blit_punct(0);
             {
                 let at = output.buf.len();
-                output.blit(75, 2);
+                output.blit(143, 2);
                 output.buf.push(TokenTree::from(lf.clone()));
-                output.blit(77, 6);
+                output.blit(145, 6);
                 output.buf.push(TokenTree::from(lf.clone()));
                 output.buf.push(ctx.crate_path.clone());
-                output.blit(83, 3);
+                output.blit(151, 3);
                 output.tt_group(Delimiter::Parenthesis, at);
             };
-            output.blit(41, 12);
+            output.blit(12, 12);
             output.buf.push(ctx.crate_path.clone());
-            output.blit(36, 5);
+            output.blit(72, 5);
             output.buf.push(TokenTree::from(lf.clone()));
-            output.blit(13, 2);
+            output.blit(49, 2);
             output.buf.push(ctx.crate_path.clone());
-            output.blit(86, 4);
+            output.blit(154, 4);
             output
                 .buf
                 .push(TokenTree::Group(Group::new(Delimiter::Brace, inner)));
+            output.buf.extend_from_slice(items);
             output.tt_group(Delimiter::Brace, at);
         };
     };
@@ -295,11 +432,11 @@ fn impl_to_toml(output: &mut RustWriter, ctx: &Ctx, inner: TokenStream) {
 fn emit_failed_return(out: &mut RustWriter, ctx: &Ctx) {
     let at = out.buf.len();
     {
-        out.blit(90, 2);
+        out.blit(158, 2);
         {
             let at = out.buf.len();
             out.buf.push(ctx.crate_path.clone());
-            out.blit(55, 3);
+            out.blit(24, 3);
             out.tt_group(Delimiter::Parenthesis, at);
         };
         out.blit_punct(2);
@@ -312,31 +449,31 @@ fn emit_failed_return(out: &mut RustWriter, ctx: &Ctx) {
 fn emit_table_alloc(out: &mut RustWriter, ctx: &Ctx, var: &str, capacity: usize) {
     let var_id = Ident::new(var, Span::mixed_site());
     {
-        out.blit(92, 2);
+        out.blit(160, 2);
         {
             let at = out.buf.len();
-            out.blit_ident(101);
+            out.blit_ident(8);
             out.buf.push(TokenTree::from(var_id.clone()));
             out.tt_group(Delimiter::Parenthesis, at);
         };
         out.blit_punct(3);
         out.buf.push(ctx.crate_path.clone());
-        out.blit(94, 6);
+        out.blit(162, 7);
         {
             let at = out.buf.len();
             out.buf
                 .push(TokenTree::Literal(Literal::usize_unsuffixed(capacity)));
-            out.blit(78, 2);
+            out.blit(146, 2);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit_ident(109);
+        out.blit_ident(13);
         {
             let at = out.buf.len();
-            out.blit(90, 2);
+            out.blit(158, 2);
             {
                 let at = out.buf.len();
                 out.buf.push(ctx.crate_path.clone());
-                out.blit(100, 6);
+                out.blit(169, 6);
                 {
                     let at = out.buf.len();
                     out.buf.push(TokenTree::Literal(Literal::string(
@@ -361,65 +498,95 @@ fn emit_table_field_deser(
     skip_keys: &[Literal],
 ) {
     let recoverable = ctx.target.recoverable;
-    let mut flatten_field: Option<&Field> = None;
+    let mut flatten_fields: Vec<&Field> = Vec::new();
+    let mut catch_all: Option<&Field> = None;
     for field in fields {
+        if field.has_checks(FROM_TOML)
+            && field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0
+        {
+            Error::span_msg(
+                "validate and constraints cannot be used on skipped or flattened fields",
+                field.name.span(),
+            )
+        }
         if field.flags & Field::WITH_FLATTEN != 0 {
-            if flatten_field.is_some() {
-                Error::msg("Only one #[toml(flatten)] field is allowed")
+            if !is_flatten_any(field.with(FROM_TOML)) {
+                if catch_all.is_some() {
+                    Error::span_msg("At most one #[toml(flatten)] field can collect the remaining keys, flatten derived structs with `with = flatten_any`",
+                            field.name.span())
+                }
+                catch_all = Some(field);
             }
-            flatten_field = Some(field);
+            flatten_fields.push(field);
         }
     }
+    let key_span_tracked = resolve_field_relations(ctx, fields, variant);
     if recoverable {
         {
-            out.blit(106, 6);
+            out.blit(175, 6);
         };
         {
-            out.blit(112, 6);
+            out.blit(181, 7);
             out.buf.push(TokenTree::Literal(Literal::u64_suffixed(0)));
             out.blit_punct(2);
         };
     }
+    let loose_keys = !ctx.target.key_match.is_exact();
     for field in fields {
         if field.flags & Field::WITH_FLATTEN != 0 {
             {
-                out.blit(118, 4);
+                out.blit(175, 2);
+                out.push_ident(&flatten_partial_ident(field));
+                out.blit_punct(3);
             };
             emit_flatten_prefix(out, ctx, field, FROM_TOML);
             {
-                out.blit(122, 5);
+                out.blit(188, 6);
             };
             continue;
         }
-        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+        if let Some(source) = field.span_source() {
+            match source {
+                SpanSource::Table => {
+                    let table_id = Ident::new(table_ident, Span::mixed_site());
+                    {
+                        out.blit_ident(0);
+                        out.push_ident(field.name);
+                        out.blit_punct(3);
+                        out.buf.push(TokenTree::from(table_id.clone()));
+                        out.blit(194, 5);
+                    };
+                }
+                SpanSource::Key => {
+                    out.blit_ident(0);
+                    out.push_ident(field.name);
+                    out.blit(199, 3);
+                }
+            }
+        } else if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
             emit_field_default(out, field, FROM_TOML, false);
         } else if field.flags & Field::WITH_FROM_TOML_OPTION != 0 {
-            out.blit(106, 2);
+            out.blit(175, 2);
             out.push_ident(field.name);
             out.blit_punct(9);
             out.buf.extend_from_slice(field.ty);
-            out.blit(127, 3);
+            out.blit(202, 3);
         } else {
-            out.blit(106, 2);
+            out.blit(175, 2);
             out.push_ident(field.name);
-            out.blit(130, 5);
+            out.blit(205, 5);
             out.buf.extend_from_slice(field.ty);
-            out.blit(135, 2);
-        }
-        if field.attr.has_aliases(FROM_TOML) {
-            let span_ident = Ident::new(
-                &{
-                    let mut s = field.name.to_string();
-                    s.push_str("_first_span");
-                    s
-                },
-                Span::mixed_site(),
-            );
+            out.blit(210, 2);
+        }
+        if field.flags & Field::WITH_FROM_TOML_SKIP == 0
+            && (loose_keys || field.attr.has_aliases(FROM_TOML))
+        {
+            let span_ident = first_span_ident(field);
             let zero = TokenTree::Literal(Literal::u32_suffixed(0));
             {
-                out.blit(106, 2);
+                out.blit(175, 2);
                 out.push_ident(&span_ident);
-                out.blit(137, 8);
+                out.blit(212, 8);
                 {
                     let at = out.buf.len();
                     out.buf.push(zero.clone());
@@ -431,18 +598,32 @@ fn emit_table_field_deser(
             };
         }
     }
+    for (field, &tracked) in fields.iter().zip(&key_span_tracked) {
+        if tracked {
+            out.blit(175, 2);
+            out.push_ident(&key_span_ident(field));
+            out.blit(220, 3);
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(223, 7);
+        }
+    }
     emit_for_table_header(out, table_ident);
     let for_body_at = out.buf.len();
-    {
-        out.blit(145, 4);
-    };
+    if loose_keys {
+        emit_match_key(out, ctx, fields, variant);
+        {
+            out.blit(230, 2);
+        };
+    } else {
+        out.blit(232, 4);
+    }
     let arms_at = out.buf.len();
     for skip_key in skip_keys {
         out.buf.push(skip_key.clone().into());
-        out.blit(149, 3);
+        out.blit(236, 4);
     }
     let mut required_idx: u32 = 0;
-    for field in fields {
+    for (field_idx, field) in fields.iter().enumerate() {
         if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
             continue;
         }
@@ -450,7 +631,7 @@ fn emit_table_field_deser(
         let is_default = field.flags & Field::WITH_FROM_TOML_DEFAULT != 0;
         let is_option = field.flags & Field::WITH_FROM_TOML_OPTION != 0;
         let is_required = !is_option && !is_default;
-        let has_aliases = field.attr.has_aliases(FROM_TOML);
+        let tracks_first_span = loose_keys || field.attr.has_aliases(FROM_TOML);
         let has_deprecated_aliases = field.attr.has_deprecated_aliases(FROM_TOML);
         {
             out.buf.push(name_lit.clone().into());
@@ -466,16 +647,28 @@ fn emit_table_field_deser(
                 out.buf.push(alias.clone().into());
             });
         {
-            out.blit(149, 2);
+            out.blit(236, 2);
         };
         let arm_body_at = out.buf.len();
+        if key_span_tracked[field_idx] {
+            {
+                out.push_ident(&key_span_ident(field));
+                out.blit(240, 2);
+                {
+                    let at = out.buf.len();
+                    out.blit(242, 3);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.blit_punct(2);
+            };
+        }
         if recoverable && is_required {
             if required_idx < 64 {
                 let mask = TokenTree::Literal(Literal::u64_suffixed(1u64 << required_idx));
                 {
-                    out.blit_ident(53);
+                    out.blit_ident(100);
                     {
-                        out.blit(152, 2);
+                        out.blit(245, 2);
                     };
                     out.buf.push(mask);
                     out.blit_punct(2);
@@ -483,31 +676,24 @@ fn emit_table_field_deser(
             }
             required_idx += 1;
         }
-        if has_aliases {
-            let span_ident = Ident::new(
-                &{
-                    let mut s = field.name.to_string();
-                    s.push_str("_first_span");
-                    s
-                },
-                Span::mixed_site(),
-            );
+        if tracks_first_span {
+            let span_ident = first_span_ident(field);
             {
-                out.blit_ident(105);
+                out.blit_ident(5);
                 out.push_ident(field.name);
-                out.blit(154, 3);
+                out.blit(247, 4);
                 {
                     let at = out.buf.len();
-                    out.blit(90, 2);
+                    out.blit(158, 2);
                     {
                         let at = out.buf.len();
-                        out.blit(157, 3);
+                        out.blit(251, 3);
                         {
                             let at = out.buf.len();
                             out.buf.push(name_lit.clone().into());
-                            out.blit(160, 5);
+                            out.blit(254, 5);
                             out.push_ident(&span_ident);
-                            out.blit(165, 2);
+                            out.blit(259, 2);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
                         out.tt_group(Delimiter::Parenthesis, at);
@@ -523,11 +709,18 @@ fn emit_table_field_deser(
                 .attr
                 .for_each_deprecated_alias(FROM_TOML, &mut |tag, alias| {
                     {
-                        out.blit(167, 6);
+                        out.blit_ident(5);
+                        if loose_keys {
+                            out.blit_ident(45);
+                        };
+                        if !loose_keys {
+                            out.blit(233, 3);
+                        };
+                        out.blit(261, 2);
                         out.buf.push(alias.clone().into());
                         {
                             let at = out.buf.len();
-                            out.blit(173, 3);
+                            out.blit(263, 3);
                             {
                                 let at = out.buf.len();
                                 {
@@ -537,11 +730,11 @@ fn emit_table_field_deser(
                                         out.buf.push(TokenTree::Literal(Literal::u32_suffixed(0)));
                                     }
                                 };
-                                out.blit(176, 2);
+                                out.blit(266, 2);
                                 out.buf.push(alias.clone().into());
-                                out.blit(176, 2);
+                                out.blit(266, 2);
                                 out.buf.push(name_for_new.clone().into());
-                                out.blit(178, 6);
+                                out.blit(268, 6);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.blit_punct(2);
@@ -551,7 +744,7 @@ fn emit_table_field_deser(
                 });
         }
         {
-            out.blit_ident(100);
+            out.blit_ident(18);
         };
         let ty = if is_option {
             option_inner_ty(field.ty)
@@ -560,170 +753,143 @@ fn emit_table_field_deser(
         };
         emit_from_toml_call(out, ctx, field, ty);
         let match_body_at = out.buf.len();
-        if has_aliases {
-            let span_ident = Ident::new(
-                &{
-                    let mut s = field.name.to_string();
-                    s.push_str("_first_span");
-                    s
-                },
-                Span::mixed_site(),
-            );
+        {
+            out.blit_ident(2);
+            {
+                let at = out.buf.len();
+                out.blit_ident(10);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit(236, 2);
+        };
+        let ok_at = out.buf.len();
+        emit_field_checks(out, field, ty, is_required, recoverable);
+        let assign_at = out.buf.len();
+        {
+            out.push_ident(field.name);
+            out.blit(240, 2);
+            {
+                let at = out.buf.len();
+                out.blit_ident(10);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit_punct(2);
+        };
+        if tracks_first_span {
+            let span_ident = first_span_ident(field);
             {
-                out.blit_ident(115);
+                out.push_ident(&span_ident);
+                out.blit(274, 5);
+            };
+        }
+        out.tt_group(Delimiter::Brace, assign_at);
+        out.tt_group(Delimiter::Brace, ok_at);
+        {
+            if is_required && !recoverable {
+                out.blit_ident(7);
                 {
                     let at = out.buf.len();
-                    out.blit_ident(111);
+                    out.blit_ident(32);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
-                out.blit(149, 2);
+                out.blit(279, 4);
                 {
                     let at = out.buf.len();
-                    out.push_ident(field.name);
-                    out.blit(184, 2);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(111);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(2);
-                    out.push_ident(&span_ident);
-                    out.blit(186, 5);
-                    out.tt_group(Delimiter::Brace, at);
-                };
-                if is_required && !recoverable {
-                    out.blit_ident(113);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(93);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit(191, 4);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(93);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(13);
-                };
-                if is_required && recoverable {
-                    out.blit_ident(113);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(106);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit(149, 2);
-                    {
-                        let at = out.buf.len();
-                        out.blit(195, 4);
-                        out.tt_group(Delimiter::Brace, at);
-                    };
-                    out.blit_punct(13);
-                };
-                if !is_required {
-                    out.blit_ident(113);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(106);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit(199, 4);
+                    out.blit_ident(32);
+                    out.tt_group(Delimiter::Parenthesis, at);
                 };
+                out.blit_punct(13);
             };
-        } else {
-            {
-                out.blit_ident(115);
+            if is_required && recoverable {
+                out.blit_ident(7);
                 {
                     let at = out.buf.len();
-                    out.blit_ident(111);
+                    out.blit_ident(20);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
-                out.blit(149, 2);
+                out.blit(236, 2);
                 {
                     let at = out.buf.len();
-                    out.push_ident(field.name);
-                    out.blit(184, 2);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(111);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(2);
+                    out.blit(283, 4);
                     out.tt_group(Delimiter::Brace, at);
                 };
-                if is_required && !recoverable {
-                    out.blit_ident(113);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(93);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit(191, 4);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(93);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(13);
+                out.blit_punct(13);
+            };
+            if !is_required {
+                out.blit_ident(7);
+                {
+                    let at = out.buf.len();
+                    out.blit_ident(20);
+                    out.tt_group(Delimiter::Parenthesis, at);
                 };
-                if is_required && recoverable {
-                    out.blit_ident(113);
+                out.blit(287, 5);
+            };
+        };
+        out.tt_group(Delimiter::Brace, match_body_at);
+        out.tt_group(Delimiter::Brace, arm_body_at);
+    }
+    let mut expected = Vec::new();
+    for field in fields {
+        if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
+            expected.push(field_name_lit(ctx, field, variant));
+        }
+    }
+    match flatten_fields.as_slice() {
+        [] => emit_unknown_field_arm(out, ctx, &expected, &[]),
+        [ff] => {
+            {
+                out.blit(292, 3);
+            };
+            let wild_at = out.buf.len();
+            emit_flatten_insert(out, ctx, ff);
+            out.tt_group(Delimiter::Brace, wild_at);
+        }
+        _ => {
+            {
+                out.blit(292, 3);
+            };
+            let wild_at = out.buf.len();
+            for ff in &flatten_fields {
+                if catch_all.is_some_and(|ca| std::ptr::eq(ca, *ff)) {
+                    continue;
+                }
+                {
+                    out.blit(295, 2);
+                    out.buf.extend_from_slice(ff.ty);
+                    out.blit_ident(11);
+                    out.buf.push(ctx.crate_path.clone());
+                    out.blit(42, 5);
+                    out.push_ident(&ctx.lifetime);
+                    out.blit(297, 5);
                     {
                         let at = out.buf.len();
-                        out.blit_ident(106);
+                        out.blit(233, 3);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit(149, 2);
-                    {
-                        let at = out.buf.len();
-                        out.blit(195, 4);
-                        out.tt_group(Delimiter::Brace, at);
-                    };
-                    out.blit_punct(13);
                 };
-                if !is_required {
-                    out.blit_ident(113);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(106);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit(199, 4);
+                let if_at = out.buf.len();
+                emit_flatten_insert(out, ctx, ff);
+                out.tt_group(Delimiter::Brace, if_at);
+                {
+                    out.blit_ident(13);
                 };
-            };
+            }
+            if let Some(ca) = catch_all {
+                let else_at = out.buf.len();
+                emit_flatten_insert(out, ctx, ca);
+                out.tt_group(Delimiter::Brace, else_at);
+            } else {
+                emit_unknown_field_body(out, ctx, &expected, &flatten_fields);
+            }
+            out.tt_group(Delimiter::Brace, wild_at);
         }
-        out.tt_group(Delimiter::Brace, match_body_at);
-        out.tt_group(Delimiter::Brace, arm_body_at);
-    }
-    if let Some(ff) = flatten_field {
-        {
-            out.blit(203, 3);
-        };
-        let wild_at = out.buf.len();
-        {
-            out.blit(206, 3);
-        };
-        emit_flatten_prefix(out, ctx, ff, FROM_TOML);
-        {
-            out.blit(209, 3);
-            {
-                let at = out.buf.len();
-                out.blit(212, 9);
-                out.tt_group(Delimiter::Parenthesis, at);
-            };
-            out.blit_punct(2);
-        };
-        out.tt_group(Delimiter::Brace, wild_at);
-    } else {
-        emit_unknown_field_arm(out, ctx);
     }
     out.tt_group(Delimiter::Brace, arms_at);
     out.tt_group(Delimiter::Brace, for_body_at);
-    if let Some(ff) = flatten_field {
+    for ff in &flatten_fields {
         let table_id = Ident::new(table_ident, Span::mixed_site());
         {
-            out.blit(221, 2);
+            out.blit(302, 2);
             {
                 let at = out.buf.len();
                 out.push_ident(ff.name);
@@ -733,18 +899,20 @@ fn emit_table_field_deser(
         };
         emit_flatten_prefix(out, ctx, ff, FROM_TOML);
         {
-            out.blit(223, 3);
+            out.blit(304, 3);
             {
                 let at = out.buf.len();
-                out.blit(212, 2);
+                out.blit(95, 2);
                 out.buf.push(TokenTree::from(table_id.clone()));
-                out.blit(226, 2);
+                out.blit_punct(13);
+                out.push_ident(&flatten_partial_ident(ff));
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit_ident(109);
+            out.blit_ident(13);
         };
         emit_failed_return(out, ctx);
     }
+    emit_field_relation_checks(out, ctx, fields, variant);
     if recoverable {
         required_idx = 0;
         for field in fields {
@@ -759,34 +927,34 @@ fn emit_table_field_deser(
             }
             let name_lit = field_name_lit(ctx, field, variant);
             {
-                out.blit_ident(105);
+                out.blit_ident(5);
                 out.push_ident(field.name);
-                out.blit(228, 3);
+                out.blit(307, 4);
             };
             let outer_at = out.buf.len();
             if required_idx < 64 {
                 let mask = TokenTree::Literal(Literal::u64_suffixed(1u64 << required_idx));
                 let zero = TokenTree::Literal(Literal::u64_suffixed(0));
                 {
-                    out.blit_ident(105);
+                    out.blit_ident(5);
                 };
                 let paren_at = out.buf.len();
                 {
-                    out.blit(231, 2);
+                    out.blit(311, 2);
                     out.buf.push(mask);
                 };
                 out.tt_group(Delimiter::Parenthesis, paren_at);
                 {
-                    out.blit(171, 2);
+                    out.blit(261, 2);
                     out.buf.push(zero);
                 };
                 let inner_at = out.buf.len();
                 {
-                    out.blit(233, 3);
+                    out.blit(313, 3);
                     {
                         let at = out.buf.len();
                         out.buf.push(name_lit.into());
-                        out.blit(32, 2);
+                        out.blit(68, 2);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
                     out.blit_punct(2);
@@ -794,15 +962,15 @@ fn emit_table_field_deser(
                 out.tt_group(Delimiter::Brace, inner_at);
             } else {
                 {
-                    out.blit(236, 3);
+                    out.blit(316, 3);
                 };
                 let inner_at = out.buf.len();
                 {
-                    out.blit(233, 3);
+                    out.blit(313, 3);
                     {
                         let at = out.buf.len();
                         out.buf.push(name_lit.into());
-                        out.blit(32, 2);
+                        out.blit(68, 2);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
                     out.blit_punct(2);
@@ -810,21 +978,21 @@ fn emit_table_field_deser(
                 out.tt_group(Delimiter::Brace, inner_at);
             }
             {
-                out.blit(195, 4);
+                out.blit(283, 4);
             };
             out.tt_group(Delimiter::Brace, outer_at);
             required_idx += 1;
         }
         {
-            out.blit(239, 2);
+            out.blit(319, 2);
         };
         let if_at = out.buf.len();
         {
-            out.blit(90, 2);
+            out.blit(158, 2);
             {
                 let at = out.buf.len();
                 out.buf.push(ctx.crate_path.clone());
-                out.blit(55, 3);
+                out.blit(24, 3);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
             out.blit_punct(2);
@@ -841,15 +1009,15 @@ fn emit_table_field_deser(
         if field.flags & Field::WITH_FROM_TOML_DEFAULT != 0 {
             emit_field_default(out, field, FROM_TOML, true);
         } else if recoverable {
-            out.blit_ident(116);
+            out.blit_ident(0);
             out.push_ident(field.name);
             out.blit_punct(3);
             out.push_ident(field.name);
-            out.blit(241, 4);
+            out.blit(321, 6);
         } else {
             let name_lit = field_name_lit(ctx, field, variant);
             {
-                out.blit(92, 2);
+                out.blit(160, 2);
                 {
                     let at = out.buf.len();
                     out.push_ident(field.name);
@@ -857,18 +1025,18 @@ fn emit_table_field_deser(
                 };
                 out.blit_punct(3);
                 out.push_ident(field.name);
-                out.blit(245, 4);
+                out.blit(327, 6);
             };
             let else_at = out.buf.len();
             {
-                out.blit(90, 2);
+                out.blit(158, 2);
                 {
                     let at = out.buf.len();
-                    out.blit(233, 3);
+                    out.blit(313, 3);
                     {
                         let at = out.buf.len();
                         out.buf.push(name_lit.into());
-                        out.blit(32, 2);
+                        out.blit(68, 2);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
                     out.tt_group(Delimiter::Parenthesis, at);
@@ -882,531 +1050,3039 @@ fn emit_table_field_deser(
         }
     }
 }
-/// Emit a field default: either as initial `let field = default;` or as unwrap `let field = field.unwrap_or...;`
-fn emit_field_default(out: &mut RustWriter, field: &Field, direction: u8, is_unwrap: bool) {
-    if is_unwrap {
-        if let Some(default_kind) = field.default(direction) {
-            match default_kind {
-                DefaultKind::Custom(tokens) => {
-                    {
-                        out.blit_ident(116);
-                        out.push_ident(field.name);
-                        out.blit_punct(3);
-                        out.push_ident(field.name);
-                        out.blit(249, 2);
-                        {
-                            let at = out.buf.len();
-                            out.blit(251, 2);
-                            out.buf.extend_from_slice(tokens.as_slice());
-                            out.tt_group(Delimiter::Parenthesis, at);
-                        };
-                        out.blit_punct(2);
-                    };
-                }
-                DefaultKind::Default => {
-                    out.blit_ident(116);
-                    out.push_ident(field.name);
-                    out.blit_punct(3);
-                    out.push_ident(field.name);
-                    out.blit(253, 4);
-                }
-            }
-        } else {
-            out.blit_ident(116);
-            out.push_ident(field.name);
-            out.blit_punct(3);
-            out.push_ident(field.name);
-            out.blit(253, 4);
+/// Emits `let __name = __ctx.match_key(..);`, resolving the key to the field
+/// name it matches under the container's `key_match` rule.
+fn emit_match_key(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    fields: &[Field],
+    variant: Option<&EnumVariant>,
+) {
+    let rule = &ctx.target.key_match;
+    let mut names: Vec<(String, Literal)> = Vec::new();
+    for field in fields {
+        if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+            continue;
         }
-    } else {
-        if let Some(default_kind) = field.default(direction) {
-            match default_kind {
-                DefaultKind::Custom(tokens) => {
-                    out.blit_ident(116);
-                    out.push_ident(field.name);
-                    out.blit_punct(3);
-                    out.buf.extend_from_slice(tokens.as_slice());
-                    out.blit_punct(2);
-                }
-                DefaultKind::Default => {
-                    out.blit_ident(116);
-                    out.push_ident(field.name);
-                    out.blit(257, 7);
+        let mut push = |lit: Literal| {
+            let crate::lit::InlineKind::String(text) = crate::lit::literal_inline(lit.to_string())
+            else {
+                Error::span_msg("Expected a string literal", lit.span())
+            };
+            let normalized = rule.normalize(&text);
+            for (other, _) in &names {
+                if *other != text && rule.normalize(other) == normalized {
+                    let mut keys = String::from("keys `");
+                    keys.push_str(other);
+                    keys.push_str("` and `");
+                    keys.push_str(&text);
+                    keys.push('`');
+                    Error::span_msg_ctx(
+                        "cannot be told apart under key_match",
+                        &(keys),
+                        field.name.span(),
+                    )
                 }
             }
-        } else {
-            out.blit_ident(116);
-            out.push_ident(field.name);
-            out.blit(257, 7);
+            names.push((text, lit));
+        };
+        push(field_name_lit(ctx, field, variant));
+        field
+            .attr
+            .for_each_alias(FROM_TOML, &mut |alias| push(alias.clone()));
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |_, alias| push(alias.clone()));
+    }
+    let rule_ident = Ident::new(rule.variant_name(), Span::call_site());
+    {
+        out.blit(333, 6);
+    };
+    let args_at = out.buf.len();
+    {
+        out.blit(339, 5);
+    };
+    let list_at = out.buf.len();
+    for (_, lit) in &names {
+        out.buf.push(lit.clone().into());
+        out.blit_punct(13);
+    }
+    out.tt_group(Delimiter::Bracket, list_at);
+    {
+        out.blit_punct(13);
+        out.buf.push(ctx.crate_path.clone());
+        out.blit(344, 5);
+        out.push_ident(&rule_ident);
+    };
+    out.tt_group(Delimiter::Parenthesis, args_at);
+    {
+        out.blit_punct(2);
+    };
+}
+fn first_span_ident(field: &Field) -> Ident {
+    let mut name = field.name.to_string();
+    name.push_str("_first_span");
+    Ident::new(&name, Span::mixed_site())
+}
+fn key_span_ident(field: &Field) -> Ident {
+    let mut name = field.name.to_string();
+    name.push_str("_key_span");
+    Ident::new(&name, Span::mixed_site())
+}
+fn find_related_field(fields: &[Field], ident: &Ident) -> usize {
+    let name = ident.to_string();
+    for (i, field) in fields.iter().enumerate() {
+        if field.name.to_string() == name {
+            if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+                Error::span_msg("Cannot refer to a skipped or flattened field", ident.span())
+            }
+            return i;
         }
     }
+    Error::span_msg_ctx("Unknown field", &(name), ident.span())
 }
-fn emit_table_field_ser(
-    out: &mut RustWriter,
+/// Validates `conflicts_with`, `requires` and `exactly_one_of` and returns which
+/// fields need their key span tracked during deserialization.
+fn resolve_field_relations(
     ctx: &Ctx,
     fields: &[Field],
-    table_ident: &str,
     variant: Option<&EnumVariant>,
-    self_access: bool,
-) {
-    let table_id = Ident::new(table_ident, Span::mixed_site());
-    for field in fields {
-        if field.flags & (Field::WITH_TO_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
-            continue;
-        }
-        let name_lit = field_name_lit(ctx, field, variant);
-        let with_path = field.with(TO_TOML);
-        let skip_if = field.skip(TO_TOML).filter(|tokens| !tokens.is_empty());
-        let first_ty_ident = if let Some(TokenTree::Ident(ident)) = field.ty.first() {
-            ident.to_string()
-        } else {
-            String::new()
+) -> Vec<bool> {
+    let mut tracked = Vec::new();
+    tracked.resize(fields.len(), false);
+    for (i, field) in fields.iter().enumerate() {
+        let mut mark = |other: &Ident| {
+            if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+                Error::span_msg(
+                    "conflicts_with and requires cannot be used on skipped or flattened fields",
+                    field.name.span(),
+                )
+            }
+            let j = find_related_field(fields, other);
+            if i == j {
+                Error::span_msg("A field cannot refer to itself", other.span())
+            }
+            tracked[i] = true;
+            tracked[j] = true;
         };
-        let field_ref = {
-            let len = out.buf.len();
-            if self_access {
-                out.blit(264, 3);
-                out.push_ident(field.name);
-            };
-            if !self_access {
-                out.push_ident(field.name);
+        field.attr.for_each_conflict(FROM_TOML, &mut mark);
+        field.attr.for_each_requirement(FROM_TOML, &mut mark);
+    }
+    if variant.is_none() {
+        for group in &ctx.target.exactly_one_of {
+            for ident in group {
+                tracked[find_related_field(fields, ident)] = true;
+            }
+        }
+    }
+    tracked
+}
+/// Wraps the report emitted since `report_at` in a block that fails
+/// deserialization, returning immediately unless the container is recoverable.
+fn emit_relation_failure(out: &mut RustWriter, ctx: &Ctx, report_at: usize) {
+    let report = TokenTree::Group(Group::new(Delimiter::None, out.split_off_stream(report_at)));
+    let at = out.buf.len();
+    if ctx.target.recoverable {
+        out.buf.push(report);
+        out.blit(349, 5);
+    } else {
+        {
+            out.blit(158, 2);
+            {
+                let at = out.buf.len();
+                out.buf.push(report);
+                out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.split_off_stream(len)
+            out.blit_punct(2);
         };
-        let is_option = first_ty_ident == "Option";
-        let style = field.style(TO_TOML);
-        let emit_start = out.buf.len();
-        if let Some(with) = with_path {
-            let val_expr = if is_option {
+    }
+    out.tt_group(Delimiter::Brace, at);
+}
+fn emit_field_relation_checks(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    fields: &[Field],
+    variant: Option<&EnumVariant>,
+) {
+    let mut conflicts: Vec<(usize, usize)> = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        field.attr.for_each_conflict(FROM_TOML, &mut |other| {
+            let j = find_related_field(fields, other);
+            let pair = (i.min(j), i.max(j));
+            if !conflicts.contains(&pair) {
+                conflicts.push(pair);
+            }
+        });
+    }
+    for (i, j) in conflicts {
+        let (a, b) = (&fields[i], &fields[j]);
+        {
+            out.blit(354, 2);
+            {
+                let at = out.buf.len();
+                out.blit_ident(9);
                 {
-                    out.blit(267, 3);
-                    {
-                        let at = out.buf.len();
-                        out.blit_ident(111);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(3);
-                    out.buf.push(TokenTree::Group(Group::new(
-                        Delimiter::None,
-                        field_ref.clone(),
-                    )));
+                    let at = out.buf.len();
+                    out.blit_ident(120);
+                    out.tt_group(Delimiter::Parenthesis, at);
                 };
-                let len = out.buf.len();
-                out.blit_ident(111);
-                out.split_off_stream(len)
-            } else {
-                field_ref.clone()
+                out.blit(356, 2);
+                {
+                    let at = out.buf.len();
+                    out.blit_ident(121);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.tt_group(Delimiter::Parenthesis, at);
             };
-            let insert_start = out.buf.len();
+            out.blit_punct(3);
             {
-                out.buf.push(TokenTree::from(table_id.clone()));
-                out.blit(270, 2);
+                let at = out.buf.len();
+                out.push_ident(&key_span_ident(a));
+                out.blit_punct(13);
+                out.push_ident(&key_span_ident(b));
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        let at = out.buf.len();
+        {
+            out.blit(358, 3);
+            {
+                let at = out.buf.len();
+                out.buf.push(field_name_lit(ctx, a, variant).into());
+                out.blit(361, 3);
+                out.buf.push(field_name_lit(ctx, b, variant).into());
+                out.blit(364, 4);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        emit_relation_failure(out, ctx, at);
+    }
+    for field in fields {
+        field.attr.for_each_requirement(FROM_TOML, &mut |other| {
+            let required = &fields[find_related_field(fields, other)];
+            {
+                out.blit(354, 2);
                 {
                     let at = out.buf.len();
-                    out.buf.push(ctx.crate_path.clone());
-                    out.blit(272, 6);
+                    out.blit_ident(9);
                     {
                         let at = out.buf.len();
-                        out.buf.push(name_lit.into());
+                        out.blit_ident(122);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
+                    out.blit(368, 2);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.blit_punct(3);
+                {
+                    let at = out.buf.len();
+                    out.push_ident(&key_span_ident(field));
                     out.blit_punct(13);
-                    out.buf.extend_from_slice(with);
-                    out.blit(278, 3);
-                    {
-                        let at = out.buf.len();
-                        out.buf
-                            .push(TokenTree::Group(Group::new(Delimiter::None, val_expr)));
-                        out.blit(78, 2);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(8);
-                    if let Some(style) = style {
-                        out.blit(281, 2);
-                        {
-                            let at = out.buf.len();
-                            out.buf.push(ctx.crate_path.clone());
-                            out.blit(283, 5);
-                            out.buf.push(TokenTree::from(style.clone()));
-                            out.tt_group(Delimiter::Parenthesis, at);
-                        };
-                    };
-                    out.blit(288, 3);
+                    out.push_ident(&key_span_ident(required));
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
-                out.blit_punct(2);
             };
-            if is_option {
-                let insert_body = out.split_off_stream(insert_start);
-                out.buf
-                    .push(TokenTree::Group(Group::new(Delimiter::Brace, insert_body)));
-            }
-        } else if is_option {
+            let at = out.buf.len();
             {
-                out.blit(267, 3);
+                out.blit(370, 3);
                 {
                     let at = out.buf.len();
-                    out.blit_ident(111);
+                    out.buf.push(field_name_lit(ctx, required, variant).into());
+                    out.blit_punct(13);
+                    out.buf.push(field_name_lit(ctx, field, variant).into());
+                    out.blit(373, 4);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
-                out.blit(291, 2);
-                out.buf.extend_from_slice(field.ty);
-                out.blit_ident(104);
+            };
+            emit_relation_failure(out, ctx, at);
+        });
+    }
+    if variant.is_some() {
+        return;
+    }
+    for group in &ctx.target.exactly_one_of {
+        let mut candidates: Vec<TokenTree> = Vec::new();
+        let mut names: Vec<TokenTree> = Vec::new();
+        for ident in group {
+            let field = &fields[find_related_field(fields, ident)];
+            let name: TokenTree = field_name_lit(ctx, field, variant).into();
+            let at = out.buf.len();
+            {
+                out.buf.push(name.clone());
+                out.blit_punct(13);
+                out.push_ident(&key_span_ident(field));
+            };
+            out.tt_group(Delimiter::Parenthesis, at);
+            {
+                out.blit_punct(13);
+            };
+            candidates.extend(out.buf.drain(at..));
+            {
+                out.buf.push(name);
+                out.blit_punct(13);
+            };
+            names.extend(out.buf.drain(at..));
+        }
+        let candidates = TokenTree::Group(Group::new(
+            Delimiter::Bracket,
+            candidates.into_iter().collect(),
+        ));
+        let names = TokenTree::Group(Group::new(Delimiter::Bracket, names.into_iter().collect()));
+        let group_at = out.buf.len();
+        {
+            out.blit(377, 6);
+            {
+                let at = out.buf.len();
+                out.blit(383, 3);
                 out.buf.push(ctx.crate_path.clone());
-                out.blit(293, 7);
+                out.blit(97, 3);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit(386, 5);
+            {
+                let at = out.buf.len();
+                out.blit(391, 3);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit_ident(137);
+            out.buf.push(candidates);
+        };
+        let for_at = out.buf.len();
+        {
+            out.blit(160, 2);
+            {
+                let at = out.buf.len();
+                out.blit_ident(69);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit(394, 3);
+            {
+                let at = out.buf.len();
+                out.blit_ident(199);
+                out.tt_group(Delimiter::Brace, at);
+            };
+            out.blit(397, 4);
+            {
+                let at = out.buf.len();
                 {
                     let at = out.buf.len();
-                    out.buf.push(TokenTree::Group(Group::new(
-                        Delimiter::None,
-                        field_ref.clone(),
-                    )));
-                    out.blit(78, 2);
+                    out.blit(401, 3);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
-                out.blit_punct(8);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit(404, 2);
+        };
+        let at = out.buf.len();
+        {
+            out.blit(358, 3);
+            {
+                let at = out.buf.len();
+                out.blit(406, 9);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        emit_relation_failure(out, ctx, at);
+        {
+            out.blit_ident(13);
+            {
+                let at = out.buf.len();
+                out.blit(415, 3);
                 {
                     let at = out.buf.len();
-                    out.buf.push(TokenTree::from(table_id.clone()));
-                    out.blit(270, 2);
                     {
                         let at = out.buf.len();
-                        out.buf.push(ctx.crate_path.clone());
-                        out.blit(272, 6);
-                        {
-                            let at = out.buf.len();
-                            out.buf.push(name_lit.into());
-                            out.tt_group(Delimiter::Parenthesis, at);
-                        };
-                        out.blit(300, 2);
-                        if let Some(style) = style {
-                            out.blit(281, 2);
-                            {
-                                let at = out.buf.len();
-                                out.buf.push(ctx.crate_path.clone());
-                                out.blit(283, 5);
-                                out.buf.push(TokenTree::from(style.clone()));
-                                out.tt_group(Delimiter::Parenthesis, at);
-                            };
-                        };
-                        out.blit(288, 3);
+                        out.blit(391, 3);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit_punct(2);
-                    out.tt_group(Delimiter::Brace, at);
+                    out.tt_group(Delimiter::Parenthesis, at);
                 };
+                out.blit_punct(2);
+                out.tt_group(Delimiter::Brace, at);
             };
-        } else {
+        };
+        out.tt_group(Delimiter::Brace, for_at);
+        {
+            out.blit(418, 6);
+        };
+        let at = out.buf.len();
+        {
+            out.blit(424, 3);
             {
-                out.buf.push(TokenTree::from(table_id.clone()));
-                out.blit(270, 2);
-                {
+                let at = out.buf.len();
+                out.blit_punct(5);
+                out.buf.push(names);
+                out.blit(68, 2);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        emit_relation_failure(out, ctx, at);
+        out.tt_group(Delimiter::Brace, group_at);
+    }
+}
+/// Joins tokens into a compact display string, e.g. `Vec<String>` or `1..=65535`.
+fn compact_tokens(tokens: &[TokenTree]) -> String {
+    let mut out = String::new();
+    let mut prev_word = false;
+    for tok in tokens {
+        let word = match tok {
+            TokenTree::Ident(_) | TokenTree::Literal(_) => true,
+            _ => false,
+        };
+        if word && prev_word {
+            out.push(' ');
+        }
+        out.push_str(&tok.to_string());
+        prev_word = word;
+    }
+    out
+}
+/// Emits the `if <violated> { <report> } else` chain checking `__val` against the
+/// field's validator and constraints. The caller follows it with the assignment block.
+fn emit_field_checks(
+    out: &mut RustWriter,
+    field: &Field,
+    ty: &[TokenTree],
+    is_required: bool,
+    recoverable: bool,
+) {
+    fn failure(out: &mut RustWriter, report_at: usize, is_required: bool, recoverable: bool) {
+        let report = TokenTree::Group(Group::new(Delimiter::None, out.split_off_stream(report_at)));
+        let at = out.buf.len();
+        if is_required && !recoverable {
+            {
+                out.blit(158, 2);
+                {
                     let at = out.buf.len();
-                    out.buf.push(ctx.crate_path.clone());
-                    out.blit(272, 6);
+                    out.buf.push(report);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.blit_punct(2);
+            };
+        } else {
+            {
+                out.buf.push(report);
+                out.blit_punct(2);
+                if is_required {
+                    out.blit(283, 4);
+                };
+            };
+        }
+        out.tt_group(Delimiter::Brace, at);
+        {
+            out.blit_ident(13);
+        };
+    }
+    if let Some(validate) = field.validate(FROM_TOML) {
+        {
+            out.blit(427, 3);
+            {
+                let at = out.buf.len();
+                out.blit_ident(32);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit_punct(3);
+            {
+                let at = out.buf.len();
+                out.buf.extend_from_slice(validate);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            {
+                let at = out.buf.len();
+                out.blit(430, 2);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        let at = out.buf.len();
+        {
+            out.blit(432, 3);
+            {
+                let at = out.buf.len();
+                out.blit(435, 3);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        failure(out, at, is_required, recoverable);
+    }
+    let mut min_len: Option<&[TokenTree]> = None;
+    let mut max_len: Option<&[TokenTree]> = None;
+    field.for_each_constraint(FROM_TOML, &mut |constraint| match constraint {
+        Constraint::MinLen(tokens) => min_len = Some(tokens),
+        Constraint::MaxLen(tokens) => max_len = Some(tokens),
+        _ => (),
+    });
+    let length = match (min_len, max_len) {
+        (Some(min), Some(max)) => {
+            let mut s = String::from("length ");
+            s.push_str(&compact_tokens(min));
+            s.push_str("..=");
+            s.push_str(&compact_tokens(max));
+            s
+        }
+        (Some(min), None) => {
+            let mut s = String::from("length >= ");
+            s.push_str(&compact_tokens(min));
+            s
+        }
+        (None, Some(max)) => {
+            let mut s = String::from("length <= ");
+            s.push_str(&compact_tokens(max));
+            s
+        }
+        (None, None) => String::new(),
+    };
+    let ty_lit = TokenTree::Literal(Literal::string(&compact_tokens(ty)));
+    field.for_each_constraint(FROM_TOML, &mut |constraint| {
+        let range = match constraint {
+            Constraint::Range(tokens) => {
+                {
+                    out.blit(316, 2);
                     {
                         let at = out.buf.len();
-                        out.buf.push(name_lit.into());
+                        out.buf.extend_from_slice(tokens);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit(302, 2);
-                    out.buf.extend_from_slice(field.ty);
-                    out.blit_ident(104);
-                    out.buf.push(ctx.crate_path.clone());
-                    out.blit(304, 7);
+                    out.blit(438, 2);
+                    {
+                        let at = out.buf.len();
+                        out.blit(430, 2);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                };
+                compact_tokens(tokens)
+            }
+            Constraint::MinLen(tokens) => {
+                {
+                    out.blit(440, 7);
+                    {
+                        let at = out.buf.len();
+                        out.buf.extend_from_slice(tokens);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                };
+                length.clone()
+            }
+            Constraint::MaxLen(tokens) => {
+                {
+                    out.blit(447, 7);
+                    {
+                        let at = out.buf.len();
+                        out.buf.extend_from_slice(tokens);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                };
+                length.clone()
+            }
+            Constraint::NonEmpty => {
+                {
+                    out.blit(454, 6);
+                };
+                String::from("non-empty")
+            }
+            Constraint::OneOf(values) => {
+                let mut expected: Vec<TokenTree> = Vec::new();
+                let mut current = String::new();
+                for tok in values {
+                    match tok {
+                        TokenTree::Punct(p) if p.as_char() == ',' => {
+                            expected.push(TokenTree::Literal(Literal::string(&current)));
+                            expected.push(tok.clone());
+                            current.clear();
+                        }
+                        TokenTree::Literal(lit) => {
+                            match crate::lit::literal_inline(lit.to_string()) {
+                                crate::lit::InlineKind::String(text) => current.push_str(&text),
+                                _ => current.push_str(&lit.to_string()),
+                            }
+                        }
+                        _ => current.push_str(&tok.to_string()),
+                    }
+                }
+                if !current.is_empty() {
+                    expected.push(TokenTree::Literal(Literal::string(&current)));
+                }
+                {
+                    out.blit(316, 2);
+                    out.buf.push(TokenTree::Group(Group::new(
+                        Delimiter::Bracket,
+                        values.iter().cloned().collect(),
+                    )));
+                    out.blit(460, 6);
+                    {
+                        let at = out.buf.len();
+                        out.blit(466, 8);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                };
+                let at = out.buf.len();
+                {
+                    out.blit(474, 3);
                     {
                         let at = out.buf.len();
+                        out.blit_punct(5);
                         out.buf.push(TokenTree::Group(Group::new(
-                            Delimiter::None,
-                            field_ref.clone(),
+                            Delimiter::Bracket,
+                            expected.into_iter().collect(),
                         )));
-                        out.blit(78, 2);
+                        out.blit(259, 2);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                };
+                failure(out, at, is_required, recoverable);
+                return;
+            }
+        };
+        let at = out.buf.len();
+        {
+            out.blit(477, 3);
+            {
+                let at = out.buf.len();
+                out.blit_punct(5);
+                out.buf.push(ty_lit.clone());
+                out.blit(266, 2);
+                out.buf.push(TokenTree::Literal(Literal::string(&range)));
+                out.blit(259, 2);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        failure(out, at, is_required, recoverable);
+    });
+}
+/// Emit a field default: either as initial `let field = default;` or as unwrap `let field = field.unwrap_or...;`
+fn emit_field_default(out: &mut RustWriter, field: &Field, direction: u8, is_unwrap: bool) {
+    if is_unwrap {
+        if let Some(default_kind) = field.default(direction) {
+            match default_kind {
+                DefaultKind::Custom(tokens) => {
+                    {
+                        out.blit_ident(0);
+                        out.push_ident(field.name);
+                        out.blit_punct(3);
+                        out.push_ident(field.name);
+                        out.blit(480, 3);
+                        {
+                            let at = out.buf.len();
+                            out.blit(10, 2);
+                            out.buf.extend_from_slice(tokens.as_slice());
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(2);
+                    };
+                }
+                DefaultKind::Default => {
+                    out.blit_ident(0);
+                    out.push_ident(field.name);
+                    out.blit_punct(3);
+                    out.push_ident(field.name);
+                    out.blit(483, 5);
+                }
+            }
+        } else {
+            out.blit_ident(0);
+            out.push_ident(field.name);
+            out.blit_punct(3);
+            out.push_ident(field.name);
+            out.blit(483, 5);
+        }
+    } else {
+        if let Some(default_kind) = field.default(direction) {
+            match default_kind {
+                DefaultKind::Custom(tokens) => {
+                    out.blit_ident(0);
+                    out.push_ident(field.name);
+                    out.blit_punct(3);
+                    out.buf.extend_from_slice(tokens.as_slice());
+                    out.blit_punct(2);
+                }
+                DefaultKind::Default => {
+                    out.blit_ident(0);
+                    out.push_ident(field.name);
+                    out.blit(488, 8);
+                }
+            }
+        } else {
+            out.blit_ident(0);
+            out.push_ident(field.name);
+            out.blit(488, 8);
+        }
+    }
+}
+fn emit_table_field_ser(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    fields: &[Field],
+    table_ident: &str,
+    variant: Option<&EnumVariant>,
+    self_access: bool,
+) {
+    let table_id = Ident::new(table_ident, Span::mixed_site());
+    for field in fields {
+        if field.flags & (Field::WITH_TO_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+            continue;
+        }
+        let name_lit = field_name_lit(ctx, field, variant);
+        let with_path = field.with(TO_TOML);
+        let skip_if = field.skip(TO_TOML).filter(|tokens| !tokens.is_empty());
+        let first_ty_ident = if let Some(TokenTree::Ident(ident)) = field.ty.first() {
+            ident.to_string()
+        } else {
+            String::new()
+        };
+        let field_ref = {
+            let len = out.buf.len();
+            if self_access {
+                out.blit(496, 3);
+                out.push_ident(field.name);
+            };
+            if !self_access {
+                out.push_ident(field.name);
+            };
+            out.split_off_stream(len)
+        };
+        let is_option = first_ty_ident == "Option";
+        let style = field.style(TO_TOML);
+        let emit_start = out.buf.len();
+        if let Some(with) = with_path {
+            let val_expr = if is_option {
+                {
+                    out.blit(398, 3);
+                    {
+                        let at = out.buf.len();
+                        out.blit_ident(10);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    out.blit_punct(3);
+                    out.buf.push(TokenTree::Group(Group::new(
+                        Delimiter::None,
+                        field_ref.clone(),
+                    )));
+                };
+                let len = out.buf.len();
+                out.blit_ident(10);
+                out.split_off_stream(len)
+            } else {
+                field_ref.clone()
+            };
+            let insert_start = out.buf.len();
+            {
+                out.buf.push(TokenTree::from(table_id.clone()));
+                out.blit(499, 2);
+                {
+                    let at = out.buf.len();
+                    out.buf.push(ctx.crate_path.clone());
+                    out.blit(501, 6);
+                    {
+                        let at = out.buf.len();
+                        out.buf.push(name_lit.into());
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    out.blit_punct(13);
+                    out.buf.extend_from_slice(with);
+                    out.blit(507, 3);
+                    {
+                        let at = out.buf.len();
+                        out.buf
+                            .push(TokenTree::Group(Group::new(Delimiter::None, val_expr)));
+                        out.blit(146, 2);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit_punct(8);
+                    out.blit_punct(12);
                     if let Some(style) = style {
-                        out.blit(281, 2);
+                        out.blit(510, 2);
                         {
                             let at = out.buf.len();
                             out.buf.push(ctx.crate_path.clone());
-                            out.blit(283, 5);
+                            out.blit(512, 5);
                             out.buf.push(TokenTree::from(style.clone()));
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
                     };
-                    out.blit(288, 3);
+                    out.blit(517, 3);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
                 out.blit_punct(2);
             };
-        }
-        if let Some(skip_tokens) = skip_if {
-            let emit_body = out.split_off_stream(emit_start);
+            if is_option {
+                let insert_body = out.split_off_stream(insert_start);
+                out.buf
+                    .push(TokenTree::Group(Group::new(Delimiter::Brace, insert_body)));
+            }
+        } else if is_option {
             {
-                out.blit(236, 2);
+                out.blit(398, 3);
                 {
                     let at = out.buf.len();
-                    out.buf.extend_from_slice(skip_tokens);
+                    out.blit_ident(10);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
+                out.blit(520, 2);
+                out.buf.extend_from_slice(field.ty);
+                out.blit_ident(11);
+                out.buf.push(ctx.crate_path.clone());
+                out.blit(522, 8);
                 {
                     let at = out.buf.len();
-                    out.buf
-                        .push(TokenTree::Group(Group::new(Delimiter::None, field_ref)));
+                    out.buf.push(TokenTree::Group(Group::new(
+                        Delimiter::None,
+                        field_ref.clone(),
+                    )));
+                    out.blit(146, 2);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
-                out.buf
-                    .push(TokenTree::Group(Group::new(Delimiter::Brace, emit_body)));
-            };
-        }
-    }
-    for field in fields {
-        if field.flags & Field::WITH_FLATTEN != 0 {
-            let field_ref = {
-                let len = out.buf.len();
+                out.blit_punct(12);
+                {
+                    let at = out.buf.len();
+                    out.buf.push(TokenTree::from(table_id.clone()));
+                    out.blit(499, 2);
+                    {
+                        let at = out.buf.len();
+                        out.buf.push(ctx.crate_path.clone());
+                        out.blit(501, 6);
+                        {
+                            let at = out.buf.len();
+                            out.buf.push(name_lit.into());
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit(530, 2);
+                        if let Some(style) = style {
+                            out.blit(510, 2);
+                            {
+                                let at = out.buf.len();
+                                out.buf.push(ctx.crate_path.clone());
+                                out.blit(512, 5);
+                                out.buf.push(TokenTree::from(style.clone()));
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
+                        };
+                        out.blit(517, 3);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    out.blit_punct(2);
+                    out.tt_group(Delimiter::Brace, at);
+                };
+            };
+        } else {
+            {
+                out.buf.push(TokenTree::from(table_id.clone()));
+                out.blit(499, 2);
+                {
+                    let at = out.buf.len();
+                    out.buf.push(ctx.crate_path.clone());
+                    out.blit(501, 6);
+                    {
+                        let at = out.buf.len();
+                        out.buf.push(name_lit.into());
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    out.blit(532, 2);
+                    out.buf.extend_from_slice(field.ty);
+                    out.blit_ident(11);
+                    out.buf.push(ctx.crate_path.clone());
+                    out.blit(534, 7);
+                    {
+                        let at = out.buf.len();
+                        out.buf.push(TokenTree::Group(Group::new(
+                            Delimiter::None,
+                            field_ref.clone(),
+                        )));
+                        out.blit(146, 2);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    out.blit_punct(12);
+                    if let Some(style) = style {
+                        out.blit(510, 2);
+                        {
+                            let at = out.buf.len();
+                            out.buf.push(ctx.crate_path.clone());
+                            out.blit(512, 5);
+                            out.buf.push(TokenTree::from(style.clone()));
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                    };
+                    out.blit(517, 3);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.blit_punct(2);
+            };
+        }
+        if let Some(skip_tokens) = skip_if {
+            let emit_body = out.split_off_stream(emit_start);
+            {
+                out.blit(316, 2);
+                {
+                    let at = out.buf.len();
+                    out.buf.extend_from_slice(skip_tokens);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                {
+                    let at = out.buf.len();
+                    out.buf.push(TokenTree::Group(Group::new(
+                        Delimiter::None,
+                        field_ref.clone(),
+                    )));
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.buf
+                    .push(TokenTree::Group(Group::new(Delimiter::Brace, emit_body)));
+            };
+        }
+        let default = field.default(FROM_TOML);
+        if field.is_skip_if_default() && default.is_none() {
+            Error::span_msg(
+                "skip_if_default requires a default value",
+                field.name.span(),
+            )
+        }
+        if let Some(default) =
+            default.filter(|_| field.is_skip_if_default() || ctx.target.skip_if_default)
+        {
+            let emit_body = out.split_off_stream(emit_start);
+            {
+                out.blit_ident(5);
+                out.buf.push(ctx.crate_path.clone());
+                out.blit(541, 10);
+                {
+                    let at = out.buf.len();
+                    out.blit(551, 3);
+                    out.buf.extend_from_slice(field.ty);
+                    out.blit_punct(3);
+                    {
+                        match default {
+                            DefaultKind::Custom(tokens) => {
+                                out.buf.extend_from_slice(tokens.as_slice());
+                            }
+                            DefaultKind::Default => {
+                                out.blit(489, 6);
+                            }
+                        }
+                    };
+                    out.blit_punct(2);
+                    out.buf
+                        .push(TokenTree::Group(Group::new(Delimiter::None, field_ref)));
+                    out.blit(554, 4);
+                    out.tt_group(Delimiter::Brace, at);
+                };
+                out.buf
+                    .push(TokenTree::Group(Group::new(Delimiter::Brace, emit_body)));
+            };
+        }
+    }
+    for field in fields {
+        if field.flags & Field::WITH_FLATTEN != 0 {
+            let field_ref = {
+                let len = out.buf.len();
                 if self_access {
-                    out.blit(264, 3);
+                    out.blit(496, 3);
                     out.push_ident(field.name);
                 };
                 if !self_access {
                     out.push_ident(field.name);
                 };
-                out.split_off_stream(len)
+                out.split_off_stream(len)
+            };
+            emit_flatten_prefix(out, ctx, field, TO_TOML);
+            {
+                out.blit(558, 4);
+                {
+                    let at = out.buf.len();
+                    out.buf
+                        .push(TokenTree::Group(Group::new(Delimiter::None, field_ref)));
+                    out.blit(562, 5);
+                    out.buf.push(TokenTree::from(table_id.clone()));
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.blit(30, 2);
+            };
+        }
+    }
+}
+fn emit_struct_variant_to_arm(out: &mut RustWriter, variant: &EnumVariant, arm_body_start: usize) {
+    let arm_body = out.split_off_stream(arm_body_start);
+    {
+        out.blit(91, 3);
+        out.push_ident(variant.name);
+        {
+            let at = out.buf.len();
+            {
+                for field in variant.fields {
+                    out.blit_ident(226);
+                    out.push_ident(field.name);
+                    out.blit_punct(13);
+                }
+            };
+            out.tt_group(Delimiter::Brace, at);
+        };
+        out.blit(236, 2);
+        out.buf
+            .push(TokenTree::Group(Group::new(Delimiter::Brace, arm_body)));
+    };
+}
+fn count_ser_fields(fields: &[Field]) -> usize {
+    let mut count = 0;
+    for f in fields {
+        if f.flags & (Field::WITH_TO_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
+            count += 1;
+        }
+    }
+    count
+}
+/// Emit Ok(Self::Variant { field1, field2, ... }) for struct variant deserialization
+fn emit_ok_self_variant(out: &mut RustWriter, variant: &EnumVariant) {
+    {
+        out.blit_ident(2);
+        {
+            let at = out.buf.len();
+            out.blit(91, 3);
+            out.push_ident(variant.name);
+            {
+                let at = out.buf.len();
+                {
+                    for field in variant.fields {
+                        out.push_ident(field.name);
+                        out.blit_punct(13);
+                    }
+                };
+                out.tt_group(Delimiter::Brace, at);
+            };
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+    };
+}
+fn struct_from_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let start = out.buf.len();
+    {
+        out.blit(302, 2);
+        {
+            let at = out.buf.len();
+            out.blit_ident(28);
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+        out.blit(567, 4);
+        {
+            let at = out.buf.len();
+            out.blit_ident(1);
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+        out.blit_ident(13);
+    };
+    emit_failed_return(out, ctx);
+    if version_field(fields).is_some() {
+        {
+            out.blit(571, 11);
+            {
+                let at = out.buf.len();
+                out.blit_ident(28);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit(30, 2);
+        };
+    }
+    emit_table_field_deser(out, ctx, fields, "__table", None, &[]);
+    {
+        out.blit_ident(2);
+        {
+            let at = out.buf.len();
+            out.blit_ident(4);
+            {
+                let at = out.buf.len();
+                {
+                    for field in fields {
+                        out.push_ident(field.name);
+                        out.blit_punct(13);
+                    }
+                };
+                out.tt_group(Delimiter::Brace, at);
+            };
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+    };
+    let body = out.split_off_stream(start);
+    let items: Vec<TokenTree> = emit_claims_key(out, ctx, fields).into_iter().collect();
+    impl_from_toml(out, ctx, body, &items);
+}
+/// Emits the `claims_key` and `expected_keys` overrides reporting the keys
+/// read by the struct's fields, so it can share a table with other flattened
+/// structs.
+fn emit_claims_key(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) -> TokenStream {
+    let start = out.buf.len();
+    let mut names: Vec<Literal> = Vec::new();
+    let mut nested: Vec<&Field> = Vec::new();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        if field.flags & Field::WITH_FLATTEN != 0 {
+            if !is_flatten_any(field.with(FROM_TOML)) {
+                return TokenStream::new();
+            }
+            nested.push(field);
+            continue;
+        }
+        names.push(field_name_lit(ctx, field, None));
+        field
+            .attr
+            .for_each_alias(FROM_TOML, &mut |alias| names.push(alias.clone()));
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |_, alias| names.push(alias.clone()));
+    }
+    {
+        out.blit(582, 2);
+        {
+            let at = out.buf.len();
+            out.blit(584, 4);
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+        out.blit(588, 3);
+    };
+    let body_at = out.buf.len();
+    if ctx.target.key_match.is_exact() {
+        let expr_at = out.buf.len();
+        {
+            out.blit(232, 2);
+        };
+        let arms_at = out.buf.len();
+        if !names.is_empty() {
+            for (i, name) in names.iter().enumerate() {
+                {
+                    if i > 0 {
+                        out.blit_punct(11);
+                    };
+                    out.buf.push(name.clone().into());
+                };
+            }
+            {
+                out.blit(591, 4);
+            };
+        }
+        {
+            out.blit(595, 4);
+        };
+        out.tt_group(Delimiter::Brace, arms_at);
+        if !nested.is_empty() {
+            out.tt_group(Delimiter::Parenthesis, expr_at);
+        }
+    } else {
+        let rule_ident = Ident::new(ctx.target.key_match.variant_name(), Span::call_site());
+        let list_at = out.buf.len();
+        for name in &names {
+            out.buf.push(name.clone().into());
+            out.blit_punct(13);
+        }
+        out.tt_group(Delimiter::Bracket, list_at);
+        {
+            out.blit(460, 6);
+            {
+                let at = out.buf.len();
+                out.blit(599, 3);
+                out.buf.push(ctx.crate_path.clone());
+                out.blit(344, 5);
+                out.push_ident(&rule_ident);
+                out.blit(602, 2);
+                {
+                    let at = out.buf.len();
+                    out.blit(604, 3);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+    }
+    for field in &nested {
+        {
+            out.blit(607, 3);
+            out.buf.extend_from_slice(field.ty);
+            out.blit_ident(11);
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(42, 5);
+            out.push_ident(&ctx.lifetime);
+            out.blit(297, 5);
+            {
+                let at = out.buf.len();
+                out.blit_ident(17);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+    }
+    out.tt_group(Delimiter::Brace, body_at);
+    {
+        out.blit(610, 2);
+        {
+            let at = out.buf.len();
+            out.blit(612, 19);
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+    };
+    let body_at = out.buf.len();
+    {
+        out.blit(631, 3);
+    };
+    let args_at = out.buf.len();
+    {
+        out.blit_punct(5);
+    };
+    let list_at = out.buf.len();
+    for field in fields {
+        if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
+            out.buf.push(field_name_lit(ctx, field, None).into());
+            out.blit_punct(13);
+        }
+    }
+    out.tt_group(Delimiter::Bracket, list_at);
+    out.tt_group(Delimiter::Parenthesis, args_at);
+    {
+        out.blit_punct(2);
+    };
+    for field in &nested {
+        {
+            out.blit_punct(4);
+            out.buf.extend_from_slice(field.ty);
+            out.blit_ident(11);
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(42, 5);
+            out.push_ident(&ctx.lifetime);
+            out.blit(634, 5);
+            {
+                let at = out.buf.len();
+                out.blit_ident(96);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit_punct(2);
+        };
+    }
+    out.tt_group(Delimiter::Brace, body_at);
+    out.split_off_stream(start)
+}
+fn struct_to_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let start = out.buf.len();
+    emit_table_alloc(out, ctx, "__table", count_ser_fields(fields));
+    emit_table_field_ser(out, ctx, fields, "__table", None, true);
+    {
+        out.blit_ident(2);
+        {
+            let at = out.buf.len();
+            out.blit(639, 5);
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+    };
+    let body = out.split_off_stream(start);
+    let mut items = Vec::new();
+    if ctx.target.doc_comments {
+        items.extend({
+            let len = out.buf.len();
+            out.blit(644, 2);
+            {
+                let at = out.buf.len();
+                out.blit(496, 2);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit(646, 12);
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(658, 7);
+            {
+                let at = out.buf.len();
+                out.blit_ident(9);
+                {
+                    let at = out.buf.len();
+                    out.blit(665, 3);
+                    out.buf.push(ctx.crate_path.clone());
+                    out.blit(668, 12);
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.tt_group(Delimiter::Brace, at);
+            };
+            out.split_off_stream(len)
+        });
+    }
+    impl_to_toml(out, ctx, body, &items);
+}
+/// Writes tokens the way they are usually spelled in source, for the type
+/// names and default expressions recorded in schemas.
+fn write_source(tokens: TokenStream, out: &mut String) {
+    let mut prev_word = false;
+    for tt in tokens {
+        match &tt {
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                if prev_word {
+                    out.push(' ');
+                }
+                out.push_str(&tt.to_string());
+                prev_word = true;
+            }
+            TokenTree::Punct(punct) => {
+                out.push(punct.as_char());
+                if match punct.as_char() {
+                    ',' | ';' => true,
+                    _ => false,
+                } {
+                    out.push(' ');
+                }
+                prev_word = false;
+            }
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{ ", " }"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                write_source(group.stream(), out);
+                out.push_str(close);
+                prev_word = false;
+            }
+        }
+    }
+}
+fn source_literal(tokens: &[TokenTree]) -> Literal {
+    let mut text = String::new();
+    write_source(tokens.iter().cloned().collect(), &mut text);
+    Literal::string(&text)
+}
+fn bool_ident(value: bool) -> Ident {
+    Ident::new(if value { "true" } else { "false" }, Span::call_site())
+}
+/// Emits `&[docs...]` for the doc comment literals of an item.
+fn emit_doc_slice(out: &mut RustWriter, docs: &[Literal]) {
+    {
+        out.blit_punct(5);
+    };
+    let at = out.buf.len();
+    for doc in docs {
+        out.buf.push(TokenTree::Literal(doc.clone()));
+        out.blit_punct(13);
+    }
+    out.tt_group(Delimiter::Bracket, at);
+}
+/// Emits an expression of type `fn() -> Schema` describing `ty`.
+fn emit_schema_fn(out: &mut RustWriter, ctx: &Ctx, ty: &[TokenTree], with: Option<&[TokenTree]>) {
+    let typed = match with {
+        None => true,
+        Some(path) => match path.last() {
+            Some(TokenTree::Ident(id)) if id.to_string() == "flatten_any" => true,
+            _ => false,
+        },
+    };
+    if typed {
+        out.blit_punct(4);
+        out.buf.extend_from_slice(ty);
+        out.blit_ident(11);
+        out.buf.push(ctx.crate_path.clone());
+        out.blit(668, 10);
+    } else {
+        out.blit_punct(4);
+        out.buf.push(ctx.crate_path.clone());
+        out.blit(680, 8);
+        out.buf.push(ctx.crate_path.clone());
+        out.blit(668, 10);
+    }
+    {
+        out.blit(688, 6);
+        out.buf.push(ctx.crate_path.clone());
+        out.blit(658, 6);
+    };
+}
+fn emit_field_schemas(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    fields: &[Field],
+    variant: Option<&EnumVariant>,
+) {
+    let skip = if ctx.target.from_toml {
+        Field::WITH_FROM_TOML_SKIP
+    } else {
+        Field::WITH_TO_TOML_SKIP
+    };
+    {
+        out.blit_ident(48);
+    };
+    let outer = out.buf.len();
+    {
+        out.blit_punct(5);
+    };
+    let at = out.buf.len();
+    for field in fields {
+        if field.flags & skip != 0 {
+            continue;
+        }
+        let field_start = out.buf.len();
+        {
+            out.buf
+                .push(TokenTree::Literal(field_name_lit(ctx, field, variant)));
+            out.blit(266, 2);
+        };
+        let aliases = out.buf.len();
+        field.attr.for_each_alias(FROM_TOML, &mut |lit| {
+            out.buf.push(TokenTree::Literal(lit.clone()));
+            out.blit_punct(13);
+        });
+        out.tt_group(Delimiter::Bracket, aliases);
+        {
+            out.blit(266, 2);
+        };
+        let deprecated = out.buf.len();
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |_, lit| {
+                out.buf.push(TokenTree::Literal(lit.clone()));
+                out.blit_punct(13);
+            });
+        out.tt_group(Delimiter::Bracket, deprecated);
+        {
+            out.blit_punct(13);
+        };
+        emit_doc_slice(out, &field.attr.docs);
+        {
+            out.blit_punct(13);
+            out.buf.push(TokenTree::Literal(source_literal(field.ty)));
+            out.blit_punct(13);
+        };
+        match field.default(FROM_TOML) {
+            None => {
+                out.buf.push(ctx.crate_path.clone());
+                out.blit(694, 9);
+            }
+            Some(DefaultKind::Default) => {
+                out.buf.push(ctx.crate_path.clone());
+                out.blit(703, 9);
+            }
+            Some(DefaultKind::Custom(expr)) => {
+                {
+                    out.buf.push(ctx.crate_path.clone());
+                    out.blit(712, 9);
+                    {
+                        let at = out.buf.len();
+                        out.buf.push(TokenTree::Literal(source_literal(expr)));
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                };
+            }
+        }
+        {
+            out.blit_punct(13);
+            out.push_ident(&bool_ident(field.flags & Field::WITH_FLATTEN != 0));
+            out.blit_punct(13);
+        };
+        emit_schema_fn(out, ctx, field.ty, field.with(FROM_TOML | TO_TOML));
+        out.tt_group(Delimiter::Parenthesis, field_start);
+        let body = out.buf.pop().unwrap();
+        {
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(721, 9);
+            out.buf.push(body);
+            out.blit_punct(13);
+        };
+    }
+    out.tt_group(Delimiter::Bracket, at);
+    out.tt_group(Delimiter::Brace, outer);
+}
+/// Emits the `TomlSchema` impl for `#[toml(Schema)]` with the given body.
+fn impl_toml_schema(output: &mut RustWriter, ctx: &Ctx, body: TokenStream) {
+    let target = ctx.target;
+    let any_generics = !target.generics.is_empty();
+    {
+        output.blit_punct(14);
+        {
+            let at = output.buf.len();
+            output.blit_ident(47);
+            output.tt_group(Delimiter::Bracket, at);
+        };
+        output.blit_ident(49);
+        if any_generics {
+            output.blit_punct(4);
+            fmt_generics(output, &target.generics, DEF);
+            output.blit_punct(0);
+        };
+        output.buf.push(ctx.crate_path.clone());
+        output.blit(730, 7);
+        output.push_ident(&target.name);
+        if any_generics {
+            output.blit_punct(4);
+            fmt_generics(output, &target.generics, USE);
+            output.blit_punct(0);
+        };
+        if !target.where_clauses.is_empty()
+            || !target.generic_field_types.is_empty()
+            || !target.generic_flatten_field_types.is_empty()
+        {
+            output.blit_ident(91);
+            for ty in &target.generic_field_types {
+                output.buf.extend_from_slice(ty);
+                output.blit_punct(9);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(132, 7);
+            }
+            for ty in &target.generic_flatten_field_types {
+                output.buf.extend_from_slice(ty);
+                output.blit_punct(9);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(132, 7);
+            }
+            output.buf.extend_from_slice(&target.where_clauses);
+        };
+        {
+            let at = output.buf.len();
+            output.blit(737, 6);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(658, 6);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Brace, body)));
+            output.tt_group(Delimiter::Brace, at);
+        };
+    };
+}
+/// Emits the schema of a proxy or transparent container, if it has one.
+fn emit_delegated_schema(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) -> bool {
+    let target = ctx.target;
+    let proxy = target
+        .from_type
+        .as_ref()
+        .or(target.try_from_type.as_ref())
+        .or(target.into_type.as_ref())
+        .or(target.try_into_type.as_ref());
+    if let Some(proxy) = proxy {
+        {
+            out.blit_punct(4);
+            out.buf.extend_from_slice(proxy);
+            out.blit_ident(11);
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(668, 12);
+        };
+        return true;
+    }
+    if target.transparent_impl {
+        let [single_field] = fields else {
+            Error::msg("Struct must contain a single field to use transparent")
+        };
+        let at = out.buf.len();
+        emit_schema_fn(
+            out,
+            ctx,
+            single_field.ty,
+            single_field.with(FROM_TOML | TO_TOML),
+        );
+        out.tt_group(Delimiter::Parenthesis, at);
+        out.tt_group_empty(Delimiter::Parenthesis);
+        return true;
+    }
+    false
+}
+fn schema_struct(output: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let target = ctx.target;
+    let start = output.buf.len();
+    if !emit_delegated_schema(output, ctx, fields) {
+        let unknown = match target.unknown_fields {
+            UnknownFieldPolicy::Warn { .. } => "Warn",
+            UnknownFieldPolicy::Ignore => "Ignore",
+            UnknownFieldPolicy::Deny { .. } => "Deny",
+        };
+        let name = Literal::string(&target.name.to_string());
+        let body_start = output.buf.len();
+        {
+            output.buf.push(TokenTree::Literal(name));
+            output.blit_punct(13);
+        };
+        emit_doc_slice(output, &target.docs);
+        {
+            output.blit_punct(13);
+        };
+        emit_field_schemas(output, ctx, fields, None);
+        {
+            output.blit_punct(13);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(743, 8);
+            output.push_ident(&Ident::new(unknown, Span::call_site()));
+            output.blit_punct(13);
+        };
+        output.tt_group(Delimiter::Parenthesis, body_start);
+        let body = output.buf.pop().unwrap();
+        {
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(751, 9);
+            {
+                let at = output.buf.len();
+                output.blit_ident(48);
+                {
+                    let at = output.buf.len();
+                    output.blit_punct(5);
+                    output.buf.push(ctx.crate_path.clone());
+                    output.blit(760, 9);
+                    output.buf.push(body);
+                    output.tt_group(Delimiter::Brace, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+    }
+    let body = output.split_off_stream(start);
+    impl_toml_schema(output, ctx, body);
+}
+fn schema_tuple_struct(output: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let start = output.buf.len();
+    if !emit_delegated_schema(output, ctx, fields) {
+        let [single_field] = fields else {
+            return;
+        };
+        let at = output.buf.len();
+        emit_schema_fn(
+            output,
+            ctx,
+            single_field.ty,
+            single_field.with(FROM_TOML | TO_TOML),
+        );
+        output.tt_group(Delimiter::Parenthesis, at);
+        output.tt_group_empty(Delimiter::Parenthesis);
+    }
+    let body = output.split_off_stream(start);
+    impl_toml_schema(output, ctx, body);
+}
+fn emit_tuple_schemas(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let at = out.buf.len();
+    for field in fields {
+        emit_schema_fn(out, ctx, field.ty, field.with(FROM_TOML | TO_TOML));
+        {
+            out.blit_punct(13);
+        };
+    }
+    out.tt_group(Delimiter::Bracket, at);
+}
+fn schema_enum(output: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], values: &[i64]) {
+    let target = ctx.target;
+    let start = output.buf.len();
+    if !emit_delegated_schema(output, ctx, &[]) {
+        let name = Literal::string(&target.name.to_string());
+        let body_start = output.buf.len();
+        {
+            output.buf.push(TokenTree::Literal(name));
+            output.blit_punct(13);
+        };
+        emit_doc_slice(output, &target.docs);
+        {
+            output.blit_punct(13);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(769, 8);
+        };
+        match (&target.tag, &target.content) {
+            _ if target.untagged => {
+                output.blit_ident(188);
+            }
+            _ if match target.unit_repr {
+                UnitRepr::Int => true,
+                _ => false,
+            } =>
+            {
+                output.blit_ident(168);
+            }
+            _ if match target.unit_repr {
+                UnitRepr::StrOrInt => true,
+                _ => false,
+            } =>
+            {
+                output.blit_ident(178);
+            }
+            (Some(tag), Some(content)) => {
+                {
+                    output.blit_ident(157);
+                    {
+                        let at = output.buf.len();
+                        output.blit(777, 2);
+                        output.buf.push(TokenTree::Literal(tag.clone()));
+                        output.blit(779, 3);
+                        output.buf.push(TokenTree::Literal(content.clone()));
+                        output.tt_group(Delimiter::Brace, at);
+                    };
+                };
+            }
+            (Some(tag), None) => {
+                {
+                    output.blit_ident(170);
+                    {
+                        let at = output.buf.len();
+                        output.blit(777, 2);
+                        output.buf.push(TokenTree::Literal(tag.clone()));
+                        output.tt_group(Delimiter::Brace, at);
+                    };
+                };
+            }
+            _ => {
+                output.blit_ident(163);
+            }
+        }
+        {
+            output.blit(782, 2);
+        };
+        let variants_outer = output.buf.len();
+        {
+            output.blit_punct(5);
+        };
+        let variants_start = output.buf.len();
+        for (i, variant) in variants.iter().enumerate() {
+            let variant_start = output.buf.len();
+            {
+                output
+                    .buf
+                    .push(TokenTree::Literal(variant_name_literal(ctx, variant)));
+                output.blit(266, 2);
+            };
+            let aliases = output.buf.len();
+            variant.attr.for_each_alias(FROM_TOML, &mut |lit| {
+                output.buf.push(TokenTree::Literal(lit.clone()));
+                output.blit_punct(13);
+            });
+            output.tt_group(Delimiter::Bracket, aliases);
+            {
+                output.blit(266, 2);
+            };
+            let deprecated = output.buf.len();
+            variant
+                .attr
+                .for_each_deprecated_alias(FROM_TOML, &mut |_, lit| {
+                    output.buf.push(TokenTree::Literal(lit.clone()));
+                    output.blit_punct(13);
+                });
+            output.tt_group(Delimiter::Bracket, deprecated);
+            {
+                output.blit_punct(13);
+            };
+            emit_doc_slice(output, &variant.attr.docs);
+            {
+                output.blit_punct(13);
+                output.push_ident(&bool_ident(variant.other));
+                output.blit_punct(13);
+                {
+                    if let Some(value) = values.get(i) {
+                        {
+                            output.blit_ident(9);
+                            {
+                                let at = output.buf.len();
+                                int_literal(output, *value);
+                                output.tt_group(Delimiter::Parenthesis, at);
+                            };
+                        };
+                    } else {
+                        output.blit_ident(25);
+                    }
+                };
+                output.blit_punct(13);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(784, 8);
+            };
+            match variant.kind {
+                EnumKind::None => {
+                    output.blit_ident(186);
+                }
+                EnumKind::Tuple => {
+                    {
+                        output.blit_ident(185);
+                    };
+                    let at = output.buf.len();
+                    {
+                        output.blit_punct(5);
+                    };
+                    emit_tuple_schemas(output, ctx, variant.fields);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                }
+                EnumKind::Struct => {
+                    {
+                        output.blit_ident(117);
+                    };
+                    let at = output.buf.len();
+                    emit_field_schemas(output, ctx, variant.fields, Some(variant));
+                    output.tt_group(Delimiter::Parenthesis, at);
+                }
+            }
+            output.tt_group(Delimiter::Parenthesis, variant_start);
+            let body = output.buf.pop().unwrap();
+            {
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(792, 9);
+                output.buf.push(body);
+                output.blit_punct(13);
+            };
+        }
+        output.tt_group(Delimiter::Bracket, variants_start);
+        output.tt_group(Delimiter::Brace, variants_outer);
+        output.tt_group(Delimiter::Parenthesis, body_start);
+        let body = output.buf.pop().unwrap();
+        {
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(801, 9);
+            {
+                let at = output.buf.len();
+                output.blit_ident(48);
+                {
+                    let at = output.buf.len();
+                    output.blit_punct(5);
+                    output.buf.push(ctx.crate_path.clone());
+                    output.blit(810, 9);
+                    output.buf.push(body);
+                    output.tt_group(Delimiter::Brace, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+    }
+    let body = output.split_off_stream(start);
+    impl_toml_schema(output, ctx, body);
+}
+fn emit_proxy_from_toml(output: &mut RustWriter, ctx: &Ctx) -> bool {
+    if let Some(from_ty) = &ctx.target.from_type {
+        let body = {
+            let len = output.buf.len();
+            output.blit(302, 2);
+            {
+                let at = output.buf.len();
+                output.blit_ident(46);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(520, 2);
+            output.buf.extend_from_slice(from_ty);
+            output.blit_ident(11);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(42, 5);
+            output.push_ident(&ctx.lifetime);
+            output.blit(819, 5);
+            {
+                let at = output.buf.len();
+                output.blit(824, 3);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit_ident(13);
+            emit_failed_return(output, ctx);
+            output.blit_ident(2);
+            {
+                let at = output.buf.len();
+                output.blit(827, 12);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(46);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.split_off_stream(len)
+        };
+        impl_from_toml(output, &ctx, body, &[]);
+        true
+    } else if let Some(try_from_ty) = &ctx.target.try_from_type {
+        let body = {
+            let len = output.buf.len();
+            output.blit(302, 2);
+            {
+                let at = output.buf.len();
+                output.blit_ident(46);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(520, 2);
+            output.buf.extend_from_slice(try_from_ty);
+            output.blit_ident(11);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(42, 5);
+            output.push_ident(&ctx.lifetime);
+            output.blit(819, 5);
+            {
+                let at = output.buf.len();
+                output.blit(824, 3);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit_ident(13);
+            emit_failed_return(output, ctx);
+            output.blit(839, 14);
+            {
+                let at = output.buf.len();
+                output.blit_ident(46);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            {
+                let at = output.buf.len();
+                output.blit_ident(2);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(10);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit(853, 3);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(10);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit(856, 2);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(32);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit(858, 3);
+                {
+                    let at = output.buf.len();
+                    output.blit(432, 3);
+                    {
+                        let at = output.buf.len();
+                        output.blit(861, 3);
+                        output.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit_punct(13);
+                output.tt_group(Delimiter::Brace, at);
+            };
+            output.split_off_stream(len)
+        };
+        impl_from_toml(output, &ctx, body, &[]);
+        true
+    } else {
+        false
+    }
+}
+fn emit_proxy_to_toml(output: &mut RustWriter, ctx: &Ctx) -> bool {
+    if let Some(into_ty) = &ctx.target.into_type {
+        let body = {
+            let len = output.buf.len();
+            output.blit(864, 3);
+            output.buf.extend_from_slice(into_ty);
+            output.blit(867, 13);
+            {
+                let at = output.buf.len();
+                output.blit(880, 12);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(12);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(37, 2);
+            {
+                let at = output.buf.len();
+                output.blit_punct(4);
+                output.buf.extend_from_slice(into_ty);
+                output.blit_ident(11);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(534, 7);
+                {
+                    let at = output.buf.len();
+                    output.blit(892, 4);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit(896, 3);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(15);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.split_off_stream(len)
+        };
+        impl_to_toml(output, ctx, body, &[]);
+        true
+    } else if let Some(try_into_ty) = &ctx.target.try_into_type {
+        let body = {
+            let len = output.buf.len();
+            output.blit(864, 3);
+            output.buf.extend_from_slice(try_into_ty);
+            output.blit(899, 15);
+            {
+                let at = output.buf.len();
+                output.blit(880, 12);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(12);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            {
+                let at = output.buf.len();
+                output.blit_ident(2);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(10);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit(914, 5);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(32);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit(279, 4);
+                {
+                    let at = output.buf.len();
+                    output.buf.push(ctx.crate_path.clone());
+                    output.blit(169, 6);
+                    {
+                        let at = output.buf.len();
+                        output.blit(919, 12);
+                        {
+                            let at = output.buf.len();
+                            output.blit(931, 13);
+                            {
+                                let at = output.buf.len();
+                                output.blit(944, 2);
+                                output.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            output.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        output.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit_punct(13);
+                output.tt_group(Delimiter::Brace, at);
+            };
+            output.blit(37, 2);
+            {
+                let at = output.buf.len();
+                output.blit_punct(4);
+                output.buf.extend_from_slice(try_into_ty);
+                output.blit_ident(11);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(534, 7);
+                {
+                    let at = output.buf.len();
+                    output.blit(892, 4);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit(896, 3);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(15);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.split_off_stream(len)
+        };
+        impl_to_toml(output, ctx, body, &[]);
+        true
+    } else {
+        false
+    }
+}
+/// Generates inherent `from_toml`/`to_toml` functions on a local mirror of a
+/// foreign struct, for use with `#[toml(with = Mirror)]`.
+///
+/// The bodies construct and destructure the remote type with every field of
+/// the mirror, so a mismatched name, type, or field count fails to compile.
+fn handle_remote_struct(
+    output: &mut RustWriter,
+    ctx: &Ctx,
+    remote: &[TokenTree],
+    fields: &[Field],
+) {
+    let target = ctx.target;
+    if !target.generics.is_empty() {
+        Error::msg("remote cannot be used on generic types")
+    }
+    if target.transparent_impl
+        || target.from_type.is_some()
+        || target.try_from_type.is_some()
+        || target.into_type.is_some()
+        || target.try_into_type.is_some()
+    {
+        Error::msg("remote cannot be combined with transparent, from, try_from, into, or try_into")
+    }
+    let start = output.buf.len();
+    if target.from_toml {
+        let body_start = output.buf.len();
+        {
+            output.blit(302, 2);
+            {
+                let at = output.buf.len();
+                output.blit_ident(28);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(567, 4);
+            {
+                let at = output.buf.len();
+                output.blit_ident(1);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit_ident(13);
+        };
+        emit_failed_return(output, ctx);
+        emit_table_field_deser(output, ctx, fields, "__table", None, &[]);
+        {
+            output.blit_ident(2);
+            {
+                let at = output.buf.len();
+                output.buf.extend_from_slice(remote);
+                {
+                    let at = output.buf.len();
+                    {
+                        for field in fields {
+                            output.push_ident(field.name);
+                            output.blit_punct(13);
+                        }
+                    };
+                    output.tt_group(Delimiter::Brace, at);
+                };
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        let body = output.split_off_stream(body_start);
+        let body = wrap_container_validate(output, ctx, remote, body);
+        {
+            output.buf.extend_from_slice(&target.vis);
+            output.blit(946, 4);
+            output.push_ident(&ctx.lifetime);
+            output.blit_punct(0);
+            {
+                let at = output.buf.len();
+                output.blit(58, 4);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(62, 5);
+                output.push_ident(&ctx.lifetime);
+                output.blit(67, 5);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(72, 5);
+                output.push_ident(&ctx.lifetime);
+                output.blit(49, 2);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(12, 12);
+            output.buf.extend_from_slice(remote);
+            output.blit_punct(13);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(24, 4);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Brace, body)));
+        };
+    }
+    if target.to_toml {
+        let body_start = output.buf.len();
+        {
+            output.blit_ident(0);
+            output.buf.extend_from_slice(remote);
+            {
+                let at = output.buf.len();
+                {
+                    for field in fields {
+                        if field.flags & Field::WITH_TO_TOML_SKIP != 0 {
+                            output.push_ident(field.name);
+                            output.blit(950, 3);
+                        } else {
+                            output.push_ident(field.name);
+                            output.blit_punct(13);
+                        }
+                    }
+                };
+                output.tt_group(Delimiter::Brace, at);
+            };
+            output.blit(953, 3);
+        };
+        emit_table_alloc(output, ctx, "__table", count_ser_fields(fields));
+        emit_table_field_ser(output, ctx, fields, "__table", None, false);
+        {
+            output.blit_ident(2);
+            {
+                let at = output.buf.len();
+                output.blit(639, 5);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+        };
+        let body = output.split_off_stream(body_start);
+        let lf = Ident::new("__de", Span::mixed_site());
+        {
+            output.buf.extend_from_slice(&target.vis);
+            output.blit(139, 4);
+            output.buf.push(TokenTree::from(lf.clone()));
+            output.blit_punct(0);
+            {
+                let at = output.buf.len();
+                output.blit(956, 4);
+                output.buf.push(TokenTree::from(lf.clone()));
+                output.buf.extend_from_slice(remote);
+                output.blit(146, 5);
+                output.buf.push(TokenTree::from(lf.clone()));
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(151, 3);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(12, 12);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(72, 5);
+            output.buf.push(TokenTree::from(lf.clone()));
+            output.blit(49, 2);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(154, 4);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Brace, body)));
+        };
+    }
+    {
+        output.blit_punct(14);
+        {
+            let at = output.buf.len();
+            output.blit_ident(102);
+            {
+                let at = output.buf.len();
+                output.blit_ident(200);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.tt_group(Delimiter::Bracket, at);
+        };
+        output.blit(960, 2);
+        {
+            let at = output.buf.len();
+            output.blit(962, 3);
+            output.tt_group(Delimiter::Parenthesis, at);
+        };
+        output.blit(12, 2);
+        output.buf.extend_from_slice(remote);
+        {
+            let at = output.buf.len();
+            output.blit(965, 2);
+            {
+                let at = output.buf.len();
+                {
+                    for field in fields {
+                        output.push_ident(field.name);
+                        output.blit_punct(13);
+                    }
+                };
+                output.tt_group(Delimiter::Brace, at);
+            };
+            output.blit(953, 3);
+            output.buf.extend_from_slice(remote);
+            {
+                let at = output.buf.len();
+                {
+                    for field in fields {
+                        output.push_ident(field.name);
+                        output.blit_punct(13);
+                    }
+                };
+                output.tt_group(Delimiter::Brace, at);
+            };
+            output.tt_group(Delimiter::Brace, at);
+        };
+    };
+    let inner = output.split_off_stream(start);
+    {
+        output.blit_punct(14);
+        {
+            let at = output.buf.len();
+            output.blit_ident(47);
+            output.tt_group(Delimiter::Bracket, at);
+        };
+        output.blit_ident(49);
+        output.push_ident(&target.name);
+        output
+            .buf
+            .push(TokenTree::Group(Group::new(Delimiter::Brace, inner)));
+    };
+}
+/// Returns the `#[toml(version)]` field, rejecting more than one.
+fn version_field<'f>(fields: &'f [Field<'f>]) -> Option<&'f Field<'f>> {
+    let mut found = None;
+    for field in fields {
+        if field.is_version() {
+            if found.is_some() {
+                Error::span_msg(
+                    "Only one #[toml(version)] field is allowed",
+                    field.name.span(),
+                )
+            }
+            found = Some(field);
+        }
+    }
+    found
+}
+/// Rejects versioning and partial attributes outside of plain structs.
+fn reject_named_struct_attrs(target: &DeriveTargetInner, fields: &[Field]) {
+    if target.migrations.is_some() {
+        Error::msg("migrations can only be used on structs with a #[toml(version)] field")
+    }
+    if target.partial.is_some() {
+        Error::msg("partial can only be used on structs with named fields")
+    }
+    for field in fields {
+        if field.is_version() {
+            Error::span_msg(
+                "version can only be used on fields of structs with named fields",
+                field.name.span(),
+            )
+        }
+        if field.is_partial() {
+            Error::span_msg(
+                "partial can only be used on fields of structs with named fields",
+                field.name.span(),
+            )
+        }
+    }
+}
+/// Implements `Versioned`, naming the version key and the migration chain.
+fn impl_versioned(output: &mut RustWriter, ctx: &Ctx, field: &Field) {
+    let target = ctx.target;
+    if target.remote.is_some()
+        || target.transparent_impl
+        || target.from_type.is_some()
+        || target.try_from_type.is_some()
+    {
+        Error::span_msg(
+            "version cannot be combined with remote, transparent, from, or try_from",
+            field.name.span(),
+        )
+    }
+    if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) != 0 {
+        Error::span_msg(
+            "version cannot be used on skipped or flattened fields",
+            field.name.span(),
+        )
+    }
+    let any_generics = !target.generics.is_empty();
+    let key = field_name_lit(ctx, field, None);
+    let empty = Vec::new();
+    let migrations = target.migrations.as_ref().unwrap_or(&empty);
+    let list_start = output.buf.len();
+    for migration in migrations {
+        output.buf.extend_from_slice(migration);
+        output.blit_punct(13);
+    }
+    let list = output.split_off_stream(list_start);
+    let ty_start = output.buf.len();
+    {
+        output.buf.push(ctx.crate_path.clone());
+        output.blit(967, 3);
+    };
+    let slice_ty = output.split_off_stream(ty_start);
+    {
+        output.blit_punct(14);
+        {
+            let at = output.buf.len();
+            output.blit_ident(47);
+            output.tt_group(Delimiter::Bracket, at);
+        };
+        output.blit_ident(49);
+        if any_generics {
+            output.blit_punct(4);
+            fmt_generics(output, &target.generics, DEF);
+            output.blit_punct(0);
+        };
+        output.buf.push(ctx.crate_path.clone());
+        output.blit(970, 4);
+        output.push_ident(&target.name);
+        if any_generics {
+            output.blit_punct(4);
+            fmt_generics(output, &target.generics, USE);
+            output.blit_punct(0);
+        };
+        if !target.where_clauses.is_empty() {
+            output.blit_ident(91);
+            output.buf.extend_from_slice(&target.where_clauses);
+        };
+        {
+            let at = output.buf.len();
+            output.blit(974, 8);
+            output.buf.push(key.into());
+            output.blit(982, 7);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Bracket, slice_ty)));
+            output.blit(555, 2);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Bracket, list)));
+            output.blit_punct(2);
+            output.tt_group(Delimiter::Brace, at);
+        };
+    };
+}
+/// The field type of a `#[toml(partial)]` field's own partial type.
+fn emit_nested_partial_ty(out: &mut RustWriter, ctx: &Ctx, field: &Field) {
+    let ty = if field.flags & Field::WITH_FROM_TOML_OPTION != 0 {
+        option_inner_ty(field.ty)
+    } else {
+        field.ty
+    };
+    {
+        out.blit_punct(4);
+        out.buf.extend_from_slice(ty);
+        out.blit_ident(11);
+        out.buf.push(ctx.crate_path.clone());
+        out.blit(989, 7);
+    };
+}
+/// Declares the companion type of `#[toml(partial = Name)]`, every field
+/// wrapped in `Option<Layered<_>>`.
+///
+/// The declaration derives `FromToml` itself, forwarding the key spelling
+/// and unknown field policy, and is emitted outside of the `const` block so
+/// it can be named.
+fn partial_struct(output: &mut RustWriter, ctx: &Ctx, partial: &Ident, fields: &[Field]) {
+    let target = ctx.target;
+    let mut doc = String::from(" Partial form of [`");
+    doc.push_str(&target.name.to_string());
+    doc.push_str("`] for merging configuration layers, see [`toml_spanner::Partial`].");
+    output.blit_punct(17);
+    let at = output.buf.len();
+    {
+        output.blit(996, 2);
+        output.buf.push(Literal::string(&doc).into());
+    };
+    output.tt_group(Delimiter::Bracket, at);
+    output.blit_punct(17);
+    let at = output.buf.len();
+    {
+        output.blit_ident(203);
+        {
+            let at = output.buf.len();
+            output.blit(998, 2);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(1000, 3);
+            output.tt_group(Delimiter::Parenthesis, at);
+        };
+    };
+    output.tt_group(Delimiter::Bracket, at);
+    output.blit_punct(17);
+    let at = output.buf.len();
+    {
+        output.blit_ident(153);
+    };
+    let args_at = output.buf.len();
+    {
+        output.blit_ident(21);
+    };
+    match &target.unknown_fields {
+        UnknownFieldPolicy::Ignore => {
+            output.blit(1003, 2);
+        }
+        UnknownFieldPolicy::Deny { tag } => {
+            {
+                output.blit(1005, 2);
+            };
+            if let Some(tag) = tag {
+                let tag_at = output.buf.len();
+                output.buf.extend_from_slice(tag);
+                output.tt_group(Delimiter::Bracket, tag_at);
+            }
+        }
+        UnknownFieldPolicy::Warn { tag: Some(tag) } => {
+            {
+                output.blit(1007, 3);
+            };
+            let tag_at = output.buf.len();
+            output.buf.extend_from_slice(tag);
+            output.tt_group(Delimiter::Bracket, tag_at);
+        }
+        UnknownFieldPolicy::Warn { tag: None } => (),
+    }
+    if !target.key_match.is_exact() {
+        output.blit(1010, 3);
+        output
+            .buf
+            .push(Literal::string(target.key_match.attr_value()).into());
+    }
+    output.tt_group(Delimiter::Parenthesis, args_at);
+    output.tt_group(Delimiter::Bracket, at);
+    {
+        output.buf.extend_from_slice(&target.vis);
+        output.blit_ident(237);
+        output.push_ident(partial);
+    };
+    let body_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        if field.flags & Field::WITH_FLATTEN != 0 || field.with(FROM_TOML).is_some() {
+            Error::span_msg(
+                "partial cannot be used with flatten or with fields",
+                field.name.span(),
+            )
+        }
+        for doc in &field.attr.docs {
+            output.blit_punct(17);
+            let at = output.buf.len();
+            {
+                output.blit(996, 2);
+                output.buf.push(TokenTree::Literal(doc.clone()));
+            };
+            output.tt_group(Delimiter::Bracket, at);
+        }
+        output.blit_punct(17);
+        let at = output.buf.len();
+        {
+            output.blit_ident(153);
+        };
+        let args_at = output.buf.len();
+        {
+            output.blit(1013, 2);
+            output.buf.push(field_name_lit(ctx, field, None).into());
+        };
+        field.attr.for_each_alias(FROM_TOML, &mut |alias| {
+            output.blit(1015, 3);
+            output.buf.push(alias.clone().into());
+        });
+        field
+            .attr
+            .for_each_deprecated_alias(FROM_TOML, &mut |tag, alias| {
+                {
+                    output.blit(1018, 3);
+                };
+                if let Some(tag) = tag {
+                    let tag_at = output.buf.len();
+                    output.buf.extend_from_slice(tag);
+                    output.tt_group(Delimiter::Bracket, tag_at);
+                }
+                {
+                    output.buf.push(alias.clone().into());
+                };
+            });
+        output.tt_group(Delimiter::Parenthesis, args_at);
+        output.tt_group(Delimiter::Bracket, at);
+        {
+            output.buf.extend_from_slice(&target.vis);
+            output.push_ident(field.name);
+            output.blit(220, 3);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(1021, 4);
+        };
+        if field.is_partial() {
+            emit_nested_partial_ty(output, ctx, field);
+        } else if field.flags & Field::WITH_FROM_TOML_OPTION != 0 {
+            output.buf.extend_from_slice(option_inner_ty(field.ty));
+        } else {
+            output.buf.extend_from_slice(field.ty);
+        }
+        {
+            output.blit(1025, 3);
+        };
+    }
+    output.tt_group(Delimiter::Brace, body_at);
+}
+/// Implements `Partial` for the companion type and `HasPartial` for the
+/// struct.
+fn impl_partial(output: &mut RustWriter, ctx: &Ctx, partial: &Ident, fields: &[Field]) {
+    let target = ctx.target;
+    if !target.generics.is_empty() {
+        Error::msg("partial cannot be used on generic types")
+    }
+    if target.remote.is_some() || target.transparent_impl {
+        Error::msg("partial cannot be combined with remote or transparent")
+    }
+    let full = &target.name;
+    let merge_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        if field.is_partial() {
+            {
+                output.push_ident(field.name);
+                output.blit_punct(9);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(1028, 6);
+                {
+                    let at = output.buf.len();
+                    output.blit(497, 2);
+                    output.push_ident(field.name);
+                    output.blit(1034, 3);
+                    output.push_ident(field.name);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit_punct(13);
+            };
+        } else {
+            {
+                output.push_ident(field.name);
+                output.blit(1037, 3);
+                output.push_ident(field.name);
+                output.blit(1040, 2);
+                {
+                    let at = output.buf.len();
+                    output.blit(497, 2);
+                    output.push_ident(field.name);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit_punct(13);
+            };
+        }
+    }
+    let merge = output.split_off_stream(merge_at);
+    let layer_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        {
+            output.blit(398, 3);
+            {
+                let at = output.buf.len();
+                output.blit_ident(6);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(1042, 5);
+            output.push_ident(field.name);
+            {
+                let at = output.buf.len();
+                output.blit(1047, 3);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(97);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit_punct(2);
+                if field.is_partial() {
+                    output.buf.push(ctx.crate_path.clone());
+                    output.blit(1050, 6);
+                    {
+                        let at = output.buf.len();
+                        output.blit(1056, 7);
+                        output.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    output.blit_punct(2);
+                };
+                output.tt_group(Delimiter::Brace, at);
+            };
+        };
+    }
+    let set_layer = output.split_off_stream(layer_at);
+    let top_layer_at = output.buf.len();
+    {
+        output.blit(1063, 11);
+    };
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        {
+            output.blit(398, 3);
+            {
+                let at = output.buf.len();
+                output.blit_ident(6);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(1074, 4);
+            output.push_ident(field.name);
+            {
+                let at = output.buf.len();
+                output.blit(1078, 5);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(9);
+                    {
+                        let at = output.buf.len();
+                        output.blit(1083, 3);
+                        output.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.blit_punct(2);
+                output.tt_group(Delimiter::Brace, at);
+            };
+        };
+    }
+    {
+        output.blit_ident(83);
+    };
+    let top_layer = output.split_off_stream(top_layer_at);
+    let resolve_at = output.buf.len();
+    for field in fields {
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            emit_field_default(output, field, FROM_TOML, false);
+            continue;
+        }
+        let key = field_name_lit(ctx, field, None);
+        let is_option = field.flags & Field::WITH_FROM_TOML_OPTION != 0;
+        let is_default = field.flags & Field::WITH_FROM_TOML_DEFAULT != 0;
+        if field.is_partial() {
+            {
+                output.blit_ident(0);
+                output.push_ident(field.name);
+                output.blit_punct(3);
+            };
+            let nested: Vec<TokenTree> = {
+                let len = output.buf.len();
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(1086, 6);
+                {
+                    let at = output.buf.len();
+                    output.blit(341, 2);
+                    output.buf.push(key.into());
+                    output.blit(1092, 6);
+                    output.tt_group(Delimiter::Parenthesis, at);
+                };
+                output.split_off_stream(len)
+            }
+            .into_iter()
+            .collect();
+            if is_option || is_default {
+                {
+                    output.blit(1098, 3);
+                    output.push_ident(field.name);
+                };
+                let arms_at = output.buf.len();
+                {
+                    output.blit(1101, 4);
+                };
+                let value_at = output.buf.len();
+                if is_option {
+                    output.blit_ident(25);
+                } else {
+                    match field.default(FROM_TOML) {
+                        Some(DefaultKind::Custom(tokens)) => {
+                            output.buf.extend_from_slice(tokens.as_slice());
+                        }
+                        _ => {
+                            output.blit(489, 6);
+                        }
+                    }
+                }
+                output.tt_group(Delimiter::Parenthesis, value_at);
+                {
+                    output.blit(1105, 4);
+                    output.buf.extend_from_slice(&nested);
+                    if is_option {
+                        output.blit(1109, 2);
+                        {
+                            let at = output.buf.len();
+                            output.blit_ident(9);
+                            output.tt_group(Delimiter::Parenthesis, at);
+                        };
+                    };
+                    output.blit_punct(13);
+                };
+                output.tt_group(Delimiter::Brace, arms_at);
+                {
+                    output.blit_punct(2);
+                };
+            } else {
+                {
+                    {
+                        let at = output.buf.len();
+                        output.blit(1111, 5);
+                        output.push_ident(field.name);
+                        output.blit_punct(2);
+                        output.buf.extend_from_slice(&nested);
+                        output.tt_group(Delimiter::Brace, at);
+                    };
+                    output.blit_punct(2);
+                };
+            }
+        } else if is_option || is_default {
+            {
+                output.blit_ident(0);
+                output.push_ident(field.name);
+                output.blit(1116, 4);
+                output.push_ident(field.name);
+                {
+                    let at = output.buf.len();
+                    output.blit_ident(9);
+                    {
+                        let at = output.buf.len();
+                        output.blit_ident(6);
+                        output.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    output.blit(1102, 3);
+                    {
+                        let at = output.buf.len();
+                        output.blit(1058, 3);
+                        output.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    output.blit(1120, 6);
+                    output.tt_group(Delimiter::Brace, at);
+                };
+                output.blit_punct(2);
             };
-            emit_flatten_prefix(out, ctx, field, TO_TOML);
+            if !is_option {
+                emit_field_default(output, field, FROM_TOML, true);
+            }
+        } else {
             {
-                out.blit(311, 3);
+                output.blit_ident(0);
+                output.push_ident(field.name);
+                output.blit_punct(3);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(1126, 7);
                 {
-                    let at = out.buf.len();
-                    out.buf
-                        .push(TokenTree::Group(Group::new(Delimiter::None, field_ref)));
-                    out.blit(314, 5);
-                    out.buf.push(TokenTree::from(table_id.clone()));
-                    out.tt_group(Delimiter::Parenthesis, at);
+                    let at = output.buf.len();
+                    output.blit(497, 2);
+                    output.push_ident(field.name);
+                    output.blit_punct(13);
+                    output.buf.push(key.into());
+                    output.blit(1092, 6);
+                    output.tt_group(Delimiter::Parenthesis, at);
                 };
-                out.blit(319, 2);
+                output.blit_punct(2);
             };
         }
     }
-}
-fn emit_struct_variant_to_arm(out: &mut RustWriter, variant: &EnumVariant, arm_body_start: usize) {
-    let arm_body = out.split_off_stream(arm_body_start);
     {
-        out.blit(321, 3);
-        out.push_ident(variant.name);
-        {
-            let at = out.buf.len();
-            {
-                for field in variant.fields {
-                    out.blit_ident(17);
-                    out.push_ident(field.name);
-                    out.blit_punct(13);
-                }
-            };
-            out.tt_group(Delimiter::Brace, at);
-        };
-        out.blit(149, 2);
-        out.buf
-            .push(TokenTree::Group(Group::new(Delimiter::Brace, arm_body)));
+        output.blit_ident(9);
     };
-}
-fn count_ser_fields(fields: &[Field]) -> usize {
-    let mut count = 0;
-    for f in fields {
-        if f.flags & (Field::WITH_TO_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
-            count += 1;
-        }
-    }
-    count
-}
-/// Emit Ok(Self::Variant { field1, field2, ... }) for struct variant deserialization
-fn emit_ok_self_variant(out: &mut RustWriter, variant: &EnumVariant) {
+    let construct_at = output.buf.len();
     {
-        out.blit_ident(115);
+        output.push_ident(full);
+    };
+    let fields_at = output.buf.len();
+    for field in fields {
+        let fallible = field.flags & Field::WITH_FROM_TOML_SKIP == 0
+            && (field.is_partial()
+                || field.flags & (Field::WITH_FROM_TOML_OPTION | Field::WITH_FROM_TOML_DEFAULT)
+                    == 0);
         {
-            let at = out.buf.len();
-            out.blit(321, 3);
-            out.push_ident(variant.name);
-            {
-                let at = out.buf.len();
-                {
-                    for field in variant.fields {
-                        out.push_ident(field.name);
-                        out.blit_punct(13);
-                    }
-                };
-                out.tt_group(Delimiter::Brace, at);
+            output.push_ident(field.name);
+            output.blit_punct(9);
+            output.push_ident(field.name);
+            if fallible {
+                output.blit_punct(12);
             };
-            out.tt_group(Delimiter::Parenthesis, at);
+            output.blit_punct(13);
         };
-    };
-}
-fn struct_from_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
-    let start = out.buf.len();
+    }
+    output.tt_group(Delimiter::Brace, fields_at);
+    output.tt_group(Delimiter::Parenthesis, construct_at);
+    let resolve = output.split_off_stream(resolve_at);
     {
-        out.blit(221, 2);
+        output.blit_punct(14);
         {
-            let at = out.buf.len();
-            out.blit_ident(74);
-            out.tt_group(Delimiter::Parenthesis, at);
+            let at = output.buf.len();
+            output.blit_ident(47);
+            output.tt_group(Delimiter::Bracket, at);
         };
-        out.blit(324, 4);
+        output.blit_ident(49);
+        output.buf.push(ctx.crate_path.clone());
+        output.blit(1133, 4);
+        output.push_ident(full);
         {
-            let at = out.buf.len();
-            out.blit_ident(114);
-            out.tt_group(Delimiter::Parenthesis, at);
+            let at = output.buf.len();
+            output.blit(1137, 3);
+            output.push_ident(partial);
+            output.blit_punct(2);
+            output.tt_group(Delimiter::Brace, at);
         };
-        out.blit_ident(109);
-    };
-    emit_failed_return(out, ctx);
-    emit_table_field_deser(out, ctx, fields, "__table", None, &[]);
-    {
-        out.blit_ident(115);
+        output.blit_punct(14);
         {
-            let at = out.buf.len();
-            out.blit_ident(110);
-            {
-                let at = out.buf.len();
-                {
-                    for field in fields {
-                        out.push_ident(field.name);
-                        out.blit_punct(13);
-                    }
-                };
-                out.tt_group(Delimiter::Brace, at);
-            };
-            out.tt_group(Delimiter::Parenthesis, at);
+            let at = output.buf.len();
+            output.blit_ident(47);
+            output.tt_group(Delimiter::Bracket, at);
         };
-    };
-    let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
-}
-fn struct_to_toml(out: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
-    let start = out.buf.len();
-    emit_table_alloc(out, ctx, "__table", count_ser_fields(fields));
-    emit_table_field_ser(out, ctx, fields, "__table", None, true);
-    {
-        out.blit_ident(115);
+        output.blit_ident(49);
+        output.buf.push(ctx.crate_path.clone());
+        output.blit(1140, 4);
+        output.push_ident(partial);
         {
-            let at = out.buf.len();
-            out.blit(328, 4);
-            out.tt_group(Delimiter::Parenthesis, at);
-        };
-    };
-    let body = out.split_off_stream(start);
-    impl_to_toml(out, ctx, body);
-}
-fn emit_proxy_from_toml(output: &mut RustWriter, ctx: &Ctx) -> bool {
-    if let Some(from_ty) = &ctx.target.from_type {
-        let body = {
-            let len = output.buf.len();
-            output.blit(221, 2);
+            let at = output.buf.len();
+            output.blit(1144, 3);
+            output.push_ident(full);
+            output.blit(1147, 3);
             {
                 let at = output.buf.len();
-                output.blit_ident(64);
+                output.blit(1150, 5);
                 output.tt_group(Delimiter::Parenthesis, at);
             };
-            output.blit(291, 2);
-            output.buf.extend_from_slice(from_ty);
-            output.blit_ident(104);
-            output.buf.push(ctx.crate_path.clone());
-            output.blit(6, 5);
-            output.push_ident(&ctx.lifetime);
-            output.blit(332, 5);
+            output.blit(1155, 3);
             {
                 let at = output.buf.len();
-                output.blit(337, 3);
-                output.tt_group(Delimiter::Parenthesis, at);
+                output.blit_ident(4);
+                output
+                    .buf
+                    .push(TokenTree::Group(Group::new(Delimiter::Brace, merge)));
+                output.tt_group(Delimiter::Brace, at);
             };
-            output.blit_ident(109);
-            emit_failed_return(output, ctx);
-            output.blit_ident(115);
+            output.blit(1158, 2);
             {
                 let at = output.buf.len();
-                output.blit(340, 12);
-                {
-                    let at = output.buf.len();
-                    output.blit_ident(64);
-                    output.tt_group(Delimiter::Parenthesis, at);
-                };
+                output.blit(1160, 7);
                 output.tt_group(Delimiter::Parenthesis, at);
             };
-            output.split_off_stream(len)
-        };
-        impl_from_toml(output, &ctx, body);
-        true
-    } else if let Some(try_from_ty) = &ctx.target.try_from_type {
-        let body = {
-            let len = output.buf.len();
-            output.blit(221, 2);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Brace, set_layer)));
+            output.blit(1167, 3);
             {
                 let at = output.buf.len();
-                output.blit_ident(64);
+                output.blit(496, 2);
                 output.tt_group(Delimiter::Parenthesis, at);
             };
-            output.blit(291, 2);
-            output.buf.extend_from_slice(try_from_ty);
-            output.blit_ident(104);
-            output.buf.push(ctx.crate_path.clone());
-            output.blit(6, 5);
-            output.push_ident(&ctx.lifetime);
-            output.blit(332, 5);
+            output.blit(1170, 6);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Brace, top_layer)));
+            output.blit(1176, 2);
             {
                 let at = output.buf.len();
-                output.blit(337, 3);
+                output.blit(1178, 18);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(1196, 15);
+                output.buf.push(ctx.crate_path.clone());
+                output.blit(1211, 5);
                 output.tt_group(Delimiter::Parenthesis, at);
             };
-            output.blit_ident(109);
-            emit_failed_return(output, ctx);
-            output.blit(352, 13);
+            output.blit(1170, 4);
+            output.push_ident(full);
+            output.blit_punct(0);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Brace, resolve)));
+            output.tt_group(Delimiter::Brace, at);
+        };
+    };
+}
+/// Implements `UpdateFromToml`, moving each changed field of the new value
+/// into `self` and recording its key.
+fn impl_update(output: &mut RustWriter, ctx: &Ctx, fields: &[Field]) {
+    let target = ctx.target;
+    let body_at = output.buf.len();
+    {
+        output.blit(1216, 4);
+        output.buf.push(ctx.crate_path.clone());
+        output.blit(1220, 9);
+    };
+    for field in fields {
+        if field.flags & Field::WITH_FLATTEN != 0 {
+            Error::span_msg(
+                "Update cannot be used with flatten fields",
+                field.name.span(),
+            )
+        }
+        if field.span_source().is_some() {
             {
-                let at = output.buf.len();
-                output.blit_ident(64);
-                output.tt_group(Delimiter::Parenthesis, at);
+                output.blit(497, 2);
+                output.push_ident(field.name);
+                output.blit(1229, 3);
+                output.push_ident(field.name);
+                output.blit_punct(2);
             };
+            continue;
+        }
+        if field.flags & Field::WITH_FROM_TOML_SKIP != 0 {
+            continue;
+        }
+        let key = field_name_lit(ctx, field, None);
+        if field.is_update() {
             {
-                let at = output.buf.len();
-                output.blit_ident(115);
-                {
-                    let at = output.buf.len();
-                    output.blit_ident(111);
-                    output.tt_group(Delimiter::Parenthesis, at);
-                };
-                output.blit(365, 3);
+                output.blit(1232, 3);
                 {
                     let at = output.buf.len();
-                    output.blit_ident(111);
-                    output.tt_group(Delimiter::Parenthesis, at);
-                };
-                output.blit(368, 2);
-                {
-                    let at = output.buf.len();
-                    output.blit_ident(93);
+                    output.buf.push(key.into());
+                    output.blit_punct(13);
+                    output.buf.push(ctx.crate_path.clone());
+                    output.blit(1235, 6);
+                    {
+                        let at = output.buf.len();
+                        output.blit(1043, 4);
+                        output.push_ident(field.name);
+                        output.blit(1241, 3);
+                        output.push_ident(field.name);
+                        output.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    output.blit_punct(13);
                     output.tt_group(Delimiter::Parenthesis, at);
                 };
-                output.blit(370, 3);
+                output.blit_punct(2);
+            };
+        } else {
+            {
+                output.blit(1244, 3);
+                output.push_ident(field.name);
+                output.blit(1247, 4);
+                output.push_ident(field.name);
                 {
                     let at = output.buf.len();
-                    output.blit(373, 3);
+                    output.blit(497, 2);
+                    output.push_ident(field.name);
+                    output.blit(1229, 3);
+                    output.push_ident(field.name);
+                    output.blit(1251, 4);
                     {
                         let at = output.buf.len();
-                        output.blit(376, 3);
+                        output.buf.push(key.into());
+                        output.blit_punct(13);
+                        output.buf.push(ctx.crate_path.clone());
+                        output.blit(1255, 8);
                         output.tt_group(Delimiter::Parenthesis, at);
                     };
-                    output.tt_group(Delimiter::Parenthesis, at);
+                    output.blit_punct(2);
+                    output.tt_group(Delimiter::Brace, at);
                 };
-                output.blit_punct(13);
-                output.tt_group(Delimiter::Brace, at);
             };
-            output.split_off_stream(len)
-        };
-        impl_from_toml(output, &ctx, body);
-        true
-    } else {
-        false
+        }
     }
+    {
+        output.blit_ident(79);
+    };
+    let body = output.split_off_stream(body_at);
+    {
+        output.blit_punct(14);
+        {
+            let at = output.buf.len();
+            output.blit_ident(47);
+            output.tt_group(Delimiter::Bracket, at);
+        };
+        output.blit(39, 3);
+        output.push_ident(&ctx.lifetime);
+        output.blit_punct(0);
+        output.buf.push(ctx.crate_path.clone());
+        output.blit(1263, 5);
+        output.push_ident(&ctx.lifetime);
+        output.blit(47, 2);
+        output.push_ident(&target.name);
+        {
+            let at = output.buf.len();
+            output.blit(1268, 2);
+            {
+                let at = output.buf.len();
+                output.blit(1270, 7);
+                output.tt_group(Delimiter::Parenthesis, at);
+            };
+            output.blit(12, 2);
+            output.buf.push(ctx.crate_path.clone());
+            output.blit(1220, 3);
+            output
+                .buf
+                .push(TokenTree::Group(Group::new(Delimiter::Brace, body)));
+            output.tt_group(Delimiter::Brace, at);
+        };
+    };
 }
 fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[Field]) {
     let ctx = Ctx::new(output, target);
+    if target.schema {
+        schema_struct(output, &ctx, fields);
+    }
+    if let Some(partial) = &target.partial {
+        impl_partial(output, &ctx, partial, fields);
+    } else {
+        for field in fields {
+            if field.is_partial() {
+                Error::span_msg(
+                    "partial fields require a partial container attribute",
+                    field.name.span(),
+                )
+            }
+        }
+    }
+    if target.update {
+        impl_update(output, &ctx, fields);
+    } else {
+        for field in fields {
+            if field.is_update() {
+                Error::span_msg(
+                    "update fields require the Update container attribute",
+                    field.name.span(),
+                )
+            }
+        }
+    }
+    if target.from_toml {
+        if let Some(field) = version_field(fields) {
+            impl_versioned(output, &ctx, field);
+        } else if target.migrations.is_some() {
+            Error::msg("migrations requires a #[toml(version)] field")
+        }
+    }
+    if let Some(remote) = &target.remote {
+        handle_remote_struct(output, &ctx, remote, fields);
+        return;
+    }
     if target.from_toml && !emit_proxy_from_toml(output, &ctx) {
         if target.transparent_impl {
             let [single_field] = fields else {
@@ -1414,26 +4090,26 @@ fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[
             };
             let body = {
                 let len = output.buf.len();
-                output.blit_punct(5);
+                output.blit_punct(4);
                 output.buf.extend_from_slice(single_field.ty);
-                output.blit_ident(104);
+                output.blit_ident(11);
                 output.buf.push(ctx.crate_path.clone());
-                output.blit(6, 5);
+                output.blit(42, 5);
                 output.push_ident(&ctx.lifetime);
-                output.blit(332, 5);
+                output.blit(819, 5);
                 {
                     let at = output.buf.len();
-                    output.blit(337, 3);
+                    output.blit(824, 3);
                     output.tt_group(Delimiter::Parenthesis, at);
                 };
                 output.split_off_stream(len)
             };
-            impl_from_toml(output, &ctx, body);
+            impl_from_toml(output, &ctx, body, &[]);
         } else {
             struct_from_toml(output, &ctx, fields);
         }
     }
-    if target.to_toml {
+    if target.to_toml && !emit_proxy_to_toml(output, &ctx) {
         if target.transparent_impl {
             let [single_field] = fields else {
                 Error::msg("Struct must contain a single field to use transparent")
@@ -1442,12 +4118,12 @@ fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[
                 {
                     let len = output.buf.len();
                     output.buf.extend_from_slice(with);
-                    output.blit(278, 3);
+                    output.blit(507, 3);
                     {
                         let at = output.buf.len();
-                        output.blit(264, 3);
+                        output.blit(496, 3);
                         output.push_ident(single_field.name);
-                        output.blit(78, 2);
+                        output.blit(146, 2);
                         output.tt_group(Delimiter::Parenthesis, at);
                     };
                     output.split_off_stream(len)
@@ -1455,22 +4131,22 @@ fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[
             } else {
                 {
                     let len = output.buf.len();
-                    output.blit_punct(5);
+                    output.blit_punct(4);
                     output.buf.extend_from_slice(single_field.ty);
-                    output.blit_ident(104);
+                    output.blit_ident(11);
                     output.buf.push(ctx.crate_path.clone());
-                    output.blit(304, 7);
+                    output.blit(534, 7);
                     {
                         let at = output.buf.len();
-                        output.blit(264, 3);
+                        output.blit(496, 3);
                         output.push_ident(single_field.name);
-                        output.blit(78, 2);
+                        output.blit(146, 2);
                         output.tt_group(Delimiter::Parenthesis, at);
                     };
                     output.split_off_stream(len)
                 }
             };
-            impl_to_toml(output, &ctx, body);
+            impl_to_toml(output, &ctx, body, &[]);
         } else {
             struct_to_toml(output, &ctx, fields);
         }
@@ -1478,64 +4154,71 @@ fn handle_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[
 }
 fn handle_tuple_struct(output: &mut RustWriter, target: &DeriveTargetInner, fields: &[Field]) {
     let ctx = Ctx::new(output, target);
+    reject_named_struct_attrs(target, fields);
+    if target.remote.is_some() {
+        Error::msg("remote can only be used on structs with named fields")
+    }
+    if target.schema {
+        schema_tuple_struct(output, &ctx, fields);
+    }
     if target.from_toml && !emit_proxy_from_toml(output, &ctx) {
         if let [single_field] = fields {
             let body = {
                 let len = output.buf.len();
-                output.blit(221, 2);
+                output.blit(302, 2);
                 {
                     let at = output.buf.len();
-                    output.blit_ident(111);
+                    output.blit_ident(10);
                     output.tt_group(Delimiter::Parenthesis, at);
                 };
-                output.blit(291, 2);
+                output.blit(520, 2);
                 output.buf.extend_from_slice(single_field.ty);
-                output.blit_ident(104);
+                output.blit_ident(11);
                 output.buf.push(ctx.crate_path.clone());
-                output.blit(6, 5);
+                output.blit(42, 5);
                 output.push_ident(&ctx.lifetime);
-                output.blit(332, 5);
+                output.blit(819, 5);
                 {
                     let at = output.buf.len();
-                    output.blit(337, 3);
+                    output.blit(824, 3);
                     output.tt_group(Delimiter::Parenthesis, at);
                 };
-                output.blit_ident(109);
+                output.blit_ident(13);
                 emit_failed_return(output, &ctx);
-                output.blit_ident(115);
+                output.blit_ident(2);
                 {
                     let at = output.buf.len();
                     output.push_ident(&target.name);
                     {
                         let at = output.buf.len();
-                        output.blit_ident(111);
+                        output.blit_ident(10);
                         output.tt_group(Delimiter::Parenthesis, at);
                     };
                     output.tt_group(Delimiter::Parenthesis, at);
                 };
                 output.split_off_stream(len)
             };
-            impl_from_toml(output, &ctx, body);
+            impl_from_toml(output, &ctx, body, &[]);
         } else {
             Error::msg(
                 "FromToml on tuple structs requires exactly one field (transparent delegation)",
             )
         }
     }
-    if target.to_toml {
+    if target.to_toml && !emit_proxy_to_toml(output, &ctx) {
         if let [single_field] = fields {
             let body = if let Some(with) = single_field.with(TO_TOML) {
                 {
                     let len = output.buf.len();
                     output.buf.extend_from_slice(with);
-                    output.blit(278, 3);
+                    output.blit(507, 3);
                     {
                         let at = output.buf.len();
-                        output.blit(264, 3);
+                        output.blit(496, 3);
                         output
                             .buf
                             .push(TokenTree::Literal(Literal::usize_unsuffixed(0)));
-                        output.blit(78, 2);
+                        output.blit(146, 2);
                         output.tt_group(Delimiter::Parenthesis, at);
                     };
                     output.split_off_stream(len)
@@ -1543,24 +4226,24 @@ fn handle_tuple_struct(output: &mut RustWriter, target: &DeriveTargetInner, fiel
             } else {
                 {
                     let len = output.buf.len();
-                    output.blit_punct(5);
+                    output.blit_punct(4);
                     output.buf.extend_from_slice(single_field.ty);
-                    output.blit_ident(104);
+                    output.blit_ident(11);
                     output.buf.push(ctx.crate_path.clone());
-                    output.blit(304, 7);
+                    output.blit(534, 7);
                     {
                         let at = output.buf.len();
-                        output.blit(264, 3);
+                        output.blit(496, 3);
                         output
                             .buf
                             .push(TokenTree::Literal(Literal::usize_unsuffixed(0)));
-                        output.blit(78, 2);
+                        output.blit(146, 2);
                         output.tt_group(Delimiter::Parenthesis, at);
                     };
                     output.split_off_stream(len)
                 }
             };
-            impl_to_toml(output, &ctx, body);
+            impl_to_toml(output, &ctx, body, &[]);
         } else {
             Error::msg(
                 "ToToml on tuple structs requires exactly one field (transparent delegation)",
@@ -1596,11 +4279,11 @@ fn emit_tag_insert(
     let table_id = Ident::new(table_var, Span::mixed_site());
     {
         out.push_ident(&table_id);
-        out.blit(270, 2);
+        out.blit(499, 2);
         {
             let at = out.buf.len();
             out.buf.push(ctx.crate_path.clone());
-            out.blit(272, 6);
+            out.blit(501, 6);
             {
                 let at = out.buf.len();
                 out.buf.push(tag_lit.clone().into());
@@ -1608,13 +4291,13 @@ fn emit_tag_insert(
             };
             out.blit_punct(13);
             out.buf.push(ctx.crate_path.clone());
-            out.blit(379, 6);
+            out.blit(1277, 6);
             {
                 let at = out.buf.len();
                 out.buf.push(name_lit.into());
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit(288, 3);
+            out.blit(517, 3);
             out.tt_group(Delimiter::Parenthesis, at);
         };
         out.blit_punct(2);
@@ -1628,55 +4311,110 @@ fn find_other_variant<'a>(variants: &'a [EnumVariant]) -> Option<&'a EnumVariant
     }
     None
 }
-fn emit_unknown_field_arm(out: &mut RustWriter, ctx: &Ctx) {
-    match &ctx.target.unknown_fields {
+/// Emits the `_` arm for keys matching no field. `expected` lists the keys
+/// offered as suggestions.
+fn emit_unknown_field_arm(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    expected: &[Literal],
+    flattened: &[&Field],
+) {
+    {
+        out.blit(292, 3);
+    };
+    emit_unknown_field_body(out, ctx, expected, flattened);
+}
+/// Emits the block handling a key that matches no field, per the container's
+/// unknown field policy. The keys of `flattened` fields are suggested along
+/// with `expected`.
+fn emit_unknown_field_body(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    expected: &[Literal],
+    flattened: &[&Field],
+) {
+    let expected = {
+        let mut ts = TokenStream::new();
+        for key in expected {
+            ts.extend([
+                key.clone().into(),
+                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+            ]);
+        }
+        TokenTree::Group(Group::new(Delimiter::Bracket, ts))
+    };
+    let (report, deny) = match &ctx.target.unknown_fields {
         UnknownFieldPolicy::Ignore => {
-            out.blit(385, 4);
+            {
+                out.tt_group_empty(Delimiter::Brace);
+            };
+            return;
         }
-        UnknownFieldPolicy::Warn { tag } => {
+        UnknownFieldPolicy::Warn { tag } => (tag, false),
+        UnknownFieldPolicy::Deny { tag } => (tag, true),
+    };
+    let body_at = out.buf.len();
+    let flattened_keys = !flattened.is_empty();
+    if flattened_keys {
+        {
+            out.blit(1283, 16);
             {
-                out.blit(203, 3);
-                {
-                    let at = out.buf.len();
-                    out.blit(389, 3);
-                    {
-                        let at = out.buf.len();
-                        {
-                            emit_tag_value(out, tag.as_deref())
-                        };
-                        out.blit(392, 6);
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(2);
-                    out.tt_group(Delimiter::Brace, at);
-                };
+                let at = out.buf.len();
+                out.blit_punct(5);
+                out.buf.push(expected.clone());
+                out.tt_group(Delimiter::Parenthesis, at);
             };
-        }
-        UnknownFieldPolicy::Deny { tag } => {
+            out.blit_punct(2);
+        };
+        for field in flattened {
             {
-                out.blit(203, 3);
+                out.blit_punct(4);
+                out.buf.extend_from_slice(field.ty);
+                out.blit_ident(11);
+                out.buf.push(ctx.crate_path.clone());
+                out.blit(42, 5);
+                out.push_ident(&ctx.lifetime);
+                out.blit(634, 5);
                 {
                     let at = out.buf.len();
-                    out.blit(90, 2);
-                    {
-                        let at = out.buf.len();
-                        out.blit(389, 3);
-                        {
-                            let at = out.buf.len();
-                            {
-                                emit_tag_value(out, tag.as_deref())
-                            };
-                            out.blit(392, 6);
-                            out.tt_group(Delimiter::Parenthesis, at);
-                        };
-                        out.tt_group(Delimiter::Parenthesis, at);
-                    };
-                    out.blit_punct(2);
-                    out.tt_group(Delimiter::Brace, at);
+                    out.blit(1299, 3);
+                    out.tt_group(Delimiter::Parenthesis, at);
                 };
+                out.blit_punct(2);
             };
         }
     }
+    {
+        if deny {
+            out.blit(158, 2);
+        };
+    };
+    let err_at = out.buf.len();
+    {
+        out.blit(1302, 4);
+        {
+            let at = out.buf.len();
+            {
+                emit_tag_value(out, report.as_deref())
+            };
+            out.blit(1306, 5);
+            if flattened_keys {
+                out.blit(1311, 2);
+            };
+            if !flattened_keys {
+                out.blit_punct(5);
+                out.buf.push(expected);
+            };
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+    };
+    if deny {
+        out.tt_group(Delimiter::Parenthesis, err_at);
+    }
+    {
+        out.blit_punct(2);
+    };
+    out.tt_group(Delimiter::Brace, body_at);
 }
 fn emit_tag_value(out: &mut RustWriter, tag: Option<&[TokenTree]>) {
     if let Some(tag_tokens) = tag {
@@ -1693,10 +4431,10 @@ fn emit_wildcard_arm(
 ) {
     if let Some(ov) = other_variant {
         {
-            out.blit(398, 4);
+            out.blit(1313, 4);
             {
                 let at = out.buf.len();
-                out.blit(321, 3);
+                out.blit(91, 3);
                 out.push_ident(ov.name);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
@@ -1704,15 +4442,15 @@ fn emit_wildcard_arm(
         };
     } else {
         {
-            out.blit(402, 4);
+            out.blit(1317, 4);
             {
                 let at = out.buf.len();
-                out.blit(406, 3);
+                out.blit(1321, 3);
                 {
                     let at = out.buf.len();
-                    out.blit_punct(6);
+                    out.blit_punct(5);
                     out.buf.push(TokenTree::Literal(Literal::string(msg)));
-                    out.blit(32, 2);
+                    out.blit(68, 2);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
                 out.tt_group(Delimiter::Parenthesis, at);
@@ -1724,33 +4462,64 @@ fn emit_wildcard_arm(
 fn emit_for_table_header(out: &mut RustWriter, table_var: &str) {
     let table_id = Ident::new(table_var, Span::mixed_site());
     {
-        out.blit_ident(55);
+        out.blit_ident(37);
     };
     let pat_at = out.buf.len();
     {
-        out.blit(214, 3);
+        out.blit(339, 3);
     };
     out.tt_group(Delimiter::Parenthesis, pat_at);
     {
-        out.blit_ident(11);
+        out.blit_ident(137);
         out.buf.push(TokenTree::from(table_id.clone()));
     };
 }
+/// Returns `true` if `with` names `helper::flatten_any`, which reads the field
+/// through its `FromToml` impl rather than collecting arbitrary keys.
+fn is_flatten_any(with: Option<&[TokenTree]>) -> bool {
+    match with.and_then(|path| path.last()) {
+        Some(TokenTree::Ident(id)) if id.to_string() == "flatten_any" => true,
+        _ => false,
+    }
+}
+fn flatten_partial_ident(field: &Field) -> Ident {
+    let mut name = String::from("__flatten_");
+    name.push_str(&field.name.to_string());
+    Ident::new(&name, Span::mixed_site())
+}
+/// Emits `let _ = <prefix>::insert(..);` adding the current entry to the
+/// partial of the flattened `field`.
+fn emit_flatten_insert(out: &mut RustWriter, ctx: &Ctx, field: &Field) {
+    {
+        out.blit(1324, 3);
+    };
+    emit_flatten_prefix(out, ctx, field, FROM_TOML);
+    {
+        out.blit(1327, 3);
+        {
+            let at = out.buf.len();
+            out.blit(1330, 8);
+            out.push_ident(&flatten_partial_ident(field));
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+        out.blit_punct(2);
+    };
+}
 fn emit_flatten_prefix(out: &mut RustWriter, ctx: &Ctx, field: &Field, direction: u8) {
     if let Some(with) = field.with(direction) {
         out.buf.extend_from_slice(with);
     } else {
         if direction == FROM_TOML {
-            out.blit_punct(5);
+            out.blit_punct(4);
             out.buf.extend_from_slice(field.ty);
-            out.blit_ident(104);
+            out.blit_ident(11);
             out.buf.push(ctx.crate_path.clone());
-            out.blit(15, 5);
+            out.blit(51, 5);
             out.push_ident(&ctx.lifetime);
-            out.blit(332, 2);
+            out.blit(297, 2);
         } else {
             out.buf.push(ctx.crate_path.clone());
-            out.blit(67, 3);
+            out.blit(128, 3);
         }
     }
 }
@@ -1758,25 +4527,25 @@ fn emit_from_toml_call(out: &mut RustWriter, ctx: &Ctx, field: &Field, ty: &[Tok
     if let Some(with) = field.with(FROM_TOML) {
         {
             out.buf.extend_from_slice(with);
-            out.blit(334, 3);
+            out.blit(821, 3);
             {
                 let at = out.buf.len();
-                out.blit(409, 3);
+                out.blit(1338, 3);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
         };
     } else {
         {
-            out.blit_punct(5);
+            out.blit_punct(4);
             out.buf.extend_from_slice(ty);
-            out.blit_ident(104);
+            out.blit_ident(11);
             out.buf.push(ctx.crate_path.clone());
-            out.blit(6, 5);
+            out.blit(42, 5);
             out.push_ident(&ctx.lifetime);
-            out.blit(332, 5);
+            out.blit(1341, 5);
             {
                 let at = out.buf.len();
-                out.blit(409, 3);
+                out.blit(1346, 7);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
         };
@@ -1784,25 +4553,31 @@ fn emit_from_toml_call(out: &mut RustWriter, ctx: &Ctx, field: &Field, ty: &[Tok
 }
 fn enum_from_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
     let start = out.buf.len();
-    let other_variant = find_other_variant(variants);
     {
-        out.blit(221, 2);
+        out.blit(302, 2);
         {
             let at = out.buf.len();
-            out.blit_ident(70);
+            out.blit_ident(61);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit(412, 4);
+        out.blit(1353, 5);
         {
             let at = out.buf.len();
-            out.blit_ident(114);
+            out.blit_ident(1);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit_ident(109);
+        out.blit_ident(13);
     };
     emit_failed_return(out, ctx);
+    emit_unit_name_match(out, ctx, variants);
+    let body = out.split_off_stream(start);
+    impl_from_toml(out, ctx, body, &[]);
+}
+/// Emits the `match s` that maps a string `s` to a unit variant by name.
+fn emit_unit_name_match(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
+    let other_variant = find_other_variant(variants);
     {
-        out.blit(416, 2);
+        out.blit(1358, 2);
         {
             let at = out.buf.len();
             {
@@ -1810,27 +4585,31 @@ fn enum_from_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVarian
                     if variant.other {
                         continue;
                     }
-                    let name_lit = variant_name_literal(ctx, variant);
+                    emit_variant_pattern(out, ctx, variant);
                     {
-                        out.buf.push(name_lit.into());
-                        out.blit(365, 3);
+                        out.blit(236, 2);
                         {
                             let at = out.buf.len();
-                            out.blit(321, 3);
-                            out.push_ident(variant.name);
-                            out.tt_group(Delimiter::Parenthesis, at);
+                            emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Str);
+                            out.blit_ident(2);
+                            {
+                                let at = out.buf.len();
+                                out.blit(91, 3);
+                                out.push_ident(variant.name);
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            out.tt_group(Delimiter::Brace, at);
                         };
-                        out.blit_punct(13);
                     };
                 }
             };
             {
                 if let Some(ov) = other_variant {
                     {
-                        out.blit(398, 4);
+                        out.blit(1313, 4);
                         {
                             let at = out.buf.len();
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(ov.name);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
@@ -1852,15 +4631,339 @@ fn enum_from_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVarian
                         TokenTree::Group(Group::new(Delimiter::Bracket, ts))
                     };
                     {
-                        out.blit(402, 4);
+                        out.blit(1317, 4);
+                        {
+                            let at = out.buf.len();
+                            out.blit(1360, 4);
+                            {
+                                let at = out.buf.len();
+                                out.blit_punct(5);
+                                out.buf.push(expected_array);
+                                out.blit(68, 2);
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(13);
+                    };
+                }
+            };
+            out.tt_group(Delimiter::Brace, at);
+        };
+    };
+}
+/// Where the name of a variant was read from, for deprecation warnings.
+enum VariantSite {
+    /// A string `s` from `__item`.
+    Str,
+
+    /// The single `key` of an externally tagged table, holding `value`.
+    Key,
+
+    /// The string `__tag` from `__tag_item`.
+    Tag,
+}
+fn has_deprecated_variant_aliases(variants: &[EnumVariant]) -> bool {
+    variants
+        .iter()
+        .any(|variant| variant.attr.has_deprecated_aliases(FROM_TOML))
+}
+/// Emits the pattern matching the name of `variant` or any of its aliases.
+fn emit_variant_pattern(out: &mut RustWriter, ctx: &Ctx, variant: &EnumVariant) {
+    {
+        out.buf.push(variant_name_literal(ctx, variant).into());
+    };
+    variant.attr.for_each_alias(FROM_TOML, &mut |alias| {
+        out.blit_punct(11);
+        out.buf.push(alias.clone().into());
+    });
+    variant
+        .attr
+        .for_each_deprecated_alias(FROM_TOML, &mut |_, alias| {
+            out.blit_punct(11);
+            out.buf.push(alias.clone().into());
+        });
+}
+/// Emits a deprecation warning for each deprecated alias of `variant`,
+/// reported if the name read at `site` is that alias.
+fn emit_deprecated_variant_checks(
+    out: &mut RustWriter,
+    ctx: &Ctx,
+    variant: &EnumVariant,
+    site: VariantSite,
+) {
+    let new = variant_name_literal(ctx, variant);
+    variant
+        .attr
+        .for_each_deprecated_alias(FROM_TOML, &mut |tag, alias| {
+            let zero = [TokenTree::Literal(Literal::u32_suffixed(0))];
+            let tag = tag.unwrap_or(&zero);
+            match site {
+                VariantSite::Str => {
+                    out.blit(1364, 4);
+                    out.buf.push(alias.clone().into());
+                    {
+                        let at = out.buf.len();
+                        out.blit(1368, 3);
+                        {
+                            let at = out.buf.len();
+                            out.buf.extend_from_slice(tag);
+                            out.blit(266, 2);
+                            out.buf.push(alias.clone().into());
+                            out.blit(266, 2);
+                            out.buf.push(new.clone().into());
+                            out.blit(68, 2);
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(2);
+                        out.tt_group(Delimiter::Brace, at);
+                    };
+                }
+                VariantSite::Key => {
+                    out.blit(1371, 6);
+                    out.buf.push(alias.clone().into());
+                    {
+                        let at = out.buf.len();
+                        out.blit(263, 3);
+                        {
+                            let at = out.buf.len();
+                            out.buf.extend_from_slice(tag);
+                            out.blit(266, 2);
+                            out.buf.push(alias.clone().into());
+                            out.blit(266, 2);
+                            out.buf.push(new.clone().into());
+                            out.blit(1377, 6);
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(2);
+                        out.tt_group(Delimiter::Brace, at);
+                    };
+                }
+                VariantSite::Tag => {
+                    out.blit(1383, 4);
+                    out.buf.push(alias.clone().into());
+                    {
+                        let at = out.buf.len();
+                        out.blit(1368, 3);
+                        {
+                            let at = out.buf.len();
+                            out.buf.extend_from_slice(tag);
+                            out.blit(266, 2);
+                            out.buf.push(alias.clone().into());
+                            out.blit(266, 2);
+                            out.buf.push(new.clone().into());
+                            out.blit(1387, 2);
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(2);
+                        out.tt_group(Delimiter::Brace, at);
+                    };
+                }
+            }
+        });
+}
+/// Computes the integer each unit variant is encoded as, following Rust's
+/// rules for implicit discriminants.
+fn unit_discriminants(variants: &[EnumVariant]) -> Vec<i64> {
+    fn parse_int(lit: &Literal) -> Option<i128> {
+        let text = lit.to_string().replace('_', "");
+        let (radix, digits) = match text.get(..2) {
+            Some("0x") => (16, &text[2..]),
+            Some("0o") => (8, &text[2..]),
+            Some("0b") => (2, &text[2..]),
+            _ => (10, &text[..]),
+        };
+        let digits = digits.split(['i', 'u']).next()?;
+        i128::from_str_radix(digits, radix).ok()
+    }
+    let mut values = Vec::with_capacity(variants.len());
+    let mut next = 0i128;
+    for variant in variants {
+        let value = match variant.discriminant {
+            [] => Some(next),
+            [TokenTree::Literal(lit)] => parse_int(lit),
+            [TokenTree::Punct(neg), TokenTree::Literal(lit)] if neg.as_char() == '-' => {
+                parse_int(lit).map(|value| -value)
+            }
+            _ => None,
+        };
+        let Some(value) = value else {
+            Error::span_msg(
+                "repr = \"int\" requires integer literal discriminants",
+                variant.name.span(),
+            )
+        };
+        let Ok(int) = i64::try_from(value) else {
+            Error::span_msg("Discriminant does not fit in an i64", variant.name.span())
+        };
+        values.push(int);
+        next = value + 1;
+    }
+    values
+}
+fn int_literal(out: &mut RustWriter, value: i64) {
+    if value < 0 {
+        out.blit_punct(20);
+    }
+    out.buf.push(TokenTree::Literal(Literal::u64_unsuffixed(
+        value.unsigned_abs(),
+    )));
+}
+fn enum_from_toml_int(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], values: &[i64]) {
+    let start = out.buf.len();
+    if match ctx.target.unit_repr {
+        UnitRepr::StrOrInt => true,
+        _ => false,
+    } {
+        {
+            out.blit(398, 3);
+            {
+                let at = out.buf.len();
+                out.blit_ident(61);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.blit(1389, 6);
+            {
+                let at = out.buf.len();
+                out.blit_ident(16);
+                emit_unit_name_match(out, ctx, variants);
+                out.blit_punct(2);
+                out.tt_group(Delimiter::Brace, at);
+            };
+            out.blit(1395, 8);
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(1403, 6);
+            {
+                let at = out.buf.len();
+                out.blit(158, 2);
+                {
+                    let at = out.buf.len();
+                    out.blit(1321, 3);
+                    {
+                        let at = out.buf.len();
+                        out.blit_punct(5);
+                        out.buf
+                            .push(TokenTree::Literal(Literal::string("a string or integer")));
+                        out.blit(68, 2);
+                        out.tt_group(Delimiter::Parenthesis, at);
+                    };
+                    out.tt_group(Delimiter::Parenthesis, at);
+                };
+                out.blit_punct(2);
+                out.tt_group(Delimiter::Brace, at);
+            };
+        };
+    }
+    {
+        out.blit(302, 2);
+        {
+            let at = out.buf.len();
+            out.blit_ident(6);
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+        out.blit(1409, 4);
+        out.buf.push(ctx.crate_path.clone());
+        out.blit(42, 5);
+        out.push_ident(&ctx.lifetime);
+        out.blit(819, 5);
+        {
+            let at = out.buf.len();
+            out.blit(824, 3);
+            out.tt_group(Delimiter::Parenthesis, at);
+        };
+        out.blit_ident(13);
+    };
+    emit_failed_return(out, ctx);
+    let mut known: Vec<i64> = Vec::new();
+    for (variant, value) in variants.iter().zip(values) {
+        if !variant.other {
+            known.push(*value);
+        }
+    }
+    known.sort_unstable();
+    let contiguous = match (known.first(), known.last()) {
+        (Some(min), Some(max)) => *max as i128 - *min as i128 + 1 == known.len() as i128,
+        _ => false,
+    };
+    {
+        out.blit(1413, 2);
+        {
+            let at = out.buf.len();
+            {
+                for (variant, value) in variants.iter().zip(values) {
+                    if variant.other {
+                        continue;
+                    }
+                    {
+                        int_literal(out, *value);
+                        out.blit(853, 3);
+                        {
+                            let at = out.buf.len();
+                            out.blit(91, 3);
+                            out.push_ident(variant.name);
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(13);
+                    };
+                }
+            };
+            {
+                if let Some(ov) = find_other_variant(variants) {
+                    {
+                        out.blit(1313, 4);
+                        {
+                            let at = out.buf.len();
+                            out.blit(91, 3);
+                            out.push_ident(ov.name);
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(13);
+                    };
+                } else if contiguous {
+                    let name = Literal::string(&ctx.target.name.to_string());
+                    let mut range = known[0].to_string();
+                    range.push_str("..=");
+                    range.push_str(&known[known.len() - 1].to_string());
+                    let range = Literal::string(&range);
+                    {
+                        out.blit(1317, 4);
+                        {
+                            let at = out.buf.len();
+                            out.blit(477, 3);
+                            {
+                                let at = out.buf.len();
+                                out.blit_punct(5);
+                                out.buf.push(name.into());
+                                out.blit(266, 2);
+                                out.buf.push(range.into());
+                                out.blit(68, 2);
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(13);
+                    };
+                } else {
+                    let expected_array = {
+                        let mut ts = TokenStream::new();
+                        for value in &known {
+                            ts.extend([
+                                TokenTree::Literal(Literal::string(&value.to_string())),
+                                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+                            ]);
+                        }
+                        TokenTree::Group(Group::new(Delimiter::Bracket, ts))
+                    };
+                    {
+                        out.blit(1317, 4);
                         {
                             let at = out.buf.len();
-                            out.blit(418, 3);
+                            out.blit(474, 3);
                             {
                                 let at = out.buf.len();
-                                out.blit_punct(6);
+                                out.blit_punct(5);
                                 out.buf.push(expected_array);
-                                out.blit(32, 2);
+                                out.blit(68, 2);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.tt_group(Delimiter::Parenthesis, at);
@@ -1869,32 +4972,67 @@ fn enum_from_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVarian
                     };
                 }
             };
-            out.tt_group(Delimiter::Brace, at);
+            out.tt_group(Delimiter::Brace, at);
+        };
+    };
+    let body = out.split_off_stream(start);
+    impl_from_toml(out, ctx, body, &[]);
+}
+fn enum_to_toml_int(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], values: &[i64]) {
+    let start = out.buf.len();
+    {
+        out.blit(1415, 7);
+        {
+            let at = out.buf.len();
+            {
+                for (variant, value) in variants.iter().zip(values) {
+                    {
+                        out.blit(91, 3);
+                        out.push_ident(variant.name);
+                        out.blit(236, 2);
+                        int_literal(out, *value);
+                        out.blit_punct(13);
+                    };
+                }
+            };
+            out.tt_group(Delimiter::Brace, at);
+        };
+        out.blit(37, 2);
+        {
+            let at = out.buf.len();
+            out.buf.push(ctx.crate_path.clone());
+            out.blit(1422, 6);
+            {
+                let at = out.buf.len();
+                out.blit_ident(6);
+                out.tt_group(Delimiter::Parenthesis, at);
+            };
+            out.tt_group(Delimiter::Parenthesis, at);
         };
     };
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_to_toml(out, ctx, body, &[]);
 }
 fn enum_to_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
     let start = out.buf.len();
     {
-        out.blit_ident(115);
+        out.blit_ident(2);
         {
             let at = out.buf.len();
             out.buf.push(ctx.crate_path.clone());
-            out.blit(379, 6);
+            out.blit(1277, 6);
             {
                 let at = out.buf.len();
-                out.blit(421, 2);
+                out.blit(1098, 2);
                 {
                     let at = out.buf.len();
                     {
                         for variant in variants {
                             let name_lit = variant_name_literal(ctx, variant);
                             {
-                                out.blit(321, 3);
+                                out.blit(91, 3);
                                 out.push_ident(variant.name);
-                                out.blit(149, 2);
+                                out.blit(236, 2);
                                 out.buf.push(name_lit.into());
                                 out.blit_punct(13);
                             };
@@ -1908,12 +5046,12 @@ fn enum_to_toml_string(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]
         };
     };
     let body = out.split_off_stream(start);
-    impl_to_toml(out, ctx, body);
+    impl_to_toml(out, ctx, body, &[]);
 }
 fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode: &TagMode) {
     let start = out.buf.len();
     {
-        out.blit(421, 2);
+        out.blit(1098, 2);
     };
     let arms_at = out.buf.len();
     for variant in variants {
@@ -1926,19 +5064,19 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
             EnumKind::None => {
                 if let Some(tag) = tag_lit {
                     {
-                        out.blit(321, 3);
+                        out.blit(91, 3);
                         out.push_ident(variant.name);
-                        out.blit(149, 2);
+                        out.blit(236, 2);
                         {
                             let at = out.buf.len();
                             emit_table_alloc(out, ctx, "table", 1);
                             {
                                 emit_tag_insert(out, ctx, "table", tag, name_lit)
                             };
-                            out.blit_ident(115);
+                            out.blit_ident(2);
                             {
                                 let at = out.buf.len();
-                                out.blit(423, 4);
+                                out.blit(1428, 5);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.tt_group(Delimiter::Brace, at);
@@ -1946,13 +5084,13 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                     };
                 } else {
                     {
-                        out.blit(321, 3);
+                        out.blit(91, 3);
                         out.push_ident(variant.name);
-                        out.blit(365, 3);
+                        out.blit(853, 3);
                         {
                             let at = out.buf.len();
                             out.buf.push(ctx.crate_path.clone());
-                            out.blit(379, 6);
+                            out.blit(1277, 6);
                             {
                                 let at = out.buf.len();
                                 out.buf.push(name_lit.into());
@@ -1975,40 +5113,40 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                 } {
                     if let Some(with) = field.with(TO_TOML) {
                         {
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(variant.name);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(88);
+                                out.blit_ident(50);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(149, 2);
+                            out.blit(236, 2);
                             out.buf.extend_from_slice(with);
-                            out.blit(278, 3);
+                            out.blit(507, 3);
                             {
                                 let at = out.buf.len();
-                                out.blit(427, 3);
+                                out.blit(1433, 3);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.blit_punct(13);
                         };
                     } else {
                         {
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(variant.name);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(88);
+                                out.blit_ident(50);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(430, 3);
+                            out.blit(1436, 3);
                             out.buf.extend_from_slice(field.ty);
-                            out.blit_ident(104);
+                            out.blit_ident(11);
                             out.buf.push(ctx.crate_path.clone());
-                            out.blit(304, 7);
+                            out.blit(534, 7);
                             {
                                 let at = out.buf.len();
-                                out.blit(427, 3);
+                                out.blit(1433, 3);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.blit_punct(13);
@@ -2018,14 +5156,14 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                     let cap = if tag_lit.is_some() { 2 } else { 1 };
                     if let Some(with) = field.with(TO_TOML) {
                         {
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(variant.name);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(88);
+                                out.blit_ident(50);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(149, 2);
+                            out.blit(236, 2);
                             {
                                 let at = out.buf.len();
                                 emit_table_alloc(out, ctx, "table", cap);
@@ -2034,11 +5172,11 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                                         emit_tag_insert(out, ctx, "table", tag, name_lit.clone());
                                     }
                                 };
-                                out.blit(433, 3);
+                                out.blit(1439, 3);
                                 {
                                     let at = out.buf.len();
                                     out.buf.push(ctx.crate_path.clone());
-                                    out.blit(272, 6);
+                                    out.blit(501, 6);
                                     {
                                         let at = out.buf.len();
                                         {
@@ -2058,19 +5196,19 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                                     };
                                     out.blit_punct(13);
                                     out.buf.extend_from_slice(with);
-                                    out.blit(278, 3);
+                                    out.blit(507, 3);
                                     {
                                         let at = out.buf.len();
-                                        out.blit(427, 3);
+                                        out.blit(1433, 3);
                                         out.tt_group(Delimiter::Parenthesis, at);
                                     };
-                                    out.blit(436, 4);
+                                    out.blit(1442, 4);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(440, 2);
+                                out.blit(37, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(423, 4);
+                                    out.blit(1428, 5);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
                                 out.tt_group(Delimiter::Brace, at);
@@ -2078,14 +5216,14 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                         };
                     } else {
                         {
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(variant.name);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(88);
+                                out.blit_ident(50);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(149, 2);
+                            out.blit(236, 2);
                             {
                                 let at = out.buf.len();
                                 emit_table_alloc(out, ctx, "table", cap);
@@ -2094,11 +5232,11 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                                         emit_tag_insert(out, ctx, "table", tag, name_lit.clone());
                                     }
                                 };
-                                out.blit(433, 3);
+                                out.blit(1439, 3);
                                 {
                                     let at = out.buf.len();
                                     out.buf.push(ctx.crate_path.clone());
-                                    out.blit(272, 6);
+                                    out.blit(501, 6);
                                     {
                                         let at = out.buf.len();
                                         {
@@ -2116,23 +5254,23 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                                         };
                                         out.tt_group(Delimiter::Parenthesis, at);
                                     };
-                                    out.blit(302, 2);
+                                    out.blit(532, 2);
                                     out.buf.extend_from_slice(field.ty);
-                                    out.blit_ident(104);
+                                    out.blit_ident(11);
                                     out.buf.push(ctx.crate_path.clone());
-                                    out.blit(304, 7);
+                                    out.blit(534, 7);
                                     {
                                         let at = out.buf.len();
-                                        out.blit(427, 3);
+                                        out.blit(1433, 3);
                                         out.tt_group(Delimiter::Parenthesis, at);
                                     };
-                                    out.blit(436, 4);
+                                    out.blit(1442, 4);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(440, 2);
+                                out.blit(37, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(423, 4);
+                                    out.blit(1428, 5);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
                                 out.tt_group(Delimiter::Brace, at);
@@ -2161,23 +5299,23 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                     TagMode::External => {
                         emit_table_alloc(out, ctx, "outer", 1);
                         {
-                            out.blit(442, 3);
+                            out.blit(1446, 3);
                             {
                                 let at = out.buf.len();
                                 out.buf.push(ctx.crate_path.clone());
-                                out.blit(272, 6);
+                                out.blit(501, 6);
                                 {
                                     let at = out.buf.len();
                                     out.buf.push(name_lit.into());
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(445, 8);
+                                out.blit(1449, 9);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(440, 2);
+                            out.blit(37, 2);
                             {
                                 let at = out.buf.len();
-                                out.blit(453, 4);
+                                out.blit(1458, 5);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                         };
@@ -2186,33 +5324,33 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
                         emit_table_alloc(out, ctx, "outer", 2);
                         emit_tag_insert(out, ctx, "outer", tag, name_lit);
                         {
-                            out.blit(442, 3);
+                            out.blit(1446, 3);
                             {
                                 let at = out.buf.len();
                                 out.buf.push(ctx.crate_path.clone());
-                                out.blit(272, 6);
+                                out.blit(501, 6);
                                 {
                                     let at = out.buf.len();
                                     out.buf.push(TokenTree::Literal((*content).clone()));
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(445, 8);
+                                out.blit(1449, 9);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(440, 2);
+                            out.blit(37, 2);
                             {
                                 let at = out.buf.len();
-                                out.blit(453, 4);
+                                out.blit(1458, 5);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                         };
                     }
                     _ => {
                         {
-                            out.blit_ident(115);
+                            out.blit_ident(2);
                             {
                                 let at = out.buf.len();
-                                out.blit(423, 4);
+                                out.blit(1428, 5);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                         };
@@ -2224,7 +5362,7 @@ fn enum_to_toml(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant], mode:
     }
     out.tt_group(Delimiter::Brace, arms_at);
     let body = out.split_off_stream(start);
-    impl_to_toml(out, ctx, body);
+    impl_to_toml(out, ctx, body, &[]);
 }
 fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
     let has_unit = ctx.target.enum_flags & ENUM_CONTAINS_UNIT_VARIANT != 0;
@@ -2235,7 +5373,7 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
     if has_unit {
         let if_body_start = out.buf.len();
         {
-            out.blit(457, 3);
+            out.blit(1463, 3);
             {
                 let at = out.buf.len();
                 {
@@ -2245,17 +5383,26 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                             _ => false,
                         } && !variant.other
                         {
-                            let name_lit = variant_name_literal(ctx, variant);
+                            emit_variant_pattern(out, ctx, variant);
                             {
-                                out.buf.push(name_lit.into());
-                                out.blit(365, 3);
+                                out.blit(236, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(321, 3);
-                                    out.push_ident(variant.name);
-                                    out.tt_group(Delimiter::Parenthesis, at);
+                                    emit_deprecated_variant_checks(
+                                        out,
+                                        ctx,
+                                        variant,
+                                        VariantSite::Str,
+                                    );
+                                    out.blit_ident(2);
+                                    {
+                                        let at = out.buf.len();
+                                        out.blit(91, 3);
+                                        out.push_ident(variant.name);
+                                        out.tt_group(Delimiter::Parenthesis, at);
+                                    };
+                                    out.tt_group(Delimiter::Brace, at);
                                 };
-                                out.blit_punct(13);
                             };
                         }
                     }
@@ -2267,50 +5414,50 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
         };
         let if_body = out.split_off_stream(if_body_start);
         {
-            out.blit(267, 3);
+            out.blit(398, 3);
             {
                 let at = out.buf.len();
-                out.blit_ident(70);
+                out.blit_ident(61);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit(460, 5);
+            out.blit(1389, 6);
             out.buf
                 .push(TokenTree::Group(Group::new(Delimiter::Brace, if_body)));
         };
     }
     if has_complex {
         {
-            out.blit(221, 2);
+            out.blit(302, 2);
             {
                 let at = out.buf.len();
-                out.blit_ident(95);
+                out.blit_ident(34);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit(324, 4);
+            out.blit(567, 4);
             {
                 let at = out.buf.len();
-                out.blit_ident(114);
+                out.blit_ident(1);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit_ident(109);
+            out.blit_ident(13);
         };
         emit_failed_return(out, ctx);
         {
-            out.blit(465, 8);
+            out.blit(1466, 9);
         };
         let err_body_start = out.buf.len();
         {
-            out.blit(90, 2);
+            out.blit(158, 2);
             {
                 let at = out.buf.len();
-                out.blit(406, 3);
+                out.blit(1321, 3);
                 {
                     let at = out.buf.len();
-                    out.blit_punct(6);
+                    out.blit_punct(5);
                     out.buf.push(TokenTree::Literal(Literal::string(
                         "a table with exactly one key",
                     )));
-                    out.blit(32, 2);
+                    out.blit(68, 2);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
                 out.tt_group(Delimiter::Parenthesis, at);
@@ -2324,27 +5471,27 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
             TokenStream::from(TokenTree::Literal(Literal::usize_unsuffixed(0))),
         ));
         {
-            out.blit(236, 2);
+            out.blit(316, 2);
             {
                 let at = out.buf.len();
-                out.blit(473, 6);
+                out.blit(1475, 7);
                 out.buf.push(one_lit);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
             out.buf
                 .push(TokenTree::Group(Group::new(Delimiter::Brace, err_body)));
-            out.blit_ident(116);
+            out.blit_ident(0);
             {
                 let at = out.buf.len();
-                out.blit(479, 3);
+                out.blit(1482, 3);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit(482, 3);
+            out.blit(1485, 3);
             out.buf.push(zero_index);
             out.blit_punct(2);
         };
         {
-            out.blit(485, 4);
+            out.blit(1488, 4);
         };
         let arms_at = out.buf.len();
         for variant in variants {
@@ -2355,7 +5502,6 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
             {
                 continue;
             }
-            let name_lit = variant_name_literal(ctx, variant);
             match variant.kind {
                 EnumKind::Tuple => {
                     if variant.fields.len() != 1 {
@@ -2364,62 +5510,63 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                         )
                     }
                     let field = &variant.fields[0];
+                    emit_variant_pattern(out, ctx, variant);
                     {
-                        out.buf.push(name_lit.into());
-                        out.blit(149, 2);
+                        out.blit(236, 2);
                     };
                     let arm_at = out.buf.len();
+                    emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Key);
                     if let Some(with) = field.with(FROM_TOML) {
                         {
-                            out.blit(221, 2);
+                            out.blit(302, 2);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(111);
+                                out.blit_ident(10);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.blit_punct(3);
                             out.buf.extend_from_slice(with);
-                            out.blit(334, 3);
+                            out.blit(821, 3);
                             {
                                 let at = out.buf.len();
-                                out.blit(489, 3);
+                                out.blit(1492, 3);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit_ident(109);
+                            out.blit_ident(13);
                         };
                     } else {
                         {
-                            out.blit(221, 2);
+                            out.blit(302, 2);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(111);
+                                out.blit_ident(10);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(291, 2);
+                            out.blit(520, 2);
                             out.buf.extend_from_slice(field.ty);
-                            out.blit_ident(104);
+                            out.blit_ident(11);
                             out.buf.push(ctx.crate_path.clone());
-                            out.blit(6, 5);
+                            out.blit(42, 5);
                             out.push_ident(&ctx.lifetime);
-                            out.blit(332, 5);
+                            out.blit(819, 5);
                             {
                                 let at = out.buf.len();
-                                out.blit(489, 3);
+                                out.blit(1492, 3);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit_ident(109);
+                            out.blit_ident(13);
                         };
                     }
                     emit_failed_return(out, ctx);
                     {
-                        out.blit_ident(115);
+                        out.blit_ident(2);
                         {
                             let at = out.buf.len();
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(variant.name);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(111);
+                                out.blit_ident(10);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.tt_group(Delimiter::Parenthesis, at);
@@ -2428,28 +5575,29 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                     out.tt_group(Delimiter::Brace, arm_at);
                 }
                 EnumKind::Struct => {
+                    emit_variant_pattern(out, ctx, variant);
                     {
-                        out.buf.push(name_lit.into());
-                        out.blit(149, 2);
+                        out.blit(236, 2);
                     };
                     let arm_at = out.buf.len();
+                    emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Key);
                     {
-                        out.blit(492, 5);
+                        out.blit(1495, 5);
                     };
                     {
-                        out.blit(221, 2);
+                        out.blit(302, 2);
                         {
                             let at = out.buf.len();
-                            out.blit_ident(65);
+                            out.blit_ident(81);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
-                        out.blit(497, 4);
+                        out.blit(1500, 4);
                         {
                             let at = out.buf.len();
-                            out.blit_ident(114);
+                            out.blit_ident(1);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
-                        out.blit_ident(109);
+                        out.blit_ident(13);
                     };
                     emit_failed_return(out, ctx);
                     emit_table_field_deser(
@@ -2470,16 +5618,16 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
         out.tt_group(Delimiter::Brace, arms_at);
     } else if !has_unit {
         {
-            out.blit_ident(113);
+            out.blit_ident(7);
             {
                 let at = out.buf.len();
-                out.blit(406, 3);
+                out.blit(1321, 3);
                 {
                     let at = out.buf.len();
-                    out.blit_punct(6);
+                    out.blit_punct(5);
                     out.buf
                         .push(TokenTree::Literal(Literal::string("a known variant")));
-                    out.blit(32, 2);
+                    out.blit(68, 2);
                     out.tt_group(Delimiter::Parenthesis, at);
                 };
                 out.tt_group(Delimiter::Parenthesis, at);
@@ -2487,7 +5635,7 @@ fn enum_from_toml_external(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
         };
     }
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
 }
 fn enum_from_toml_internal(
     out: &mut RustWriter,
@@ -2500,79 +5648,87 @@ fn enum_from_toml_internal(
     }
     let start = out.buf.len();
     {
-        out.blit(221, 2);
+        out.blit(302, 2);
         {
             let at = out.buf.len();
-            out.blit_ident(74);
+            out.blit_ident(28);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit(324, 4);
+        out.blit(567, 4);
         {
             let at = out.buf.len();
-            out.blit_ident(114);
+            out.blit_ident(1);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit_ident(109);
+        out.blit_ident(13);
     };
     emit_failed_return(out, ctx);
+    let deprecated = has_deprecated_variant_aliases(variants);
     {
-        out.blit(501, 12);
+        out.blit(1504, 12);
+        if deprecated {
+            out.blit(1516, 6);
+        };
     };
     emit_for_table_header(out, "__table");
     let tag_body_at = out.buf.len();
     {
-        out.blit(167, 6);
+        out.blit(1522, 6);
         out.buf.push(tag_lit.clone().into());
         {
             let at = out.buf.len();
-            out.blit(221, 2);
+            out.blit(302, 2);
             {
                 let at = out.buf.len();
-                out.blit_ident(66);
+                out.blit_ident(82);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit(513, 5);
+            out.blit(1528, 5);
             out.buf.push(ctx.crate_path.clone());
-            out.blit(6, 5);
+            out.blit(42, 5);
             out.push_ident(&ctx.lifetime);
-            out.blit(332, 5);
+            out.blit(819, 5);
             {
                 let at = out.buf.len();
-                out.blit(409, 3);
+                out.blit(1338, 3);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit_ident(109);
+            out.blit_ident(13);
             emit_failed_return(out, ctx);
-            out.blit(518, 3);
+            out.blit(1533, 3);
             {
                 let at = out.buf.len();
-                out.blit_ident(66);
+                out.blit_ident(82);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit(521, 3);
+            out.blit_punct(2);
+            if deprecated {
+                out.blit(1536, 4);
+            };
+            out.blit(1540, 2);
             out.tt_group(Delimiter::Brace, at);
         };
     };
     out.tt_group(Delimiter::Brace, tag_body_at);
     {
-        out.blit(92, 2);
+        out.blit(160, 2);
         {
             let at = out.buf.len();
-            out.blit_ident(94);
+            out.blit_ident(30);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit(524, 3);
+        out.blit(1542, 3);
     };
     let else_at = out.buf.len();
     {
-        out.blit(90, 2);
+        out.blit(158, 2);
         {
             let at = out.buf.len();
-            out.blit(233, 3);
+            out.blit(313, 3);
             {
                 let at = out.buf.len();
                 out.buf.push(tag_lit.clone().into());
-                out.blit(32, 2);
+                out.blit(68, 2);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
             out.tt_group(Delimiter::Parenthesis, at);
@@ -2585,40 +5741,40 @@ fn enum_from_toml_internal(
     };
     let other_variant = find_other_variant(variants);
     {
-        out.blit(527, 2);
+        out.blit(1545, 2);
     };
     let arms_at = out.buf.len();
     for variant in variants {
         if variant.other {
             continue;
         }
-        let name_lit = variant_name_literal(ctx, variant);
         match variant.kind {
             EnumKind::None => {
+                emit_variant_pattern(out, ctx, variant);
                 {
-                    out.buf.push(name_lit.into());
-                    out.blit(149, 2);
+                    out.blit(236, 2);
                 };
                 let arm_at = out.buf.len();
+                emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag);
                 emit_for_table_header(out, "__table");
                 let check_at = out.buf.len();
                 match &ctx.target.unknown_fields {
                     UnknownFieldPolicy::Ignore => {
-                        out.blit(529, 5);
+                        out.blit(1547, 5);
                     }
                     UnknownFieldPolicy::Warn { tag } => {
                         {
-                            out.blit(534, 6);
+                            out.blit(1552, 6);
                             out.buf.push(tag_lit.clone().into());
                             {
                                 let at = out.buf.len();
-                                out.blit(389, 3);
+                                out.blit(1558, 3);
                                 {
                                     let at = out.buf.len();
                                     {
                                         emit_tag_value(out, tag.as_deref())
                                     };
-                                    out.blit(392, 6);
+                                    out.blit(1561, 6);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
                                 out.blit_punct(2);
@@ -2628,20 +5784,20 @@ fn enum_from_toml_internal(
                     }
                     UnknownFieldPolicy::Deny { tag } => {
                         {
-                            out.blit(534, 6);
+                            out.blit(1552, 6);
                             out.buf.push(tag_lit.clone().into());
                             {
                                 let at = out.buf.len();
-                                out.blit(90, 2);
+                                out.blit(158, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(389, 3);
+                                    out.blit(1558, 3);
                                     {
                                         let at = out.buf.len();
                                         {
                                             emit_tag_value(out, tag.as_deref())
                                         };
-                                        out.blit(392, 6);
+                                        out.blit(1561, 6);
                                         out.tt_group(Delimiter::Parenthesis, at);
                                     };
                                     out.tt_group(Delimiter::Parenthesis, at);
@@ -2654,10 +5810,10 @@ fn enum_from_toml_internal(
                 }
                 out.tt_group(Delimiter::Brace, check_at);
                 {
-                    out.blit_ident(115);
+                    out.blit_ident(2);
                     {
                         let at = out.buf.len();
-                        out.blit(321, 3);
+                        out.blit(91, 3);
                         out.push_ident(variant.name);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
@@ -2665,13 +5821,14 @@ fn enum_from_toml_internal(
                 out.tt_group(Delimiter::Brace, arm_at);
             }
             EnumKind::Struct => {
+                emit_variant_pattern(out, ctx, variant);
                 {
-                    out.buf.push(name_lit.into());
-                    out.blit(149, 2);
+                    out.blit(236, 2);
                 };
                 let arm_at = out.buf.len();
+                emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag);
                 {
-                    out.blit(540, 5);
+                    out.blit(1567, 5);
                 };
                 emit_table_field_deser(
                     out,
@@ -2690,7 +5847,7 @@ fn enum_from_toml_internal(
     emit_wildcard_arm(out, ctx, other_variant, "a known variant");
     out.tt_group(Delimiter::Brace, arms_at);
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
 }
 fn enum_from_toml_adjacent(
     out: &mut RustWriter,
@@ -2701,102 +5858,110 @@ fn enum_from_toml_adjacent(
 ) {
     let start = out.buf.len();
     {
-        out.blit(221, 2);
+        out.blit(302, 2);
         {
             let at = out.buf.len();
-            out.blit_ident(74);
+            out.blit_ident(28);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit(324, 4);
+        out.blit(567, 4);
         {
             let at = out.buf.len();
-            out.blit_ident(114);
+            out.blit_ident(1);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit_ident(109);
+        out.blit_ident(13);
     };
     emit_failed_return(out, ctx);
+    let deprecated = has_deprecated_variant_aliases(variants);
     {
-        out.blit(545, 19);
+        out.blit(1504, 12);
+        if deprecated {
+            out.blit(1516, 6);
+        };
+        out.blit(1572, 7);
         out.buf.push(ctx.crate_path.clone());
-        out.blit(36, 5);
+        out.blit(72, 5);
         out.push_ident(&ctx.lifetime);
-        out.blit(564, 5);
+        out.blit(1579, 5);
     };
     emit_for_table_header(out, "__table");
     let for_body_at = out.buf.len();
     {
-        out.blit(145, 4);
+        out.blit(232, 4);
     };
     let extract_arms_at = out.buf.len();
     {
         out.buf.push(tag_lit.clone().into());
-        out.blit(149, 2);
+        out.blit(236, 2);
         {
             let at = out.buf.len();
-            out.blit(221, 2);
+            out.blit(302, 2);
             {
                 let at = out.buf.len();
-                out.blit_ident(66);
+                out.blit_ident(82);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit(513, 5);
+            out.blit(1528, 5);
             out.buf.push(ctx.crate_path.clone());
-            out.blit(6, 5);
+            out.blit(42, 5);
             out.push_ident(&ctx.lifetime);
-            out.blit(332, 5);
+            out.blit(819, 5);
             {
                 let at = out.buf.len();
-                out.blit(409, 3);
+                out.blit(1338, 3);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
-            out.blit_ident(109);
+            out.blit_ident(13);
             emit_failed_return(out, ctx);
-            out.blit(518, 3);
+            out.blit(1533, 3);
             {
                 let at = out.buf.len();
-                out.blit_ident(66);
+                out.blit_ident(82);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
             out.blit_punct(2);
+            if deprecated {
+                out.blit(1536, 4);
+            };
             out.tt_group(Delimiter::Brace, at);
         };
         out.buf.push(content_lit.clone().into());
-        out.blit(149, 2);
+        out.blit(236, 2);
         {
             let at = out.buf.len();
-            out.blit(569, 3);
+            out.blit(1584, 3);
             {
                 let at = out.buf.len();
-                out.blit_ident(98);
+                out.blit_ident(6);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
             out.blit_punct(2);
             out.tt_group(Delimiter::Brace, at);
         };
     };
-    emit_unknown_field_arm(out, ctx);
+    emit_unknown_field_arm(out, ctx, &[tag_lit.clone(), content_lit.clone()], &[]);
     out.tt_group(Delimiter::Brace, extract_arms_at);
     out.tt_group(Delimiter::Brace, for_body_at);
     {
-        out.blit(92, 2);
+        out.blit(160, 2);
         {
             let at = out.buf.len();
-            out.blit_ident(94);
+            out.blit_ident(30);
             out.tt_group(Delimiter::Parenthesis, at);
         };
-        out.blit(524, 3);
+        out.blit(1542, 3);
     };
     let else_at = out.buf.len();
     {
-        out.blit(90, 2);
+        out.blit(158, 2);
         {
             let at = out.buf.len();
-            out.blit(233, 3);
+            out.blit(313, 3);
             {
                 let at = out.buf.len();
                 out.buf.push(tag_lit.clone().into());
-                out.blit(32, 2);
+                out.blit(68, 2);
                 out.tt_group(Delimiter::Parenthesis, at);
             };
             out.tt_group(Delimiter::Parenthesis, at);
@@ -2809,25 +5974,25 @@ fn enum_from_toml_adjacent(
     };
     let other_variant = find_other_variant(variants);
     {
-        out.blit(527, 2);
+        out.blit(1545, 2);
     };
     let arms_at = out.buf.len();
     for variant in variants {
         if variant.other {
             continue;
         }
-        let name_lit = variant_name_literal(ctx, variant);
         match variant.kind {
             EnumKind::None => {
+                emit_variant_pattern(out, ctx, variant);
                 {
-                    out.buf.push(name_lit.into());
-                    out.blit(149, 2);
+                    out.blit(236, 2);
                     {
                         let at = out.buf.len();
-                        out.blit_ident(115);
+                        emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag);
+                        out.blit_ident(2);
                         {
                             let at = out.buf.len();
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(variant.name);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
@@ -2836,30 +6001,31 @@ fn enum_from_toml_adjacent(
                 };
             }
             EnumKind::Tuple | EnumKind::Struct => {
+                emit_variant_pattern(out, ctx, variant);
                 {
-                    out.buf.push(name_lit.into());
-                    out.blit(149, 2);
+                    out.blit(236, 2);
                 };
                 let arm_at = out.buf.len();
+                emit_deprecated_variant_checks(out, ctx, variant, VariantSite::Tag);
                 {
-                    out.blit(92, 2);
+                    out.blit(160, 2);
                     {
                         let at = out.buf.len();
-                        out.blit_ident(87);
+                        out.blit_ident(44);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit(572, 3);
+                    out.blit(1587, 3);
                 };
                 let ce_at = out.buf.len();
                 {
-                    out.blit(90, 2);
+                    out.blit(158, 2);
                     {
                         let at = out.buf.len();
-                        out.blit(233, 3);
+                        out.blit(313, 3);
                         {
                             let at = out.buf.len();
                             out.buf.push(content_lit.clone().into());
-                            out.blit(32, 2);
+                            out.blit(68, 2);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
                         out.tt_group(Delimiter::Parenthesis, at);
@@ -2878,55 +6044,55 @@ fn enum_from_toml_adjacent(
                         let field = &variant.fields[0];
                         if let Some(with) = field.with(FROM_TOML) {
                             {
-                                out.blit(221, 2);
+                                out.blit(302, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(111);
+                                    out.blit_ident(10);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
                                 out.blit_punct(3);
                                 out.buf.extend_from_slice(with);
-                                out.blit(334, 3);
+                                out.blit(821, 3);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(575, 3);
+                                    out.blit(1590, 3);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit_ident(109);
+                                out.blit_ident(13);
                             };
                         } else {
                             {
-                                out.blit(221, 2);
+                                out.blit(302, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(111);
+                                    out.blit_ident(10);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(291, 2);
+                                out.blit(520, 2);
                                 out.buf.extend_from_slice(field.ty);
-                                out.blit_ident(104);
+                                out.blit_ident(11);
                                 out.buf.push(ctx.crate_path.clone());
-                                out.blit(6, 5);
+                                out.blit(42, 5);
                                 out.push_ident(&ctx.lifetime);
-                                out.blit(332, 5);
+                                out.blit(819, 5);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(575, 3);
+                                    out.blit(1590, 3);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit_ident(109);
+                                out.blit_ident(13);
                             };
                         }
                         emit_failed_return(out, ctx);
                         {
-                            out.blit_ident(115);
+                            out.blit_ident(2);
                             {
                                 let at = out.buf.len();
-                                out.blit(321, 3);
+                                out.blit(91, 3);
                                 out.push_ident(variant.name);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(111);
+                                    out.blit_ident(10);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
                                 out.tt_group(Delimiter::Parenthesis, at);
@@ -2935,22 +6101,22 @@ fn enum_from_toml_adjacent(
                     }
                     EnumKind::Struct => {
                         {
-                            out.blit(578, 5);
+                            out.blit(1593, 5);
                         };
                         {
-                            out.blit(221, 2);
+                            out.blit(302, 2);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(65);
+                                out.blit_ident(81);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(583, 4);
+                            out.blit(1598, 4);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(114);
+                                out.blit_ident(1);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit_ident(109);
+                            out.blit_ident(13);
                         };
                         emit_failed_return(out, ctx);
                         emit_table_field_deser(
@@ -2972,39 +6138,123 @@ fn enum_from_toml_adjacent(
     emit_wildcard_arm(out, ctx, other_variant, "a known variant");
     out.tt_group(Delimiter::Brace, arms_at);
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
+}
+/// Describes the shape a variant of an untagged enum accepts, for the error
+/// reported when no variant matches.
+fn untagged_expecting(ctx: &Ctx, variant: &EnumVariant) -> Literal {
+    if let Some(expecting) = &variant.expecting {
+        return expecting.clone();
+    }
+    let mut text = String::new();
+    match variant.kind {
+        EnumKind::None => {
+            let name = variant_name_literal(ctx, variant);
+            let crate::lit::InlineKind::String(name) = crate::lit::literal_inline(name.to_string())
+            else {
+                Error::span_msg("Expected a string literal", name.span())
+            };
+            text.push('"');
+            text.push_str(&name);
+            text.push('"');
+        }
+        EnumKind::Tuple => {
+            text.push('`');
+            for tok in variant.fields[0].ty {
+                text.push_str(&tok.to_string());
+            }
+            text.push('`');
+        }
+        EnumKind::Struct => {
+            text.push_str("table");
+            let mut keys = Vec::new();
+            for field in variant.fields {
+                if field.flags & (Field::WITH_FROM_TOML_SKIP | Field::WITH_FLATTEN) == 0 {
+                    keys.push(field_name_lit(ctx, field, Some(variant)));
+                }
+            }
+            for (i, key) in keys.iter().enumerate() {
+                text.push_str(if i == 0 {
+                    " with `"
+                } else if i + 1 == keys.len() {
+                    " or `"
+                } else {
+                    ", `"
+                });
+                let crate::lit::InlineKind::String(key) =
+                    crate::lit::literal_inline(key.to_string())
+                else {
+                    Error::span_msg("Expected a string literal", key.span())
+                };
+                text.push_str(&key);
+                text.push('`');
+            }
+        }
+    }
+    Literal::string(&text)
 }
 fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVariant]) {
     let start = out.buf.len();
+    let mut expecting = Vec::new();
+    let mut candidates = 0;
+    for variant in variants {
+        expecting.push(untagged_expecting(ctx, variant));
+        if variant.final_if.is_none()
+            && !match variant.kind {
+                EnumKind::None => true,
+                _ => false,
+            }
+        {
+            candidates += 1;
+        }
+    }
     let last_index = variants.len() - 1;
-    let last_is_unhinted =
-        variants[last_index].try_if.is_none() && variants[last_index].final_if.is_none();
-    for (i, variant) in variants.iter().enumerate() {
-        let has_hint = variant.try_if.is_some() || variant.final_if.is_some();
-        let is_last = i == last_index && !has_hint;
-        let propagate = is_last || variant.final_if.is_some();
+    let last = &variants[last_index];
+    let single = candidates == 1
+        && last.try_if.is_none()
+        && last.final_if.is_none()
+        && !match last.kind {
+            EnumKind::None => true,
+            _ => false,
+        };
+    let any_attempts = candidates > 0 && !single;
+    if any_attempts {
+        out.blit(1602, 30);
+    }
+    let mut attempted = false;
+    for (i, (variant, expecting)) in variants.iter().zip(&expecting).enumerate() {
+        let propagate = variant.final_if.is_some() || (single && i == last_index);
         let name_lit = variant_name_literal(ctx, variant);
         let inner_start = out.buf.len();
         match variant.kind {
             EnumKind::None => {
                 {
-                    out.blit(267, 3);
+                    out.blit(398, 3);
                     {
                         let at = out.buf.len();
-                        out.blit_ident(38);
+                        out.blit_ident(129);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit(460, 5);
+                    out.blit(1389, 6);
                     {
                         let at = out.buf.len();
-                        out.blit(587, 4);
+                        out.blit(1632, 4);
                         out.buf.push(name_lit.into());
                         {
                             let at = out.buf.len();
-                            out.blit(591, 2);
+                            if attempted {
+                                out.blit(1636, 5);
+                                {
+                                    let at = out.buf.len();
+                                    out.blit_ident(56);
+                                    out.tt_group(Delimiter::Parenthesis, at);
+                                };
+                                out.blit_punct(2);
+                            };
+                            out.blit(1641, 2);
                             {
                                 let at = out.buf.len();
-                                out.blit(321, 3);
+                                out.blit(91, 3);
                                 out.push_ident(variant.name);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
@@ -3014,17 +6264,26 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                         out.tt_group(Delimiter::Brace, at);
                     };
                     if propagate {
-                        out.blit(90, 2);
+                        if attempted {
+                            out.blit(1636, 5);
+                            {
+                                let at = out.buf.len();
+                                out.blit_ident(56);
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            out.blit_punct(2);
+                        };
+                        out.blit(158, 2);
                         {
                             let at = out.buf.len();
-                            out.blit(406, 3);
+                            out.blit(1321, 3);
                             {
                                 let at = out.buf.len();
-                                out.blit_punct(6);
+                                out.blit_punct(5);
                                 out.buf.push(TokenTree::Literal(Literal::string(
                                     "a matching variant",
                                 )));
-                                out.blit(32, 2);
+                                out.blit(68, 2);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
                             out.tt_group(Delimiter::Parenthesis, at);
@@ -3038,186 +6297,187 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                     Error::msg("Only single-field tuple variants are supported in untagged enums")
                 }
                 let field = &variant.fields[0];
-                let match_start = out.buf.len();
+                let call_start = out.buf.len();
                 if let Some(with) = field.with(FROM_TOML) {
                     {
-                        out.blit_ident(100);
                         out.buf.extend_from_slice(with);
-                        out.blit(334, 3);
+                        out.blit(821, 3);
                         {
                             let at = out.buf.len();
-                            out.blit(337, 3);
+                            out.blit(824, 3);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
-                        {
-                            let at = out.buf.len();
-                            out.blit_ident(115);
-                            {
-                                let at = out.buf.len();
-                                out.blit_ident(111);
-                                out.tt_group(Delimiter::Parenthesis, at);
-                            };
-                            out.blit(593, 4);
-                            {
-                                let at = out.buf.len();
-                                out.blit(321, 3);
-                                out.push_ident(variant.name);
-                                {
-                                    let at = out.buf.len();
-                                    out.blit_ident(111);
-                                    out.tt_group(Delimiter::Parenthesis, at);
-                                };
-                                out.tt_group(Delimiter::Parenthesis, at);
-                            };
-                            out.blit_punct(13);
-                            if propagate {
-                                out.blit_ident(113);
-                                {
-                                    let at = out.buf.len();
-                                    out.blit_ident(93);
-                                    out.tt_group(Delimiter::Parenthesis, at);
-                                };
-                                out.blit(191, 4);
-                                {
-                                    let at = out.buf.len();
-                                    out.blit_ident(93);
-                                    out.tt_group(Delimiter::Parenthesis, at);
-                                };
-                                out.blit_punct(13);
-                            };
-                            if !propagate {
-                                out.blit_ident(113);
-                                {
-                                    let at = out.buf.len();
-                                    out.blit_ident(106);
-                                    out.tt_group(Delimiter::Parenthesis, at);
-                                };
-                                out.blit(149, 2);
-                                {
-                                    let at = out.buf.len();
-                                    out.blit(597, 5);
-                                    {
-                                        let at = out.buf.len();
-                                        out.blit_ident(73);
-                                        out.tt_group(Delimiter::Parenthesis, at);
-                                    };
-                                    out.blit_punct(2);
-                                    out.tt_group(Delimiter::Brace, at);
-                                };
-                            };
-                            out.tt_group(Delimiter::Brace, at);
-                        };
                     };
                 } else {
                     {
-                        out.blit(602, 2);
+                        out.blit_punct(4);
                         out.buf.extend_from_slice(field.ty);
-                        out.blit_ident(104);
+                        out.blit_ident(11);
                         out.buf.push(ctx.crate_path.clone());
-                        out.blit(6, 5);
+                        out.blit(42, 5);
                         out.push_ident(&ctx.lifetime);
-                        out.blit(332, 5);
+                        out.blit(819, 5);
                         {
                             let at = out.buf.len();
-                            out.blit(337, 3);
+                            out.blit(824, 3);
                             out.tt_group(Delimiter::Parenthesis, at);
                         };
+                    };
+                }
+                let call = out.split_off_stream(call_start);
+                if propagate {
+                    {
+                        if attempted {
+                            out.blit(1636, 5);
+                            {
+                                let at = out.buf.len();
+                                out.blit_ident(56);
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            out.blit_punct(2);
+                        };
+                        out.blit_ident(18);
+                        out.buf
+                            .push(TokenTree::Group(Group::new(Delimiter::None, call)));
                         {
                             let at = out.buf.len();
-                            out.blit_ident(115);
+                            out.blit_ident(2);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(111);
+                                out.blit_ident(10);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(593, 4);
+                            out.blit(1643, 4);
                             {
                                 let at = out.buf.len();
-                                out.blit(321, 3);
+                                out.blit(91, 3);
                                 out.push_ident(variant.name);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(111);
+                                    out.blit_ident(10);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
+                            out.blit(856, 2);
+                            {
+                                let at = out.buf.len();
+                                out.blit_ident(32);
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
+                            out.blit(279, 4);
+                            {
+                                let at = out.buf.len();
+                                out.blit_ident(32);
+                                out.tt_group(Delimiter::Parenthesis, at);
+                            };
                             out.blit_punct(13);
-                            if propagate {
-                                out.blit_ident(113);
+                            out.tt_group(Delimiter::Brace, at);
+                        };
+                    };
+                } else {
+                    {
+                        {
+                            let at = out.buf.len();
+                            out.blit(1647, 12);
+                            out.buf
+                                .push(TokenTree::Group(Group::new(Delimiter::None, call)));
+                            {
+                                let at = out.buf.len();
+                                out.blit_ident(2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(93);
+                                    out.blit_ident(10);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(191, 4);
+                                out.blit(236, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(93);
-                                    out.tt_group(Delimiter::Parenthesis, at);
+                                    if attempted {
+                                        out.blit(1659, 5);
+                                        {
+                                            let at = out.buf.len();
+                                            out.blit(1664, 4);
+                                            out.tt_group(Delimiter::Parenthesis, at);
+                                        };
+                                        out.blit_punct(2);
+                                    };
+                                    out.blit(1641, 2);
+                                    {
+                                        let at = out.buf.len();
+                                        out.blit(91, 3);
+                                        out.push_ident(variant.name);
+                                        {
+                                            let at = out.buf.len();
+                                            out.blit_ident(10);
+                                            out.tt_group(Delimiter::Parenthesis, at);
+                                        };
+                                        out.tt_group(Delimiter::Parenthesis, at);
+                                    };
+                                    out.blit_punct(2);
+                                    out.tt_group(Delimiter::Brace, at);
                                 };
-                                out.blit_punct(13);
-                            };
-                            if !propagate {
-                                out.blit_ident(113);
+                                out.blit_ident(7);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(106);
+                                    out.blit_ident(20);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(149, 2);
+                                out.blit(1668, 5);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(597, 5);
                                     {
                                         let at = out.buf.len();
-                                        out.blit_ident(73);
+                                        out.buf.push(expecting.clone().into());
+                                        out.blit(1673, 2);
                                         out.tt_group(Delimiter::Parenthesis, at);
                                     };
-                                    out.blit_punct(2);
-                                    out.tt_group(Delimiter::Brace, at);
+                                    out.tt_group(Delimiter::Parenthesis, at);
                                 };
+                                out.blit_punct(13);
+                                out.tt_group(Delimiter::Brace, at);
                             };
                             out.tt_group(Delimiter::Brace, at);
                         };
                     };
                 }
-                if !propagate {
-                    let match_body = out.split_off_stream(match_start);
+            }
+            EnumKind::Struct => {
+                if propagate && attempted {
                     {
-                        let at = out.buf.len();
-                        out.blit(604, 10);
-                        out.buf
-                            .push(TokenTree::Group(Group::new(Delimiter::None, match_body)));
-                        out.tt_group(Delimiter::Brace, at);
+                        out.blit(1636, 5);
+                        {
+                            let at = out.buf.len();
+                            out.blit_ident(56);
+                            out.tt_group(Delimiter::Parenthesis, at);
+                        };
+                        out.blit_punct(2);
                     };
                 }
-            }
-            EnumKind::Struct => {
                 let body_start = out.buf.len();
                 {
-                    out.blit(221, 2);
+                    out.blit(302, 2);
                     {
                         let at = out.buf.len();
-                        out.blit_ident(65);
+                        out.blit_ident(81);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit(324, 4);
+                    out.blit(567, 4);
                     {
                         let at = out.buf.len();
-                        out.blit_ident(114);
+                        out.blit_ident(1);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit_ident(109);
+                    out.blit_ident(13);
                 };
                 emit_failed_return(out, ctx);
                 emit_table_field_deser(out, ctx, variant.fields, "__subtable", Some(variant), &[]);
                 if propagate {
                     {
-                        out.blit(591, 2);
+                        out.blit(1641, 2);
                         {
                             let at = out.buf.len();
-                            out.blit(321, 3);
+                            out.blit(91, 3);
                             out.push_ident(variant.name);
                             {
                                 let at = out.buf.len();
@@ -3241,48 +6501,63 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                     {
                         {
                             let at = out.buf.len();
-                            out.blit(614, 25);
+                            out.blit(1675, 26);
                             out.buf.push(ctx.crate_path.clone());
-                            out.blit(639, 5);
+                            out.blit(1701, 5);
                             {
                                 let at = out.buf.len();
-                                out.blit(251, 2);
+                                out.blit(10, 2);
                                 out.buf.push(closure_body_group);
                                 out.tt_group(Delimiter::Parenthesis, at);
                             };
-                            out.blit(644, 4);
+                            out.blit(1706, 5);
                             {
                                 let at = out.buf.len();
-                                out.blit_ident(115);
+                                out.blit_ident(2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(111);
+                                    out.blit_ident(10);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(593, 4);
+                                out.blit(236, 2);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(111);
-                                    out.tt_group(Delimiter::Parenthesis, at);
+                                    if attempted {
+                                        out.blit(1659, 5);
+                                        {
+                                            let at = out.buf.len();
+                                            out.blit(1664, 4);
+                                            out.tt_group(Delimiter::Parenthesis, at);
+                                        };
+                                        out.blit_punct(2);
+                                    };
+                                    out.blit(1641, 2);
+                                    {
+                                        let at = out.buf.len();
+                                        out.blit_ident(10);
+                                        out.tt_group(Delimiter::Parenthesis, at);
+                                    };
+                                    out.blit_punct(2);
+                                    out.tt_group(Delimiter::Brace, at);
                                 };
-                                out.blit(368, 2);
+                                out.blit_ident(7);
                                 {
                                     let at = out.buf.len();
-                                    out.blit_ident(106);
+                                    out.blit_ident(20);
                                     out.tt_group(Delimiter::Parenthesis, at);
                                 };
-                                out.blit(149, 2);
+                                out.blit(1668, 5);
                                 {
                                     let at = out.buf.len();
-                                    out.blit(597, 5);
                                     {
                                         let at = out.buf.len();
-                                        out.blit_ident(73);
+                                        out.buf.push(expecting.clone().into());
+                                        out.blit(1673, 2);
                                         out.tt_group(Delimiter::Parenthesis, at);
                                     };
-                                    out.blit_punct(2);
-                                    out.tt_group(Delimiter::Brace, at);
+                                    out.tt_group(Delimiter::Parenthesis, at);
                                 };
+                                out.blit_punct(13);
                                 out.tt_group(Delimiter::Brace, at);
                             };
                             out.tt_group(Delimiter::Brace, at);
@@ -3291,6 +6566,14 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
                 }
             }
         }
+        if !propagate
+            && !match variant.kind {
+                EnumKind::None => true,
+                _ => false,
+            }
+        {
+            attempted = true;
+        }
         if let Some(predicate) = variant.try_if.as_deref().or(variant.final_if.as_deref()) {
             let inner_code = out.split_off_stream(inner_start);
             let inner_group = TokenTree::Group(Group::new(Delimiter::Brace, inner_code));
@@ -3303,26 +6586,26 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
             {
                 {
                     let at = out.buf.len();
-                    out.blit(648, 4);
+                    out.blit(1711, 4);
                     {
                         let at = out.buf.len();
-                        out.blit(24, 2);
+                        out.blit(60, 2);
                         out.buf.push(ctx.crate_path.clone());
-                        out.blit(26, 5);
+                        out.blit(62, 5);
                         out.push_ident(&ctx.lifetime);
-                        out.blit(652, 3);
+                        out.blit(1715, 3);
                         out.buf.push(ctx.crate_path.clone());
-                        out.blit(36, 5);
+                        out.blit(72, 5);
                         out.push_ident(&ctx.lifetime);
                         out.blit_punct(0);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
-                    out.blit(655, 4);
+                    out.blit(1718, 4);
                     out.buf.push(pred_group);
-                    out.blit(659, 3);
+                    out.blit(1722, 3);
                     {
                         let at = out.buf.len();
-                        out.blit(337, 3);
+                        out.blit(824, 3);
                         out.tt_group(Delimiter::Parenthesis, at);
                     };
                     out.buf.push(inner_group);
@@ -3331,34 +6614,70 @@ fn enum_from_toml_untagged(out: &mut RustWriter, ctx: &Ctx, variants: &[EnumVari
             };
         }
     }
-    if !last_is_unhinted {
+    if !single {
         {
-            out.blit_ident(113);
-            {
-                let at = out.buf.len();
-                out.blit(406, 3);
-                {
-                    let at = out.buf.len();
-                    out.blit_punct(6);
-                    out.buf
-                        .push(TokenTree::Literal(Literal::string("a matching variant")));
-                    out.blit(32, 2);
-                    out.tt_group(Delimiter::Parenthesis, at);
-                };
-                out.tt_group(Delimiter::Parenthesis, at);
-            };
+            out.blit_ident(7);
+        };
+        let err_at = out.buf.len();
+        {
+            out.blit(1725, 3);
+        };
+        let args_at = out.buf.len();
+        {
+            out.blit_punct(5);
+        };
+        let list_at = out.buf.len();
+        for lit in &expecting {
+            out.buf.push(lit.clone().into());
+            out.blit_punct(13);
+        }
+        out.tt_group(Delimiter::Bracket, list_at);
+        {
+            out.blit(266, 2);
         };
+        if any_attempts {
+            out.blit_ident(78);
+        } else {
+            out.tt_group_empty(Delimiter::Bracket);
+        }
+        {
+            out.blit(68, 2);
+        };
+        out.tt_group(Delimiter::Parenthesis, args_at);
+        out.tt_group(Delimiter::Parenthesis, err_at);
     }
     let body = out.split_off_stream(start);
-    impl_from_toml(out, ctx, body);
+    impl_from_toml(out, ctx, body, &[]);
 }
 fn handle_enum(output: &mut RustWriter, target: &DeriveTargetInner, variants: &[EnumVariant]) {
     if target.content.is_some() && target.tag.is_none() {
         Error::msg("content attribute requires tag to also be set")
     }
+    if !target.exactly_one_of.is_empty() {
+        Error::msg("exactly_one_of can only be used on structs with named fields")
+    }
+    if target.remote.is_some() {
+        Error::msg("remote can only be used on structs with named fields")
+    }
     if target.untagged && (target.tag.is_some() || target.content.is_some()) {
         Error::msg("untagged cannot be combined with tag or content attributes")
     }
+    if target.untagged
+        || match target.unit_repr {
+            UnitRepr::Int => true,
+            _ => false,
+        }
+    {
+        for variant in variants {
+            if variant.attr.has_aliases(FROM_TOML) || variant.attr.has_deprecated_aliases(FROM_TOML)
+            {
+                Error::span_msg(
+                    "alias and deprecated_alias cannot be used on untagged or repr = \"int\" enums",
+                    variant.name.span(),
+                )
+            }
+        }
+    }
     if !target.untagged {
         for variant in variants {
             if variant.try_if.is_some() || variant.final_if.is_some() {
@@ -3367,6 +6686,12 @@ fn handle_enum(output: &mut RustWriter, target: &DeriveTargetInner, variants: &[
                     variant.name.span(),
                 )
             }
+            if variant.expecting.is_some() {
+                Error::span_msg(
+                    "expecting can only be used on untagged enums",
+                    variant.name.span(),
+                )
+            }
         }
     }
     {
@@ -3395,16 +6720,35 @@ fn handle_enum(output: &mut RustWriter, target: &DeriveTargetInner, variants: &[
             Error::msg("only one variant can be marked #[toml(other)]")
         }
     }
-    let ctx = Ctx::new(output, target);
+    reject_named_struct_attrs(target, &[]);
+    for variant in variants {
+        reject_named_struct_attrs(target, variant.fields);
+    }
     let is_string_enum = !target.untagged
         && target.tag.is_none()
         && target.enum_flags & ENUM_CONTAINS_UNIT_VARIANT != 0
         && target.enum_flags & (ENUM_CONTAINS_STRUCT_VARIANT | ENUM_CONTAINS_TUPLE_VARIANT) == 0;
+    let values = if target.unit_repr.is_str() {
+        Vec::new()
+    } else if is_string_enum {
+        unit_discriminants(variants)
+    } else {
+        Error::msg(
+            "repr = \"int\" and \"str_or_int\" require an enum with only unit variants and no tag",
+        )
+    };
+    let ctx = Ctx::new(output, target);
+    if target.schema {
+        schema_enum(output, &ctx, variants, &values);
+    }
     if target.from_toml && !emit_proxy_from_toml(output, &ctx) {
         if target.untagged {
             enum_from_toml_untagged(output, &ctx, variants);
         } else {
             match (&target.tag, &target.content) {
+                (None, _) if !values.is_empty() => {
+                    enum_from_toml_int(output, &ctx, variants, &values)
+                }
                 (None, _) if is_string_enum => enum_from_toml_string(output, &ctx, variants),
                 (None, _) => enum_from_toml_external(output, &ctx, variants),
                 (Some(tag_lit), None) => enum_from_toml_internal(output, &ctx, variants, tag_lit),
@@ -3414,8 +6758,13 @@ fn handle_enum(output: &mut RustWriter, target: &DeriveTargetInner, variants: &[
             }
         }
     }
-    if target.to_toml {
-        if is_string_enum {
+    if target.to_toml && !emit_proxy_to_toml(output, &ctx) {
+        if match target.unit_repr {
+            UnitRepr::Int => true,
+            _ => false,
+        } {
+            enum_to_toml_int(output, &ctx, variants, &values);
+        } else if is_string_enum {
             enum_to_toml_string(output, &ctx, variants);
         } else {
             let mode = if target.untagged {
@@ -3451,24 +6800,99 @@ pub fn inner_derive(stream: TokenStream) -> TokenStream {
         untagged: false,
         from_type: None,
         try_from_type: None,
+        into_type: None,
+        try_into_type: None,
+        remote: None,
+        vis: Vec::new(),
+        doc_comments: false,
+        schema: false,
+        update: false,
+        skip_if_default: false,
+        has_key_span: false,
+        docs: Vec::new(),
         unknown_fields: UnknownFieldPolicy::Warn { tag: None },
+        key_match: KeyMatch::Exact,
+        unit_repr: UnitRepr::Str,
         recoverable: false,
+        validate: None,
+        exactly_one_of: Vec::new(),
+        migrations: None,
+        partial: None,
     };
     let (kind, body) = ast::extract_derive_target(&mut target, &outer_tokens);
+    if !target.unit_repr.is_str()
+        && !match kind {
+            DeriveTargetKind::Enum => true,
+            _ => false,
+        }
+    {
+        Error::msg("repr can only be used on enums")
+    }
     if !(target.from_toml || target.to_toml) {
         target.from_toml = true;
     }
+    if target.update {
+        if !match kind {
+            DeriveTargetKind::Struct => true,
+            _ => false,
+        } {
+            Error::msg("Update can only be used on structs with named fields")
+        }
+        if !target.from_toml {
+            Error::msg("Update requires FromToml")
+        }
+        if !target.generics.is_empty() {
+            Error::msg("Update cannot be used on generic types")
+        }
+        if target.remote.is_some()
+            || target.transparent_impl
+            || target.from_type.is_some()
+            || target.try_from_type.is_some()
+        {
+            Error::msg("Update cannot be combined with remote, transparent, from, or try_from")
+        }
+    }
+    if target.doc_comments {
+        if !match kind {
+            DeriveTargetKind::Struct => true,
+            _ => false,
+        } {
+            Error::msg("doc_comments can only be used on structs with named fields")
+        }
+        if !target.to_toml {
+            Error::msg("doc_comments requires ToToml")
+        }
+        if !target.schema {
+            Error::msg("doc_comments requires Schema")
+        }
+        if target.remote.is_some()
+            || target.transparent_impl
+            || target.into_type.is_some()
+            || target.try_into_type.is_some()
+        {
+            Error::msg(
+                "doc_comments cannot be combined with remote, transparent, into, or try_into",
+            )
+        }
+    }
     let field_toks: Vec<TokenTree> = body.into_iter().collect();
     let mut tt_buf = Vec::<TokenTree>::new();
     let mut field_buf = Vec::<Field>::new();
     let mut pool = MemoryPool::<FieldAttrs>::new();
     let mut attr_buf = pool.allocator();
     let mut rust_writer = RustWriter::new();
+    let mut outer = TokenStream::new();
     match kind {
         DeriveTargetKind::Struct => {
             ast::parse_struct_fields(&mut field_buf, &field_toks, &mut attr_buf);
             ast::scan_fields(&mut target, &mut field_buf);
             handle_struct(&mut rust_writer, &target, &field_buf);
+            if let Some(partial) = &target.partial {
+                let mut writer = RustWriter::new();
+                let ctx = Ctx::new(&mut writer, &target);
+                partial_struct(&mut writer, &ctx, partial, &field_buf);
+                outer = writer.split_off_stream(0);
+            }
         }
         DeriveTargetKind::TupleStruct => {
             let t = Ident::new("a", Span::call_site());
@@ -3488,26 +6912,26 @@ pub fn inner_derive(stream: TokenStream) -> TokenStream {
         }
     }
     let ts = rust_writer.split_off_stream(0);
+    rust_writer.buf.extend(outer);
+    let out = &mut rust_writer;
     {
-        let len = rust_writer.buf.len();
-        rust_writer.blit_punct(15);
+        out.blit_punct(14);
         {
-            let at = rust_writer.buf.len();
-            rust_writer.blit_ident(5);
+            let at = out.buf.len();
+            out.blit_ident(102);
             {
-                let at = rust_writer.buf.len();
-                rust_writer.blit(662, 4);
-                rust_writer.tt_group(Delimiter::Parenthesis, at);
+                let at = out.buf.len();
+                out.blit(1728, 4);
+                out.tt_group(Delimiter::Parenthesis, at);
             };
-            rust_writer.tt_group(Delimiter::Bracket, at);
+            out.tt_group(Delimiter::Bracket, at);
         };
-        rust_writer.blit(666, 5);
-        rust_writer
-            .buf
+        out.blit(1732, 6);
+        out.buf
             .push(TokenTree::Group(Group::new(Delimiter::Brace, ts)));
-        rust_writer.blit_punct(2);
-        rust_writer.split_off_stream(len)
-    }
+        out.blit_punct(2);
+    };
+    rust_writer.split_off_stream(0)
 }
 pub fn derive(stream: TokenStream) -> TokenStream {
     Error::try_catch_handle(stream, inner_derive)
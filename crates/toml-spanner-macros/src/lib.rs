@@ -127,6 +127,13 @@ impl Error {
 ///
 /// When `#[derive(Toml)]` is used with no trait attribute, it defaults to [`FromToml`].
 ///
+/// `Schema` implements [`TomlSchema`], a static description of the expected
+/// TOML shape. [Read more](#tomlschema)
+///
+/// `Update` implements [`UpdateFromToml`] on structs, applying a reloaded
+/// config to an existing value and reporting which fields changed.
+/// [Read more](#tomlupdate)
+///
 /// The rest of the attributes are described in the following tables. Note that some
 /// attributes apply only to certain traits.
 ///
@@ -136,23 +143,37 @@ impl Error {
 /// | Format | Supported Traits | Description |
 /// |--------|------------------|-------------|
 /// | `content = "..."` | `FromToml`, `ToToml` | Field containing the data content of an adjacently tagged enum. Must be used with `tag`. |
+/// | `doc_comments` | `ToToml` | Write each field's `///` doc comment as a leading comment in `to_string` output. [Read more](#tomldoc_comments) |
 /// | `deny_unknown_fields` | `FromToml` | Unknown keys cause an error and immediately return `Failed`. |
+/// | `exactly_one_of = [...]` | `FromToml` | Exactly one of the listed fields must be present. Can appear multiple times. [Read more](#field-relations) |
 /// | `ignore_unknown_fields` | `FromToml` | Unknown keys are silently ignored (no errors recorded). |
+/// | `key_match = "..."` | `FromToml` | Accept keys differing in case or `-`/`_` separators, with a warning. [Read more](#tomlkey_match--) |
+/// | `migrations = [...]` | `FromToml` | Upgrade steps for older format versions, applied before deserializing. [Read more](#tomlversion-and-migrations) |
+/// | `partial = Name` | `FromToml` | Generate a companion type with every field optional, for merging configuration layers. [Read more](#tomlpartial--name) |
 /// | `recoverable` | `FromToml` | Defers field failures to accumulate as many errors as possible. |
 /// | `warn_unknown_fields` | `FromToml` | Unknown keys record errors but still construct the value (same as default). |
 /// | `from = Type` | `FromToml` | Deserialize by deserializing `Type`, then converting via `From`. |
+/// | `into = Type` | `ToToml` | Serialize by converting a clone into `Type` via `Into`, then serializing it. [Read more](#tomlinto--type--tomltry_into--type) |
+/// | `remote = "path::Type"` | `FromToml`, `ToToml` | Generate `from_toml`/`to_toml` functions for a foreign struct, for use with `with`. [Read more](#tomlremote--pathtype) |
 /// | `rename_all = "..."` | `FromToml`, `ToToml` | Renames variants and fields not explicitly renamed. |
+/// | `repr = "..."` | `FromToml`, `ToToml` | Encode an all-unit enum as an integer (`"int"`) or accept either its name or integer (`"str_or_int"`). [Read more](#enum-with-tomlrepr--) |
 /// | `rename_all_fields = "..."` | `FromToml`, `ToToml` | On enums, overrides `rename_all` for fields in struct variants. |
+/// | `skip_if_default` | `ToToml` | Omit every field with a `default` while it equals that default. [Read more](#tomlskip_if_default) |
 /// | `tag = "..."` | `FromToml`, `ToToml` | Field containing the enum variant discriminator. |
 /// | `transparent` | `FromToml`, `ToToml` | Traits delegate to the single inner type. |
 /// | `try_from = Type` | `FromToml` | Deserialize by deserializing `Type`, then converting via `TryFrom`. |
+/// | `try_into = Type` | `ToToml` | Serialize by converting a clone into `Type` via `TryInto`, then serializing it. [Read more](#tomlinto--type--tomltry_into--type) |
 /// | `untagged` | `FromToml`, `ToToml` | Only data content of an enum is stored. |
+/// | `validate = ...` | `FromToml` | Check the constructed value with the provided function. [Read more](#tomlvalidate---on-containers) |
 ///
 /// ## Enum Variant Attributes
 /// These are `toml` attributes that appear above a variant in an enum.
 ///
 /// | Format | Supported Traits | Description |
 /// |--------|------------------|-------------|
+/// | `alias = "..."` | `FromToml` | Also accept this name for the variant. Can appear multiple times. Not for untagged or `repr = "int"` enums. |
+/// | `deprecated_alias = "..."` | `FromToml` | Like `alias`, but records an [`ErrorKind::DeprecatedVariant`] warning at the name. |
+/// | `expecting = "..."` | `FromToml` | Untagged only: describes the variant in the error reported when no variant matches. |
 /// | `final_if = \|ctx, item\| bool` | `FromToml` | Untagged only: skip variant when predicate is false, commit on match. |
 /// | `other` | `FromToml` | Catch-all variant for unknown tag values. Unit variant only. |
 /// | `rename = "..."` | `FromToml`, `ToToml` | Use provided string as variant name. |
@@ -165,13 +186,27 @@ impl Error {
 /// | Format | Supported Traits | Description |
 /// |--------|------------------|-------------|
 /// | `alias = "..."` | `FromToml` | Use provided string as an alternative field name. Can appear multiple times. |
+/// | `conflicts_with = ...` | `FromToml` | Reject the table if this field and any of the named fields are both present. [Read more](#field-relations) |
 /// | `default [= ...]` | `FromToml` | Use `Default::default()` or provided expression if field is missing. |
 /// | `flatten` | `FromToml`, `ToToml` | Flatten the contents of the field into the container it is defined in. |
+/// | `key_span` | `FromToml`, `ToToml` | Receive the span of the key the table was found under. Skipped when serializing. [Read more](#tomlspan--tomlkey_span) |
+/// | `max_len = ...` | `FromToml` | Reject values whose `len()` is greater than provided bound. [Read more](#constraints) |
+/// | `min_len = ...` | `FromToml` | Reject values whose `len()` is less than provided bound. [Read more](#constraints) |
+/// | `non_empty` | `FromToml` | Reject values that are empty. [Read more](#constraints) |
+/// | `one_of = [...]` | `FromToml` | Reject values not equal to one of the provided literals. [Read more](#constraints) |
+/// | `partial` | `FromToml` | Use the field type's own partial type in the container's partial type. [Read more](#tomlpartial--name) |
+/// | `range = ...` | `FromToml` | Reject values outside of the provided range. [Read more](#constraints) |
 /// | `rename = "..."` | `FromToml`, `ToToml` | Use provided string as field name. |
 /// | `required` | `FromToml` | Field must be present even if the type is `Option<T>`. |
+/// | `requires = ...` | `FromToml` | Reject the table if this field is present without the named fields. [Read more](#field-relations) |
 /// | `skip` | `FromToml`, `ToToml` | Omit field while serializing, use default value when deserializing. |
 /// | `skip_if = ...` | `ToToml` | Omit field while serializing if provided predicate returns true. |
+/// | `skip_if_default` | `ToToml` | Omit field while serializing if it equals its `default`. [Read more](#tomlskip_if_default) |
+/// | `span` | `FromToml`, `ToToml` | Receive the span of the table itself. Skipped when serializing. [Read more](#tomlspan--tomlkey_span) |
 /// | `style = ...` | `ToToml` | Control serialization style for tables and arrays (`Header`, `Inline`, `Dotted`). |
+/// | `update` | `FromToml` | Update the field in place through its own `UpdateFromToml`, reporting the nested paths that changed. [Read more](#tomlupdate) |
+/// | `validate = ...` | `FromToml` | Check the field value with the provided predicate, reporting at the field. [Read more](#tomlvalidate---on-fields) |
+/// | `version` | `FromToml` | Key holding the format version, upgraded through the container `migrations`. [Read more](#tomlversion-and-migrations) |
 /// | `with = ...` | `FromToml`, `ToToml` | Use methods from specified module instead of trait. [Read more](#tomlwith---on-fields) |
 ///
 /// ## Trait Aliases
@@ -295,6 +330,28 @@ impl Error {
 /// `skip` is useful when you need a field on the Rust side that is not present in
 /// the TOML data.
 ///
+/// #### `#[toml(span)]` / `#[toml(key_span)]`
+///
+/// Fills a [`Span`] field with the location of the struct's table instead of
+/// reading a key. `span` receives the span of the table itself and `key_span`
+/// the span of the key it was found under, as passed to
+/// [`FromToml::from_toml_at_key`]. Elements of an array of tables receive the key
+/// of the array, and a struct deserialized as the document root receives an
+/// empty span.
+///
+/// Both fields are skipped by `ToToml`, so a struct can keep its location for
+/// later diagnostics without wrapping it in `Spanned<T>`.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(Toml)]
+/// struct Server {
+///     host: String,
+///     #[toml(key_span)]
+///     key_span: Span,
+/// }
+/// ```
+///
 /// #### `#[toml(flatten)]`
 ///
 /// Captures all unrecognized keys from the table into a map-like field. The field
@@ -302,8 +359,6 @@ impl Error {
 /// (for serialization). Built-in implementations exist for `BTreeMap<K, V>` and
 /// `HashMap<K, V>`.
 ///
-/// At most one flatten field is allowed per struct or variant.
-///
 /// ```ignore
 /// #[derive(Toml)]
 /// #[toml(FromToml, ToToml)]
@@ -318,6 +373,29 @@ impl Error {
 /// that implement [`FromToml`]/[`ToToml`] rather than the flatten traits. The built-in
 /// [`flatten_any`] module handles the common case.
 ///
+/// Several `flatten_any` fields can share the parent's table, for example reusable
+/// logging and TLS settings. Each key goes to the first field whose type claims it
+/// through `FromToml::claims_key`; derived structs with named fields claim their own
+/// keys and aliases, `Option`, `Box`, `Spanned` and `Layered` defer to the type they wrap,
+/// and other types claim every key. At most one other flatten field may
+/// collect the remaining keys, otherwise they are reported as unknown by the parent,
+/// with a suggestion drawn from its own keys and those listed by each field's
+/// `FromToml::expected_keys`.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, ToToml)]
+/// struct Server {
+///     port: u16,
+///     #[toml(flatten, with = flatten_any)]
+///     logging: Logging,
+///     #[toml(flatten, with = flatten_any)]
+///     tls: Tls,
+///     #[toml(flatten)]
+///     extras: BTreeMap<String, String>,
+/// }
+/// ```
+///
 /// #### `#[toml(default [= ...])]`
 ///
 /// For non-`Option` types, use `Default::default()` or the provided expression
@@ -380,6 +458,117 @@ impl Error {
 /// }
 /// ```
 ///
+/// #### `#[toml(skip_if_default)]`
+///
+/// Omits the field while it equals the value `FromToml` would fill in for it,
+/// given by its `default` attribute, so generated files only list what differs.
+/// Reading the file back yields the same value. On a container, it applies to
+/// every field with a `default`. The field type must implement `PartialEq`.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(Toml, skip_if_default)]
+/// struct Config {
+///     name: String,
+///     #[toml(default = 8080)]
+///     port: u16,
+/// }
+/// ```
+///
+/// [`Formatting::with_expanded_defaults`] writes these fields anyway, for a fully
+/// expanded file, as does [`to_template`].
+///
+/// #### `#[toml(validate = ...)]` on fields
+///
+/// Checks the field value once it has been deserialized. The function can be
+/// specified by a path or inline using closure syntax, is provided the value via
+/// reference, and returns `Result<(), E>` where `E` implements `Display`. For
+/// `Option<T>` fields (auto-detected optional) it is provided the inner `T`.
+///
+/// An `Err` is recorded in the [`Context`] at the field's value, so the error
+/// carries its span and TOML path. A failing required field is treated like one
+/// that failed to deserialize: it returns [`Failed`], or with `recoverable` the
+/// remaining fields are still checked. A failing optional field is treated like
+/// one that failed to deserialize as well: the field is left `None` and
+/// construction continues, but the recorded error still makes
+/// [`Document::to`] and [`from_str`] fail.
+///
+/// ```ignore
+/// fn non_zero(port: &u16) -> Result<(), &'static str> {
+///     if *port == 0 { Err("port must be non-zero") } else { Ok(()) }
+/// }
+///
+/// #[derive(Toml)]
+/// #[toml(FromToml)]
+/// struct Server {
+///     #[toml(validate = non_zero)]
+///     port: u16,
+///     #[toml(validate = |s: &String| if s.is_empty() { Err("empty host") } else { Ok(()) })]
+///     host: String,
+/// }
+/// ```
+///
+/// #### Constraints
+///
+/// Common checks can be declared without writing a validator. Each is applied to
+/// the field value after deserialization, with the same failure behavior as
+/// `validate`, and reports an error at the offending value:
+///
+/// | Attribute | Check | Error |
+/// |-----------|-------|-------|
+/// | `range = a..=b` | `(a..=b).contains(&value)`, any Rust range expression | `OutOfRange` |
+/// | `min_len = n` | `value.len() >= n` | `OutOfRange` |
+/// | `max_len = n` | `value.len() <= n` | `OutOfRange` |
+/// | `non_empty` | `!value.is_empty()` | `OutOfRange` |
+/// | `one_of = [...]` | `value` equals one of the literals | `UnexpectedValue` |
+///
+/// `OutOfRange` errors name the field type and the violated bound, so a `range`
+/// on a port reads the same as the library's own integer range errors. For
+/// strings, `len()` is the length in bytes.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml)]
+/// struct Server {
+///     #[toml(range = 1..=65535)]
+///     port: u32,
+///     #[toml(non_empty, max_len = 253)]
+///     host: String,
+///     #[toml(one_of = ["debug", "info", "warn", "error"])]
+///     log_level: Option<String>,
+/// }
+/// ```
+///
+/// #### Field relations
+///
+/// `conflicts_with` and `requires` on a field, and `exactly_one_of` on the
+/// container, check which keys are present in the table. Each takes a Rust field
+/// name or a list of them, such as `conflicts_with = [git, path]`, and errors
+/// refer to the fields by their TOML key names:
+///
+/// | Attribute | Check | Error |
+/// |-----------|-------|-------|
+/// | `conflicts_with = other` | not both keys present | `ConflictingFields`, at the later key with the earlier one labeled |
+/// | `requires = other` | `other` present whenever this key is | `MissingRequiredBy`, at this key |
+/// | `exactly_one_of = [a, b, ...]` | exactly one of the keys present | `ConflictingFields` if several, `MissingOneOf` at the table if none |
+///
+/// Presence is decided by the key, so a key whose value failed to deserialize
+/// still counts. The checks run after every field is read; in a `recoverable`
+/// container all violations are reported, otherwise the first one returns.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, exactly_one_of = [git, path, version])]
+/// struct Dependency {
+///     #[toml(conflicts_with = path)]
+///     git: Option<String>,
+///     #[toml(requires = git)]
+///     branch: Option<String>,
+///     path: Option<String>,
+///     version: Option<String>,
+/// }
+/// ```
+///
 /// ### Detailed Container Attribute Descriptions
 ///
 /// #### `#[toml(transparent)]`
@@ -406,6 +595,29 @@ impl Error {
 /// Per-variant `#[toml(rename_all = "...")]` overrides both the container
 /// `rename_all` and `rename_all_fields` for that variant's fields.
 ///
+/// #### `#[toml(key_match = "...")]`
+///
+/// Controls how keys are matched to field names and aliases when
+/// deserializing. The possible values are `"exact"` (the default),
+/// `"ignore_case"`, `"ignore_separators"`, which treats `-` and `_` as absent,
+/// and `"ignore_case_and_separators"`.
+///
+/// A key that only matches after normalization is accepted and records a
+/// non-fatal [`NonCanonicalKey`] error naming the canonical spelling, so
+/// tools can surface it as a warning. Exact spellings record nothing.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, rename_all = "kebab-case", key_match = "ignore_case_and_separators")]
+/// struct Dependency {
+///     // Also accepts `default_features` and `defaultFeatures`.
+///     default_features: bool,
+/// }
+/// ```
+///
+/// Fields whose names or aliases normalize to the same key are rejected at
+/// compile time.
+///
 /// #### Unknown field policies
 ///
 /// By default, unrecognized keys are recorded as errors in the deserialization
@@ -422,6 +634,9 @@ impl Error {
 /// `#[toml(ignore_unknown_fields)]` silently discards unknown keys without
 /// recording any errors.
 ///
+/// When an unknown key is a likely typo of a field's key, the error carries
+/// that key as a suggestion, rendered as "did you mean `port`?".
+///
 /// Both `warn_unknown_fields` and `deny_unknown_fields` support an optional
 /// error tag in brackets. The tag is stored in the [`UnexpectedKey { tag }`](https://docs.rs/toml-spanner/latest/toml_spanner/enum.ErrorKind.html#variant.UnexpectedKey)
 /// error variant and can be used for programmatic filtering or attaching
@@ -456,6 +671,92 @@ impl Error {
 /// Avoid using on structs that serve as variants of an `untagged` enum, as
 /// untagged deserialization relies on early failure to distinguish variants.
 ///
+/// #### `#[toml(version)]` and `migrations`
+///
+/// Marks the integer field holding a struct's format version, for configs
+/// whose layout changes between releases. Versions count from 1. The
+/// container attribute `migrations = [...]` lists the upgrade steps, oldest
+/// first, so the current version is one more than their number.
+///
+/// Before any field is read, the version key is checked. A table that is
+/// missing the key is treated as version 1. An older table is copied and each
+/// step from its version onwards rewrites the copy in place, after which the
+/// version key is set to the current version and deserialization proceeds
+/// as usual. Newer or non-integer versions are errors.
+///
+/// A step is a [`Migration`], a function receiving a [`Migrator`] and the
+/// [`Table`] being upgraded. Entries it moves keep their source spans, so
+/// errors found afterwards still point into the original document.
+/// [`Migrator::rename_key`] renames a key and records a non-fatal
+/// [`Deprecated`] error at the old key, tagged with the version it was
+/// written in.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, migrations = [v1_to_v2])]
+/// struct Config {
+///     #[toml(version)]
+///     version: u32,
+///     threads: u32,
+/// }
+///
+/// // Version 2 renamed `workers` to `threads`.
+/// fn v1_to_v2<'de>(m: &mut Migrator<'_, 'de>, table: &mut Table<'de>) -> Result<(), Failed> {
+///     m.rename_key(table, &"workers", &"threads");
+///     Ok(())
+/// }
+/// ```
+///
+/// The derive also implements [`Versioned`], so the upgrade can be run on its
+/// own and written back to disk with comments intact:
+///
+/// ```ignore
+/// let (ctx, table) = doc.split();
+/// if let Some(migrated) = ctx.migrate::<Config>(table)? {
+///     let bytes = Formatting::preserved_from(&doc)
+///         .with_migration(&migrated)
+///         .format_table_to_bytes(migrated.table, &arena);
+///     std::fs::write(path, bytes)?;
+/// }
+/// ```
+///
+/// #### `#[toml(partial = Name)]`
+///
+/// Declares a companion struct `Name` for layered configuration, such as
+/// defaults overridden by a system file, a user file and then command line
+/// flags. Each field of `Name` is an `Option<`[`Layered<T>`]`>`, which records
+/// the span and layer the value was read from. `Name` derives `FromToml`
+/// with the container's field names, aliases, `key_match` and unknown field
+/// policy, so every layer is read with the usual diagnostics.
+///
+/// Mark a field `partial` to nest the field type's own partial type instead
+/// of reading the whole table from one layer. The field type must derive
+/// `partial` as well.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, partial = ConfigLayer)]
+/// struct Config {
+///     name: String,
+///     #[toml(default = 8080)]
+///     port: u16,
+///     #[toml(partial)]
+///     server: Server,
+/// }
+///
+/// let config = defaults.with_layer(0)
+///     .merge(user.with_layer(1))
+///     .merge(cli.with_layer(2))
+///     .resolve()?;
+/// ```
+///
+/// The [`Partial`] trait provides `merge`, in which the fields set in the upper
+/// layer win, and `resolve`, which builds the full struct. Unset fields take
+/// their defaults, and every required field still missing is reported as a
+/// [`MissingField`] with the span and layer of the table it was expected in.
+/// Field checks such as `validate` and constraints apply when reading the full
+/// struct only. `flatten` and `with` fields are not supported.
+///
 /// #### `#[toml(from = Type)]` / `#[toml(try_from = Type)]`
 ///
 /// Instead of deserializing each field individually, the macro deserializes a proxy
@@ -483,6 +784,203 @@ impl Error {
 /// These attributes apply to `FromToml` only. Normal `ToToml` generation is
 /// unaffected and can be combined freely.
 ///
+/// #### `#[toml(into = Type)]` / `#[toml(try_into = Type)]`
+///
+/// The `ToToml` counterpart of `from`/`try_from`: the macro clones the value,
+/// converts the clone into the proxy type, and serializes the proxy. `into` uses
+/// `Into<Type>` and `try_into` uses `TryInto<Type>`, whose error must implement
+/// `Display` and is returned as a [`ToTomlError`]. The container must implement
+/// `Clone`.
+///
+/// ```ignore
+/// #[derive(Toml, Clone)]
+/// #[toml(Toml, from = RawEndpoint, into = RawEndpoint)]
+/// struct Endpoint(String);
+/// ```
+///
+/// The item produced by the proxy is moved into the arena with
+/// `Item::into_owned_in`, which copies only the strings borrowed from the proxy,
+/// since the proxy does not outlive the call.
+///
+/// #### `#[toml(doc_comments)]`
+///
+/// Overrides [`ToToml::doc_comments`] to return the struct's [`TomlSchema`],
+/// so [`to_string`] and [`Formatting::format`] write each field's `///` doc
+/// comment as a leading `#` comment on its key or section header. Fields whose
+/// type is a struct are written as `[sections]` with their own comments.
+/// Unlike [`to_template`], nothing is commented out. Requires `ToToml`,
+/// `Schema`, and a struct with named fields.
+///
+/// Keys already present in a [`Formatting::preserved_from`] source are written
+/// with the comments of that source, so comments a user edited or removed
+/// are left alone. Only freshly emitted keys receive doc comments.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(ToToml, Schema, doc_comments)]
+/// struct Config {
+///     /// Name of the service.
+///     name: String,
+/// }
+///
+/// let text = toml_spanner::to_string(&Config { name: "app".into() })?;
+/// // # Name of the service.
+/// // name = "app"
+/// ```
+///
+/// #### `#[toml(Schema)]`
+///
+/// Implements [`TomlSchema`] for the container, describing the TOML it reads
+/// and writes: each field's key after renaming, its aliases and deprecated
+/// aliases, doc comment, Rust type, default, and whether it is flattened, the
+/// unknown field policy, and for enums the representation and every variant.
+/// Tools like documentation generators or completion engines can walk this
+/// description instead of parsing Rust source.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, Schema)]
+/// struct Config {
+///     /// Port to listen on.
+///     #[toml(default = 8080)]
+///     port: u16,
+/// }
+///
+/// let Schema::Struct(config) = Config::schema() else { unreachable!() };
+/// assert_eq!(config.fields[0].default, FieldDefault::Expr("8080"));
+/// ```
+///
+/// Every field type must implement [`TomlSchema`], as the standard types do.
+/// Fields converted `with` a custom module are described as any value, since
+/// the module decides their shape. `from`, `try_from`, `into`, `try_into`,
+/// and `transparent` containers describe themselves with the schema of the
+/// proxy or inner type.
+///
+/// `Schema::to_json_schema`, or `schema::json_schema::<Config>()`, renders the
+/// description as a JSON Schema document for editors such as Even Better TOML.
+/// Doc comments become descriptions, custom defaults that are TOML literals
+/// become `default` values, and `deny_unknown_fields` becomes
+/// `"additionalProperties": false`.
+///
+/// Together with `ToToml`, the schema lets [`to_template`] write the struct
+/// as a commented example config. Each field's `///` doc comment becomes a
+/// leading `#` comment, fields with a default (including `Option` fields) are
+/// commented out, and fields whose type is also a struct are written as
+/// `[sections]`.
+///
+/// ```ignore
+/// #[derive(Toml, Default)]
+/// #[toml(ToToml, Schema)]
+/// struct Config {
+///     /// Name of the service.
+///     name: String,
+///     /// Worker threads.
+///     #[toml(default)]
+///     workers: u32,
+/// }
+///
+/// let template = toml_spanner::to_template(&Config::default())?;
+/// // # Name of the service.
+/// // name = ""
+/// // # Worker threads.
+/// // # workers = 0
+/// ```
+///
+/// #### `#[toml(Update)]`
+///
+/// Implements [`UpdateFromToml`] for a struct with named fields, for hot
+/// reloading configuration. Its `update` method moves each field of a newly
+/// read value into the existing one only when the two differ, and returns a
+/// [`ChangeSet`] with the key path of every field that changed, so callers can
+/// restart just the affected subsystems. Field types must implement
+/// `PartialEq`.
+///
+/// Mark a field `update` to apply the nested value through its own
+/// `UpdateFromToml` instead of replacing it, reporting paths such as
+/// `server.port`. `Option` fields of such types update in place when set on
+/// both sides.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, Update)]
+/// struct Config {
+///     name: String,
+///     #[toml(update)]
+///     server: Server,
+///     #[toml(skip)]
+///     reloads: u32,
+/// }
+///
+/// let changes = doc.update(&mut config)?;
+/// if changes.affects(&["server"]) {
+///     restart_server(&config.server);
+/// }
+/// ```
+///
+/// `Document::update` only touches the value when the new document converts
+/// without errors. `skip` fields keep their current value, while `span` and
+/// `key_span` fields are refreshed silently. Flattened fields are not
+/// supported, nor are `remote`, `transparent`, `from`, `try_from` or generic
+/// containers.
+///
+/// #### `#[toml(remote = "path::Type")]`
+///
+/// Derives for a struct defined in another crate. The struct carrying the
+/// attribute is a local mirror that lists the same fields with the same types;
+/// instead of trait impls, the macro generates inherent `from_toml` and
+/// `to_toml` functions on the mirror that read and write the remote type. Use
+/// them with `#[toml(with = Mirror)]` on a field of the foreign type.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(Toml, remote = "winit::Size")]
+/// struct SizeDef {
+///     width: u32,
+///     height: u32,
+/// }
+///
+/// #[derive(Toml)]
+/// #[toml(Toml)]
+/// struct Config {
+///     #[toml(with = SizeDef)]
+///     size: winit::Size,
+/// }
+/// ```
+///
+/// The generated code constructs and destructures the remote type with every
+/// field of the mirror, so a missing, extra, or mistyped field is a compile
+/// error. All fields of the remote type must be visible from the mirror's
+/// module, and the functions take the mirror's visibility. Field attributes
+/// work as on any other struct. Remote derives support named-field structs
+/// without generics, and cannot be combined with `transparent`, `from`,
+/// `try_from`, `into`, or `try_into`.
+///
+/// #### `#[toml(validate = ...)]` on containers
+///
+/// Runs the provided function on the constructed value, for checks that span
+/// several fields. The function has the signature
+/// `fn(&Self, &mut Context<'de>) -> Result<(), Failed>` and reports its own
+/// errors through the [`Context`]. It only runs once every field has
+/// deserialized, so it never sees a partially built value.
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, validate = Range::check)]
+/// struct Range {
+///     min: Spanned<u32>,
+///     max: u32,
+/// }
+///
+/// impl Range {
+///     fn check(&self, ctx: &mut Context<'_>) -> Result<(), Failed> {
+///         if self.min.value > self.max {
+///             return Err(ctx.report_error_at("min exceeds max", self.min.span));
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+///
 /// ### Enum Representations
 ///
 /// #### Default enum representation for TOML
@@ -596,7 +1094,15 @@ impl Error {
 ///
 /// Variants are distinguished by structure, not a tag field. Deserialization tries
 /// each variant in declaration order until one succeeds. Errors from failed
-/// attempts are automatically cleaned up.
+/// attempts are set aside.
+///
+/// When no variant matches, a single [`ErrorKind::NoMatchingVariant`] lists the
+/// shape each variant accepts, such as ``expected one of: `String`, table with
+/// `git` or `path` ``. Unit variants are described by their name and tuple variants
+/// by their field type; `#[toml(expecting = "...")]` on a variant overrides this.
+/// [`Error::notes`] then points at the errors each attempted variant failed with.
+/// When the last variant is the only one that is not a unit variant or gated by
+/// `final_if`, its errors are reported directly instead.
 ///
 /// <table width="100%">
 /// <tr><td width="47%">Enum</td><td>TOML for Example::Variant</td></tr><tr><td>
@@ -644,6 +1150,41 @@ impl Error {
 /// }
 /// ```
 ///
+/// #### Enum with `#[toml(repr = "...")]`
+///
+/// Only for enums whose variants are all unit variants. With `repr = "int"`
+/// each variant is stored as its discriminant, which is either an integer
+/// literal or one more than the previous variant's, starting at `0`.
+/// With `repr = "str_or_int"` both the variant name and the discriminant
+/// are accepted, and the name is written. The default, `repr = "str"`,
+/// only accepts names.
+///
+/// An integer matching no variant is reported as [`ErrorKind::OutOfRange`]
+/// when the discriminants are contiguous and as [`ErrorKind::UnexpectedValue`]
+/// otherwise. A `#[toml(other)]` variant catches it instead.
+///
+/// <table width="100%">
+/// <tr><td width="47%">Enum</td><td>TOML for Level::Warn</td></tr><tr><td>
+///
+/// ```ignore
+/// #[derive(Toml)]
+/// #[toml(FromToml, ToToml,
+///     repr = "int")]
+/// enum Level {
+///     Debug = 1,
+///     Info,
+///     Warn,
+/// }
+/// ```
+///
+/// </td><td>
+///
+/// ```toml
+/// 3
+/// ```
+///
+/// </td></tr></table>
+///
 /// ### Built-in Helpers
 ///
 /// The [`toml_spanner::helper`] module provides modules for use with the `with` field
@@ -716,11 +1257,40 @@ impl Error {
 ///
 /// [`FromToml`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.FromToml.html
 /// [`ToToml`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.ToToml.html
+/// [`ToTomlError`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.ToTomlError.html
+/// [`to_template`]: https://docs.rs/toml-spanner/latest/toml_spanner/fn.to_template.html
+/// [`ErrorKind::OutOfRange`]: https://docs.rs/toml-spanner/latest/toml_spanner/enum.ErrorKind.html#variant.OutOfRange
+/// [`ErrorKind::UnexpectedValue`]: https://docs.rs/toml-spanner/latest/toml_spanner/enum.ErrorKind.html#variant.UnexpectedValue
+/// [`ErrorKind::DeprecatedVariant`]: https://docs.rs/toml-spanner/latest/toml_spanner/enum.ErrorKind.html#variant.DeprecatedVariant
+/// [`ErrorKind::NoMatchingVariant`]: https://docs.rs/toml-spanner/latest/toml_spanner/enum.ErrorKind.html#variant.NoMatchingVariant
+/// [`Error::notes`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Error.html#method.notes
+/// [`ToToml::doc_comments`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.ToToml.html#method.doc_comments
+/// [`to_string`]: https://docs.rs/toml-spanner/latest/toml_spanner/fn.to_string.html
+/// [`Formatting::format`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Formatting.html#method.format
+/// [`Formatting::preserved_from`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Formatting.html#method.preserved_from
+/// [`TomlSchema`]: https://docs.rs/toml-spanner/latest/toml_spanner/schema/trait.TomlSchema.html
 /// [`FromFlattened`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.FromFlattened.html
 /// [`ToFlattened`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.ToFlattened.html
 /// [`Context`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Context.html
 /// [`Item`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Item.html
 /// [`Failed`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Failed.html
+/// [`Document::to`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Document.html#method.to
+/// [`from_str`]: https://docs.rs/toml-spanner/latest/toml_spanner/fn.from_str.html
+/// [`NonCanonicalKey`]: https://docs.rs/toml-spanner/latest/toml_spanner/enum.ErrorKind.html#variant.NonCanonicalKey
+/// [`Deprecated`]: https://docs.rs/toml-spanner/latest/toml_spanner/enum.ErrorKind.html#variant.Deprecated
+/// [`Table`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Table.html
+/// [`Migration`]: https://docs.rs/toml-spanner/latest/toml_spanner/type.Migration.html
+/// [`Migrator`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Migrator.html
+/// [`Migrator::rename_key`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Migrator.html#method.rename_key
+/// [`Layered<T>`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Layered.html
+/// [`Partial`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.Partial.html
+/// [`MissingField`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.MissingField.html
+/// [`Formatting::with_expanded_defaults`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Formatting.html#method.with_expanded_defaults
+/// [`UpdateFromToml`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.UpdateFromToml.html
+/// [`ChangeSet`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.ChangeSet.html
+/// [`Versioned`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.Versioned.html
+/// [`Span`]: https://docs.rs/toml-spanner/latest/toml_spanner/struct.Span.html
+/// [`FromToml::from_toml_at_key`]: https://docs.rs/toml-spanner/latest/toml_spanner/trait.FromToml.html#method.from_toml_at_key
 /// [`toml_spanner::helper`]: https://docs.rs/toml-spanner/latest/toml_spanner/helper/index.html
 /// [`flatten_any`]: https://docs.rs/toml-spanner/latest/toml_spanner/helper/flatten_any/index.html
 /// [`parse_string`]: https://docs.rs/toml-spanner/latest/toml_spanner/helper/parse_string/index.html
@@ -37,9 +37,13 @@ impl RustWriter {
     pub fn blit(&mut self, start: u32, len: u32) {
         let start = start as usize;
         let len = len as usize;
-        let src = &cache::BLIT_SRC[start..start + len];
-        self.buf.extend(src.iter().map(|i| {
-            let index = *i as usize;
+        let mut src = cache::BLIT_SRC[start..start + len].iter();
+        // Ids from `u8::MAX` up are escaped by a `u8::MAX` byte, see the preprocessor.
+        let ids = std::iter::from_fn(|| match *src.next()? {
+            u8::MAX => Some(u8::MAX as usize + *src.next()? as usize),
+            byte => Some(byte as usize),
+        });
+        self.buf.extend(ids.map(|index| {
             if let Some(punct) = self.punct.get(index) {
                 return TokenTree::Punct(punct.clone());
             }
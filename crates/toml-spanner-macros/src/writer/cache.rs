@@ -1,146 +1,283 @@
 use proc_macro::{Punct, Spacing};
 pub static BLIT_SRC: &[u8] =
-b"\n\tM?\x05\x07\n\ts\x05\x07\x00I\x00\r\n\t3\x05\x07Hx\x84\t\x06w\n\t1\x05\x07\x00\r\x82\t\x06\n\tc\x05\x07\x0e\x00\n\t_\n\tK\n\tD\x05\x80\r\n\tQ\x00\n\thI\n\th\r\n\t4\rHr\x05\x07\x06\x07n\r}\t\x06\x07\n\t\x12\n\t5\x00~\x83\x86u\n\t\x15\n\t-\n\t5\n\t>\x86wa\x03\x1b\x02\x86wG\t.\x03\x86wF\x03\n\t\x1e\x87\x02\x03Z\x02\x03Z\n\t\x05\x00\x02\x03M\n\t\x14\n\tmvy\x01d\x04\x00\x88\x0c\x03\x01!\x87\x84\x01&\ry\x01f\r\rt{y\x01d\x04\x03\x84\x01%\r\x06\ry\x01f\rt\x03u\x03y\x01f\x02\x04\x00~\x83a\x03N\x02\x04\x00\x88\r|\x04\x00\x86|\x03\n\t\x1f\x84\ry\rt\r\x06wF\x86\x85\n\t\x1c\rF\x01 \x87G\x06\x84\x01b{\x10a{a\x01/\x87\x02\x01)\x87\x7f\x010\x0c\x0b\x01A\x87\x02\x032\n\t=\x87\x02\x06n\x01{\x86u\x01k\n\tg\n\tm\n\tr\x01P\n\tE\n\t\r}\r\x03\x05\n\th\x00\n\t+\r\x81\r\x05\n\th\x00\n\tr\n\t*\r}\r\x06w\x08\x02\x80\n\t\x03\x82\x01e\\\x01l\x87\x00\x00\n\tx\x84\r\x82\n\t_\n\t<\n\t\x13\n\t>v\n\t_\n\t<\n\t\x16\n\t,\x04\x00\x85\r\x83\x04\x00\x83\x84\x01$o\r\x82\n\tc\n\tL|\x04\x00\x88\x84\x01W\rt\ry\x01f|\x04\x00\x85|\x04\x00\x83\x84\x01^\x84\rt\x03\x82\x01(vX\x84\x01\'vnq\x01l\x87j\r}\x04\x00\x05q\x01k\x08\r}\r\x02\x85V\x01k\rq\x01l\x87\r}\rV\x01l\x87~vX\x03\x82\x019\x87\x86U\x03q\x01U\x87\x02U\x01J\x87\x04\x03@\r`\x03\x06Uv@\x01d\x84\r`\x86\x82\x03`\x02\x03`\x01e\x86wp\tC\x05\x06Y\x00\x03Z\x02\x03\x05\x06Yzp\x03u\x02\x19\x02\x03p\x7fvp\x86|\x03y\x02{y\x01d\x11\x03\x86S\x03\\\x02\x86wp\tC\x05\x06Y\x00\x03Z\x02\x86wi\tC\x05\x06\x00\x00\x03Z\x02i\x03u\x03i\x7f\x84\ri\x86\x82\x03i\x02\x03i\x01e{8\x04\x03~\x85\x04\x00~\x85\x84\x01]\x01Ov\x05\x86[\x03\x84\x01]\x01J\x87\x02\x86[\x03\x84\x01]\x01J\x87\x02\x867\t\n\t_\n\tK\n\tD\x05\x80\r\n\tQ\x00\x03\x87\x02v7\x866\tH\x00\r\x06\x0e\x00\x18\x03\x02{6\x1a\n\t\";|\t\x87\x03";
-pub const IDENT_SIZE: usize = 117;
-pub static NAMES: [&str; 117] = [
-    "Arena",
-    "From",
+b"\n\t\x82\x9a\n\t\xf7\x16Z\x03\x0f\x0b\x08\x00\n\t)\n\tK\n\tA\x04\n\t?\x00\xff\x11\x0c\x02\x05Z\r\x17\x0c\x02\x18G\x04\x07\n\t+\x04\x07\x00;\x00\r\n\t\x88\x04\x07,-\x17\t\x05\x1e\n\tT\x04\x07\x00\r\x19\t\x05\n\t1\x04\x07\x08\x00\n\t)\n\tK\n\tA\x04\x1a\r\x1a\n\t\x80\x17\r\n\tW\n\t]\xff\x11\r\x19,\x80\x00\r\x95\t\n\tW\r\x19\t\x05\n\t5;\n\t5\r\n\t\x8c\r\n\t$\n\t9\r,0\x04\x07\x05\x07\"\r%\t\x05\x07\n\t\x86\n\tc\x00&\x1d\x16\x1f\n\t\xca\n\t\xff\x0c\n\tc\n\t^\x16\x1eM\x03\x9d\x02\x16\x1ez\t\xff\r\x03\n\t\xe7\xff\x11\x02\x01=\xff\x11\x02\x03\x95\x02\x03/\x02\x03/\n\t\x04\x00\x02\x03\x82\n\tW\n\t.\t3\x04\n\tW\x00\x03/\x02(C(\'\x01<\x06\x00\xff\x12\x03\x1f\'\x01=\x0f\x03\x01\xeb\xff\x11\x17\x01\xfb\r\'\x01=\r\r\x1c\x06\x03\x17\x01\xa8\r\x05\r\'\x01=\r\x1c\x03\'\x01=\x02\x06\x00&\x1dM\x03a\x02\x06\x00\xff\x12\r*\x06\x00\x1b\x04\x00\x00\n\t~\x16\x18\n\t\xe5\x01\xa1\xff\x11z\x05\x17\x01R\x1b\x12M\x1bM\x01\xff\x0e\xff\x11\x02\x01\xff\x05\xff\x11#\x16C\x03\x17\x01\xf0\'\r\x1c\r\x05\n\t\x8a\n\t\x02M\x03a\x02\x1b\x16\r\x1f\x17\x01\xa6\r\x8e\r\r\x8f\r\x19\r/\x17\x01\xfd\r\x90\r\x19\x16\x1ef\t3\x04\x05>\r\x00\x03/\x02;C\r[\x03[#\x02\x1b\x16\x1f\x93\r\x94\x03fC\r[\r\x93\r\x94\r\x19f\x03\x1f\x1bf\x01\xa1\xff\x11\x17\x01\xfc\x1b\x16\x1d\x05 \x17\x01\xa76\r\x1c\x01\xdb\x1b \x01Q\xff\x11\x04\x1b \x01Q\xff\x11\x00\x1b \x01\xea\xff\x11\x01\xa2\xff\x11\x01\x98\x0b\x91\x0b\x15\x91\x06\x03 \x17\x01\xac\x17\x01\xaa\x01\xff\x0f\x01\xb1\xff\x11\x02\x03O\n\t]\xff\x11\x02\x05\"\x01\x01I\n\t@\n\t.\n\t0\x01\x85\n\tt\n\t\r%\r\x03\x04\n\t5\x00\n\t\xff\x07\r \r\x04\n\t5\x00\n\t0\n\t\xbb\n\t\xe3\xff\x11\x0f\x0b\x16\x92\t\x10\x03\x05\x92\n\t\xff\x06\r%\r\x05\x1e\x03\x19\x01J\x162\x03\x17\x01\xf3\n\t\x04\x1a\x00,~\'\t\x05>\x08\x00\x99\x06\x00a\r*\x06\x00\x9d\x0bC\x0b\x01\xf1\'\rC\x0f\x0b\x04,\x7fv\t\x05\x1e\n\t)\n\t\x84\n\tX\x04\x05\x07`>\x00v\x01\xe4\x00\x00\n\t\x7f2\x017\xff\x11,\xe2\x08\x00\n\t)\n\t\xf4\n\t3\x04\n\t$\n\tV\x00\x04\x1a!\n\t$\n\t9\x00\n\t$\xff\x11\n\t1\x04\x07`\x00!!,\xff\x11\x08\x00\n\t$\n\ts\n\t\xc7\n\t$\n\ts\n\tO\n\t$\n\ts\n\t\xb8\n\t$\n\t\xba\n\t.\n\t$\n\t9;,$\xff\x11\x08\x00\n\t$\n\t\xd1\n\t\n\t$\n\tV\n\t\x8b\n\t$\n\t\xc9\n\t.\n\t$\n\t\xb6\n\t\xae\t\r\xdc\t\rF\n\t$\n\t\xd4\n\t\n\t$\n\t\xd5\n\t.\n\t$\n\tV\n\t\xb5\n\t$\n\t\xb7\n\t.\x00\x00\n\t-\x17\r\x19\n\t)\n\tk\n\t\xbc\n\t^(\n\t)\n\tk\n\t\xcd\n\t\xff\n\x06\x00\x18\r\x1d\x06\x00\x1d6\r\x19\x16D\t\x03\n\t)\n\tk\n\t\xc1\n\t\xe9\n\t)\n\tj\n\t\x87\n\tj\x05D\r%\x0c\x01\xa0\x03(\n\t)\n\tk\n\t\xce\n\t\xff\x0b\x06\x00 \r\x1d\n\t)\n\t\xd9\n\t\xb4\n\t\xc6\n\t)\n\to\n\t\xcb\n\t\xff\x08\x056,-\x04\x07\t*\r\x03\x1c\x02\x1c\t\x05\x07,\xd7\x1c\t\x1a\x16\x1a\n\t\xc4\n\t\xd6;F\xd3\t\x05\x07`>\x03\x02F\xc3\t\x05\x07`\n\t\x89\x00\n\tb\x9b\x03O\r\n\t\xcc\r\xe6\r\xdf\r\xff\x10\r\xec\x03\xf9\x03\r\xd8\x03\r\xe0\x03\n\tU\x04\x00\x00\r\n\tU\n\t\xa3\r{\x01\t{\x01\x01\xf5\x03\x05\x1e\"\x01\x1c\x01\x81\n\tb\n\t\x81\x05\x1e\x1c\x01L\rw\x16\x1ei\t3\x04\x83\x00\x03/\x02\x03\x05\"\x01i\x03i\x01\xf2\x1c\x01\xee\n\tU\n\t\xad\ry\r2\rx(\"\x01/\x06\x00\x1f\r\x1c\x06\x00\x01\xef\x16\x1c\x03\"\x01\x03(\"\x01\r/\x06\x00/\r\n\tU\n\t\xff\x03\n\t\x89;\xb0b\x03\n\tb;\xb0\xbd\x03\x02,\xa3\"\r{\t\x1a\x08\x00\x1a,\x81\x05\x1e\"\rw\t\x83,\xff\t\x08\x003\x04\x83\x00,\xad\"\ry\t\x05\x1eX\x04\x05\x07`>\x00\r2\t3\x04\n\tU\x04\xff\x11\x00\x00\rx\t\x05\x1eX\x04\n\t\xc5\x00\r\x16\x1ee\x03\n\tr\n\t.\xff\x11\x02\x03Y\x01e\x01\xa4\n\t\x8d\n\t\xb2\rY\x01\x1b\"\x01\x10\x03Y\x01\x02e\x01\xa4\n\tr\n\t\xfa\xff\x11\n\t\x8d\x04\x07,\xb2\x05\x1e\"\rY\t\x1a\n\t1\n\to\x16\x1eu\x03\n\t)\n\t\x84\n\tX\n\t^\x05\x1eu\x17\x01\xff\x00\r\'\r\x1c\r\x05u*\x06\x00\x18*\x06\x00\x1d\x17\x01_\x16*\x03\n\t\xe8\x17\r\'\r\x1c\r\x05\x1e\x17\r\x1c\x00\x00\n\t\x80\x17\r\'\x01=\r\x1c\x03\x19\x01\xff\x02(S\x17\x01\xff\x01\x1bS\x06\x03\x17\x01\xa9\x1bm\x01<\x06\x03\rm\x01=\rL\x1b4\x06\x03\r\\\x03\x19\x01}\xff\x11\x1b\x19\x01\xed\xff\x11\x10\x03\n\t\xc2\n\t\xbf\x03\x04\x9e!(\x1c\x16\x1c\t\x9e\x03(\"\n\t1\n\t^8\x017\xff\x11H\r%\x06\x00\x048\x01I\x0c\r%\rn\x01I\r8\x017\xff\x11\r%\rn\x017\xff\x11&(S\x16l\x038\x01l\xff\x11\x02l\x01Q\xff\x11\x06\x03m\rL\x03\x05l(m\x01<\x17\rL\x16\x19\x03L\x02\x03L\x01J\x16\x1e4\t3\x04\x05>\x00\x03/\x02\x16\x1e\\\x03\x19\x02\x1b\'\x01<\x06\x03\x03\x04\x05>!4\x03\x1f\\\x03\x1c\x02\xda\x02\x034#(4\x16*\x03\'\x02\x1b\'\x01<\x10\x03\x17\x01\xab\r\x1c\r\'\x01=\x16g\x032\x02\x16\x1eB\t3\x04\x05\x00\x00\x03/\x02B\x03\x1f\x03B#\x17\rB\x16\x19\x03B\x02\x03B\x01J\x16N\x03\x17\x01:\x01Q\xff\x11\x02\x16\x1ed\x03\n\t)\n\t\x84\n\tX\n\t.\xff\x11\x02\x1b\x97\x06\x03\x17\x01:\x01p&\x18\x06\x00&\x18\x16P\x03\x17\x01:\x01Q\xff\x11\x02(\x17\x01:\x01\x9cN\x13\x01P\x06\x00d\x01\xa5\rP\x16P\x03\x17\x01:\x01Q\xff\x11\x02\x16Z\t\n\t)\n\tK\n\tA\x04\x1a\r\n\t?\x00\x03\xff\x11\x02(Z\x16\x96\t,\x00\r\x05\x08\x00\x99\x03\x02\x1b\x96\x17\x01\xfe\x9a\n\t\xf6F*\t\xff\x11\x03";
+pub const IDENT_SIZE: usize = 250;
+pub static NAMES: [&str; 250] = [
+    "let",
+    "__ctx",
+    "Ok",
+    "__item",
+    "Self",
+    "if",
+    "__value",
+    "Err",
+    "mut",
+    "Some",
+    "__val",
+    "as",
+    "self",
+    "else",
+    "schema",
+    "__arena",
+    "return",
+    "__key",
+    "match",
+    "std",
+    "_",
+    "FromToml",
+    "fn",
+    "from_toml",
+    "new",
+    "None",
+    "to_toml",
+    "Item",
+    "__table",
+    "Option",
+    "__tag",
+    "ToToml",
+    "__e",
+    "into_item",
+    "table",
+    "TomlSchema",
+    "errors",
+    "for",
+    "name",
+    "span",
+    "str",
+    "Failed",
+    "Key",
+    "Result",
+    "__content",
+    "__name",
+    "__proxy",
+    "automatically_derived",
+    "const",
+    "impl",
+    "inner",
+    "insert_unique",
+    "require_table",
+    "result",
+    "value",
+    "__failed",
+    "__start",
+    "Default",
+    "__err_len",
+    "len",
+    "report_missing_field",
+    "s",
+    "Context",
+    "Layered",
+    "Schema",
     "Span",
-    "Table",
-    "TryFrom",
+    "Vec",
+    "__new",
+    "__result",
+    "__span",
+    "__tag_item",
+    "default",
+    "from",
+    "report_expected_but_found",
+    "static",
+    "true",
+    "Partial",
+    "ToTomlError",
+    "__attempts",
+    "__changes",
+    "__one_of",
+    "__subtable",
+    "__t",
+    "__top",
+    "clone",
+    "convert",
+    "entries",
+    "key",
+    "outer",
+    "string",
+    "truncate",
+    "where",
+    "ChangeSet",
+    "FieldDefault",
+    "TableStyle",
+    "__expected",
+    "__keys",
+    "__layer",
+    "__missing",
+    "__path",
+    "__seen",
+    "__upper",
     "allow",
+    "as_str",
+    "claims_key",
+    "expected_keys",
+    "from_toml_at_key",
+    "set_layer",
+    "toml_spanner",
+    "u32",
+    "vec",
+    "with_style_of_array_or_table",
+    "Arena",
+    "Clone",
+    "FromFlattened",
+    "HasPartial",
+    "KeyMatch",
+    "Struct",
+    "ToFlattened",
+    "UpdateFromToml",
+    "__a",
+    "__b",
+    "__by",
+    "__c",
+    "__default",
+    "__first",
+    "__first_span",
+    "__key_span",
+    "__pred",
+    "__s",
+    "any",
     "bool",
-    "break",
     "clippy",
+    "doc",
+    "drain",
     "false",
-    "finish",
+    "i64",
     "in",
+    "into_owned_in",
+    "is_none",
+    "iter",
+    "merge",
+    "nest",
+    "push",
+    "report_conflicting_fields",
+    "report_custom_error",
+    "report_deprecated_field",
+    "report_deprecated_variant",
+    "report_out_of_range",
+    "report_unexpected_key",
+    "report_unexpected_value",
+    "resolve_in",
+    "tag",
+    "toml",
+    "type",
+    "unwrap_or_default",
+    "update",
+    "Adjacent",
+    "Cow",
+    "Enum",
+    "EnumRepr",
+    "EnumSchema",
+    "Expr",
+    "External",
+    "FieldSchema",
+    "Formatting",
+    "From",
+    "Full",
+    "Int",
+    "Integer",
+    "Internal",
+    "Into",
+    "Kind",
+    "MIGRATIONS",
+    "Migration",
+    "MissingField",
+    "Owned",
+    "Required",
+    "StrOrInt",
+    "StructSchema",
+    "Table",
+    "ToString",
+    "Toml",
+    "TryFrom",
+    "TryInto",
+    "Tuple",
+    "Unit",
+    "UnknownFields",
+    "Untagged",
+    "VERSION_KEY",
+    "VariantKind",
+    "VariantSchema",
+    "Versioned",
+    "__toml_remote_mirror",
+    "alias",
+    "borrow",
+    "break",
+    "contains",
+    "content",
+    "continue",
+    "dead_code",
+    "deny_unknown_fields",
+    "deprecated_alias",
+    "derive",
+    "doc_comments",
+    "expands_defaults",
+    "extend_from_slice",
+    "finish",
+    "ignore_unknown_fields",
     "init",
     "insert",
-    "is_none",
+    "into",
+    "is_empty",
     "is_some",
+    "key_match",
+    "kind",
+    "layer",
+    "map",
+    "match_key",
+    "matches",
+    "max",
+    "migrated_table",
+    "option",
+    "or",
     "question_mark",
+    "redundant_closure_call",
     "ref",
-    "report_custom_error",
-    "report_deprecated_field",
+    "rename",
+    "replaced",
     "report_duplicate_field",
+    "report_missing_one_of",
+    "report_missing_required_by",
+    "report_no_matching_variant",
+    "report_unexpected_key_among",
     "report_unexpected_variant",
     "require_string",
+    "required",
+    "struct",
     "take",
     "to_flattened",
     "to_optional_toml",
+    "to_string",
+    "top_layer",
     "try_from",
+    "try_into",
     "try_with_capacity",
     "u64",
     "unwrap",
     "unwrap_or_else",
-    "Context",
-    "Default",
-    "FromFlattened",
-    "ToFlattened",
-    "ToTomlError",
-    "__pred",
-    "__result",
-    "__s",
-    "as_str",
-    "automatically_derived",
-    "const",
-    "convert",
-    "default",
-    "from",
-    "impl",
-    "key",
-    "unwrap_or_default",
-    "where",
-    "Option",
-    "Result",
-    "TableStyle",
-    "__flatten_partial",
-    "__seen",
-    "fn",
-    "for",
-    "len",
-    "result",
-    "string",
-    "toml_spanner",
-    "true",
-    "truncate",
-    "with_style_of_array_or_table",
-    "Failed",
-    "__proxy",
-    "__subtable",
-    "__t",
-    "entries",
-    "outer",
-    "report_unexpected_key",
-    "s",
-    "str",
-    "None",
-    "__err_len",
-    "__table",
-    "errors",
-    "report_expected_but_found",
-    "std",
-    "value",
-    "__failed",
-    "report_missing_field",
-    "Item",
-    "name",
-    "require_table",
-    "span",
-    "Key",
-    "ToToml",
-    "__content",
-    "inner",
-    "insert_unique",
-    "into_item",
-    "new",
-    "self",
-    "__e",
-    "__tag",
-    "table",
-    "to_toml",
-    "FromToml",
-    "__value",
-    "Some",
-    "match",
-    "mut",
-    "from_toml",
-    "__key",
-    "as",
-    "if",
-    "_",
-    "__arena",
-    "return",
-    "else",
-    "Self",
-    "__val",
-    "__item",
-    "Err",
-    "__ctx",
-    "Ok",
-    "let",
+    "warn_unknown_fields",
 ];
-pub const PUNCT_SIZE: usize = 18;
+pub const PUNCT_SIZE: usize = 22;
 pub fn punct_cache_initial_state() -> [Punct; PUNCT_SIZE] {
     [
         Punct::new('>', Spacing::Alone),
         Punct::new('.', Spacing::Alone),
         Punct::new(';', Spacing::Alone),
         Punct::new('=', Spacing::Alone),
-        Punct::new('=', Spacing::Joint),
         Punct::new('<', Spacing::Alone),
         Punct::new('&', Spacing::Alone),
+        Punct::new('=', Spacing::Joint),
         Punct::new('\'', Spacing::Joint),
-        Punct::new('?', Spacing::Alone),
+        Punct::new('-', Spacing::Joint),
         Punct::new(':', Spacing::Alone),
         Punct::new(':', Spacing::Joint),
         Punct::new('|', Spacing::Alone),
-        Punct::new('|', Spacing::Joint),
+        Punct::new('?', Spacing::Alone),
         Punct::new(',', Spacing::Alone),
-        Punct::new('-', Spacing::Joint),
         Punct::new('#', Spacing::Joint),
-        Punct::new('!', Spacing::Alone),
+        Punct::new('|', Spacing::Joint),
         Punct::new('!', Spacing::Joint),
+        Punct::new('#', Spacing::Alone),
+        Punct::new('!', Spacing::Alone),
+        Punct::new('.', Spacing::Joint),
+        Punct::new('-', Spacing::Alone),
+        Punct::new('*', Spacing::Alone),
     ]
 }
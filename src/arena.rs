@@ -274,6 +274,27 @@ impl Arena {
         }
     }
 
+    /// Returns `true` if `ptr` points into memory allocated by this arena.
+    pub(crate) fn contains(&self, ptr: *const u8) -> bool {
+        let addr = ptr as usize;
+        let mut current = self.slab.get();
+        loop {
+            // Safety: current is either a heap slab or the static sentinel.
+            let header = unsafe { current.as_ref() };
+            if header.size == 0 {
+                return false;
+            }
+            let base = current.as_ptr() as usize;
+            if addr >= base + HEADER_SIZE && addr < base + header.size {
+                return true;
+            }
+            match header.prev {
+                Some(prev) => current = prev,
+                None => return false,
+            }
+        }
+    }
+
     /// Allocates a copy of `s` in the arena and returns a reference to it.
     pub fn alloc_str(&self, s: &str) -> &str {
         if s.is_empty() {
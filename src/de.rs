@@ -8,15 +8,15 @@ use std::hash::Hash;
 use std::num::NonZeroU64;
 use std::path::PathBuf;
 
-use foldhash::HashMap;
+use foldhash::{HashMap, HashSet};
 
 use std::fmt::{self, Debug, Display};
 
 use crate::Value;
-use crate::error::ErrorInner;
+use crate::error::{ErrorInner, NoMatchingVariant};
 use crate::{
     Arena, Key, Span, Table,
-    error::{Error, ErrorKind, MaybeTomlPath, PathComponent},
+    error::{Error, ErrorKind, MaybeTomlPath, PathComponent, closest_match},
     item::{self, Item},
     parser::{INDEXED_TABLE_THRESHOLD, KeyRef},
 };
@@ -26,15 +26,16 @@ use crate::{
 /// After `FromToml` runs, errors contain raw item pointers (via `TomlPath::uncomputed`).
 /// This function walks the tree to find which table entry or array element each
 /// pointer belongs to, then replaces the uncomputed path with a real one.
-pub(crate) fn compute_paths(root: &Table<'_>, errors: &mut [Error]) {
+/// The `migrated` copies made by [`Context::migrate`] are walked in place of
+/// the tables they replace.
+pub(crate) fn compute_paths(
+    root: &Table<'_>,
+    migrated: &[(usize, &Table<'_>)],
+    errors: &mut [Error],
+) {
     let mut pending: Vec<(*const u8, Option<&mut MaybeTomlPath>)> = Vec::new();
     for error in errors.iter_mut() {
-        if error.path.is_uncomputed() {
-            pending.push((
-                error.path.uncomputed_ptr() as *const u8,
-                Some(&mut error.path),
-            ));
-        }
+        push_pending_paths(error, &mut pending);
     }
     if pending.is_empty() {
         return;
@@ -42,7 +43,24 @@ pub(crate) fn compute_paths(root: &Table<'_>, errors: &mut [Error]) {
     pending.sort_unstable_by_key(|(addr, _)| *addr as usize);
 
     let mut path_stack: [PathComponent<'_>; 32] = [PathComponent::Index(0); 32];
-    compute_paths_walk(root.as_item(), &mut pending, &mut path_stack, 0);
+    compute_paths_walk(root.as_item(), migrated, &mut pending, &mut path_stack, 0);
+}
+
+/// Queues the uncomputed path of `error` and of the errors nested in its
+/// notes.
+fn push_pending_paths<'a>(
+    error: &'a mut Error,
+    pending: &mut Vec<(*const u8, Option<&'a mut MaybeTomlPath>)>,
+) {
+    let Error { kind, path, .. } = error;
+    if path.is_uncomputed() {
+        pending.push((path.uncomputed_ptr() as *const u8, Some(path)));
+    }
+    if let ErrorInner::NoMatchingVariant(inner) = kind {
+        for (_, note) in &mut inner.notes {
+            push_pending_paths(note, pending);
+        }
+    }
 }
 
 fn pending_region_start(
@@ -54,6 +72,7 @@ fn pending_region_start(
 
 fn compute_paths_walk<'de>(
     item: &Item<'de>,
+    migrated: &[(usize, &Table<'de>)],
     pending: &mut [(*const u8, Option<&mut MaybeTomlPath>)],
     path_stack: &mut [PathComponent<'de>; 32],
     path_depth: usize,
@@ -61,6 +80,23 @@ fn compute_paths_walk<'de>(
     if path_depth >= path_stack.len() {
         return;
     }
+    for &(original, copy) in migrated {
+        if original != item as *const Item<'de> as usize {
+            continue;
+        }
+        // Errors reported at the copy itself take the path of `item`.
+        let copy_addr = copy as *const Table<'de> as usize;
+        let start_idx = pending_region_start(pending, copy_addr);
+        for slot in &mut pending[start_idx..] {
+            if slot.0 as usize != copy_addr {
+                break;
+            }
+            if let Some(path) = slot.1.take() {
+                *path = MaybeTomlPath::from_components(&path_stack[..path_depth]);
+            }
+        }
+        compute_paths_walk(copy.as_item(), migrated, pending, path_stack, path_depth);
+    }
     match item.value() {
         Value::Table(table) => {
             let entries = table.entries();
@@ -89,7 +125,7 @@ fn compute_paths_walk<'de>(
 
             for (key, child) in table {
                 path_stack[path_depth] = PathComponent::Key(*key);
-                compute_paths_walk(child, pending, path_stack, path_depth + 1);
+                compute_paths_walk(child, migrated, pending, path_stack, path_depth + 1);
             }
         }
         Value::Array(array) => {
@@ -120,7 +156,7 @@ fn compute_paths_walk<'de>(
             let mut idx = 0; // For some reason more efficient then iter() enumerate()
             for child in array {
                 path_stack[path_depth] = PathComponent::Index(idx);
-                compute_paths_walk(child, pending, path_stack, path_depth + 1);
+                compute_paths_walk(child, migrated, pending, path_stack, path_depth + 1);
                 idx += 1;
             }
         }
@@ -170,6 +206,8 @@ pub struct TableHelper<'ctx, 'table, 'de> {
     // Used for detecting unused fields or iterating over remaining for flatten into collection.
     used_count: u32,
     used: &'de mut FixedBitset,
+    // Names passed to lookups, suggested for unexpected keys.
+    looked_up: Vec<&'de str>,
 }
 
 #[repr(transparent)]
@@ -257,7 +295,13 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
     /// Prefer [`Item::table_helper`] inside [`FromToml`] implementations, or
     /// [`Document::table_helper`](crate::Document::table_helper) for the root table.
     pub fn new(ctx: &'ctx mut Context<'de>, table: &'t Table<'de>) -> Self {
-        let table_id = if table.len() > INDEXED_TABLE_THRESHOLD && table.meta.is_span_mode() {
+        // Tables upgraded by `Context::migrate` keep the key spans of the
+        // parsed tables they were copied from, which the index would resolve
+        // to the original entries.
+        let table_id = if table.len() > INDEXED_TABLE_THRESHOLD
+            && table.meta.is_span_mode()
+            && !ctx.unindexed.contains(&(table.entries().as_ptr() as usize))
+        {
             table.entries()[0].0.span.start as i32
         } else {
             -1
@@ -268,6 +312,7 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
             table,
             table_id,
             used_count: 0,
+            looked_up: Vec::new(),
         }
     }
 
@@ -308,7 +353,7 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
         name: &'static str,
         func: fn(&Item<'de>) -> Result<T, Error>,
     ) -> Result<T, Failed> {
-        let Some((_, item)) = self.optional_entry(name) else {
+        let Some((_, item)) = self.lookup(name) else {
             return Err(self.report_missing_field(name));
         };
 
@@ -329,7 +374,7 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
         name: &'static str,
         func: fn(&Item<'de>) -> Result<T, Error>,
     ) -> Option<T> {
-        let Some((_, item)) = self.optional_entry(name) else {
+        let Some((_, item)) = self.lookup(name) else {
             return None;
         };
 
@@ -366,7 +411,7 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
     /// access to the parsed value. Returns [`None`] when the key is missing
     /// (no error recorded). The field is marked as consumed.
     pub fn optional_item(&mut self, name: &'static str) -> Option<&'t Item<'de>> {
-        if let Some((_, item)) = self.optional_entry(name) {
+        if let Some((_, item)) = self.lookup(name) {
             Some(item)
         } else {
             None
@@ -387,7 +432,7 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
         &mut self,
         name: &'static str,
     ) -> Result<&'t (Key<'de>, Item<'de>), Failed> {
-        match self.optional_entry(name) {
+        match self.lookup(name) {
             Some(entry) => Ok(entry),
             None => Err(self.report_missing_field(name)),
         }
@@ -399,6 +444,17 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
     /// when the key's [`Span`](crate::Span) is needed in addition to the
     /// value. The field is marked as consumed.
     pub fn optional_entry(&mut self, key: &str) -> Option<&'t (Key<'de>, Item<'de>)> {
+        let name = self.ctx.arena.alloc_str(key);
+        self.looked_up.push(name);
+        self.take_entry(key)
+    }
+
+    fn lookup(&mut self, name: &'static str) -> Option<&'t (Key<'de>, Item<'de>)> {
+        self.looked_up.push(name);
+        self.take_entry(name)
+    }
+
+    fn take_entry(&mut self, key: &str) -> Option<&'t (Key<'de>, Item<'de>)> {
         let Some(entry) = self.get_entry(key) else {
             return None;
         };
@@ -439,11 +495,11 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
     /// Returns [`Failed`] if the key is absent or if conversion fails.
     /// In both cases the error is pushed onto the shared [`Context`].
     pub fn required<T: FromToml<'de>>(&mut self, name: &'static str) -> Result<T, Failed> {
-        let Some((_, val)) = self.optional_entry(name) else {
+        let Some((key, val)) = self.lookup(name) else {
             return Err(self.report_missing_field(name));
         };
 
-        T::from_toml(self.ctx, val)
+        T::from_toml_at_key(self.ctx, key.span, val)
     }
 
     /// Extracts and converts an optional field via [`FromToml`], returning
@@ -453,12 +509,12 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
     /// The field is marked as consumed so [`require_empty`](Self::require_empty)
     /// will not flag it as unexpected.
     pub fn optional<T: FromToml<'de>>(&mut self, name: &str) -> Option<T> {
-        let Some((_, val)) = self.optional_entry(name) else {
+        let Some((key, val)) = self.optional_entry(name) else {
             return None;
         };
 
         #[allow(clippy::manual_ok_err)]
-        match T::from_toml(self.ctx, val) {
+        match T::from_toml_at_key(self.ctx, key.span, val) {
             Ok(value) => Some(value),
             Err(_) => None,
         }
@@ -489,7 +545,8 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
     /// [`optional`](Self::optional).
     ///
     /// Call as the last step in a [`FromToml`] implementation to reject
-    /// unknown keys.
+    /// unknown keys. An unknown key close to a name looked up through this
+    /// helper gets that name as its [`suggestion`](Error::suggestion).
     ///
     /// # Errors
     ///
@@ -505,12 +562,8 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
         let mut had_unexpected = false;
         for (i, (key, item)) in self.table.entries().iter().enumerate() {
             if !self.used.get(i) {
-                self.ctx.errors.push(Error {
-                    kind: ErrorInner::Static(ErrorKind::UnexpectedKey { tag: 0 }),
-                    span: key.span,
-                    path: MaybeTomlPath::uncomputed(item),
-                });
-
+                self.ctx
+                    .report_unexpected_key_near(0, key, item, self.looked_up.iter().copied());
                 had_unexpected = true;
             }
         }
@@ -530,6 +583,12 @@ impl<'ctx, 't, 'de> TableHelper<'ctx, 't, 'de> {
 pub struct Context<'de> {
     pub arena: &'de Arena,
     pub(crate) index: HashMap<KeyRef<'de>, usize>,
+    /// Entry addresses of the tables upgraded by [`Context::migrate`], which
+    /// the index doesn't describe.
+    pub(crate) unindexed: HashSet<usize>,
+    /// Upgraded copies made by [`Context::migrate`], each with the address of
+    /// the table it replaces.
+    pub(crate) migrated: Vec<(usize, &'de Table<'de>)>,
     pub errors: Vec<Error>,
     pub(crate) source: &'de str,
 }
@@ -565,10 +624,70 @@ impl<'de> Context<'de> {
         &mut self,
         expected: &'static [&'static str],
         found: &Item<'de>,
+    ) -> Failed {
+        let kind = match found
+            .as_str()
+            .and_then(|name| closest_match(name, expected.iter().copied()))
+        {
+            Some(suggestion) => ErrorInner::UnexpectedVariant {
+                expected,
+                suggestion,
+            },
+            None => ErrorInner::Static(ErrorKind::UnexpectedVariant { expected }),
+        };
+        self.errors.push(Error {
+            kind,
+            span: found.span(),
+            path: MaybeTomlPath::uncomputed(found),
+        });
+        Failed
+    }
+
+    /// Records that `found` matched none of the variants of an untagged enum
+    /// and returns [`Failed`].
+    ///
+    /// `expected` describes the shape accepted by each variant. `attempts`
+    /// pairs the description of each variant that was tried with the number
+    /// of errors recorded when its attempt began; the errors recorded since
+    /// the first attempt are moved into the [`Error::notes`], each under the
+    /// variant it was rejected with.
+    #[cold]
+    pub fn report_no_matching_variant(
+        &mut self,
+        expected: &'static [&'static str],
+        attempts: &[(&'static str, usize)],
+        found: &Item<'de>,
+    ) -> Failed {
+        let start = attempts.first().map_or(self.errors.len(), |(_, at)| *at);
+        let mut notes = Vec::with_capacity(self.errors.len() - start);
+        let mut attempt = 0;
+        for (i, error) in self.errors.drain(start..).enumerate() {
+            while attempts
+                .get(attempt + 1)
+                .is_some_and(|(_, at)| *at <= start + i)
+            {
+                attempt += 1;
+            }
+            notes.push((attempts[attempt].0, error));
+        }
+        self.errors.push(Error {
+            kind: ErrorInner::NoMatchingVariant(Box::new(NoMatchingVariant { expected, notes })),
+            span: found.span(),
+            path: MaybeTomlPath::uncomputed(found),
+        });
+        Failed
+    }
+
+    /// Records an "unexpected value" error listing the accepted values and returns [`Failed`].
+    #[cold]
+    pub fn report_unexpected_value(
+        &mut self,
+        expected: &'static [&'static str],
+        found: &Item<'de>,
     ) -> Failed {
         let path = MaybeTomlPath::uncomputed(found);
         self.errors.push(Error::new_with_path(
-            ErrorKind::UnexpectedVariant { expected },
+            ErrorKind::UnexpectedValue { expected },
             found.span(),
             path,
         ));
@@ -659,6 +778,74 @@ impl<'de> Context<'de> {
         ))
     }
 
+    /// Records an error for two keys that cannot be used together and returns [`Failed`].
+    ///
+    /// Used by generated `FromToml` implementations for `conflicts_with` and
+    /// `exactly_one_of`. The error is reported at whichever key appears later
+    /// in the source, with the other key as the secondary label. `table` is
+    /// the table containing both keys.
+    #[cold]
+    pub fn report_conflicting_fields(
+        &mut self,
+        field: &'static str,
+        key_span: Span,
+        other: &'static str,
+        other_key_span: Span,
+        table: &Item<'de>,
+    ) -> Failed {
+        let (field, other, at, first) = if key_span.start < other_key_span.start {
+            (other, field, other_key_span, key_span)
+        } else {
+            (field, other, key_span, other_key_span)
+        };
+        self.push_error(Error::new_with_path(
+            ErrorKind::ConflictingFields {
+                field,
+                other,
+                first,
+            },
+            at,
+            MaybeTomlPath::uncomputed(table),
+        ))
+    }
+
+    /// Records an error for a key defined without another key it requires and
+    /// returns [`Failed`].
+    ///
+    /// Used by generated `FromToml` implementations for `requires`. The error
+    /// is reported at `key_span`, the key of `by`. `table` is the table that
+    /// is missing `field`.
+    #[cold]
+    pub fn report_missing_required_by(
+        &mut self,
+        field: &'static str,
+        by: &'static str,
+        key_span: Span,
+        table: &Item<'de>,
+    ) -> Failed {
+        self.push_error(Error::new_with_path(
+            ErrorKind::MissingRequiredBy { field, by },
+            key_span,
+            MaybeTomlPath::uncomputed(table),
+        ))
+    }
+
+    /// Records an error for a table defining none of `fields` and returns [`Failed`].
+    ///
+    /// Used by generated `FromToml` implementations for `exactly_one_of`.
+    #[cold]
+    pub fn report_missing_one_of(
+        &mut self,
+        fields: &'static [&'static str],
+        table: &Item<'de>,
+    ) -> Failed {
+        self.push_error(Error::new_with_path(
+            ErrorKind::MissingOneOf { fields },
+            table.span(),
+            MaybeTomlPath::uncomputed(table),
+        ))
+    }
+
     /// Records a deprecated-field warning with TOML path information.
     ///
     /// Unlike other `report_*` methods this is **non-fatal**: it pushes
@@ -679,6 +866,55 @@ impl<'de> Context<'de> {
         ));
     }
 
+    /// Records an [`ErrorKind::DeprecatedVariant`] warning at the span of
+    /// `item`, the value naming the variant.
+    ///
+    /// Like [`report_deprecated_field`](Self::report_deprecated_field) this
+    /// is non-fatal.
+    #[cold]
+    pub fn report_deprecated_variant(
+        &mut self,
+        tag: u32,
+        old: &'static &'static str,
+        new: &'static &'static str,
+        item: &Item<'de>,
+    ) {
+        self.errors.push(Error::new_with_path(
+            ErrorKind::DeprecatedVariant { tag, old, new },
+            item.span(),
+            MaybeTomlPath::uncomputed(item),
+        ));
+    }
+
+    /// Resolves a table key to one of the field `names` under `rule`.
+    ///
+    /// Used by generated `FromToml` implementations with
+    /// `#[toml(key_match = "...")]`. Returns the matching entry of `names`,
+    /// recording a non-fatal [`ErrorKind::NonCanonicalKey`] warning when the
+    /// key is spelled differently, or the key unchanged if nothing matches.
+    pub fn match_key(
+        &mut self,
+        key: &Key<'de>,
+        item: &Item<'de>,
+        names: &'static [&'static str],
+        rule: KeyMatch,
+    ) -> &'de str {
+        if names.contains(&key.name) {
+            return key.name;
+        }
+        for name in names {
+            if rule.matches(key.name, name) {
+                self.errors.push(Error::new_with_path(
+                    ErrorKind::NonCanonicalKey { canonical: name },
+                    key.span,
+                    MaybeTomlPath::uncomputed(item),
+                ));
+                return name;
+            }
+        }
+        key.name
+    }
+
     /// Records an unexpected-key error with TOML path information.
     #[cold]
     pub fn report_unexpected_key(&mut self, tag: u32, item: &Item<'de>, key_span: Span) -> Failed {
@@ -690,10 +926,105 @@ impl<'de> Context<'de> {
         ));
         Failed
     }
+
+    /// Records an unexpected-key error, suggesting the entry of `expected`
+    /// closest to the key when one is similar enough.
+    ///
+    /// Used by generated `FromToml` implementations, which pass the keys of
+    /// their fields, including those of flattened structs.
+    #[cold]
+    pub fn report_unexpected_key_among(
+        &mut self,
+        tag: u32,
+        key: &Key<'de>,
+        item: &Item<'de>,
+        expected: &[&str],
+    ) -> Failed {
+        self.report_unexpected_key_near(tag, key, item, expected.iter().copied())
+    }
+
+    #[cold]
+    fn report_unexpected_key_near<'a>(
+        &mut self,
+        tag: u32,
+        key: &Key<'de>,
+        item: &Item<'de>,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Failed {
+        let kind = match closest_match(key.name, candidates) {
+            Some(suggestion) => ErrorInner::UnexpectedKey {
+                tag,
+                suggestion: suggestion.into(),
+            },
+            None => ErrorInner::Static(ErrorKind::UnexpectedKey { tag }),
+        };
+        self.errors.push(Error {
+            kind,
+            span: key.span,
+            path: MaybeTomlPath::uncomputed(item),
+        });
+        Failed
+    }
 }
 
 pub use crate::Failed;
 
+/// How derived `FromToml` implementations match table keys to field names.
+///
+/// Selected with `#[toml(key_match = "...")]` on the container. Keys that
+/// only match after normalization are accepted and reported as
+/// [`ErrorKind::NonCanonicalKey`] warnings naming the expected spelling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyMatch {
+    /// Keys must equal a field name exactly. The default.
+    Exact,
+    /// ASCII case is ignored, so `Name` matches `name`.
+    IgnoreCase,
+    /// `-` and `_` are ignored, so `default_features` matches
+    /// `default-features`.
+    IgnoreSeparators,
+    /// Both case and separators are ignored, so `defaultFeatures` matches
+    /// `default-features`.
+    IgnoreCaseAndSeparators,
+}
+
+impl KeyMatch {
+    /// Returns `true` if `key` is an accepted spelling of `name`.
+    ///
+    /// ```
+    /// use toml_spanner::KeyMatch;
+    ///
+    /// assert!(KeyMatch::IgnoreCaseAndSeparators.matches("defaultFeatures", "default-features"));
+    /// assert!(!KeyMatch::IgnoreCase.matches("default_features", "default-features"));
+    /// ```
+    pub fn matches(self, key: &str, name: &str) -> bool {
+        let (ignore_case, ignore_separators) = match self {
+            KeyMatch::Exact => return key == name,
+            KeyMatch::IgnoreCase => (true, false),
+            KeyMatch::IgnoreSeparators => (false, true),
+            KeyMatch::IgnoreCaseAndSeparators => (true, true),
+        };
+        let normalized = |text| normalize_key(text, ignore_case, ignore_separators);
+        normalized(key).eq(normalized(name))
+    }
+}
+
+fn normalize_key(
+    text: &str,
+    ignore_case: bool,
+    ignore_separators: bool,
+) -> impl Iterator<Item = u8> + '_ {
+    text.bytes()
+        .filter(move |b| !(ignore_separators && matches!(b, b'-' | b'_')))
+        .map(move |b| {
+            if ignore_case {
+                b.to_ascii_lowercase()
+            } else {
+                b
+            }
+        })
+}
+
 /// Converts a TOML [`Item`] into a Rust type.
 ///
 /// `#[derive(Toml)]` generates `FromToml` by default, or add
@@ -784,12 +1115,54 @@ pub use crate::Failed;
 /// returns both the value and the accumulated errors, letting the caller
 /// decide which are acceptable.
 ///
-/// For untagged enums, the derive snapshots the error count before
-/// attempting each variant and truncates on failure, so only errors from
-/// the matching (or final) variant are reported.
+/// For untagged enums, the derive attempts each variant in turn, removing the
+/// errors of those that fail once one matches. If no variant matches, they are
+/// reported together as the
+/// [`notes`](crate::Error::notes) of an
+/// [`ErrorKind::NoMatchingVariant`](crate::ErrorKind::NoMatchingVariant).
 pub trait FromToml<'de>: Sized {
     /// Attempts to construct `Self` from a TOML [`Item`].
     fn from_toml(ctx: &mut Context<'de>, item: &Item<'de>) -> Result<Self, Failed>;
+
+    /// Like [`from_toml`](Self::from_toml), for an `item` found under the
+    /// table key spanning `key_span`.
+    ///
+    /// Table lookups through [`TableHelper`], map implementations, and
+    /// derived implementations call this. `Option`, `Box`, and the sequence
+    /// implementations pass the span on, so elements of an array see the key
+    /// of the array. The default ignores the key; the derive overrides it for
+    /// types with a `#[toml(key_span)]` field.
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        item: &Item<'de>,
+    ) -> Result<Self, Failed> {
+        let _ = key_span;
+        Self::from_toml(ctx, item)
+    }
+
+    /// Returns `true` if a table entry named `key` is read by this type.
+    ///
+    /// Used to split a table between several `#[toml(flatten)]` fields.
+    /// The default claims every key; the derive overrides it for structs
+    /// with named fields, and `Option`, `Box`, [`Spanned`](crate::Spanned)
+    /// and [`Layered`](crate::Layered) ask the type they wrap.
+    fn claims_key(key: &str) -> bool {
+        let _ = key;
+        true
+    }
+
+    /// Pushes the keys read by this type onto `keys`, to suggest one for a
+    /// misspelled key.
+    ///
+    /// Used to suggest the keys of `#[toml(flatten)]` fields next to those of
+    /// the struct holding them. The default pushes nothing; the derive
+    /// overrides it along with [`claims_key`](Self::claims_key), and
+    /// `Option`, `Box`, [`Spanned`](crate::Spanned) and
+    /// [`Layered`](crate::Layered) ask the type they wrap.
+    fn expected_keys(keys: &mut Vec<&'static str>) {
+        let _ = keys;
+    }
 }
 
 /// Trait for types that can be constructed from flattened TOML table entries.
@@ -852,7 +1225,7 @@ where
         partial: &mut Self::Partial,
     ) -> Result<(), Failed> {
         let k = key_from_toml(ctx, key)?;
-        let v = match V::from_toml(ctx, item) {
+        let v = match V::from_toml_at_key(ctx, key.span, item) {
             Ok(v) => v,
             Err(_) => return Err(Failed),
         };
@@ -886,7 +1259,7 @@ where
                     continue;
                 }
             };
-            match V::from_toml(ctx, item) {
+            match V::from_toml_at_key(ctx, key.span, item) {
                 Ok(v) => {
                     map.insert(k, v);
                 }
@@ -913,7 +1286,7 @@ where
         partial: &mut Self::Partial,
     ) -> Result<(), Failed> {
         let k = key_from_toml(ctx, key)?;
-        let v = match V::from_toml(ctx, item) {
+        let v = match V::from_toml_at_key(ctx, key.span, item) {
             Ok(v) => v,
             Err(_) => return Err(Failed),
         };
@@ -978,7 +1351,15 @@ impl<'de> FromFlattened<'de> for Item<'de> {
 
 impl<'de, T: FromToml<'de>, const N: usize> FromToml<'de> for [T; N] {
     fn from_toml(ctx: &mut Context<'de>, value: &Item<'de>) -> Result<Self, Failed> {
-        let boxed_slice = Box::<[T]>::from_toml(ctx, value)?;
+        Self::from_toml_at_key(ctx, Span::default(), value)
+    }
+
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        value: &Item<'de>,
+    ) -> Result<Self, Failed> {
+        let boxed_slice = Box::<[T]>::from_toml_at_key(ctx, key_span, value)?;
         match <Box<[T; N]>>::try_from(boxed_slice) {
             Ok(array) => Ok(*array),
             Err(res) => Err(ctx.push_error(Error::custom(
@@ -997,6 +1378,14 @@ macro_rules! impl_from_toml_tuple {
     ($len:expr, $($idx:tt => $T:ident, $var:ident),+) => {
         impl<'de, $($T: FromToml<'de>),+> FromToml<'de> for ($($T,)+) {
             fn from_toml(ctx: &mut Context<'de>, value: &Item<'de>) -> Result<Self, Failed> {
+                Self::from_toml_at_key(ctx, Span::default(), value)
+            }
+
+            fn from_toml_at_key(
+                ctx: &mut Context<'de>,
+                key_span: Span,
+                value: &Item<'de>,
+            ) -> Result<Self, Failed> {
                 let arr = value.require_array(ctx)?;
                 if arr.len() != $len {
                     return Err(ctx.push_error(Error::custom(
@@ -1011,7 +1400,7 @@ macro_rules! impl_from_toml_tuple {
                 let slice = arr.as_slice();
                 let mut had_error = false;
                 $(
-                    let $var = match $T::from_toml(ctx, &slice[$idx]) {
+                    let $var = match $T::from_toml_at_key(ctx, key_span, &slice[$idx]) {
                         Ok(v) => Some(v),
                         Err(_) => { had_error = true; None }
                     };
@@ -1051,19 +1440,52 @@ impl<'de, T: FromToml<'de>> FromToml<'de> for Option<T> {
     fn from_toml(ctx: &mut Context<'de>, value: &Item<'de>) -> Result<Self, Failed> {
         T::from_toml(ctx, value).map(Some)
     }
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        value: &Item<'de>,
+    ) -> Result<Self, Failed> {
+        T::from_toml_at_key(ctx, key_span, value).map(Some)
+    }
+    fn claims_key(key: &str) -> bool {
+        T::claims_key(key)
+    }
+    fn expected_keys(keys: &mut Vec<&'static str>) {
+        T::expected_keys(keys)
+    }
 }
 
 impl<'de, T: FromToml<'de>> FromToml<'de> for Box<T> {
     fn from_toml(ctx: &mut Context<'de>, value: &Item<'de>) -> Result<Self, Failed> {
-        match T::from_toml(ctx, value) {
+        Self::from_toml_at_key(ctx, Span::default(), value)
+    }
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        value: &Item<'de>,
+    ) -> Result<Self, Failed> {
+        match T::from_toml_at_key(ctx, key_span, value) {
             Ok(v) => Ok(Box::new(v)),
             Err(e) => Err(e),
         }
     }
+    fn claims_key(key: &str) -> bool {
+        T::claims_key(key)
+    }
+    fn expected_keys(keys: &mut Vec<&'static str>) {
+        T::expected_keys(keys)
+    }
 }
 impl<'de, T: FromToml<'de>> FromToml<'de> for Box<[T]> {
     fn from_toml(ctx: &mut Context<'de>, value: &Item<'de>) -> Result<Self, Failed> {
-        match Vec::<T>::from_toml(ctx, value) {
+        Self::from_toml_at_key(ctx, Span::default(), value)
+    }
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        value: &Item<'de>,
+    ) -> Result<Self, Failed> {
+        match Vec::<T>::from_toml_at_key(ctx, key_span, value) {
             Ok(vec) => Ok(vec.into_boxed_slice()),
             Err(e) => Err(e),
         }
@@ -1261,11 +1683,19 @@ where
     T: FromToml<'de>,
 {
     fn from_toml(ctx: &mut Context<'de>, value: &Item<'de>) -> Result<Self, Failed> {
+        Self::from_toml_at_key(ctx, Span::default(), value)
+    }
+
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        value: &Item<'de>,
+    ) -> Result<Self, Failed> {
         let arr = value.require_array(ctx)?;
         let mut result = Vec::with_capacity(arr.len());
         let mut had_error = false;
         for item in arr {
-            match T::from_toml(ctx, item) {
+            match T::from_toml_at_key(ctx, key_span, item) {
                 Ok(v) => result.push(v),
                 Err(_) => had_error = true,
             }
@@ -1279,11 +1709,19 @@ where
     T: Ord + FromToml<'de>,
 {
     fn from_toml(ctx: &mut Context<'de>, value: &Item<'de>) -> Result<Self, Failed> {
+        Self::from_toml_at_key(ctx, Span::default(), value)
+    }
+
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        value: &Item<'de>,
+    ) -> Result<Self, Failed> {
         let arr = value.require_array(ctx)?;
         let mut result = BTreeSet::new();
         let mut had_error = false;
         for item in arr {
-            match T::from_toml(ctx, item) {
+            match T::from_toml_at_key(ctx, key_span, item) {
                 Ok(v) => {
                     result.insert(v);
                 }
@@ -1311,7 +1749,7 @@ where
                     continue;
                 }
             };
-            match V::from_toml(ctx, item) {
+            match V::from_toml_at_key(ctx, key.span, item) {
                 Ok(v) => {
                     map.insert(k, v);
                 }
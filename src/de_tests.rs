@@ -389,6 +389,8 @@ fn require_custom_string_and_context_errors() {
     let mut ctx = super::Context {
         arena: &arena,
         index: Default::default(),
+        unindexed: Default::default(),
+        migrated: Vec::new(),
         errors: Vec::new(),
         source: "",
     };
@@ -1133,7 +1135,7 @@ unknown_field = true
         let _: String = th.required("host").unwrap();
         let _: i64 = th.required("port").unwrap();
         let r = th.require_empty();
-        super::compute_paths(table, &mut ctx.errors);
+        super::compute_paths(table, &ctx.migrated, &mut ctx.errors);
         r
     };
     assert!(result.is_err());
@@ -1164,7 +1166,7 @@ bogus = 1
         let mut th = super::TableHelper::new(ctx, elem_table);
         let _: String = th.required("name").unwrap();
         let r = th.require_empty();
-        super::compute_paths(table, &mut ctx.errors);
+        super::compute_paths(table, &ctx.migrated, &mut ctx.errors);
         r
     };
     assert!(result.is_err());
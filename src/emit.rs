@@ -1,21 +1,27 @@
 mod normalization;
 mod partition;
 mod reprojection;
+mod template;
 #[cfg(test)]
 pub(crate) mod test_data;
 #[cfg(test)]
 #[path = "emit_tests.rs"]
 mod tests;
 
+pub use normalization::AutoStyle;
 pub(crate) use normalization::NormalizedTable;
-pub(crate) use reprojection::reproject;
-pub(crate) use reprojection::reproject_with_span_identity;
+pub(crate) use reprojection::{ReprojectOptions, reproject_with};
+pub(crate) use template::{DocComments, promote_template_sections, schema_fields};
 
 use crate::Array;
 use crate::Table;
 use crate::arena::Arena;
-use crate::item::{ArrayStyle, Item, Key, TableStyle, Value};
+use crate::item::{
+    ArrayStyle, FloatRepr, IntegerRadix, IntegerRepr, Item, ItemMetadata, Key, KeyStyle,
+    StringRepr, TableStyle, Value,
+};
 use crate::span::Span;
+use std::cell::Cell;
 use std::io::Write;
 use std::mem::MaybeUninit;
 
@@ -23,8 +29,9 @@ use std::mem::MaybeUninit;
 /// without heap allocation. Each node lives on the call stack.
 #[derive(Clone, Copy)]
 struct Prefix<'a, 'de> {
-    name: &'de str,
-    key_span: Span,
+    key: Key<'de>,
+    /// Metadata of the item under `key`, which records how to write it.
+    meta: ItemMetadata,
     parent: Option<&'a Prefix<'a, 'de>>,
 }
 
@@ -81,6 +88,8 @@ pub(crate) struct EmitConfig<'a> {
     pub projected_source_text: &'a str,
     pub projected_source_items: &'a [&'a Item<'a>],
     pub indent: Indent,
+    /// Leading comments to write before the entries of the table.
+    pub comments: Option<&'a DocComments>,
 }
 
 struct Emitter<'a, 'b> {
@@ -88,6 +97,9 @@ struct Emitter<'a, 'b> {
     src: &'a [u8],
     src_items: &'a [&'a Item<'a>],
     indent: Indent,
+    comments: Option<&'a DocComments>,
+    /// Set while emitting inside a commented-out entry.
+    commenting: Cell<bool>,
 }
 
 fn trim_trailing_newline(buf: &mut Vec<u8>) {
@@ -116,6 +128,8 @@ pub(crate) fn emit_with_config(
         src: config.projected_source_text.as_bytes(),
         src_items: config.projected_source_items,
         indent: config.indent,
+        comments: config.comments,
+        commenting: Cell::new(false),
     };
 
     if !emit.src.is_empty() {
@@ -156,13 +170,13 @@ struct Segment<'a, 'b, 'de> {
 
 fn alloc_prefix<'b, 'de>(
     arena: &'b Arena,
-    name: &'de str,
-    key_span: Span,
+    key: Key<'de>,
+    meta: ItemMetadata,
     parent: Option<&'b Prefix<'b, 'de>>,
 ) -> &'b Prefix<'b, 'de> {
     // SAFETY:
     // - `arena.alloc(size_of::<Prefix>())` returns memory suitably sized.
-    //   Prefix contains &str (align 8) + Span (align 4) + Option<&Prefix>
+    //   Prefix contains Key (align 8) + Option<&Prefix>
     //   (align 8), so max field align is 8 which matches ALLOC_ALIGN.
     // - `ptr::write` initializes the allocation with a valid Prefix.
     // - The resulting &'b reference is valid for the arena's lifetime 'b.
@@ -171,14 +185,7 @@ fn alloc_prefix<'b, 'de>(
             .alloc(std::mem::size_of::<Prefix<'b, 'de>>())
             .cast::<Prefix<'b, 'de>>()
             .as_ptr();
-        std::ptr::write(
-            ptr,
-            Prefix {
-                name,
-                key_span,
-                parent,
-            },
-        );
+        std::ptr::write(ptr, Prefix { key, meta, parent });
         &*ptr
     }
 }
@@ -223,7 +230,7 @@ fn emit_segment_prefix(
             if *blank_sep && !out.is_empty() {
                 out.push(b'\n');
             }
-        } else {
+        } else if !out.is_empty() {
             out.push(b'\n');
         }
     } else if matches!(seg.op, EmitOp::Header(..)) && !out.is_empty() {
@@ -309,8 +316,8 @@ fn collect_segments<'a, 'b, 'de: 'b>(
             let Some(sub_table) = item.as_table() else {
                 continue;
             };
-            let dotted_node = alloc_prefix(emit.arena, key.name, key.span, dotted_prefix);
-            let sec_node = alloc_prefix(emit.arena, key.name, key.span, section_prefix);
+            let dotted_node = alloc_prefix(emit.arena, *key, item.meta, dotted_prefix);
+            let sec_node = alloc_prefix(emit.arena, *key, item.meta, section_prefix);
             // Anchor unprojected children near this container's source position.
             if !key.span.is_empty() {
                 *last_projected = key.span.start;
@@ -335,7 +342,7 @@ fn collect_segments<'a, 'b, 'de: 'b>(
             if !key.span.is_empty() {
                 *last_projected = key.span.start;
             }
-            let sec_node = alloc_prefix(emit.arena, key.name, key.span, section_prefix);
+            let sec_node = alloc_prefix(emit.arena, *key, item.meta, section_prefix);
             collect_segments(
                 sub_table,
                 None,
@@ -355,7 +362,7 @@ fn collect_segments<'a, 'b, 'de: 'b>(
             let Some(sub_table) = item.as_table() else {
                 continue;
             };
-            let node = alloc_prefix(emit.arena, key.name, key.span, section_prefix);
+            let node = alloc_prefix(emit.arena, *key, item.meta, section_prefix);
             let sort_key_opt = projected_span(item, emit).map(|s| s.start);
             let source_start = sort_key_opt.unwrap_or(u32::MAX);
 
@@ -386,7 +393,7 @@ fn collect_segments<'a, 'b, 'de: 'b>(
             let Some(arr) = item.as_array() else {
                 continue;
             };
-            let node = alloc_prefix(emit.arena, key.name, key.span, section_prefix);
+            let node = alloc_prefix(emit.arena, *key, item.meta, section_prefix);
             // Anchor unprojected elements near this AOT's source position.
             if !key.span.is_empty() {
                 *last_projected = key.span.start;
@@ -460,12 +467,13 @@ fn emit_header_body<'a, 'b, 'de: 'b>(
     out: &mut Vec<u8>,
     cursor: &mut usize,
 ) {
-    if emit_projected_header_line(item, false, emit, out, cursor) {
+    if emit_projected_header_line(item, prefix, false, emit, out, cursor) {
         emit_body_ordered(table, emit, out, cursor);
         return;
     }
 
     // Fallback: formatted header
+    write_leading_comments(item, emit, out);
     write_section_header(prefix, emit, out);
     emit_body_ordered(table, emit, out, cursor);
 }
@@ -512,6 +520,7 @@ fn emit_body_ordered<'a, 'b, 'de: 'b>(
 
 fn emit_projected_header_line(
     item: &Item<'_>,
+    prefix: &Prefix<'_, '_>,
     include_comment_prefix: bool,
     emit: &Emitter<'_, '_>,
     out: &mut Vec<u8>,
@@ -520,6 +529,10 @@ fn emit_projected_header_line(
     let Some(src_span) = projected_span(item, emit) else {
         return false;
     };
+    // A renamed key anywhere along the path makes the source header stale.
+    if prefix_has_renamed_key(prefix) {
+        return false;
+    }
     let hdr_start = src_span.start as usize;
     if hdr_start >= emit.src.len() || emit.src[hdr_start] != b'[' {
         return false;
@@ -551,12 +564,20 @@ fn emit_aot_element<'a, 'b, 'de: 'b>(
     out: &mut Vec<u8>,
     cursor: &mut usize,
 ) {
-    if emit_projected_header_line(entry, entry.meta.array_reordered(), emit, out, cursor) {
+    if emit_projected_header_line(
+        entry,
+        prefix,
+        entry.meta.array_reordered(),
+        emit,
+        out,
+        cursor,
+    ) {
         emit_ordered(sub_table, None, Some(prefix), emit, out, cursor);
         return;
     }
 
     // Fallback: formatted header
+    write_leading_comments(entry, emit, out);
     write_aot_header(prefix, emit, out);
     emit_ordered(sub_table, None, Some(prefix), emit, out, cursor);
 }
@@ -619,7 +640,8 @@ fn emit_body_entry(
     }
 
     // Fallback: formatted emission
-    write_dotted_key(dotted_prefix, key, emit, out);
+    write_leading_comments(item, emit, out);
+    write_dotted_key(dotted_prefix, key, item.meta, emit, out);
     out.extend_from_slice(b" = ");
     format_value(item, emit, out);
     out.push(b'\n');
@@ -638,6 +660,9 @@ fn emit_formatted(
     out: &mut Vec<u8>,
 ) {
     emit_formatted_body(table, None, emit, out);
+    if let Some(comments) = emit.comments {
+        comments.write_trailing(table, out);
+    }
     emit_formatted_subsections(table, section_prefix, emit, out);
 }
 
@@ -655,21 +680,27 @@ fn emit_formatted_body(
                 continue;
             };
             let node = Prefix {
-                name: key.name,
-                key_span: key.span,
+                key: *key,
+                meta: item.meta,
                 parent: dotted_prefix,
             };
+            let outer = begin_commented(item, emit, out);
             emit_formatted_body(sub_table, Some(&node), emit, out);
+            emit.commenting.set(outer);
             continue;
         }
         if item.has_header_bit() || item.is_implicit_table() || item.is_aot() {
             continue;
         }
 
-        write_dotted_key(dotted_prefix, key, emit, out);
+        let outer = begin_commented(item, emit, out);
+        let start = out.len();
+        write_dotted_key(dotted_prefix, key, item.meta, emit, out);
         out.extend_from_slice(b" = ");
         format_value(item, emit, out);
         out.push(b'\n');
+        comment_out_since(start, emit, out);
+        emit.commenting.set(outer);
     }
 }
 
@@ -682,8 +713,8 @@ fn emit_formatted_subsections(
 ) {
     for (key, item) in table {
         let node = Prefix {
-            name: key.name,
-            key_span: key.span,
+            key: *key,
+            meta: item.meta,
             parent: prefix,
         };
         if item.has_header_bit() {
@@ -693,8 +724,12 @@ fn emit_formatted_subsections(
             if !out.is_empty() {
                 out.push(b'\n');
             }
+            let outer = begin_commented(item, emit, out);
+            let start = out.len();
             write_section_header(&node, emit, out);
+            comment_out_since(start, emit, out);
             emit_formatted(sub_table, Some(&node), emit, out);
+            emit.commenting.set(outer);
         } else if item.is_implicit_table() || item.has_dotted_bit() {
             let Some(sub_table) = item.as_table() else {
                 continue;
@@ -704,6 +739,7 @@ fn emit_formatted_subsections(
             let Some(arr) = item.as_array() else {
                 continue;
             };
+            let outer = emit.commenting.get();
             for entry in arr {
                 let Some(sub_table) = entry.as_table() else {
                     continue;
@@ -711,13 +747,44 @@ fn emit_formatted_subsections(
                 if !out.is_empty() {
                     out.push(b'\n');
                 }
+                begin_commented(entry, emit, out);
+                let start = out.len();
                 write_aot_header(&node, emit, out);
+                comment_out_since(start, emit, out);
                 emit_formatted(sub_table, Some(&node), emit, out);
             }
+            emit.commenting.set(outer);
         }
     }
 }
 
+/// Writes the leading comments recorded for `item`, returning whether it
+/// is to be commented out.
+fn write_leading_comments(item: &Item<'_>, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) -> bool {
+    match emit.comments {
+        Some(comments) => comments.write_leading(item, out),
+        None => false,
+    }
+}
+
+/// Writes the leading comments of `item` and starts commenting out when it
+/// is commented out. Returns the previous state, to restore once
+/// everything nested in `item` is written.
+fn begin_commented(item: &Item<'_>, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) -> bool {
+    let outer = emit.commenting.get();
+    if write_leading_comments(item, emit, out) {
+        emit.commenting.set(true);
+    }
+    outer
+}
+
+/// Comments out the lines written since `start` while commenting.
+fn comment_out_since(start: usize, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) {
+    if emit.commenting.get() {
+        template::comment_out(out, start);
+    }
+}
+
 fn write_section_header(prefix: &Prefix<'_, '_>, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) {
     out.push(b'[');
     write_prefix_path(prefix, emit, out);
@@ -735,13 +802,14 @@ fn write_prefix_path(node: &Prefix<'_, '_>, emit: &Emitter<'_, '_>, out: &mut Ve
         write_prefix_path(parent, emit, out);
         out.push(b'.');
     }
-    emit_key(node.name, node.key_span, emit, out);
+    emit_key(&node.key, node.meta, emit, out);
 }
 
 /// Writes a dotted key with optional prefix: `prefix.key`.
 fn write_dotted_key(
     prefix: Option<&Prefix<'_, '_>>,
     key: &Key<'_>,
+    meta: ItemMetadata,
     emit: &Emitter<'_, '_>,
     out: &mut Vec<u8>,
 ) {
@@ -749,7 +817,7 @@ fn write_dotted_key(
         write_prefix_path(node, emit, out);
         out.push(b'.');
     }
-    emit_key(key.name, key.span, emit, out);
+    emit_key(key, meta, emit, out);
 }
 
 fn aot_has_blank_separators(arr: &Array<'_>, emit: &Emitter<'_, '_>) -> bool {
@@ -951,7 +1019,7 @@ fn try_emit_entry_from_source(
     }
 
     // Emit the key path (prefix + leaf key) from source spans
-    write_dotted_key(dotted_prefix, key, emit, out);
+    write_dotted_key(dotted_prefix, key, item.meta, emit, out);
     // Emit source from after key to start of value (preserves ` = ` whitespace)
     out.extend_from_slice(&emit.src[key_end..val_start]);
     // Emit value via format_value (handles full/partial container match)
@@ -1114,7 +1182,11 @@ fn try_emit_inline_table_partial(
         let leaf = &leaves[(entry & 0xFFFF_FFFF) as usize];
         let key = leaf.key;
         let val = leaf.item;
-        if !key.span.is_empty() && is_fully_projected(val, emit) {
+        if !key.span.is_empty()
+            && is_fully_projected(val, emit)
+            && !val.meta.key_renamed()
+            && leaf.prefix.iter().all(|(_, meta)| !meta.key_renamed())
+        {
             let val_span = projected_span(val, emit).unwrap();
             emit_preserved_with_comma(
                 emit,
@@ -1139,18 +1211,32 @@ fn try_emit_inline_table_partial(
 }
 
 /// Emits a key, using the original source text when the span is valid.
-fn emit_key(name: &str, span: Span, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) {
-    if !span.is_empty() && !emit.src.is_empty() {
-        out.extend_from_slice(&emit.src[span.range()]);
+/// `meta` is the metadata of the item under the key: a key renamed during
+/// reprojection keeps the source span, but its text is formatted fresh.
+fn emit_key(key: &Key<'_>, meta: ItemMetadata, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) {
+    if !key.span.is_empty() && !emit.src.is_empty() && !meta.key_renamed() {
+        out.extend_from_slice(&emit.src[key.span.range()]);
     } else {
-        format_key(name, out);
+        format_key(key.name, meta.key_style(key), out);
     }
 }
 
+/// Returns `true` if any key along `prefix` was renamed during reprojection.
+fn prefix_has_renamed_key(prefix: &Prefix<'_, '_>) -> bool {
+    let mut node = Some(prefix);
+    while let Some(n) = node {
+        if n.meta.key_renamed() {
+            return true;
+        }
+        node = n.parent;
+    }
+    false
+}
+
 /// Returns the original source bytes for a projected scalar item,
-/// or `None` if projection is unavailable or the item is unmatched.
+/// or `None` if projection is unavailable or the item is not fully matched.
 fn projected_text<'a>(item: &Item<'_>, emit: &Emitter<'a, '_>) -> Option<&'a [u8]> {
-    if emit.src.is_empty() {
+    if emit.src.is_empty() || !item.is_reprojected_full_match() {
         return None;
     }
     let src_item = item.projected(emit.src_items)?;
@@ -1239,11 +1325,9 @@ fn format_value_at(
 
 fn format_scalar(item: &Item<'_>, inline: bool, out: &mut Vec<u8>) {
     match item.value() {
-        Value::String(s) => format_string(s, inline, out),
-        Value::Integer(i) => {
-            let _ = write!(out, "{i}");
-        }
-        Value::Float(f) => format_float(*f, out),
+        Value::String(s) => format_string(s, item.string_repr(), inline, out),
+        Value::Integer(i) => format_integer(i.as_i128(), item.integer_repr(), out),
+        Value::Float(f) => format_float(*f, item.float_repr(), out),
         Value::Boolean(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
         Value::DateTime(dt) => {
             let mut buf = MaybeUninit::uninit();
@@ -1259,7 +1343,7 @@ struct InlineLeaf<'a, 'de> {
     key: &'a Key<'de>,
     item: &'a Item<'de>,
     /// Arena-allocated prefix chain from root to leaf's dotted parent.
-    prefix: &'a [(&'de str, Span)],
+    prefix: &'a [(Key<'de>, ItemMetadata)],
     /// Source position for sorting (or inherited from last projected sibling).
     sort_pos: u32,
 }
@@ -1267,32 +1351,32 @@ struct InlineLeaf<'a, 'de> {
 /// Arena-allocates a new prefix slice that extends `prefix` with one element.
 fn arena_extend_prefix<'a, 'de>(
     arena: &'a Arena,
-    prefix: &[(&'de str, Span)],
-    name: &'de str,
-    span: Span,
-) -> &'a [(&'de str, Span)] {
+    prefix: &[(Key<'de>, ItemMetadata)],
+    key: Key<'de>,
+    meta: ItemMetadata,
+) -> &'a [(Key<'de>, ItemMetadata)] {
     let new_len = prefix.len() + 1;
-    let byte_size = new_len * std::mem::size_of::<(&str, Span)>();
+    let byte_size = new_len * std::mem::size_of::<(Key<'_>, ItemMetadata)>();
     let ptr = arena.alloc(byte_size);
-    let slice_ptr = ptr.as_ptr() as *mut (&'de str, Span);
+    let slice_ptr = ptr.as_ptr() as *mut (Key<'de>, ItemMetadata);
     // SAFETY:
-    // - `byte_size` is `new_len * size_of::<(&str, Span)>()`, so the arena
+    // - `byte_size` is `new_len * size_of::<(Key, ItemMetadata)>()`, so the arena
     //   allocation is large enough for `new_len` elements.
-    // - `(&str, Span)` has align <= 8, matching ALLOC_ALIGN.
+    // - `(Key, ItemMetadata)` has align <= 8, matching ALLOC_ALIGN.
     // - The copy writes `prefix.len()` elements from the old slice (disjoint
     //   from the fresh arena allocation), then one more element at the end.
     // - After both writes, all `new_len` elements are initialized.
     unsafe {
         std::ptr::copy_nonoverlapping(prefix.as_ptr(), slice_ptr, prefix.len());
-        std::ptr::write(slice_ptr.add(prefix.len()), (name, span));
-        std::slice::from_raw_parts(slice_ptr as *const (&'de str, Span), new_len)
+        std::ptr::write(slice_ptr.add(prefix.len()), (key, meta));
+        std::slice::from_raw_parts(slice_ptr as *const (Key<'de>, ItemMetadata), new_len)
     }
 }
 
 /// Collects all leaf entries from an inline table, recursing through dotted chains.
 fn collect_inline_leaves<'a, 'de>(
     table: &'a Table<'de>,
-    prefix_chain: &'a [(&'de str, Span)],
+    prefix_chain: &'a [(Key<'de>, ItemMetadata)],
     leaves: &mut Vec<InlineLeaf<'a, 'de>>,
     last_pos: &mut u32,
     arena: &'a Arena,
@@ -1300,7 +1384,7 @@ fn collect_inline_leaves<'a, 'de>(
     for (key, val) in table {
         if let Some(sub) = val.as_table() {
             if val.has_dotted_bit() || sub.style() == TableStyle::Dotted {
-                let chain = arena_extend_prefix(arena, prefix_chain, key.name, key.span);
+                let chain = arena_extend_prefix(arena, prefix_chain, *key, val.meta);
                 if !key.span.is_empty() {
                     *last_pos = key.span.start;
                 }
@@ -1323,17 +1407,17 @@ fn collect_inline_leaves<'a, 'de>(
 /// Writes the prefix chain and leaf key for an inline leaf entry.
 fn write_inline_leaf_key(leaf: &InlineLeaf<'_, '_>, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) {
     let mut first = true;
-    for &(name, span) in leaf.prefix {
+    for (key, meta) in leaf.prefix {
         if !first {
             out.push(b'.');
         }
         first = false;
-        emit_key(name, span, emit, out);
+        emit_key(key, *meta, emit, out);
     }
     if !leaf.prefix.is_empty() {
         out.push(b'.');
     }
-    emit_key(leaf.key.name, leaf.key.span, emit, out);
+    emit_key(leaf.key, leaf.item.meta, emit, out);
 }
 
 /// Single sort monomorphization for all emit ordering.
@@ -1407,8 +1491,8 @@ fn format_inline_table(tab: &Table<'_>, emit: &Emitter<'_, '_>, out: &mut Vec<u8
         if let Some(sub) = val.as_table() {
             if val.has_dotted_bit() || sub.style() == TableStyle::Dotted {
                 let node = Prefix {
-                    name: key.name,
-                    key_span: key.span,
+                    key: *key,
+                    meta: val.meta,
                     parent: None,
                 };
                 format_inline_dotted_kv(sub, &node, emit, out, &mut first, depth);
@@ -1419,7 +1503,7 @@ fn format_inline_table(tab: &Table<'_>, emit: &Emitter<'_, '_>, out: &mut Vec<u8
             out.extend_from_slice(b", ");
         }
         first = false;
-        emit_key(key.name, key.span, emit, out);
+        emit_key(key, val.meta, emit, out);
         out.extend_from_slice(b" = ");
         format_value_at(val, emit, out, depth, true);
     }
@@ -1438,8 +1522,8 @@ fn format_inline_dotted_kv(
         if let Some(sub) = val.as_table() {
             if val.has_dotted_bit() || sub.style() == TableStyle::Dotted {
                 let node = Prefix {
-                    name: key.name,
-                    key_span: key.span,
+                    key: *key,
+                    meta: val.meta,
                     parent: Some(prefix),
                 };
                 format_inline_dotted_kv(sub, &node, emit, out, first, depth);
@@ -1452,7 +1536,7 @@ fn format_inline_dotted_kv(
         *first = false;
         write_prefix_path(prefix, emit, out);
         out.push(b'.');
-        emit_key(key.name, key.span, emit, out);
+        emit_key(key, val.meta, emit, out);
         out.extend_from_slice(b" = ");
         format_value_at(val, emit, out, depth, true);
     }
@@ -1491,7 +1575,12 @@ fn format_array(arr: &Array<'_>, emit: &Emitter<'_, '_>, out: &mut Vec<u8>) {
     out.push(b']');
 }
 
-fn format_key(name: &str, out: &mut Vec<u8>) {
+pub(crate) fn format_key(name: &str, style: KeyStyle, out: &mut Vec<u8>) {
+    match style {
+        KeyStyle::Basic => return format_basic_string(name, out),
+        KeyStyle::Literal if fits_literal(name) => return format_literal_string(name, out),
+        _ => {}
+    }
     if is_bare_key(name) {
         out.extend_from_slice(name.as_bytes());
     } else {
@@ -1515,8 +1604,72 @@ fn is_bare_key(name: &str) -> bool {
     true
 }
 
-fn format_string(s: &str, inline: bool, out: &mut Vec<u8>) {
-    if !inline && can_use_multiline_literal(s) {
+fn format_integer(value: i128, repr: Option<IntegerRepr>, out: &mut Vec<u8>) {
+    let Some(repr) = repr else {
+        let _ = write!(out, "{value}");
+        return;
+    };
+    // TOML forbids a sign on prefixed integers.
+    let radix = if value < 0 {
+        IntegerRadix::Decimal
+    } else {
+        repr.radix
+    };
+    if value < 0 {
+        out.push(b'-');
+    }
+    let magnitude = value.unsigned_abs();
+    let start = out.len();
+    let _ = match radix {
+        IntegerRadix::Decimal => write!(out, "{magnitude}"),
+        IntegerRadix::LowerHex => write!(out, "0x{magnitude:x}"),
+        IntegerRadix::UpperHex => write!(out, "0x{magnitude:X}"),
+        IntegerRadix::Octal => write!(out, "0o{magnitude:o}"),
+        IntegerRadix::Binary => write!(out, "0b{magnitude:b}"),
+    };
+    let digits_start = if radix == IntegerRadix::Decimal {
+        start
+    } else {
+        start + 2
+    };
+    group_digits(out, digits_start, repr.grouping as usize);
+}
+
+/// Inserts `_` between every `group` digits of `out[digits_start..]`,
+/// counting from the right.
+fn group_digits(out: &mut Vec<u8>, digits_start: usize, group: usize) {
+    let len = out.len() - digits_start;
+    if group == 0 || len <= group {
+        return;
+    }
+    let mut read = out.len();
+    out.resize(out.len() + (len - 1) / group, 0);
+    let mut write = out.len();
+    let mut count = 0;
+    while read > digits_start {
+        read -= 1;
+        write -= 1;
+        out[write] = out[read];
+        count += 1;
+        if count == group && read > digits_start {
+            write -= 1;
+            out[write] = b'_';
+            count = 0;
+        }
+    }
+}
+
+fn format_string(s: &str, repr: Option<StringRepr>, inline: bool, out: &mut Vec<u8>) {
+    match repr {
+        Some(StringRepr::Basic) => return format_basic_string(s, out),
+        Some(StringRepr::Literal) if fits_literal(s) => return format_literal_string(s, out),
+        Some(StringRepr::MultilineBasic) => return format_multiline_basic_string(s, out),
+        Some(StringRepr::MultilineLiteral) if can_use_multiline_literal(s, false) => {
+            return format_multiline_literal_string(s, out);
+        }
+        _ => {}
+    }
+    if !inline && can_use_multiline_literal(s, true) {
         format_multiline_literal_string(s, out);
     } else if can_use_literal(s) {
         format_literal_string(s, out);
@@ -1525,7 +1678,7 @@ fn format_string(s: &str, inline: bool, out: &mut Vec<u8>) {
     }
 }
 
-fn can_use_multiline_literal(s: &str) -> bool {
+fn can_use_multiline_literal(s: &str, require_newline: bool) -> bool {
     let mut has_newline = false;
     let mut consecutive_quotes = 0u8;
     for &b in s.as_bytes() {
@@ -1545,7 +1698,13 @@ fn can_use_multiline_literal(s: &str) -> bool {
             }
         }
     }
-    has_newline
+    has_newline || !require_newline
+}
+
+/// Returns `true` if `s` can be written as a single-line literal string.
+fn fits_literal(s: &str) -> bool {
+    !s.bytes()
+        .any(|b| b == b'\'' || (b < 0x20 && b != b'\t') || b == 0x7F)
 }
 
 fn can_use_literal(s: &str) -> bool {
@@ -1578,34 +1737,74 @@ fn format_basic_string(s: &str, out: &mut Vec<u8>) {
     for ch in s.chars() {
         match ch {
             '"' => out.extend_from_slice(b"\\\""),
-            '\\' => out.extend_from_slice(b"\\\\"),
-            '\n' => out.extend_from_slice(b"\\n"),
-            '\t' => out.extend_from_slice(b"\\t"),
-            '\r' => out.extend_from_slice(b"\\r"),
-            '\u{0008}' => out.extend_from_slice(b"\\b"),
-            '\u{000C}' => out.extend_from_slice(b"\\f"),
-            c if c < '\x20' || c == '\x7F' => {
-                let val = c as u32;
-                let hex = b"0123456789ABCDEF";
-                out.extend_from_slice(&[
-                    b'\\',
-                    b'u',
-                    b'0',
-                    b'0',
-                    hex[(val >> 4) as usize & 0xF],
-                    hex[val as usize & 0xF],
-                ]);
-            }
-            c => {
-                let mut buf = [0u8; 4];
-                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
-            }
+            c => escape_basic_char(c, out),
         }
     }
     out.push(b'"');
 }
 
-fn format_float(f: f64, out: &mut Vec<u8>) {
+fn format_multiline_basic_string(s: &str, out: &mut Vec<u8>) {
+    out.extend_from_slice(b"\"\"\"\n");
+    // Only runs of three quotes need breaking up; escaping the last quote
+    // of each keeps runs of one or two readable.
+    let mut consecutive_quotes = 0u8;
+    for ch in s.chars() {
+        match ch {
+            '"' => {
+                consecutive_quotes += 1;
+                if consecutive_quotes == 3 {
+                    out.extend_from_slice(b"\\\"");
+                    consecutive_quotes = 0;
+                } else {
+                    out.push(b'"');
+                }
+                continue;
+            }
+            '\n' => out.push(b'\n'),
+            c => escape_basic_char(c, out),
+        }
+        consecutive_quotes = 0;
+    }
+    // A quote run at the end would merge with the closing delimiter.
+    if consecutive_quotes > 0 {
+        out.truncate(out.len() - consecutive_quotes as usize);
+        for _ in 0..consecutive_quotes {
+            out.extend_from_slice(b"\\\"");
+        }
+    }
+    out.extend_from_slice(b"\"\"\"");
+}
+
+/// Writes `ch` as it appears inside a basic string, escaping backslashes
+/// and control characters. Quotes are left to the caller.
+fn escape_basic_char(ch: char, out: &mut Vec<u8>) {
+    match ch {
+        '\\' => out.extend_from_slice(b"\\\\"),
+        '\n' => out.extend_from_slice(b"\\n"),
+        '\t' => out.extend_from_slice(b"\\t"),
+        '\r' => out.extend_from_slice(b"\\r"),
+        '\u{0008}' => out.extend_from_slice(b"\\b"),
+        '\u{000C}' => out.extend_from_slice(b"\\f"),
+        c if c < '\x20' || c == '\x7F' => {
+            let val = c as u32;
+            let hex = b"0123456789ABCDEF";
+            out.extend_from_slice(&[
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                hex[(val >> 4) as usize & 0xF],
+                hex[val as usize & 0xF],
+            ]);
+        }
+        c => {
+            let mut buf = [0u8; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+}
+
+fn format_float(f: f64, repr: Option<FloatRepr>, out: &mut Vec<u8>) {
     if f.is_nan() {
         out.extend_from_slice(if f.is_sign_positive() {
             b"nan"
@@ -1615,7 +1814,26 @@ fn format_float(f: f64, out: &mut Vec<u8>) {
     } else if f.is_infinite() {
         out.extend_from_slice(if f > 0.0 { b"inf" } else { b"-inf" });
     } else {
-        let mut buffer = zmij::Buffer::new();
-        out.extend_from_slice(buffer.format(f).as_bytes());
+        match repr {
+            Some(FloatRepr::Exponent) => {
+                let _ = write!(out, "{f:e}");
+            }
+            Some(FloatRepr::UpperExponent) => {
+                let _ = write!(out, "{f:E}");
+            }
+            Some(FloatRepr::Decimal) => {
+                let start = out.len();
+                let _ = write!(out, "{f}");
+                // Whole numbers print without a fraction, which TOML would
+                // read back as an integer.
+                if !out[start..].contains(&b'.') {
+                    out.extend_from_slice(b".0");
+                }
+            }
+            None => {
+                let mut buffer = zmij::Buffer::new();
+                out.extend_from_slice(buffer.format(f).as_bytes());
+            }
+        }
     }
 }
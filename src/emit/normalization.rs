@@ -1,8 +1,107 @@
 use crate::emit::partition::ensure_body_order;
 use crate::{Array, ArrayStyle, Item, Kind, Table, TableStyle, Value, ValueMut};
+
+/// Policy for resolving automatically styled tables and arrays.
+///
+/// Tables and arrays built with [`Table::new`] and [`Array::new`], as
+/// produced by [`ToToml`](crate::ToToml), carry no explicit style. When
+/// they are emitted, this policy decides between inline tables, dotted
+/// keys, and `[headers]`, and between inline arrays and `[[headers]]`.
+/// Items with an explicit style, and items formatted after a source
+/// document, are not affected.
+///
+/// The default policy inlines tables of at most two short values, and
+/// writes everything else under headers.
+///
+/// # Examples
+///
+/// ```
+/// use toml_spanner::{AutoStyle, Formatting};
+/// use std::collections::BTreeMap;
+///
+/// let mut server = BTreeMap::new();
+/// server.insert("port", 8080);
+/// let mut root = BTreeMap::new();
+/// root.insert("server", server);
+///
+/// let output = Formatting::default()
+///     .with_auto_style(AutoStyle::new().with_dotted_single_keys(true))
+///     .format(&root)
+///     .unwrap();
+/// assert_eq!(output, "server.port = 8080\n");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AutoStyle {
+    max_inline_entries: usize,
+    max_inline_width: usize,
+    max_header_depth: usize,
+    dotted_single_keys: bool,
+    array_of_tables_headers: bool,
+}
+
+impl Default for AutoStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AutoStyle {
+    /// Creates the default policy.
+    pub const fn new() -> Self {
+        Self {
+            max_inline_entries: 2,
+            max_inline_width: usize::MAX,
+            max_header_depth: usize::MAX,
+            dotted_single_keys: false,
+            array_of_tables_headers: true,
+        }
+    }
+
+    /// Sets the largest number of entries a table may have to be written
+    /// inline. Defaults to 2; 0 writes every non-empty table under a header.
+    pub const fn with_max_inline_entries(mut self, entries: usize) -> Self {
+        self.max_inline_entries = entries;
+        self
+    }
+
+    /// Sets the largest estimated width, in characters, of an inline table
+    /// from `{` to `}`. Unlimited by default.
+    pub const fn with_max_inline_width(mut self, width: usize) -> Self {
+        self.max_inline_width = width;
+        self
+    }
+
+    /// Sets the nesting depth past which tables are no longer given their
+    /// own header.
+    ///
+    /// Root entries are at depth 1, so with a depth of 1 `[dependencies]`
+    /// keeps its header while every table inside it is written inline,
+    /// regardless of size. Unlimited by default.
+    pub const fn with_max_header_depth(mut self, depth: usize) -> Self {
+        self.max_header_depth = depth;
+        self
+    }
+
+    /// Writes a table holding a single short value as a dotted key,
+    /// `a.b = 1`, rather than as an inline table, `a = { b = 1 }`.
+    /// Off by default.
+    pub const fn with_dotted_single_keys(mut self, enabled: bool) -> Self {
+        self.dotted_single_keys = enabled;
+        self
+    }
+
+    /// Controls whether arrays whose elements are all tables are written
+    /// as `[[headers]]`. When disabled they are written as expanded inline
+    /// arrays. On by default.
+    pub const fn with_array_of_tables_headers(mut self, enabled: bool) -> Self {
+        self.array_of_tables_headers = enabled;
+        self
+    }
+}
+
 impl<'de> Table<'de> {
-    fn normalize_inner(&mut self) -> &'de NormalizedTable<'de> {
-        normalize_entries(self.value.entries_mut(), true);
+    fn normalize_inner(&mut self, policy: &AutoStyle) -> &'de NormalizedTable<'de> {
+        normalize_entries(self.value.entries_mut(), true, policy, 1);
         ensure_body_order(self.value.entries_mut());
         // SAFETY: NormalizedTable is #[repr(transparent)] over Table, so the
         // cast preserves layout. The normalize_item calls above have ensured
@@ -15,12 +114,19 @@ impl<'de> Table<'de> {
     /// Recursively corrects table and array kinds so the tree is valid
     /// for emission.
     ///
-    /// Promotes implicit tables to headers when they contain values that
-    /// would otherwise be unreachable, downgrades invalid
-    /// array-of-tables to inline arrays, and fixes kind mismatches in
-    /// nested contexts (e.g. a header table inside an inline table).
+    /// Resolves automatically styled items with `policy`, promotes
+    /// implicit tables to headers when they contain values that would
+    /// otherwise be unreachable, downgrades invalid array-of-tables to
+    /// inline arrays, and fixes kind mismatches in nested contexts (e.g. a
+    /// header table inside an inline table).
+    pub(crate) fn normalize_with(&mut self, policy: &AutoStyle) -> &'de NormalizedTable<'de> {
+        self.normalize_inner(policy)
+    }
+
+    /// [`normalize_with`](Self::normalize_with) using the default policy.
+    #[cfg(test)]
     pub(crate) fn normalize(&mut self) -> &'de NormalizedTable<'de> {
-        self.normalize_inner()
+        self.normalize_inner(&AutoStyle::new())
     }
 
     /// Checks whether this table tree is already valid for emission
@@ -159,23 +265,49 @@ fn is_small_value(item: &Item<'_>) -> bool {
     }
 }
 
-fn resolve_auto_table(sub: &mut Table<'_>, body_emitted: bool) {
+fn resolve_auto_table(sub: &mut Table<'_>, body_emitted: bool, policy: &AutoStyle, depth: usize) {
     if !sub.meta.is_auto_style() {
         return;
     }
     sub.meta.clear_auto_style();
-    if !body_emitted {
-        return;
-    }
 
-    match sub.entries() {
-        [(_, a), (_, b)] if is_small_value(a) && is_small_value(b) => (),
-        [(_, a)] if is_small_value(a) => (),
-        [] => (),
-        _ => return,
-    };
+    if body_emitted && depth > policy.max_header_depth {
+        sub.set_style(TableStyle::Inline);
+    } else if body_emitted && fits_inline(sub, policy) {
+        if policy.dotted_single_keys && sub.len() == 1 {
+            sub.set_style(TableStyle::Dotted);
+        } else {
+            sub.set_style(TableStyle::Inline);
+        }
+    } else if depth >= policy.max_header_depth {
+        // Gives the children a body to be inlined into.
+        sub.set_style(TableStyle::Header);
+    }
+}
 
-    sub.set_style(TableStyle::Inline);
+fn fits_inline(sub: &Table<'_>, policy: &AutoStyle) -> bool {
+    let entries = sub.entries();
+    if entries.is_empty() {
+        return true;
+    }
+    if entries.len() > policy.max_inline_entries {
+        return false;
+    }
+    // `{ ` + `key = value` entries joined by `, ` + ` }`
+    let mut width: usize = 4;
+    for (i, (key, item)) in entries.iter().enumerate() {
+        if !is_small_value(item) {
+            return false;
+        }
+        let Some(w) = inline_width(item) else {
+            return false;
+        };
+        if i > 0 {
+            width += 2;
+        }
+        width += key.name.len() + 3 + w;
+    }
+    width <= policy.max_inline_width
 }
 
 /// Estimates the inline rendered width of a value in characters.
@@ -204,7 +336,7 @@ fn inline_width(item: &Item<'_>) -> Option<usize> {
     }
 }
 
-fn resolve_auto_array(arr: &mut Array<'_>) {
+fn resolve_auto_array(arr: &mut Array<'_>, policy: &AutoStyle) {
     if !arr.meta.is_auto_style() {
         return;
     }
@@ -216,7 +348,7 @@ fn resolve_auto_array(arr: &mut Array<'_>) {
     }
 
     let all_tables = arr.iter().all(|e| e.kind() == Kind::Table);
-    if all_tables {
+    if all_tables && policy.array_of_tables_headers {
         arr.set_style(ArrayStyle::Header);
         return;
     }
@@ -246,39 +378,59 @@ fn resolve_auto_array(arr: &mut Array<'_>) {
     }
 }
 
-fn normalize_entries(entries: &mut [(crate::Key<'_>, Item<'_>)], body_emitted: bool) -> bool {
+fn normalize_entries(
+    entries: &mut [(crate::Key<'_>, Item<'_>)],
+    body_emitted: bool,
+    policy: &AutoStyle,
+    depth: usize,
+) -> bool {
     let mut has_body = false;
     for (_, item) in entries.iter_mut() {
-        has_body |= normalize_item(item, body_emitted);
+        has_body |= normalize_item(item, body_emitted, policy, depth);
     }
     has_body
 }
 
-fn renormalize_promoted_header_children(entries: &mut [(crate::Key<'_>, Item<'_>)]) {
+fn renormalize_promoted_header_children(
+    entries: &mut [(crate::Key<'_>, Item<'_>)],
+    policy: &AutoStyle,
+    depth: usize,
+) {
     for (_, item) in entries.iter_mut() {
         if item.as_table().is_some() {
-            normalize_item(item, true);
+            normalize_item(item, true, policy, depth);
         }
     }
 }
 
-pub(crate) fn normalize_item(item: &mut Item<'_>, body_emitted: bool) -> bool {
+/// `depth` is the nesting depth of `item` itself, 1 for root entries.
+pub(crate) fn normalize_item(
+    item: &mut Item<'_>,
+    body_emitted: bool,
+    policy: &AutoStyle,
+    depth: usize,
+) -> bool {
     match item.value_mut() {
         ValueMut::Table(sub) => {
-            resolve_auto_table(sub, body_emitted);
-            let has_body = normalize_table(sub, body_emitted);
+            resolve_auto_table(sub, body_emitted, policy, depth);
+            let has_body = normalize_table(sub, body_emitted, policy, depth);
             ensure_body_order(sub.value.entries_mut());
             has_body
         }
         ValueMut::Array(arr) => {
-            resolve_auto_array(arr);
-            normalize_array(arr)
+            resolve_auto_array(arr, policy);
+            normalize_array(arr, policy, depth)
         }
         _ => true,
     }
 }
 
-fn normalize_table(sub: &mut Table<'_>, body_emitted: bool) -> bool {
+fn normalize_table(
+    sub: &mut Table<'_>,
+    body_emitted: bool,
+    policy: &AutoStyle,
+    depth: usize,
+) -> bool {
     let kind = sub.style();
 
     match kind {
@@ -289,7 +441,7 @@ fn normalize_table(sub: &mut Table<'_>, body_emitted: bool) -> bool {
             return true;
         }
         TableStyle::Header => {
-            normalize_entries(sub.value.entries_mut(), true);
+            normalize_entries(sub.value.entries_mut(), true, policy, depth + 1);
             return false;
         }
         _ => {}
@@ -313,14 +465,15 @@ fn normalize_table(sub: &mut Table<'_>, body_emitted: bool) -> bool {
         }
     }
 
-    let mut has_body = normalize_entries(sub.value.entries_mut(), effective_body);
+    let mut has_body =
+        normalize_entries(sub.value.entries_mut(), effective_body, policy, depth + 1);
 
     if !effective_body && has_body {
         sub.set_style(TableStyle::Header);
         // Promotion to Header only changes context-sensitive descendants
         // reachable through table children; arrays and scalars are already
         // normalized identically in either context.
-        renormalize_promoted_header_children(sub.value.entries_mut());
+        renormalize_promoted_header_children(sub.value.entries_mut(), policy, depth + 1);
         return false;
     }
 
@@ -348,7 +501,8 @@ fn normalize_table(sub: &mut Table<'_>, body_emitted: bool) -> bool {
                             demoted_has_body = true;
                         } else {
                             ct.set_style(TableStyle::Dotted);
-                            demoted_has_body |= normalize_table(ct, body_emitted);
+                            demoted_has_body |=
+                                normalize_table(ct, body_emitted, policy, depth + 1);
                         }
                     } else {
                         demoted_has_body = true;
@@ -358,7 +512,7 @@ fn normalize_table(sub: &mut Table<'_>, body_emitted: bool) -> bool {
                     if arr.style() == ArrayStyle::Header {
                         arr.set_expanded();
                         arr.set_style(ArrayStyle::Inline);
-                        demoted_has_body |= normalize_array(arr);
+                        demoted_has_body |= normalize_array(arr, policy, depth + 1);
                     } else {
                         demoted_has_body = true;
                     }
@@ -383,7 +537,12 @@ fn normalize_table(sub: &mut Table<'_>, body_emitted: bool) -> bool {
 ///
 /// Like `normalize_inline` but allows nested arrays to stay expanded.
 /// `allow_dotted` is true for named table entries, false for array elements.
-fn normalize_expanded_child(item: &mut Item<'_>, allow_dotted: bool) {
+fn normalize_expanded_child(
+    item: &mut Item<'_>,
+    allow_dotted: bool,
+    policy: &AutoStyle,
+    depth: usize,
+) {
     match item.value_mut() {
         ValueMut::Table(sub) => {
             if allow_dotted {
@@ -395,18 +554,18 @@ fn normalize_expanded_child(item: &mut Item<'_>, allow_dotted: bool) {
                 sub.set_style(TableStyle::Inline);
             }
             for (_, child) in sub.value.entries_mut().iter_mut() {
-                normalize_expanded_child(child, true);
+                normalize_expanded_child(child, true, policy, depth + 1);
             }
         }
         ValueMut::Array(arr) => {
-            resolve_auto_array(arr);
-            normalize_array(arr);
+            resolve_auto_array(arr, policy);
+            normalize_array(arr, policy, depth);
         }
         _ => {}
     }
 }
 
-fn normalize_array(arr: &mut Array<'_>) -> bool {
+fn normalize_array(arr: &mut Array<'_>, policy: &AutoStyle, depth: usize) -> bool {
     let mut kind = arr.style();
 
     // AOT must be non-empty with all-table elements; otherwise downgrade.
@@ -432,14 +591,14 @@ fn normalize_array(arr: &mut Array<'_>) -> bool {
                     continue;
                 };
                 sub.set_style(TableStyle::Header);
-                normalize_entries(sub.value.entries_mut(), true);
+                normalize_entries(sub.value.entries_mut(), true, policy, depth + 1);
                 ensure_body_order(sub.value.entries_mut());
             }
             false
         }
         ArrayStyle::Inline if arr.is_expanded() => {
             for elem in &mut *arr {
-                normalize_expanded_child(elem, false);
+                normalize_expanded_child(elem, false, policy, depth + 1);
             }
             true
         }
@@ -23,6 +23,32 @@ fn maybe_force_collision(hash: u64) -> u64 {
     hash
 }
 
+/// Matching rules for [`reproject_with`].
+#[derive(Clone, Copy, Default)]
+pub struct ReprojectOptions<'a> {
+    /// Pair items only when their spans are equal, rather than by content.
+    pub span_identity: bool,
+    /// `(path, new)` key renames. A dest entry named `new` with no source
+    /// counterpart, in the table that holds the source entry at the key
+    /// `path`, is matched against that entry, as long as dest has no entry
+    /// under the old name.
+    pub renames: &'a [(Vec<&'a str>, &'a str)],
+    /// `(path, field)` identity keys. Elements of the arrays of tables at
+    /// the key `path` are paired by the value of their `field` entry
+    /// instead of by content or position.
+    pub array_identities: &'a [(Vec<&'a str>, &'a str)],
+}
+
+/// Reprojects with default options, see [`reproject_with`].
+#[cfg(test)]
+pub fn reproject<'de>(
+    src: &'de Document<'de>,
+    dest: &mut Table<'_>,
+    items: &mut Vec<&'de Item<'de>>,
+) {
+    reproject_with(src, dest, items, &ReprojectOptions::default());
+}
+
 /// Reprojects structural kinds from a parsed source onto a destination table.
 ///
 /// Takes a [`Document`] to statically enforce that the source was produced by
@@ -40,54 +66,84 @@ fn maybe_force_collision(hash: u64) -> u64 {
 ///
 /// Note: This invalidates span information in dest, but is fine because this
 /// is only used for serialization where we ignore dest spans.
-pub fn reproject<'de>(
+pub fn reproject_with<'de>(
     src: &'de Document<'de>,
     dest: &mut Table<'_>,
     items: &mut Vec<&'de Item<'de>>,
+    options: &ReprojectOptions<'_>,
 ) {
-    reproject_with_options(src, dest, items, false);
+    let cx = Reprojector {
+        index: src.table_index(),
+        span_identity: options.span_identity,
+        renames: options.renames,
+        identities: options.array_identities,
+        path: None,
+    };
+    reproject_table(&cx, src.table(), dest, items);
 }
 
-pub fn reproject_with_span_identity<'de>(
-    src: &'de Document<'de>,
-    dest: &mut Table<'_>,
-    items: &mut Vec<&'de Item<'de>>,
-) {
-    reproject_with_options(src, dest, items, true);
+/// State shared by every level of a reprojection pass.
+#[derive(Clone, Copy)]
+struct Reprojector<'a> {
+    index: &'a TableIndex<'a>,
+    span_identity: bool,
+    renames: &'a [(Vec<&'a str>, &'a str)],
+    identities: &'a [(Vec<&'a str>, &'a str)],
+    /// Key path of the source item being reprojected, innermost key first.
+    path: Option<&'a KeyPath<'a>>,
 }
 
-fn reproject_with_options<'de>(
-    src: &'de Document<'de>,
-    dest: &mut Table<'_>,
-    items: &mut Vec<&'de Item<'de>>,
-    span_identity: bool,
-) {
-    let index = src.table_index();
-    let src_table = src.table();
-    reproject_table(index, src_table, dest, items, span_identity);
+/// Stack-allocated linked list node for the key path of a source table.
+struct KeyPath<'a> {
+    name: &'a str,
+    parent: Option<&'a KeyPath<'a>>,
+}
+
+impl Reprojector<'_> {
+    /// Returns `true` if the source item being reprojected sits at the key
+    /// path `segments`.
+    fn at_path(&self, segments: &[&str]) -> bool {
+        let mut node = self.path;
+        for segment in segments.iter().rev() {
+            match node {
+                Some(n) if n.name == *segment => node = n.parent,
+                _ => return false,
+            }
+        }
+        node.is_none()
+    }
+
+    /// Returns the identity field configured for the array being reprojected.
+    fn identity_field(&self) -> Option<&str> {
+        let (_, field) = self
+            .identities
+            .iter()
+            .find(|(path, _)| self.at_path(path))?;
+        Some(field)
+    }
 }
 
 /// Returns `true` when the entire subtree is fully matched (same structure,
 /// same values, same lengths).
 ///
-/// Scalars are only given a reprojected index when fully matched.
-/// Containers always get a reprojected index when types match (even
-/// partial match), so emit can access the source item's span for
+/// Scalars paired with a scalar always get a reprojected index, so emit can
+/// keep a changed value's position and trailing comment; only a full match
+/// lets emit copy the source text. Containers always get a reprojected
+/// index when types match (even partial match), so emit can access the source item's span for
 /// cursor-based partial emission (e.g. appending to an array while
 /// preserving comments on existing elements).
 fn reproject_item<'de>(
-    index: &TableIndex<'_>,
+    cx: &Reprojector<'_>,
     src: &'de Item<'de>,
     dest: &mut Item<'_>,
     items: &mut Vec<&'de Item<'de>>,
-    span_identity: bool,
 ) -> bool {
     if dest.meta.ignore_source_formatting_recursively() {
         clear_stale_item(dest);
         return false;
     }
 
-    if span_identity && src.span() != dest.span() {
+    if cx.span_identity && src.span() != dest.span() {
         dest.meta.set_ignore_source_formatting_recursively();
         clear_stale_item(dest);
         return false;
@@ -102,11 +158,11 @@ fn reproject_item<'de>(
         (Value::DateTime(a), ValueMut::DateTime(b)) => a == b,
         (Value::Table(src_table), ValueMut::Table(dest_table)) => {
             container_match = true;
-            reproject_table(index, src_table, dest_table, items, span_identity)
+            reproject_table(cx, src_table, dest_table, items)
         }
         (Value::Array(src_array), ValueMut::Array(dest_array)) => {
             container_match = true;
-            reproject_array(index, src_array, dest_array, items, span_identity)
+            reproject_array(cx, src_array, dest_array, items)
         }
         _ => false,
     };
@@ -122,18 +178,36 @@ fn reproject_item<'de>(
         // so their spans still point into dest text. Clear them to prevent
         // emit from indexing into source text at wrong positions.
         clear_stale_item(dest);
+        if src.is_scalar() && dest.is_scalar() {
+            // A changed scalar keeps its source position and trailing
+            // comment. Without the full-match bit its value is formatted
+            // fresh rather than copied.
+            if dest.set_reprojected_index(items.len()) {
+                items.push(src);
+            }
+            carry_scalar_repr(src, dest);
+        }
     }
     full_match
 }
 
+/// Carries the spelling parsed for a changed scalar over to dest, so an
+/// edited `0o755` stays octal and an edited literal string stays literal.
+/// Explicit hints already set on dest take precedence.
+fn carry_scalar_repr(src: &Item<'_>, dest: &mut Item<'_>) {
+    let bits = src.meta.repr_bits();
+    if bits != 0 && src.kind() == dest.kind() && dest.meta.repr_bits() == 0 {
+        dest.meta.set_repr_bits(bits);
+    }
+}
+
 /// Returns `true` when every entry in dest matched a src entry in the same
 /// order and every `reproject_item` returned `true` (full structural match).
 fn reproject_table<'de>(
-    index: &TableIndex<'_>,
+    cx: &Reprojector<'_>,
     src: &'de Table<'de>,
     dest: &mut Table<'_>,
     items: &mut Vec<&'de Item<'de>>,
-    span_identity: bool,
 ) -> bool {
     let is_body_parent = matches!(dest.style(), TableStyle::Dotted | TableStyle::Inline);
     let ignore_style = dest.meta.ignore_source_style();
@@ -152,10 +226,24 @@ fn reproject_table<'de>(
     // Match entries, assign structural kinds, execute localized backfills,
     // and detect any "stuck" entries (subsections forced into body-level).
     for i in 0..entries.len() {
+        let name = entries[i].0.name;
+        let mut found = src.value.get_entry_with_index(name, cx.index);
+        let mut renamed_style = None;
+        if found.is_none() && !cx.renames.is_empty() {
+            found = renamed_source_entry(cx, src, entries, name);
+            if found.is_some() {
+                // The key text differs from source, so the entry can't be
+                // copied verbatim even if its value is unchanged.
+                all_matched = false;
+                // The borrowed source span spells the old name, so pin the
+                // quoting the dest key had before taking it.
+                renamed_style = Some(entries[i].1.meta.key_style(&entries[i].0));
+            }
+        }
         let (dst_key, dst_item) = &mut entries[i];
         let mut dst_item = dst_item;
 
-        let Some((src_key, src_item)) = src.value.get_entry_with_index(dst_key.name, index) else {
+        let Some((src_key, src_item)) = found else {
             // Unmatched Entry
             all_matched = false;
             dst_key.span = Span::default();
@@ -180,10 +268,22 @@ fn reproject_table<'de>(
         };
         dst_key.span = src_key.span;
 
-        let item_full = reproject_item(index, src_item, dst_item, items, span_identity);
+        let node = KeyPath {
+            name: src_key.name,
+            parent: cx.path,
+        };
+        let entry_cx = Reprojector {
+            path: Some(&node),
+            ..*cx
+        };
+        let item_full = reproject_item(&entry_cx, src_item, dst_item, items);
         if !item_full {
             all_matched = false;
         }
+        if let Some(style) = renamed_style {
+            dst_item.meta.set_key_style(dst_key, style);
+            dst_item.meta.set_key_renamed();
+        }
         if dst_item.meta.ignore_source_formatting_recursively() {
             dst_key.span = Span::default();
             continue;
@@ -311,7 +411,7 @@ fn reproject_table<'de>(
 
     if !ignore_style {
         ensure_valid_subsection_ordering(
-            index,
+            cx.index,
             src,
             is_body_parent,
             entries,
@@ -323,6 +423,30 @@ fn reproject_table<'de>(
     all_matched
 }
 
+/// Finds the source entry that a dest entry named `name` was renamed from.
+///
+/// Skipped when dest still has an entry under the old name, since that
+/// entry owns the source formatting.
+fn renamed_source_entry<'de>(
+    cx: &Reprojector<'_>,
+    src: &'de Table<'de>,
+    entries: &[(crate::Key<'_>, Item<'_>)],
+    name: &str,
+) -> Option<&'de (crate::Key<'de>, Item<'de>)> {
+    for (path, new) in cx.renames {
+        let Some((&old, parent)) = path.split_last() else {
+            continue;
+        };
+        if *new != name || !cx.at_path(parent) || entries.iter().any(|(key, _)| key.name == old) {
+            continue;
+        }
+        if let Some(entry) = src.value.get_entry_with_index(old, cx.index) {
+            return Some(entry);
+        }
+    }
+    None
+}
+
 fn ensure_valid_subsection_ordering(
     index: &TableIndex<'_>,
     src: &'_ Table<'_>,
@@ -511,11 +635,10 @@ const INDEX_LIMIT: usize = 32;
 /// groups and `equal_items` verification since each parsed element has a
 /// unique byte offset.
 fn reproject_array<'de>(
-    index: &TableIndex<'_>,
+    cx: &Reprojector<'_>,
     src: &'de Array<'de>,
     dest: &mut Array<'_>,
     items: &mut Vec<&'de Item<'de>>,
-    span_identity: bool,
 ) -> bool {
     let n = src.len();
     let m = dest.len();
@@ -529,9 +652,13 @@ fn reproject_array<'de>(
         return n == 0 && m == 0;
     }
 
+    if let Some(field) = cx.identity_field() {
+        return reproject_array_by_identity(cx, src, dest, items, field);
+    }
+
     // Fall back to positional for arrays exceeding the index space.
     if n > INDEX_LIMIT || m > INDEX_LIMIT {
-        return reproject_array_positional(index, src, dest, items);
+        return reproject_array_positional(cx, src, dest, items);
     }
 
     // Content-based prefix optimization: skip leading elements that are
@@ -540,13 +667,13 @@ fn reproject_array<'de>(
     let mut prefix = 0;
     let mut prefix_all_full = true;
     while let (Some(src_head), Some(dst_head)) = (src.get(prefix), dest.get_mut(prefix)) {
-        let same = if span_identity {
+        let same = if cx.span_identity {
             src_head.span() == dst_head.span()
         } else {
-            crate::item::equal_items(src_head, dst_head, Some(index))
+            crate::item::equal_items(src_head, dst_head, Some(cx.index))
         };
         if same {
-            if !reproject_item(index, src_head, dst_head, items, span_identity) {
+            if !reproject_item(cx, src_head, dst_head, items) {
                 prefix_all_full = false;
             }
             prefix += 1;
@@ -562,7 +689,7 @@ fn reproject_array<'de>(
     // Pack (key, index) into u64. Bit 63 reserved as matched flag.
     // Key is content hash (bits 62..16) or span.start depending on mode.
     let mut buf: Vec<u64> = Vec::with_capacity(n + m);
-    if span_identity {
+    if cx.span_identity {
         for (i, item) in src.iter().enumerate() {
             buf.push(((item.span().start as u64) << HASH_SHIFT) | (i as u64));
         }
@@ -604,7 +731,7 @@ fn reproject_array<'de>(
             si += 1;
         } else if sh > dh {
             di += 1;
-        } else if span_identity {
+        } else if cx.span_identity {
             // Span identity: key equality is sufficient, no content check.
             let src_idx = (src_sorted[si] & INDEX_MASK) as usize;
             dest_sorted[di] =
@@ -633,7 +760,7 @@ fn reproject_array<'de>(
             for k in 0..prefix_len {
                 let src_idx = (src_sorted[si_start + k] & INDEX_MASK) as usize;
                 let dest_idx = (dest_sorted[di_start + k] & INDEX_MASK) as usize;
-                if crate::item::equal_items(&src[src_idx], &dest[dest_idx], Some(index)) {
+                if crate::item::equal_items(&src[src_idx], &dest[dest_idx], Some(cx.index)) {
                     dest_sorted[di_start + k] =
                         MATCHED_BIT | ((src_idx as u64) << HASH_SHIFT) | (dest_idx as u64);
                     src_sorted[si_start + k] |= MATCHED_BIT;
@@ -659,7 +786,7 @@ fn reproject_array<'de>(
                         continue;
                     }
                     let src_idx = (*src_entry & INDEX_MASK) as usize;
-                    if crate::item::equal_items(&src[src_idx], &dest[dest_idx], Some(index)) {
+                    if crate::item::equal_items(&src[src_idx], &dest[dest_idx], Some(cx.index)) {
                         dest_sorted[d] =
                             MATCHED_BIT | ((src_idx as u64) << HASH_SHIFT) | (dest_idx as u64);
                         *src_entry |= MATCHED_BIT;
@@ -719,12 +846,12 @@ fn reproject_array<'de>(
         let dest_entry = &mut dest[di];
         if *entry & MATCHED_BIT != 0 {
             let src_idx = ((*entry >> HASH_SHIFT) & INDEX_MASK) as usize;
-            if !reproject_item(index, &src[src_idx], dest_entry, items, span_identity) {
+            if !reproject_item(cx, &src[src_idx], dest_entry, items) {
                 all_matched = false;
             }
         } else if fi < fc {
             let src_idx = src_sorted[fi] as usize;
-            reproject_item(index, &src[src_idx], dest_entry, items, span_identity);
+            reproject_item(cx, &src[src_idx], dest_entry, items);
             fi += 1;
             all_matched = false;
         } else {
@@ -740,21 +867,111 @@ fn reproject_array<'de>(
     all_matched
 }
 
+/// Pairs array-of-tables elements by the value of their `field` entry.
+///
+/// Unlike content matching, an element with no counterpart is never paired
+/// with a leftover source element, so a new table does not inherit the
+/// comments of a removed one.
+fn reproject_array_by_identity<'de>(
+    cx: &Reprojector<'_>,
+    src: &'de Array<'de>,
+    dest: &mut Array<'_>,
+    items: &mut Vec<&'de Item<'de>>,
+    field: &str,
+) -> bool {
+    let src = src.as_slice();
+    let build = foldhash::quality::RandomState::default();
+    let hash_id = |id: &Item<'_>| {
+        let mut h = build.build_hasher();
+        hash_item(id, &mut h, &build);
+        h.finish()
+    };
+    let src_id = |i: usize| {
+        let table = src[i].as_table()?;
+        Some(&table.value.get_entry_with_index(field, cx.index)?.1)
+    };
+
+    // Maps an identity hash to the first source element with it, and
+    // `next` chains the rest in source order.
+    let mut first: foldhash::HashMap<u64, usize> =
+        foldhash::HashMap::with_capacity_and_hasher(src.len(), Default::default());
+    let mut next = vec![usize::MAX; src.len()];
+    for i in (0..src.len()).rev() {
+        if let Some(id) = src_id(i) {
+            if let Some(following) = first.insert(hash_id(id), i) {
+                next[i] = following;
+            }
+        }
+    }
+
+    let mut consumed = vec![false; src.len()];
+    let mut pairs = Vec::with_capacity(dest.len());
+    let mut reordered = false;
+    let mut prev_src = 0;
+    for dest_item in dest.iter() {
+        let id = dest_item.as_table().and_then(|t| t.get(field));
+        let mut found = None;
+        if let Some(id) = id {
+            let mut i = first.get(&hash_id(id)).copied().unwrap_or(usize::MAX);
+            while i != usize::MAX {
+                if !consumed[i]
+                    && src_id(i).is_some_and(|src_id| crate::item::equal_items(src_id, id, None))
+                {
+                    found = Some(i);
+                    break;
+                }
+                i = next[i];
+            }
+        }
+        if let Some(i) = found {
+            consumed[i] = true;
+            if i < prev_src {
+                reordered = true;
+            }
+            prev_src = i + 1;
+        }
+        pairs.push(found);
+    }
+
+    let mut all_matched = src.len() == dest.len() && !reordered;
+    for (dest_item, pair) in dest.as_mut_slice().iter_mut().zip(pairs) {
+        match pair {
+            Some(i) => {
+                if !reproject_item(cx, &src[i], dest_item, items) {
+                    all_matched = false;
+                }
+            }
+            None => {
+                clear_stale_item(dest_item);
+                all_matched = false;
+            }
+        }
+        if reordered {
+            dest_item.meta.set_array_reordered();
+        }
+    }
+    all_matched
+}
+
 /// Simple positional fallback for arrays exceeding u16 index space.
 /// Only reachable for arrays with >65535 elements; see the u16 guard
 /// in [`reproject_array`].
 fn reproject_array_positional<'de>(
-    index: &TableIndex<'_>,
+    cx: &Reprojector<'_>,
     src: &'de Array<'de>,
     dest: &mut Array<'_>,
     items: &mut Vec<&'de Item<'de>>,
 ) -> bool {
+    let positional = Reprojector {
+        span_identity: false,
+        ..*cx
+    };
     let mut all_matched = src.len() == dest.len();
     let src = src.as_slice();
     let mut i = 0;
     for dest_item in dest.as_mut_slice() {
         if let Some(src_item) = src.get(i) {
-            if !reproject_item(index, src_item, dest_item, items, false) {
+            if !reproject_item(&positional, src_item, dest_item, items) {
                 all_matched = false;
             }
         } else {
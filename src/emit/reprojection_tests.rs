@@ -751,18 +751,18 @@ fn emit_projected_after_mutation(
 // Scalar format preservation tests: moved to testdata/emit_identity.toml
 
 #[test]
-fn changed_value_uses_default_format() {
+fn changed_value_keeps_source_radix() {
     let input = "a = 0xFF\nb = 0o77";
     let result = emit_projected_after_mutation(input, |root, _arena| {
         let b = root.get_mut("b").unwrap();
         *b = Item::from(99i64);
     });
-    // a is unchanged → preserved as hex; b is changed → default decimal
+    // a is unchanged → preserved as hex; b is changed → new value, still octal
     assert!(
         result.contains("a = 0xFF"),
         "a should be preserved: {result}"
     );
-    assert!(result.contains("b = 99"), "b should be decimal: {result}");
+    assert!(result.contains("b = 0o143"), "b should be octal: {result}");
 }
 
 #[test]
@@ -2614,3 +2614,378 @@ fn ignore_source_formatting_recursively_selective() {
         "unflagged 'b' should get Header from source"
     );
 }
+
+#[test]
+fn changed_scalars_keep_source_repr() {
+    let input = "mode = 0o755\nmask = 0xFF_FF\nsize = 1_000\npath = 'C:\\a'\nname = \"x\"\n";
+    let result = reproject_after_mutation(input, |root, _| {
+        *root.get_mut("mode").unwrap() = Item::from(0o700i64);
+        *root.get_mut("mask").unwrap() = Item::from(0xABCDi64);
+        *root.get_mut("size").unwrap() = Item::from(25_000i64);
+        *root.get_mut("path").unwrap() = Item::string("D:\\b");
+        *root.get_mut("name").unwrap() = Item::string("y\\z");
+    });
+    assert_eq!(
+        result,
+        "mode = 0o700\nmask = 0xAB_CD\nsize = 25_000\npath = 'D:\\b'\nname = \"y\\\\z\"\n"
+    );
+}
+
+#[test]
+fn explicit_repr_overrides_source_repr() {
+    let result = reproject_after_mutation("mode = 0o755\n", |root, _| {
+        let mut item = Item::from(8i64);
+        item.set_integer_repr(crate::IntegerRepr {
+            radix: crate::IntegerRadix::Binary,
+            grouping: 0,
+        });
+        *root.get_mut("mode").unwrap() = item;
+    });
+    assert_eq!(result, "mode = 0b1000\n");
+}
+
+/// Parses `input`, renames the dest keys at each `(path, new)` pair, then
+/// reprojects with those renames registered and emits.
+fn emit_after_rename(input: &str, renames: &[(&[&str], &'static str)]) -> String {
+    fn rename(table: &mut Table<'_>, path: &[&str], new: &'static str) {
+        let [name, rest @ ..] = path else {
+            return;
+        };
+        for (key, item) in table.entries_mut() {
+            if key.name != *name {
+                continue;
+            }
+            if rest.is_empty() {
+                *key = Key::new(new);
+                continue;
+            }
+            match item.value_mut() {
+                crate::ValueMut::Table(sub) => rename(sub, rest, new),
+                crate::ValueMut::Array(arr) => {
+                    for elem in arr.as_mut_slice() {
+                        if let Some(sub) = elem.as_table_mut() {
+                            rename(sub, rest, new);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let arena = Arena::new();
+    let src_doc = parse(input, &arena).unwrap();
+    let mut dest_doc = parse(input, &arena).unwrap();
+    for (path, new) in renames {
+        rename(&mut dest_doc.table, path, new);
+    }
+
+    let renames: Vec<_> = renames
+        .iter()
+        .map(|(path, new)| (path.to_vec(), *new))
+        .collect();
+    let options = super::ReprojectOptions {
+        renames: &renames,
+        ..Default::default()
+    };
+    let mut items = Vec::new();
+    super::reproject_with(&src_doc, &mut dest_doc.table, &mut items, &options);
+
+    let config = EmitConfig {
+        projected_source_text: input,
+        projected_source_items: &items,
+        ..EmitConfig::default()
+    };
+    let mut buf = Vec::new();
+    emit::emit_with_config(dest_doc.table.normalize(), &config, &arena, &mut buf);
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn renamed_scalar_keeps_comments_and_position() {
+    let input = "first = 1\n\n# leading\nold = 2 # trailing\nlast = 3\n";
+    assert_eq!(
+        emit_after_rename(input, &[(&["old"], "new")]),
+        "first = 1\n\n# leading\nnew = 2 # trailing\nlast = 3\n"
+    );
+}
+
+#[test]
+fn renamed_quoted_key_is_formatted_fresh() {
+    let input = "# c\n\"old\\u0020key\" = 1 # t\n'x' = 2\n";
+    assert_eq!(
+        emit_after_rename(input, &[(&["old key"], "new")]),
+        "# c\nnew = 1 # t\n'x' = 2\n"
+    );
+}
+
+#[test]
+fn renamed_header_table_rewrites_nested_headers() {
+    let input = "\
+# section comment
+[old] # header trailing
+a = 1 # a comment
+
+[old.sub]
+b = 2
+
+[[old.list]]
+c = 3
+";
+    assert_eq!(
+        emit_after_rename(input, &[(&["old"], "new")]),
+        "\
+# section comment
+[new]
+a = 1 # a comment
+
+[new.sub]
+b = 2
+
+[[new.list]]
+c = 3
+"
+    );
+}
+
+#[test]
+fn renamed_dotted_and_inline_keys() {
+    let input = "old.x = 1 # dotted\nt = {\n    old = 1, # inline\n    keep = 2,\n}\n";
+    assert_eq!(
+        emit_after_rename(input, &[(&["old"], "new"), (&["t", "old"], "new")]),
+        "new.x = 1 # dotted\nt = {\n    new = 1,\n    keep = 2,\n}\n"
+    );
+}
+
+#[test]
+fn rename_applies_only_at_its_path() {
+    let arena = Arena::new();
+    let input = "[a]\nold = 1 # a\n\n[b]\nold = 2 # b\n";
+    let src_doc = parse(input, &arena).unwrap();
+    let mut dest_doc = parse(input, &arena).unwrap();
+    for section in ["a", "b"] {
+        let sub = dest_doc.table.get_mut(section).unwrap();
+        let entry = &mut sub.as_table_mut().unwrap().entries_mut()[0];
+        entry.0 = Key::new("new");
+    }
+
+    let renames = [(vec!["a", "old"], "new")];
+    let options = super::ReprojectOptions {
+        renames: &renames,
+        ..Default::default()
+    };
+    let mut items = Vec::new();
+    super::reproject_with(&src_doc, &mut dest_doc.table, &mut items, &options);
+    let config = EmitConfig {
+        projected_source_text: input,
+        projected_source_items: &items,
+        ..EmitConfig::default()
+    };
+    let mut buf = Vec::new();
+    emit::emit_with_config(dest_doc.table.normalize(), &config, &arena, &mut buf);
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "[a]\nnew = 1 # a\n\n[b]\nnew = 2\n"
+    );
+}
+
+#[test]
+fn rename_applies_to_every_array_of_tables_element() {
+    let input = "[[bin]]\nold = 1 # first\n\n[[bin]]\nold = 2 # second\n";
+    assert_eq!(
+        emit_after_rename(input, &[(&["bin", "old"], "new")]),
+        "[[bin]]\nnew = 1 # first\n\n[[bin]]\nnew = 2 # second\n"
+    );
+}
+
+#[test]
+fn rename_skipped_when_old_key_still_present() {
+    let arena = Arena::new();
+    let input = "old = 1 # old\n";
+    let src_doc = parse(input, &arena).unwrap();
+    let mut dest_doc = parse(input, &arena).unwrap();
+    dest_doc
+        .table
+        .insert_unique(Key::new("new"), Item::from(2i64), &arena);
+
+    let renames = [(vec!["old"], "new")];
+    let options = super::ReprojectOptions {
+        renames: &renames,
+        ..Default::default()
+    };
+    let mut items = Vec::new();
+    super::reproject_with(&src_doc, &mut dest_doc.table, &mut items, &options);
+    let config = EmitConfig {
+        projected_source_text: input,
+        projected_source_items: &items,
+        ..EmitConfig::default()
+    };
+    let mut buf = Vec::new();
+    emit::emit_with_config(dest_doc.table.normalize(), &config, &arena, &mut buf);
+    assert_eq!(String::from_utf8(buf).unwrap(), "old = 1 # old\nnew = 2\n");
+}
+
+/// Parses `input`, applies `mutate`, then reprojects with the given array
+/// identity keys and emits.
+fn emit_with_identities(
+    input: &str,
+    identities: &[(&[&str], &str)],
+    mutate: impl for<'a> FnOnce(&mut Table<'a>, &'a Arena),
+) -> String {
+    let identities: Vec<_> = identities
+        .iter()
+        .map(|&(path, field)| (path.to_vec(), field))
+        .collect();
+    let arena = Arena::new();
+    let src_doc = parse(input, &arena).unwrap();
+    let mut dest_doc = parse(input, &arena).unwrap();
+    mutate(&mut dest_doc.table, &arena);
+
+    let options = super::ReprojectOptions {
+        array_identities: &identities,
+        ..Default::default()
+    };
+    let mut items = Vec::new();
+    super::reproject_with(&src_doc, &mut dest_doc.table, &mut items, &options);
+
+    let config = EmitConfig {
+        projected_source_text: input,
+        projected_source_items: &items,
+        ..EmitConfig::default()
+    };
+    let mut buf = Vec::new();
+    emit::emit_with_config(dest_doc.table.normalize(), &config, &arena, &mut buf);
+    String::from_utf8(buf).unwrap()
+}
+
+fn named_table<'a>(name: &'a str, version: i64, arena: &'a Arena) -> Item<'a> {
+    let mut t = Table::default();
+    t.insert_unique(Key::new("name"), Item::string(name), arena);
+    t.insert_unique(Key::new("version"), Item::from(version), arena);
+    t.set_style(TableStyle::Header);
+    t.into_item()
+}
+
+#[test]
+fn array_identity_keeps_comments_with_their_tables() {
+    let input = "\
+[[package]]
+name = \"a\" # first
+version = 1
+
+# about b
+[[package]]
+name = \"b\" # second
+version = 1
+";
+    // Drop `a`, bump `b`, and add a new `c` ahead of it.
+    let result = emit_with_identities(input, &[(&["package"], "name")], |root, arena| {
+        let packages = root.get_mut("package").unwrap().as_array_mut().unwrap();
+        let mut b = packages.as_mut_slice()[1].clone_in(arena);
+        *b.as_table_mut().unwrap().get_mut("version").unwrap() = Item::from(2i64);
+        let mut next = crate::Array::default();
+        next.push(named_table("c", 1, arena), arena);
+        next.push(b, arena);
+        next.set_style(ArrayStyle::Header);
+        *root.get_mut("package").unwrap() = next.into_item();
+    });
+    assert_eq!(
+        result,
+        "\
+[[package]]
+name = \"c\"
+version = 1
+
+# about b
+[[package]]
+name = \"b\" # second
+version = 2
+"
+    );
+}
+
+#[test]
+fn array_identity_nested_path() {
+    let input = "\
+[[target]]
+name = \"t\"
+
+[[target.bin]]
+name = \"x\" # x
+[[target.bin]]
+name = \"y\" # y
+";
+    let result = emit_with_identities(input, &[(&["target", "bin"], "name")], |root, _| {
+        let targets = root.get_mut("target").unwrap().as_array_mut().unwrap();
+        let bins = targets.as_mut_slice()[0]
+            .as_table_mut()
+            .unwrap()
+            .get_mut("bin")
+            .unwrap()
+            .as_array_mut()
+            .unwrap();
+        bins.as_mut_slice().swap(0, 1);
+    });
+    assert_eq!(
+        result,
+        "\
+[[target]]
+name = \"t\"
+
+[[target.bin]]
+name = \"y\" # y
+
+[[target.bin]]
+name = \"x\" # x
+"
+    );
+}
+
+#[test]
+fn array_identity_unmatched_first_element_starts_output() {
+    let input = "\
+[[package]]
+name = \"a\" # first
+version = 1
+";
+    // A fresh element emitted first must not open with a blank line.
+    let result = emit_with_identities(input, &[(&["package"], "name")], |root, arena| {
+        let mut next = crate::Array::default();
+        next.push(named_table("c", 1, arena), arena);
+        next.set_style(ArrayStyle::Header);
+        *root.get_mut("package").unwrap() = next.into_item();
+    });
+    assert_eq!(result, "[[package]]\nname = \"c\"\nversion = 1\n");
+}
+
+#[test]
+fn array_identity_duplicate_values_pair_in_order() {
+    let input = "\
+[[package]]
+name = \"a\" # one
+version = 1
+
+[[package]]
+name = \"a\" # two
+version = 2
+";
+    let result = emit_with_identities(input, &[(&["package"], "name")], |root, _| {
+        let packages = root.get_mut("package").unwrap().as_array_mut().unwrap();
+        for package in packages.as_mut_slice() {
+            let version = package.as_table_mut().unwrap().get_mut("version").unwrap();
+            *version = Item::from(version.as_i64().unwrap() + 10);
+        }
+    });
+    assert_eq!(
+        result,
+        "\
+[[package]]
+name = \"a\" # one
+version = 11
+
+[[package]]
+name = \"a\" # two
+version = 12
+"
+    );
+}
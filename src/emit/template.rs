@@ -0,0 +1,298 @@
+use crate::Table;
+use crate::item::{Item, KeyStyle, TableStyle};
+use crate::schema::{FieldSchema, Schema};
+use foldhash::HashMap;
+
+/// The fields described by `schema`, empty unless it is a struct.
+pub(crate) fn schema_fields(schema: Schema) -> &'static [FieldSchema] {
+    match schema {
+        Schema::Struct(schema) => schema.fields,
+        _ => &[],
+    }
+}
+
+/// The fields of a struct-typed field's own type.
+fn nested_fields(field: &FieldSchema) -> &'static [FieldSchema] {
+    schema_fields((field.schema)())
+}
+
+/// The tables written under the header of the section `item`, with the
+/// fields describing them: the table itself, or each element of an array
+/// of tables.
+fn section_tables<'a, 'de>(
+    item: &'a Item<'de>,
+    field: &FieldSchema,
+) -> (&'a [Item<'de>], &'static [FieldSchema]) {
+    match item.as_array() {
+        Some(array) if item.is_aot() => {
+            let fields = match (field.schema)() {
+                Schema::Array(element) => schema_fields(element()),
+                _ => &[],
+            };
+            (array.as_slice(), fields)
+        }
+        _ => (std::slice::from_ref(item), nested_fields(field)),
+    }
+}
+
+/// Gives every table described by a nested struct schema its own
+/// `[header]`, so its fields can carry comments.
+pub(crate) fn promote_template_sections(table: &mut Table<'_>, fields: &[FieldSchema]) {
+    for field in fields {
+        let nested = nested_fields(field);
+        if field.flatten {
+            promote_template_sections(table, nested);
+            continue;
+        }
+        if nested.is_empty() {
+            continue;
+        }
+        if let Some(sub) = table.get_mut(field.key).and_then(Item::as_table_mut) {
+            sub.set_style(TableStyle::Header);
+            promote_template_sections(sub, nested);
+        }
+    }
+}
+
+/// Leading comments the emitter writes for a table about to be emitted.
+///
+/// Entries are keyed by the address of the item they precede; for an
+/// array of tables that is its first element. Placeholders for omitted
+/// fields that no later body entry can carry are keyed by the address of
+/// their table and written after its body.
+#[derive(Default)]
+pub(crate) struct DocComments {
+    entries: HashMap<usize, EntryComments>,
+    trailing: HashMap<usize, Vec<Placeholder>>,
+}
+
+struct EntryComments {
+    /// Omitted optional fields listed before the entry.
+    placeholders: Vec<Placeholder>,
+    doc: &'static [&'static str],
+    /// Whether the entry, with everything nested in it, is commented out.
+    commented: bool,
+}
+
+/// An omitted optional field, written as `# key =`.
+struct Placeholder {
+    key: &'static str,
+    doc: &'static [&'static str],
+}
+
+impl DocComments {
+    /// Comments for a template: every field gets its doc comment, optional
+    /// fields are commented out and omitted ones are written as `# key =`.
+    pub(crate) fn template(table: &Table<'_>, fields: &'static [FieldSchema]) -> DocComments {
+        let mut comments = DocComments::default();
+        let mut pending = Vec::new();
+        comments.template_table(table, fields, false, &mut pending);
+        comments.push_trailing(table, pending);
+        comments
+    }
+
+    /// Doc comments for the keys and section headers that have no
+    /// counterpart in `source`.
+    ///
+    /// `source` is the table of the formatting reference, if any; keys
+    /// found there keep whatever comments the reference gave them.
+    /// `renames` lists the `(path, new)` key renames applied during
+    /// reprojection.
+    pub(crate) fn fresh(
+        table: &Table<'_>,
+        fields: &'static [FieldSchema],
+        source: Option<&Table<'_>>,
+        renames: &[(Vec<&str>, &str)],
+    ) -> DocComments {
+        let mut comments = DocComments::default();
+        let mut path = Vec::new();
+        comments.fresh_table(table, fields, source, renames, &mut path);
+        comments
+    }
+
+    /// Records the comments for the fields of one table. Omitted optional
+    /// fields are collected in `pending` until a body entry can carry them.
+    /// Inside a commented-out section nothing more is commented out.
+    fn template_table(
+        &mut self,
+        table: &Table<'_>,
+        fields: &'static [FieldSchema],
+        commented: bool,
+        pending: &mut Vec<Placeholder>,
+    ) {
+        for field in fields {
+            if field.flatten {
+                self.template_table(table, nested_fields(field), commented, pending);
+                continue;
+            }
+            let optional = !field.is_required();
+            let Some(item) = table.get(field.key) else {
+                if optional {
+                    pending.push(Placeholder {
+                        key: field.key,
+                        doc: field.doc,
+                    });
+                }
+                continue;
+            };
+            let Some(anchor) = anchor(item) else {
+                continue;
+            };
+            let section = is_section(item);
+            if section {
+                let (tables, fields) = section_tables(item, field);
+                for sub in tables.iter().filter_map(Item::as_table) {
+                    let mut inner = Vec::new();
+                    self.template_table(sub, fields, commented || optional, &mut inner);
+                    self.push_trailing(sub, inner);
+                }
+            }
+            let placeholders = if section {
+                Vec::new()
+            } else {
+                std::mem::take(pending)
+            };
+            self.entries.insert(
+                anchor,
+                EntryComments {
+                    placeholders,
+                    doc: field.doc,
+                    commented: optional && !commented,
+                },
+            );
+        }
+    }
+
+    /// `path` is the key path of `source` in the formatting reference.
+    fn fresh_table<'a>(
+        &mut self,
+        table: &Table<'_>,
+        fields: &'static [FieldSchema],
+        source: Option<&Table<'a>>,
+        renames: &[(Vec<&str>, &str)],
+        path: &mut Vec<&'a str>,
+    ) {
+        for field in fields {
+            if field.flatten {
+                self.fresh_table(table, nested_fields(field), source, renames, path);
+                continue;
+            }
+            let Some(item) = table.get(field.key) else {
+                continue;
+            };
+            let previous = source.and_then(|source| {
+                source.get_key_value(field.key).or_else(|| {
+                    let old = renames.iter().find_map(|(old, new)| {
+                        let (last, parent) = old.split_last()?;
+                        (*new == field.key && parent == path.as_slice()).then_some(*last)
+                    })?;
+                    source.get_key_value(old)
+                })
+            });
+            if let (None, Some(anchor)) = (previous, anchor(item)) {
+                self.entries.insert(
+                    anchor,
+                    EntryComments {
+                        placeholders: Vec::new(),
+                        doc: field.doc,
+                        commented: false,
+                    },
+                );
+            }
+            if is_section(item) {
+                if let Some((previous_key, _)) = previous {
+                    path.push(previous_key.name);
+                }
+                // Array elements are compared with the reference element at
+                // the same position.
+                let (tables, fields) = section_tables(item, field);
+                for (i, sub) in tables.iter().enumerate() {
+                    let Some(sub) = sub.as_table() else {
+                        continue;
+                    };
+                    let source = previous.and_then(|(_, previous)| match previous.as_array() {
+                        Some(array) => array.get(i)?.as_table(),
+                        None => previous.as_table(),
+                    });
+                    self.fresh_table(sub, fields, source, renames, path);
+                }
+                if previous.is_some() {
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    fn push_trailing(&mut self, table: &Table<'_>, placeholders: Vec<Placeholder>) {
+        if !placeholders.is_empty() {
+            self.trailing.insert(address(table), placeholders);
+        }
+    }
+
+    /// Writes the comments recorded for `item` and returns whether it is to
+    /// be commented out.
+    pub(crate) fn write_leading(&self, item: &Item<'_>, out: &mut Vec<u8>) -> bool {
+        let Some(entry) = self.entries.get(&address(item)) else {
+            return false;
+        };
+        write_placeholders(&entry.placeholders, out);
+        write_doc(entry.doc, out);
+        entry.commented
+    }
+
+    /// Writes the placeholders that follow the body of `table`.
+    pub(crate) fn write_trailing(&self, table: &Table<'_>, out: &mut Vec<u8>) {
+        if let Some(placeholders) = self.trailing.get(&address(table)) {
+            write_placeholders(placeholders, out);
+        }
+    }
+}
+
+/// Comments out every non-empty line of `out` from `start` on.
+pub(crate) fn comment_out(out: &mut Vec<u8>, start: usize) {
+    let tail = out.split_off(start);
+    for line in tail.split_inclusive(|&b| b == b'\n') {
+        if line != b"\n" {
+            out.extend_from_slice(b"# ");
+        }
+        out.extend_from_slice(line);
+    }
+}
+
+fn write_placeholders(placeholders: &[Placeholder], out: &mut Vec<u8>) {
+    for placeholder in placeholders {
+        write_doc(placeholder.doc, out);
+        out.extend_from_slice(b"# ");
+        super::format_key(placeholder.key, KeyStyle::Bare, out);
+        out.extend_from_slice(b" =\n");
+    }
+}
+
+fn write_doc(doc: &[&str], out: &mut Vec<u8>) {
+    for line in doc.iter().flat_map(|attr| attr.split('\n')) {
+        let line = line.strip_prefix(' ').unwrap_or(line).trim_end();
+        out.push(b'#');
+        if !line.is_empty() {
+            out.push(b' ');
+            out.extend_from_slice(line.as_bytes());
+        }
+        out.push(b'\n');
+    }
+}
+
+fn address<T>(value: &T) -> usize {
+    value as *const T as usize
+}
+
+/// The item the emitter writes first for `item`: the first element of an
+/// array of tables, otherwise the item itself.
+fn anchor(item: &Item<'_>) -> Option<usize> {
+    match item.as_array() {
+        Some(array) if item.is_aot() => array.get(0).map(address),
+        _ => Some(address(item)),
+    }
+}
+
+fn is_section(item: &Item<'_>) -> bool {
+    item.has_header_bit() || item.is_aot()
+}
@@ -1,6 +1,9 @@
 use crate::emit::{EmitConfig, emit_with_config};
 use crate::item::{Item, Value};
-use crate::{Arena, Array, ArrayStyle, Key, Table, TableStyle, parse};
+use crate::{
+    Arena, Array, ArrayStyle, FloatRepr, IntegerRadix, IntegerRepr, Key, KeyStyle, StringRepr,
+    Table, TableStyle, parse,
+};
 
 use crate::emit::test_data::{parse_test_cases, run_cases};
 
@@ -916,6 +919,87 @@ fn auto_style_array_mixed_types_downgraded_from_header() {
     assert!(!s2.contains("[["), "must not contain AOT header: {s2}");
 }
 
+#[track_caller]
+fn emit_with_policy(table: &mut Table<'_>, policy: crate::AutoStyle) -> String {
+    emit_table(table.normalize_with(&policy))
+}
+
+#[test]
+fn auto_style_policy_inline_limits() {
+    let arena = Arena::new();
+    let policy = crate::AutoStyle::new().with_max_inline_entries(3);
+    let mut root = table! { in arena; a: { x: 1, y: 2, z: 3 } };
+    assert_eq!(
+        emit_with_policy(&mut root, policy),
+        "a = { x = 1, y = 2, z = 3 }\n"
+    );
+
+    let policy = crate::AutoStyle::new().with_max_inline_entries(0);
+    let mut root = table! { in arena; a: { x: 1 }, e: {} };
+    assert_eq!(
+        emit_with_policy(&mut root, policy),
+        "e = {}\n\n[a]\nx = 1\n"
+    );
+
+    let policy = crate::AutoStyle::new().with_max_inline_width(16);
+    let mut root = table! { in arena; a: { x: 1 }, b: { long_key: 1 } };
+    assert_eq!(
+        emit_with_policy(&mut root, policy),
+        "a = { x = 1 }\n\n[b]\nlong_key = 1\n"
+    );
+
+    // The default policy has no width limit, only the entry count.
+    let mut root = table! { in arena;
+        a: { first_long_key_name: "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", second_long_key_name: "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb" }
+    };
+    assert_eq!(
+        emit_normalized(&mut root),
+        "a = { first_long_key_name = \"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\", second_long_key_name = \"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\" }\n"
+    );
+}
+
+#[test]
+fn auto_style_policy_max_header_depth() {
+    let arena = Arena::new();
+    let mut root = table! { in arena;
+        deps: { serde: { version: "1", features: ["derive"], optional: true } }
+    };
+    assert_eq!(
+        emit_with_policy(&mut root, crate::AutoStyle::new().with_max_header_depth(1)),
+        "[deps]\nserde = { version = \"1\", features = [\"derive\"], optional = true }\n"
+    );
+
+    let mut root = table! { in arena;
+        deps: { serde: { version: "1", features: ["derive"], optional: true } }
+    };
+    assert_eq!(
+        emit_normalized(&mut root),
+        "[deps.serde]\nversion = \"1\"\nfeatures = [\"derive\"]\noptional = true\n"
+    );
+}
+
+#[test]
+fn auto_style_policy_dotted_single_keys() {
+    let arena = Arena::new();
+    let policy = crate::AutoStyle::new().with_dotted_single_keys(true);
+    let mut root = table! { in arena; a: { x: 1 }, b: { x: 1, y: 2 } };
+    let s = emit_with_policy(&mut root, policy);
+    assert_eq!(s, "a.x = 1\nb = { x = 1, y = 2 }\n");
+    let doc = parse(&s, &arena).unwrap();
+    assert_eq!(doc["a"]["x"].as_i64(), Some(1));
+}
+
+#[test]
+fn auto_style_policy_array_of_tables_inline() {
+    let arena = Arena::new();
+    let policy = crate::AutoStyle::new().with_array_of_tables_headers(false);
+    let mut root = table! { in arena; servers: [{ host: "a" }, { host: "b" }] };
+    let s = emit_with_policy(&mut root, policy);
+    assert!(!s.contains("[["), "must not contain AOT header: {s}");
+    let doc = parse(&s, &arena).unwrap();
+    assert_eq!(doc["servers"][1]["host"].as_str(), Some("b"));
+}
+
 #[test]
 fn expanded_array_basic() {
     let arena = Arena::new();
@@ -1290,3 +1374,236 @@ fn formatting_indent_override() {
     let f = crate::Formatting::preserved_from(&doc).with_indentation(Indent::Tab);
     assert_eq!(f.indent, Indent::Tab);
 }
+
+#[test]
+fn integer_repr_hints() {
+    let arena = Arena::new();
+    let cases: &[(i64, IntegerRadix, u8, &str)] = &[
+        (493, IntegerRadix::Octal, 0, "0o755"),
+        (0xdead_beef, IntegerRadix::LowerHex, 4, "0xdead_beef"),
+        (0xdead_beef, IntegerRadix::UpperHex, 0, "0xDEADBEEF"),
+        (10, IntegerRadix::Binary, 2, "0b10_10"),
+        (1_000_000, IntegerRadix::Decimal, 3, "1_000_000"),
+        (100, IntegerRadix::Decimal, 3, "100"),
+        // Prefixed integers cannot carry a sign.
+        (-255, IntegerRadix::LowerHex, 3, "-255"),
+        (-1234, IntegerRadix::Decimal, 3, "-1_234"),
+    ];
+    for &(value, radix, grouping, expected) in cases {
+        let mut item = Item::from(value);
+        item.set_integer_repr(IntegerRepr { radix, grouping });
+        assert_eq!(item.integer_repr(), Some(IntegerRepr { radix, grouping }));
+        let mut table = Table::default();
+        table.insert_unique(Key::new("a"), item, &arena);
+        let out = emit_normalized(&mut table);
+        assert_eq!(out, format!("a = {expected}\n"));
+        let doc = parse(&out, &arena).unwrap();
+        assert_eq!(doc["a"].as_i64(), Some(value));
+    }
+}
+
+#[test]
+fn string_repr_hints() {
+    let arena = Arena::new();
+    let cases: &[(&str, StringRepr, &str)] = &[
+        ("C:\\path", StringRepr::Literal, "'C:\\path'"),
+        ("plain", StringRepr::Basic, "\"plain\""),
+        ("a\\b", StringRepr::Basic, "\"a\\\\b\""),
+        // Literal strings cannot hold a single quote.
+        ("it's", StringRepr::Literal, "\"it's\""),
+        (
+            "one\ntwo",
+            StringRepr::MultilineLiteral,
+            "\'\'\'\none\ntwo\'\'\'",
+        ),
+        ("x", StringRepr::MultilineBasic, "\"\"\"\nx\"\"\""),
+        (
+            "q\"\"\"q",
+            StringRepr::MultilineBasic,
+            "\"\"\"\nq\"\"\\\"q\"\"\"",
+        ),
+        ("end\"", StringRepr::MultilineBasic, "\"\"\"\nend\\\"\"\"\""),
+        (
+            "tab\tcr\r",
+            StringRepr::MultilineBasic,
+            "\"\"\"\ntab\\tcr\\r\"\"\"",
+        ),
+    ];
+    for &(value, repr, expected) in cases {
+        let mut item = Item::string(value);
+        item.set_string_repr(repr);
+        assert_eq!(item.string_repr(), Some(repr));
+        let mut table = Table::default();
+        table.insert_unique(Key::new("s"), item, &arena);
+        let out = emit_normalized(&mut table);
+        assert_eq!(out, format!("s = {expected}\n"));
+        let doc = parse(&out, &arena).unwrap();
+        assert_eq!(doc["s"].as_str(), Some(value));
+    }
+}
+
+#[test]
+fn float_repr_hints() {
+    let arena = Arena::new();
+    let cases: &[(f64, FloatRepr, &str)] = &[
+        (1_500_000.0, FloatRepr::Exponent, "1.5e6"),
+        (1_500_000.0, FloatRepr::UpperExponent, "1.5E6"),
+        (1e20, FloatRepr::Decimal, "100000000000000000000.0"),
+        (0.25, FloatRepr::Decimal, "0.25"),
+        (2.0, FloatRepr::Exponent, "2e0"),
+        (f64::INFINITY, FloatRepr::Exponent, "inf"),
+    ];
+    for &(value, repr, expected) in cases {
+        let mut item = Item::from(value);
+        item.set_float_repr(repr);
+        assert_eq!(item.float_repr(), Some(repr));
+        let mut table = Table::default();
+        table.insert_unique(Key::new("f"), item, &arena);
+        let out = emit_normalized(&mut table);
+        assert_eq!(out, format!("f = {expected}\n"));
+        let doc = parse(&out, &arena).unwrap();
+        assert_eq!(doc["f"].as_f64(), Some(value));
+    }
+}
+
+#[test]
+fn parsed_scalars_record_spelling() {
+    let arena = Arena::new();
+    let src = "a = 0xFF_FF\nb = -1_000\nc = 0o755\nd = 'lit'\ne = '''\nml'''\n\
+               f = \"\"\"ml\"\"\"\ng = \"basic\"\nh = 1.5E3\ni = 2e-1\nj = 0.5\nk = inf\n";
+    let doc = parse(src, &arena).unwrap();
+    let int = |key: &str| doc[key].item().unwrap().integer_repr();
+    let string = |key: &str| doc[key].item().unwrap().string_repr();
+    let float = |key: &str| doc[key].item().unwrap().float_repr();
+    let repr = |radix, grouping| Some(IntegerRepr { radix, grouping });
+    assert_eq!(int("a"), repr(IntegerRadix::UpperHex, 2));
+    assert_eq!(int("b"), repr(IntegerRadix::Decimal, 3));
+    assert_eq!(int("c"), repr(IntegerRadix::Octal, 0));
+    assert_eq!(string("d"), Some(StringRepr::Literal));
+    assert_eq!(string("e"), Some(StringRepr::MultilineLiteral));
+    assert_eq!(string("f"), Some(StringRepr::MultilineBasic));
+    assert_eq!(string("g"), Some(StringRepr::Basic));
+    assert_eq!(float("h"), Some(FloatRepr::UpperExponent));
+    assert_eq!(float("i"), Some(FloatRepr::Exponent));
+    assert_eq!(float("j"), Some(FloatRepr::Decimal));
+    assert_eq!(float("k"), None);
+
+    // Spans are untouched by the packed spelling.
+    for (key, text) in [
+        ("a", "0xFF_FF"),
+        ("b", "-1_000"),
+        ("e", "'''\nml'''"),
+        ("h", "1.5E3"),
+    ] {
+        assert_eq!(&src[doc[key].item().unwrap().span().range()], text);
+    }
+}
+
+#[test]
+fn oversized_literal_keeps_span_without_spelling() {
+    let arena = Arena::new();
+    let src = format!("a = '{}'\n", "x".repeat(1 << 20));
+    let mut table = parse(&src, &arena).unwrap().into_table();
+    let item = table.get_mut("a").unwrap();
+    assert_eq!(item.span().end as usize, src.len() - 1);
+    assert_eq!(item.string_repr(), None);
+    item.set_string_repr(StringRepr::Literal);
+    assert_eq!(item.string_repr(), Some(StringRepr::Literal));
+}
+
+#[test]
+fn setting_repr_keeps_parsed_span() {
+    let arena = Arena::new();
+    let src = "a = 255\nb = 'x'\nc = 1.5\n";
+    let mut table = parse(src, &arena).unwrap().into_table();
+    let spans: Vec<_> = ["a", "b", "c"]
+        .iter()
+        .map(|key| table[*key].item().unwrap().span())
+        .collect();
+
+    table.get_mut("a").unwrap().set_integer_repr(IntegerRepr {
+        radix: IntegerRadix::LowerHex,
+        grouping: 0,
+    });
+    table
+        .get_mut("b")
+        .unwrap()
+        .set_string_repr(StringRepr::Basic);
+    table
+        .get_mut("c")
+        .unwrap()
+        .set_float_repr(FloatRepr::Exponent);
+
+    for (key, span) in ["a", "b", "c"].iter().zip(spans) {
+        assert_eq!(table[*key].item().unwrap().span(), span, "{key}");
+    }
+    let item = table["a"].item().unwrap();
+    assert_eq!(item.integer_repr().unwrap().radix, IntegerRadix::LowerHex);
+    assert_eq!(item.flag(), 0);
+    assert_eq!(
+        table["b"].item().unwrap().string_repr(),
+        Some(StringRepr::Basic)
+    );
+    assert_eq!(
+        table["c"].item().unwrap().float_repr(),
+        Some(FloatRepr::Exponent)
+    );
+}
+
+#[test]
+fn repr_hints_ignore_other_kinds() {
+    let mut item = Item::from(1i64);
+    item.set_string_repr(StringRepr::Literal);
+    assert_eq!(item.string_repr(), None);
+    assert_eq!(item.integer_repr(), None);
+
+    let mut item = Item::from(1.5f64);
+    item.set_integer_repr(IntegerRepr {
+        radix: IntegerRadix::Octal,
+        grouping: 0,
+    });
+    assert_eq!(item.integer_repr(), None);
+    assert_eq!(item.float_repr(), None);
+}
+
+#[test]
+fn key_style_preserved_without_source() {
+    let input = "\"a\" = 1\n'b'.c = 2\nd = { 'e' = 3 }\n\n[\"s\".'t']\nx = 1\n";
+    assert_eq!(run_emit(input), input);
+}
+
+#[test]
+fn key_style_overrides_parsed_keys() {
+    let arena = Arena::new();
+    let mut table = parse("'a' = 1\n\"b\" = 2\nc = 3\n", &arena)
+        .unwrap()
+        .into_table();
+    assert!(table.set_key_style("a", KeyStyle::Bare));
+    assert!(table.set_key_style("b", KeyStyle::Literal));
+    assert!(table.set_key_style("c", KeyStyle::Basic));
+    assert_eq!(table.key_style("a"), Some(KeyStyle::Bare));
+    assert_eq!(table.key_style("b"), Some(KeyStyle::Literal));
+    assert_eq!(table.key_style("c"), Some(KeyStyle::Basic));
+    assert_eq!(emit_normalized(&mut table), "a = 1\n'b' = 2\n\"c\" = 3\n");
+}
+
+#[test]
+fn key_style_applied_to_new_keys() {
+    let arena = Arena::new();
+    let mut table = Table::default();
+    table.insert_unique(Key::new("a"), Item::from(1i64), &arena);
+    table.insert_unique(Key::new("b"), Item::from(2i64), &arena);
+    table.insert_unique(Key::new("it's"), Item::from(3i64), &arena);
+    table.insert_unique(Key::new("c d"), Item::from(4i64), &arena);
+    assert!(table.set_key_style("a", KeyStyle::Basic));
+    assert!(table.set_key_style("b", KeyStyle::Literal));
+    // Names that cannot take the requested style fall back to the default.
+    assert!(table.set_key_style("it's", KeyStyle::Literal));
+    assert!(table.set_key_style("c d", KeyStyle::Bare));
+    assert!(!table.set_key_style("missing", KeyStyle::Basic));
+    assert_eq!(table.key_style("a"), Some(KeyStyle::Basic));
+    assert_eq!(
+        emit_normalized(&mut table),
+        "\"a\" = 1\n'b' = 2\n\"it's\" = 3\n\"c d\" = 4\n"
+    );
+}
@@ -5,7 +5,7 @@ use crate::Item;
 use crate::{Key, Span};
 use std::fmt::{self, Debug, Display};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PathComponent<'de> {
     Key(Key<'de>),
     Index(usize),
@@ -52,6 +52,18 @@ impl<'a> std::ops::Deref for TomlPath<'a> {
     }
 }
 
+#[cfg(feature = "from-toml")]
+impl<'a> TomlPath<'a> {
+    pub(crate) fn empty() -> &'static TomlPath<'static> {
+        TomlPath::new(&[])
+    }
+
+    pub(crate) fn new<'b>(components: &'b [PathComponent<'a>]) -> &'b TomlPath<'a> {
+        // SAFETY: TomlPath is a transparent wrapper around the slice.
+        unsafe { &*(components as *const [PathComponent<'a>] as *const TomlPath<'a>) }
+    }
+}
+
 impl Debug for TomlPath<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut out = String::new();
@@ -192,7 +204,7 @@ impl MaybeTomlPath {
         self.ptr.as_ptr() as *const ()
     }
 
-    fn as_toml_path<'a>(&'a self) -> Option<&'a TomlPath<'a>> {
+    pub(crate) fn as_toml_path<'a>(&'a self) -> Option<&'a TomlPath<'a>> {
         if !self.has_path() {
             return None;
         }
@@ -253,8 +265,9 @@ unsafe impl Sync for MaybeTomlPath {}
 /// | [`message(source)`](Self::message)            | Human-readable diagnostic message                               |
 /// | [`primary_label()`](Self::primary_label)      | Optional `(Span, String)` label for the error site              |
 /// | [`secondary_label()`](Self::secondary_label)  | Optional `(Span, String)` for related locations                 |
+/// | [`notes()`](Self::notes)                      | `(Span, String)` notes, such as why each enum variant failed    |
 ///
-/// The `message`, `primary_label`, and `secondary_label` methods provide
+/// The `message`, `primary_label`, `secondary_label`, and `notes` methods provide
 /// building blocks for rich diagnostics, mapping onto the label model used by
 /// [`codespan-reporting`](https://docs.rs/codespan-reporting) and
 /// [`annotate-snippets`](https://docs.rs/annotate-snippets).
@@ -275,6 +288,9 @@ unsafe impl Sync for MaybeTomlPath {}
 ///     if let Some((span, text)) = error.secondary_label() {
 ///         labels.push(Label::secondary((), span).with_message(text));
 ///     }
+///     for (span, text) in error.notes() {
+///         labels.push(Label::secondary((), span).with_message(text));
+///     }
 ///     if let Some((span, label)) = error.primary_label() {
 ///         let l = Label::primary((), span);
 ///         labels.push(if label.is_empty() {
@@ -310,6 +326,11 @@ unsafe impl Sync for MaybeTomlPath {}
 ///             AnnotationKind::Context.span(span.range()).label(text),
 ///         );
 ///     }
+///     for (span, text) in error.notes() {
+///         snippet = snippet.annotation(
+///             AnnotationKind::Context.span(span.range()).label(text),
+///         );
+///     }
 ///     if let Some((span, label)) = error.primary_label() {
 ///         let ann = AnnotationKind::Primary.span(span.range());
 ///         snippet = snippet.annotation(if label.is_empty() {
@@ -346,6 +367,30 @@ pub struct Error {
 pub(crate) enum ErrorInner {
     Static(ErrorKind<'static>),
     Custom(Box<str>),
+    /// An unexpected key close to one of the accepted keys.
+    #[cfg(feature = "from-toml")]
+    UnexpectedKey {
+        tag: u32,
+        suggestion: Box<str>,
+    },
+    /// An unknown variant close to one of the accepted names.
+    #[cfg(feature = "from-toml")]
+    UnexpectedVariant {
+        expected: &'static [&'static str],
+        suggestion: &'static str,
+    },
+    /// A value matching no variant of an untagged enum. Boxed to keep
+    /// [`Error`] small.
+    #[cfg(feature = "from-toml")]
+    NoMatchingVariant(Box<NoMatchingVariant>),
+}
+
+#[cfg(feature = "from-toml")]
+pub(crate) struct NoMatchingVariant {
+    pub(crate) expected: &'static [&'static str],
+    /// The description of each attempted variant with each error it was
+    /// rejected with.
+    pub(crate) notes: Vec<(&'static str, Error)>,
 }
 /// The specific kind of error.
 #[non_exhaustive]
@@ -462,6 +507,30 @@ pub enum ErrorKind<'a> {
         first: Span,
     },
 
+    /// Two fields that cannot be set together were both defined.
+    ConflictingFields {
+        /// The key defined later in the table
+        field: &'static str,
+        /// The key it conflicts with
+        other: &'static str,
+        /// The span where the conflicting key was defined
+        first: Span,
+    },
+
+    /// A field was defined without another field it requires.
+    MissingRequiredBy {
+        /// The missing key
+        field: &'static str,
+        /// The key that requires it
+        by: &'static str,
+    },
+
+    /// None of a set of fields, exactly one of which is required, was defined.
+    MissingOneOf {
+        /// The keys, one of which must be defined
+        fields: &'static [&'static str],
+    },
+
     /// A field in the table is deprecated and the new key should be used instead
     Deprecated {
         /// Developer provided association tag useful for programmatic filtering
@@ -474,6 +543,26 @@ pub enum ErrorKind<'a> {
         new: &'static &'static str,
     },
 
+    /// An enum variant was named by a deprecated alias and the new name
+    /// should be used instead
+    DeprecatedVariant {
+        /// Developer provided association tag useful for programmatic filtering
+        /// or adding additional messages or notes to diagnostics such as version
+        /// info. Defaults to 0.
+        tag: u32,
+        /// The deprecated variant name
+        old: &'static &'static str,
+        /// The variant name that should be used instead
+        new: &'static &'static str,
+    },
+
+    /// A key matched a field only after ignoring case or separators, see
+    /// `#[toml(key_match = "...")]` on the derive.
+    NonCanonicalKey {
+        /// The spelling the key is expected to use
+        canonical: &'static &'static str,
+    },
+
     /// An unexpected value was encountered
     UnexpectedValue {
         /// The list of values that could have been used
@@ -486,6 +575,14 @@ pub enum ErrorKind<'a> {
         expected: &'static [&'static str],
     },
 
+    /// A value matched none of the variants of an untagged enum.
+    ///
+    /// [`Error::notes`] explains why each attempted variant was rejected.
+    NoMatchingVariant {
+        /// Descriptions of the shapes accepted by the variants
+        expected: &'static [&'static str],
+    },
+
     /// A comma is missing between elements in an array.
     MissingArrayComma,
 
@@ -525,9 +622,15 @@ impl<'a> ErrorKind<'a> {
             ErrorKind::UnquotedString => "UnquotedString",
             ErrorKind::MissingField(_) => "MissingField",
             ErrorKind::DuplicateField { .. } => "DuplicateField",
+            ErrorKind::ConflictingFields { .. } => "ConflictingFields",
+            ErrorKind::MissingRequiredBy { .. } => "MissingRequiredBy",
+            ErrorKind::MissingOneOf { .. } => "MissingOneOf",
             ErrorKind::Deprecated { .. } => "Deprecated",
+            ErrorKind::DeprecatedVariant { .. } => "DeprecatedVariant",
+            ErrorKind::NonCanonicalKey { .. } => "NonCanonicalKey",
             ErrorKind::UnexpectedValue { .. } => "UnexpectedValue",
             ErrorKind::UnexpectedVariant { .. } => "UnexpectedVariant",
+            ErrorKind::NoMatchingVariant { .. } => "NoMatchingVariant",
             ErrorKind::MissingArrayComma => "MissingArrayComma",
             ErrorKind::UnclosedArray => "UnclosedArray",
             ErrorKind::MissingInlineTableComma => "MissingInlineTableComma",
@@ -550,9 +653,44 @@ impl Error {
         match &self.kind {
             ErrorInner::Static(kind) => *kind,
             ErrorInner::Custom(error) => ErrorKind::Custom(error),
+            #[cfg(feature = "from-toml")]
+            ErrorInner::UnexpectedKey { tag, .. } => ErrorKind::UnexpectedKey { tag: *tag },
+            #[cfg(feature = "from-toml")]
+            ErrorInner::UnexpectedVariant { expected, .. } => {
+                ErrorKind::UnexpectedVariant { expected }
+            }
+            #[cfg(feature = "from-toml")]
+            ErrorInner::NoMatchingVariant(inner) => ErrorKind::NoMatchingVariant {
+                expected: inner.expected,
+            },
+        }
+    }
+
+    /// Returns the accepted name closest to a misspelled key or variant, for
+    /// [`ErrorKind::UnexpectedKey`] and [`ErrorKind::UnexpectedVariant`]
+    /// errors close enough to one.
+    pub fn suggestion(&self) -> Option<&str> {
+        match &self.kind {
+            #[cfg(feature = "from-toml")]
+            ErrorInner::UnexpectedKey { suggestion, .. } => Some(suggestion),
+            #[cfg(feature = "from-toml")]
+            ErrorInner::UnexpectedVariant { suggestion, .. } => Some(suggestion),
+            _ => None,
         }
     }
 
+    /// Returns a hint for fixing the error, such as ``did you mean `port`?``
+    /// for a misspelled key or variant. The name itself is available through
+    /// [`suggestion`](Self::suggestion).
+    pub fn help(&self) -> Option<String> {
+        let suggestion = self.suggestion()?;
+        let mut out = String::new();
+        s_push(&mut out, "did you mean `");
+        s_push(&mut out, suggestion);
+        s_push(&mut out, "`?");
+        Some(out)
+    }
+
     /// Returns the TOML path where this error occurred, if available.
     pub fn path<'a>(&'a self) -> Option<&'a TomlPath<'a>> {
         self.path.as_toml_path()
@@ -750,6 +888,31 @@ fn kind_message_inner(kind: ErrorKind<'_>, out: &mut String) {
             s_push(out, field);
             s_push_char(out, '\'');
         }
+        ErrorKind::ConflictingFields { field, other, .. } => {
+            s_push(out, "key '");
+            s_push(out, field);
+            s_push(out, "' cannot be used together with '");
+            s_push(out, other);
+            s_push_char(out, '\'');
+        }
+        ErrorKind::MissingRequiredBy { field, by } => {
+            s_push(out, "missing key '");
+            s_push(out, field);
+            s_push(out, "', required by '");
+            s_push(out, by);
+            s_push_char(out, '\'');
+        }
+        ErrorKind::MissingOneOf { fields } => {
+            s_push(out, "missing one of the keys: ");
+            let mut first = true;
+            for field in fields {
+                if !first {
+                    s_push(out, ", ");
+                }
+                first = false;
+                s_push(out, field);
+            }
+        }
         ErrorKind::Deprecated { old, new, .. } => {
             s_push(out, "key '");
             s_push(out, old);
@@ -757,6 +920,18 @@ fn kind_message_inner(kind: ErrorKind<'_>, out: &mut String) {
             s_push(out, new);
             s_push(out, "' instead");
         }
+        ErrorKind::DeprecatedVariant { old, new, .. } => {
+            s_push(out, "variant '");
+            s_push(out, old);
+            s_push(out, "' is deprecated, use '");
+            s_push(out, new);
+            s_push(out, "' instead");
+        }
+        ErrorKind::NonCanonicalKey { canonical } => {
+            s_push(out, "key should be spelled '");
+            s_push(out, canonical);
+            s_push_char(out, '\'');
+        }
         ErrorKind::UnexpectedValue { expected } => {
             s_push(out, "unexpected value, expected one of: ");
             let mut first = true;
@@ -779,6 +954,17 @@ fn kind_message_inner(kind: ErrorKind<'_>, out: &mut String) {
                 s_push(out, val);
             }
         }
+        ErrorKind::NoMatchingVariant { expected } => {
+            s_push(out, "expected one of: ");
+            let mut first = true;
+            for val in expected {
+                if !first {
+                    s_push(out, ", ");
+                }
+                first = false;
+                s_push(out, val);
+            }
+        }
         ErrorKind::MissingArrayComma => {
             s_push(out, "missing comma between elements, expected `,` in array");
         }
@@ -799,7 +985,9 @@ impl Display for Error {
         if let Some(path) = self.path() {
             let components: &[PathComponent<'_>] = path;
             let display = match self.kind() {
-                ErrorKind::DuplicateField { .. } | ErrorKind::Deprecated { .. } => {
+                ErrorKind::DuplicateField { .. }
+                | ErrorKind::Deprecated { .. }
+                | ErrorKind::NonCanonicalKey { .. } => {
                     &components[..components.len().saturating_sub(1)]
                 }
                 _ => components,
@@ -812,6 +1000,10 @@ impl Display for Error {
                 f.write_str("`")?;
             }
         }
+        if let Some(help) = self.help() {
+            f.write_str(", ")?;
+            f.write_str(&help)?;
+        }
         Ok(())
     }
 }
@@ -900,22 +1092,33 @@ impl Error {
     /// Returns the diagnostic message for this error, without the TOML path.
     ///
     /// Some error kinds extract names from `source` for richer messages.
+    /// When [`help`](Self::help) has a hint, it follows on a `help:` line.
     pub fn message(&self, source: &str) -> String {
         let mut out = String::new();
         self.message_inner(source, &mut out);
+        self.append_help(&mut out);
         out
     }
 
     /// Returns the diagnostic message for this error, with the TOML path appended.
     ///
     /// Some error kinds extract names from `source` for richer messages.
+    /// When [`help`](Self::help) has a hint, it follows on a `help:` line.
     pub fn message_with_path(&self, source: &str) -> String {
         let mut out = String::new();
         self.message_inner(source, &mut out);
         self.append_path(&mut out);
+        self.append_help(&mut out);
         out
     }
 
+    fn append_help(&self, out: &mut String) {
+        if let Some(help) = self.help() {
+            s_push(out, "\nhelp: ");
+            s_push(out, &help);
+        }
+    }
+
     fn append_path(&self, out: &mut String) {
         if let Some(p) = self.path() {
             let components: &[PathComponent<'_>] = p;
@@ -923,7 +1126,10 @@ impl Error {
                 ErrorKind::DuplicateKey { .. }
                 | ErrorKind::DuplicateTable { .. }
                 | ErrorKind::DuplicateField { .. }
-                | ErrorKind::Deprecated { .. } => &components[..components.len().saturating_sub(1)],
+                | ErrorKind::Deprecated { .. }
+                | ErrorKind::NonCanonicalKey { .. } => {
+                    &components[..components.len().saturating_sub(1)]
+                }
                 _ => components,
             };
             if !display.is_empty() {
@@ -984,6 +1190,17 @@ impl Error {
                     kind_message_inner(kind, out);
                 }
             }
+            ErrorKind::NonCanonicalKey { canonical } => {
+                if let Some(key_name) = source.get(span.range()) {
+                    s_push(out, "key '");
+                    s_push(out, key_name);
+                    s_push(out, "' should be spelled '");
+                    s_push(out, canonical);
+                    s_push_char(out, '\'');
+                } else {
+                    kind_message_inner(kind, out);
+                }
+            }
             ErrorKind::UnexpectedVariant { .. } => {
                 if let Some(value) = source.get(span.range()) {
                     s_push(out, "unknown variant ");
@@ -1059,7 +1276,20 @@ impl Error {
                 s_push_char(out, '\'');
             }
             ErrorKind::DuplicateField { .. } => s_push(out, "duplicate key"),
+            ErrorKind::ConflictingFields { .. } => s_push(out, "conflicting key"),
+            ErrorKind::MissingRequiredBy { field, .. } => {
+                s_push(out, "requires '");
+                s_push(out, field);
+                s_push_char(out, '\'');
+            }
+            ErrorKind::MissingOneOf { .. } => s_push(out, "missing key"),
             ErrorKind::Deprecated { .. } => s_push(out, "deprecated key"),
+            ErrorKind::DeprecatedVariant { .. } => s_push(out, "deprecated variant"),
+            ErrorKind::NonCanonicalKey { canonical } => {
+                s_push(out, "expected '");
+                s_push(out, canonical);
+                s_push_char(out, '\'');
+            }
             ErrorKind::UnexpectedValue { .. } => s_push(out, "unexpected value"),
             ErrorKind::UnexpectedVariant { expected } => {
                 s_push(out, "expected one of: ");
@@ -1072,6 +1302,7 @@ impl Error {
                     s_push(out, val);
                 }
             }
+            ErrorKind::NoMatchingVariant { .. } => s_push(out, "matches no variant"),
             ErrorKind::MissingArrayComma => s_push(out, "expected `,`"),
             ErrorKind::UnclosedArray => s_push(out, "expected `]`"),
             ErrorKind::MissingInlineTableComma => s_push(out, "expected `,`"),
@@ -1100,8 +1331,81 @@ impl Error {
             ErrorKind::DottedKeyInvalidType { first } => (first, "non-table"),
             ErrorKind::RedefineAsArray { first } => (first, "first defined as table"),
             ErrorKind::DuplicateField { first, .. } => (first, "first defined here"),
+            ErrorKind::ConflictingFields { first, .. } => (first, "conflicts with this key"),
             _ => return None,
         };
         Some((first, String::from(text)))
     }
+
+    /// Returns notes pointing at related locations, each with a message.
+    ///
+    /// For [`ErrorKind::NoMatchingVariant`], there is one note per error an
+    /// attempted variant was rejected with, giving the shape it expected.
+    pub fn notes(&self) -> Vec<(Span, String)> {
+        #[cfg(feature = "from-toml")]
+        if let ErrorInner::NoMatchingVariant(inner) = &self.kind {
+            let mut notes = Vec::with_capacity(inner.notes.len());
+            for (expected, error) in &inner.notes {
+                let mut text = String::new();
+                s_push(&mut text, "as ");
+                s_push(&mut text, expected);
+                s_push(&mut text, ": ");
+                s_push(&mut text, &error.to_string());
+                notes.push((error.span, text));
+            }
+            return notes;
+        }
+        Vec::new()
+    }
+}
+
+/// Returns the candidate closest to `found` by edit distance, ignoring ASCII
+/// case, if it is close enough to be a plausible typo.
+///
+/// One edit is allowed per three chars of `found`, so names shorter than
+/// that only match candidates differing in case.
+#[cfg(feature = "from-toml")]
+pub(crate) fn closest_match<'a>(
+    found: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = found.chars().count() / 3;
+    let mut best: Option<(usize, &'a str)> = None;
+    for candidate in candidates {
+        if candidate == found {
+            continue;
+        }
+        let distance = edit_distance(found, candidate);
+        if distance <= max_distance && best.is_none_or(|(d, _)| distance < d) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
+/// Edit distance over chars counting insertions, deletions, substitutions,
+/// and swaps of adjacent chars, where case-only differences cost nothing.
+#[cfg(feature = "from-toml")]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let b: Vec<char> = b.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut before = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (previous[j - 1] + cost)
+                .min(previous[j] + 1)
+                .min(current[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(before[j - 2] + 1);
+            }
+            current[j] = distance;
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
 }
@@ -40,6 +40,10 @@ pub(crate) const FLAG_MASK: u32 = 0x7;
 pub(crate) const FLAG_SHIFT: u32 = 3;
 
 pub(crate) const FLAG_NONE: u32 = 0;
+/// Scalars only: span-mode metadata whose `end_and_flag` packs the spelling
+/// recorded by the parser next to the length of the source text, in place of
+/// the absolute span end. No container predicate matches this value.
+pub(crate) const FLAG_SPELLED: u32 = 1;
 pub(crate) const FLAG_ARRAY: u32 = 2;
 pub(crate) const FLAG_AOT: u32 = 3;
 pub(crate) const FLAG_TABLE: u32 = 4;
@@ -53,6 +57,27 @@ pub(crate) const HINTS_BIT: u32 = 1 << 31;
 /// Bit 26 of `end_and_flag`: when set in hints mode, defers style decisions
 /// to normalization time. Resolved based on content heuristics.
 pub(crate) const AUTO_STYLE_BIT: u32 = 1 << 26;
+/// Bits 3-10 of `end_and_flag` in hints mode, or in span mode under
+/// `FLAG_SPELLED`: scalar representation hint. Bits 3-5 hold a 1-based
+/// radix, quoting or notation code (0 = no hint), bits 6-9 hold the integer
+/// digit grouping.
+const REPR_SHIFT: u32 = 3;
+pub(crate) const REPR_MASK: u32 = 0xFF << REPR_SHIFT;
+const REPR_KIND_MASK: u32 = 0x7;
+const REPR_GROUPING_SHIFT: u32 = 3;
+/// Bits 11-30 of `end_and_flag` under `FLAG_SPELLED`: length of the
+/// scalar's source text, so the span end is `start + length`.
+const SPELLED_LEN_SHIFT: u32 = 11;
+const SPELLED_LEN_LIMIT: u32 = 1 << 20;
+/// Bit 31 of `start_and_tag` in span mode: the key of the entry holding this
+/// item was written `'literal'`. Bare and basic keys are told apart by
+/// whether the key span is wider than its name.
+const KEY_LITERAL_BIT: u32 = 1 << 31;
+/// Bits 11-12 of `end_and_flag` in hints mode: quoting of the key of the
+/// entry holding this item, 0 = follow the key span as in span mode.
+const KEY_STYLE_SHIFT: u32 = 11;
+const KEY_STYLE_MASK: u32 = 0x3 << KEY_STYLE_SHIFT;
+const KEY_STYLE_LITERAL: u32 = 3;
 /// Value bits (above TAG_SHIFT) all set = "not projected".
 const NOT_PROJECTED: u32 = !(TAG_MASK); // 0xFFFF_FFF8
 
@@ -61,9 +86,14 @@ const NOT_PROJECTED: u32 = !(TAG_MASK); // 0xFFFF_FFF8
 /// Two variants discriminated by bit 31 of `end_and_flag`:
 ///
 /// **Span variant** (bit 31 = 0): items produced by the parser.
-/// - `start_and_tag`: bits 0-2 = tag, bits 3-30 = span start (28 bits, max 256 MiB)
+/// - `start_and_tag`: bits 0-2 = tag, bits 3-30 = span start (28 bits, max 256 MiB),
+///   bit 31 = literal entry key
 /// - `end_and_flag`: bits 0-2 = flag, bits 3-30 = span end (28 bits), bit 31 = 0
 ///
+/// Parsed scalars whose source text is shorter than 1 MiB use the flag
+/// `FLAG_SPELLED` instead, and `end_and_flag` holds bits 3-10 = the
+/// representation hint and bits 11-30 = span length.
+///
 /// **Format hints variant** (bit 31 = 1): items constructed programmatically.
 /// - `start_and_tag`: bits 0-2 = tag, bits 3-31 = projected index (all 1's = not projected)
 /// - `end_and_flag`: bit 31 = 1, bits 0-2 = flag, bits 3-30 = format hint bits
@@ -84,6 +114,20 @@ impl ItemMetadata {
         }
     }
 
+    /// Creates span-mode metadata for a parsed scalar that records its
+    /// spelling. Source text too long to pack keeps the plain span only.
+    #[inline]
+    pub(crate) fn spelled(tag: u32, start: u32, end: u32, repr: u32) -> Self {
+        let len = end - start;
+        if len >= SPELLED_LEN_LIMIT {
+            return Self::spanned(tag, FLAG_NONE, start, end);
+        }
+        Self {
+            start_and_tag: (start << TAG_SHIFT) | tag,
+            end_and_flag: (len << SPELLED_LEN_SHIFT) | (repr << REPR_SHIFT) | FLAG_SPELLED,
+        }
+    }
+
     /// Creates metadata in format-hints mode (programmatically constructed items).
     #[inline]
     pub(crate) fn hints(tag: u32, flag: u32) -> Self {
@@ -124,6 +168,110 @@ impl ItemMetadata {
         self.end_and_flag &= !AUTO_STYLE_BIT;
     }
 
+    /// Returns the scalar representation hint bits, or `0` if none are set
+    /// or the metadata is in span mode without a recorded spelling.
+    #[inline]
+    pub(crate) fn repr_bits(&self) -> u32 {
+        if self.is_span_mode() && self.flag() != FLAG_SPELLED {
+            0
+        } else {
+            (self.end_and_flag & REPR_MASK) >> REPR_SHIFT
+        }
+    }
+
+    /// Stores scalar representation hint bits. Span-mode metadata keeps its
+    /// span, unless the source text is too long to pack, in which case it
+    /// switches to hints mode.
+    #[inline]
+    pub(crate) fn set_repr_bits(&mut self, bits: u32) {
+        if self.is_span_mode() && self.flag() != FLAG_SPELLED {
+            let span = self.span_unchecked();
+            let spelled = ItemMetadata::spelled(self.tag(), span.start, span.end, 0);
+            if spelled.flag() == FLAG_SPELLED {
+                self.start_and_tag = spelled.start_and_tag | (self.start_and_tag & KEY_LITERAL_BIT);
+                self.end_and_flag = spelled.end_and_flag;
+            } else {
+                self.switch_to_hints();
+            }
+        }
+        self.end_and_flag = (self.end_and_flag & !REPR_MASK) | ((bits << REPR_SHIFT) & REPR_MASK);
+    }
+
+    /// Returns `end_and_flag` as it reads once switched to hints mode. Span
+    /// mode keeps only the flag, a recorded scalar spelling, and literal key
+    /// quoting, so leftover span bits are never read back as hints.
+    #[inline]
+    pub(crate) fn hints_end(&self) -> u32 {
+        if !self.is_span_mode() {
+            return self.end_and_flag;
+        }
+        let mut end = HINTS_BIT;
+        if self.flag() == FLAG_SPELLED {
+            end |= self.end_and_flag & REPR_MASK;
+        } else {
+            end |= self.flag();
+        }
+        if self.start_and_tag & KEY_LITERAL_BIT != 0 {
+            end |= KEY_STYLE_LITERAL << KEY_STYLE_SHIFT;
+        }
+        end
+    }
+
+    /// Switches to hints mode, dropping the span.
+    #[inline]
+    fn switch_to_hints(&mut self) {
+        self.end_and_flag = self.hints_end();
+        self.start_and_tag = NOT_PROJECTED | self.tag();
+    }
+
+    /// Records that the key of the entry holding this item is `'literal'`.
+    #[inline]
+    pub(crate) fn set_key_literal(&mut self) {
+        debug_assert!(self.is_span_mode());
+        self.start_and_tag |= KEY_LITERAL_BIT;
+    }
+
+    /// Returns how `key`, the key of the entry holding this item, is quoted.
+    pub(crate) fn key_style(&self, key: &Key<'_>) -> KeyStyle {
+        let bits = if self.is_span_mode() {
+            if self.start_and_tag & KEY_LITERAL_BIT != 0 {
+                KEY_STYLE_LITERAL
+            } else {
+                0
+            }
+        } else {
+            (self.end_and_flag & KEY_STYLE_MASK) >> KEY_STYLE_SHIFT
+        };
+        match bits {
+            1 => KeyStyle::Bare,
+            2 => KeyStyle::Basic,
+            3 => KeyStyle::Literal,
+            _ if (key.span.end - key.span.start) as usize > key.name.len() => KeyStyle::Basic,
+            _ => KeyStyle::Bare,
+        }
+    }
+
+    /// Pins how `key`, the key of the entry holding this item, is quoted.
+    /// Span mode keeps its span when `style` agrees with the key span, or
+    /// is `Literal`, and switches to hints mode otherwise.
+    pub(crate) fn set_key_style(&mut self, key: &Key<'_>, style: KeyStyle) {
+        if self.is_span_mode() {
+            let quoted = (key.span.end - key.span.start) as usize > key.name.len();
+            match style {
+                KeyStyle::Literal => return self.start_and_tag |= KEY_LITERAL_BIT,
+                KeyStyle::Basic if quoted => return self.start_and_tag &= !KEY_LITERAL_BIT,
+                KeyStyle::Bare if !quoted => return self.start_and_tag &= !KEY_LITERAL_BIT,
+                _ => self.switch_to_hints(),
+            }
+        }
+        let bits = match style {
+            KeyStyle::Bare => 1,
+            KeyStyle::Basic => 2,
+            KeyStyle::Literal => KEY_STYLE_LITERAL,
+        };
+        self.end_and_flag = (self.end_and_flag & !KEY_STYLE_MASK) | (bits << KEY_STYLE_SHIFT);
+    }
+
     /// Returns `true` if this metadata carries a source span (parser-produced).
     #[inline]
     pub(crate) fn is_span_mode(&self) -> bool {
@@ -142,37 +290,41 @@ impl ItemMetadata {
 
     /// Returns the source span without checking the variant.
     /// Valid only during deserialization on parser-produced items.
-    /// In span mode, bit 31 is always 0, so all bits above FLAG_SHIFT are span data.
+    /// In span mode, bit 31 is always 0, so all bits above FLAG_SHIFT are span
+    /// data, or the span length above SPELLED_LEN_SHIFT for a spelled scalar.
     #[inline]
     pub(crate) fn span_unchecked(&self) -> Span {
         debug_assert!(self.is_span_mode());
-        Span::new(
-            self.start_and_tag >> TAG_SHIFT,
-            self.end_and_flag >> FLAG_SHIFT,
-        )
+        let start = (self.start_and_tag & !KEY_LITERAL_BIT) >> TAG_SHIFT;
+        let end = if self.flag() == FLAG_SPELLED {
+            start + (self.end_and_flag >> SPELLED_LEN_SHIFT)
+        } else {
+            self.end_and_flag >> FLAG_SHIFT
+        };
+        Span::new(start, end)
     }
 
     #[inline]
     pub(crate) fn span_start(&self) -> u32 {
         debug_assert!(self.is_span_mode());
-        self.start_and_tag >> TAG_SHIFT
+        (self.start_and_tag & !KEY_LITERAL_BIT) >> TAG_SHIFT
     }
 
     #[inline]
     pub(crate) fn set_span_start(&mut self, v: u32) {
-        debug_assert!(self.is_span_mode());
-        self.start_and_tag = (v << TAG_SHIFT) | (self.start_and_tag & TAG_MASK);
+        debug_assert!(self.is_span_mode() && self.flag() != FLAG_SPELLED);
+        self.start_and_tag = (v << TAG_SHIFT) | (self.start_and_tag & (TAG_MASK | KEY_LITERAL_BIT));
     }
 
     #[inline]
     pub(crate) fn set_span_end(&mut self, v: u32) {
-        debug_assert!(self.is_span_mode());
+        debug_assert!(self.is_span_mode() && self.flag() != FLAG_SPELLED);
         self.end_and_flag = (v << FLAG_SHIFT) | (self.end_and_flag & FLAG_MASK);
     }
 
     #[inline]
     pub(crate) fn extend_span_end(&mut self, new_end: u32) {
-        debug_assert!(self.is_span_mode());
+        debug_assert!(self.is_span_mode() && self.flag() != FLAG_SPELLED);
         let old = self.end_and_flag;
         let current = old >> FLAG_SHIFT;
         self.end_and_flag = (current.max(new_end) << FLAG_SHIFT) | (old & FLAG_MASK);
@@ -237,6 +389,207 @@ pub enum ArrayStyle {
     Header,
 }
 
+/// Numeric base of an integer literal, see [`IntegerRepr`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegerRadix {
+    /// Base 10, such as `1000`.
+    Decimal,
+    /// Base 16 with lowercase digits, such as `0xff`.
+    LowerHex,
+    /// Base 16 with uppercase digits, such as `0xFF`.
+    UpperHex,
+    /// Base 8, such as `0o755`.
+    Octal,
+    /// Base 2, such as `0b1010`.
+    Binary,
+}
+
+/// How a TOML integer is spelled in source text.
+///
+/// Parsing records the spelling of every integer, and emit applies it when
+/// the value is written fresh, so a replaced integer keeps its radix and
+/// digit grouping. Read it with [`Item::integer_repr`] and pin a choice with
+/// [`Item::set_integer_repr`].
+///
+/// ```toml
+/// mode = 0o755          # Octal, no grouping
+/// mask = 0xFFFF_0000    # UpperHex, grouping 4
+/// size = 1_000_000      # Decimal, grouping 3
+/// ```
+///
+/// TOML forbids a sign on prefixed integers, so negative values are always
+/// written in decimal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntegerRepr {
+    /// Numeric base and prefix.
+    pub radix: IntegerRadix,
+    /// Number of digits between `_` separators, counted from the right.
+    /// Zero disables grouping. Values above 15 are clamped to 15.
+    pub grouping: u8,
+}
+
+impl IntegerRepr {
+    fn to_bits(self) -> u32 {
+        let radix = match self.radix {
+            IntegerRadix::Decimal => 1,
+            IntegerRadix::LowerHex => 2,
+            IntegerRadix::UpperHex => 3,
+            IntegerRadix::Octal => 4,
+            IntegerRadix::Binary => 5,
+        };
+        radix | ((self.grouping.min(15) as u32) << REPR_GROUPING_SHIFT)
+    }
+
+    fn from_bits(bits: u32) -> Option<IntegerRepr> {
+        let radix = match bits & REPR_KIND_MASK {
+            1 => IntegerRadix::Decimal,
+            2 => IntegerRadix::LowerHex,
+            3 => IntegerRadix::UpperHex,
+            4 => IntegerRadix::Octal,
+            5 => IntegerRadix::Binary,
+            _ => return None,
+        };
+        Some(IntegerRepr {
+            radix,
+            grouping: (bits >> REPR_GROUPING_SHIFT) as u8,
+        })
+    }
+
+    /// Recovers the representation from the source text of an integer literal.
+    pub(crate) fn from_source(text: &[u8]) -> IntegerRepr {
+        let (radix, digits) = match text {
+            [b'0', b'x', rest @ ..] => {
+                if rest.iter().any(u8::is_ascii_uppercase) {
+                    (IntegerRadix::UpperHex, rest)
+                } else {
+                    (IntegerRadix::LowerHex, rest)
+                }
+            }
+            [b'0', b'o', rest @ ..] => (IntegerRadix::Octal, rest),
+            [b'0', b'b', rest @ ..] => (IntegerRadix::Binary, rest),
+            _ => (IntegerRadix::Decimal, text),
+        };
+        let grouping = match digits.iter().rposition(|&b| b == b'_') {
+            Some(pos) => (digits.len() - pos - 1).min(15) as u8,
+            None => 0,
+        };
+        IntegerRepr { radix, grouping }
+    }
+}
+
+/// How a TOML string is quoted in source text.
+///
+/// Parsing records the quoting of every string, and emit applies it when the
+/// value is written fresh. Read it with [`Item::string_repr`] and pin a
+/// choice with [`Item::set_string_repr`]. A form that cannot represent the
+/// value, such as a literal string containing `'`, falls back to the default
+/// choice.
+///
+/// ```toml
+/// basic = "C:\\Users"     # Basic
+/// literal = 'C:\Users'    # Literal
+/// multi = """
+/// first
+/// second"""              # MultilineBasic
+/// raw = '''
+/// first
+/// second'''              # MultilineLiteral
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringRepr {
+    /// A double-quoted string with escapes, such as `"a\tb"`.
+    Basic,
+    /// A single-quoted string without escapes, such as `'a\b'`.
+    Literal,
+    /// A triple double-quoted string spanning lines.
+    MultilineBasic,
+    /// A triple single-quoted string spanning lines, without escapes.
+    MultilineLiteral,
+}
+
+impl StringRepr {
+    fn to_bits(self) -> u32 {
+        match self {
+            StringRepr::Basic => 1,
+            StringRepr::Literal => 2,
+            StringRepr::MultilineBasic => 3,
+            StringRepr::MultilineLiteral => 4,
+        }
+    }
+
+    fn from_bits(bits: u32) -> Option<StringRepr> {
+        match bits & REPR_KIND_MASK {
+            1 => Some(StringRepr::Basic),
+            2 => Some(StringRepr::Literal),
+            3 => Some(StringRepr::MultilineBasic),
+            4 => Some(StringRepr::MultilineLiteral),
+            _ => None,
+        }
+    }
+
+    /// Returns the representation of a string opened with `delim`.
+    pub(crate) fn from_delim(delim: u8, multiline: bool) -> StringRepr {
+        match (delim, multiline) {
+            (b'"', false) => StringRepr::Basic,
+            (b'"', true) => StringRepr::MultilineBasic,
+            (_, false) => StringRepr::Literal,
+            (_, true) => StringRepr::MultilineLiteral,
+        }
+    }
+}
+
+/// How a TOML float is spelled in source text.
+///
+/// Parsing records the notation of every finite float, and emit applies it
+/// when the value is written fresh. Read it with [`Item::float_repr`] and pin
+/// a choice with [`Item::set_float_repr`]. Infinities and NaN are always
+/// written as `inf` and `nan`.
+///
+/// ```toml
+/// ratio = 0.25      # Decimal
+/// rate = 1.5e6      # Exponent
+/// limit = 6.02E23   # UpperExponent
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatRepr {
+    /// Positional notation, such as `1500000.0`.
+    Decimal,
+    /// Scientific notation with a lowercase marker, such as `1.5e6`.
+    Exponent,
+    /// Scientific notation with an uppercase marker, such as `1.5E6`.
+    UpperExponent,
+}
+
+impl FloatRepr {
+    fn to_bits(self) -> u32 {
+        match self {
+            FloatRepr::Decimal => 1,
+            FloatRepr::Exponent => 2,
+            FloatRepr::UpperExponent => 3,
+        }
+    }
+
+    fn from_bits(bits: u32) -> Option<FloatRepr> {
+        match bits & REPR_KIND_MASK {
+            1 => Some(FloatRepr::Decimal),
+            2 => Some(FloatRepr::Exponent),
+            3 => Some(FloatRepr::UpperExponent),
+            _ => None,
+        }
+    }
+
+    /// Recovers the representation from the source text of a float literal.
+    pub(crate) fn from_source(text: &[u8]) -> FloatRepr {
+        if text.contains(&b'e') {
+            FloatRepr::Exponent
+        } else if text.contains(&b'E') {
+            FloatRepr::UpperExponent
+        } else {
+            FloatRepr::Decimal
+        }
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy)]
 struct PackedI128 {
@@ -483,6 +836,7 @@ impl<'de> Item<'de> {
         Self::raw_hints(TAG_STRING, FLAG_NONE, Payload { string: s })
     }
 
+    #[cfg(any(test, feature = "from-toml"))]
     #[inline]
     pub(crate) fn string_spanned(s: &'de str, span: Span) -> Self {
         Self::raw(
@@ -494,6 +848,16 @@ impl<'de> Item<'de> {
         )
     }
 
+    /// Creates a parsed string [`Item`] that records how it was quoted.
+    #[inline]
+    pub(crate) fn string_spelled(s: &'de str, span: Span, repr: StringRepr) -> Self {
+        Self {
+            meta: ItemMetadata::spelled(TAG_STRING, span.start, span.end, repr.to_bits()),
+            payload: Payload { string: s },
+        }
+    }
+
+    #[cfg(test)]
     #[inline]
     pub(crate) fn integer_spanned(i: i128, span: Span) -> Self {
         Self::raw(
@@ -507,6 +871,17 @@ impl<'de> Item<'de> {
         )
     }
 
+    /// Creates a parsed integer [`Item`] that records its radix and grouping.
+    #[inline]
+    pub(crate) fn integer_spelled(i: i128, span: Span, repr: IntegerRepr) -> Self {
+        Self {
+            meta: ItemMetadata::spelled(TAG_INTEGER, span.start, span.end, repr.to_bits()),
+            payload: Payload {
+                integer: Integer::from(i),
+            },
+        }
+    }
+
     #[inline]
     pub(crate) fn float_spanned(f: f64, span: Span) -> Self {
         Self::raw(
@@ -518,6 +893,15 @@ impl<'de> Item<'de> {
         )
     }
 
+    /// Creates a parsed float [`Item`] that records its notation.
+    #[inline]
+    pub(crate) fn float_spelled(f: f64, span: Span, repr: FloatRepr) -> Self {
+        Self {
+            meta: ItemMetadata::spelled(TAG_FLOAT, span.start, span.end, repr.to_bits()),
+            payload: Payload { float: f },
+        }
+    }
+
     #[inline]
     pub(crate) fn boolean(b: bool, span: Span) -> Self {
         Self::raw(
@@ -695,7 +1079,11 @@ impl<'de> Item<'de> {
     /// Prefer [`Table::style`] or [`Array::style`] for a typed alternative.
     #[inline]
     pub fn flag(&self) -> u32 {
-        self.meta.flag()
+        // Parsed scalars reuse the flag to mark their packed spelling.
+        match self.meta.flag() {
+            FLAG_SPELLED => FLAG_NONE,
+            flag => flag,
+        }
     }
 
     /// Returns the byte-offset span of this value in the source document.
@@ -728,32 +1116,32 @@ impl<'de> Item<'de> {
 
     #[inline]
     pub(crate) fn is_table(&self) -> bool {
-        self.flag() >= FLAG_TABLE
+        self.meta.flag() >= FLAG_TABLE
     }
 
     #[inline]
     pub(crate) fn is_array(&self) -> bool {
-        self.flag() & 6 == 2
+        self.meta.flag() & 6 == 2
     }
 
     #[inline]
     pub(crate) fn is_frozen(&self) -> bool {
-        self.flag() == FLAG_FROZEN
+        self.meta.flag() == FLAG_FROZEN
     }
 
     #[inline]
     pub(crate) fn is_aot(&self) -> bool {
-        self.flag() == FLAG_AOT
+        self.meta.flag() == FLAG_AOT
     }
 
     #[inline]
     pub(crate) fn has_header_bit(&self) -> bool {
-        self.flag() == FLAG_HEADER
+        self.meta.flag() == FLAG_HEADER
     }
 
     #[inline]
     pub(crate) fn has_dotted_bit(&self) -> bool {
-        self.flag() == FLAG_DOTTED
+        self.meta.flag() == FLAG_DOTTED
     }
 
     /// Returns `true` if this is an implicit intermediate table, a plain
@@ -762,7 +1150,7 @@ impl<'de> Item<'de> {
     /// parents for header sections and have no text of their own.
     #[inline]
     pub(crate) fn is_implicit_table(&self) -> bool {
-        self.flag() == FLAG_TABLE
+        self.meta.flag() == FLAG_TABLE
     }
 
     /// Splits this array item into disjoint borrows of the span field and array payload.
@@ -894,6 +1282,69 @@ impl<'de> Item<'de> {
         }
     }
 
+    /// Returns the representation hint of an integer item, if one is set.
+    ///
+    /// Parsed integers report the spelling found in the source text.
+    #[inline]
+    pub fn integer_repr(&self) -> Option<IntegerRepr> {
+        if self.tag() != TAG_INTEGER {
+            return None;
+        }
+        IntegerRepr::from_bits(self.meta.repr_bits())
+    }
+
+    /// Sets how this integer is written when emitted.
+    ///
+    /// Has no effect if the item is not an integer.
+    #[inline]
+    pub fn set_integer_repr(&mut self, repr: IntegerRepr) {
+        if self.tag() == TAG_INTEGER {
+            self.meta.set_repr_bits(repr.to_bits());
+        }
+    }
+
+    /// Returns the quoting hint of a string item, if one is set.
+    ///
+    /// Parsed strings report the quoting found in the source text.
+    #[inline]
+    pub fn string_repr(&self) -> Option<StringRepr> {
+        if self.tag() != TAG_STRING {
+            return None;
+        }
+        StringRepr::from_bits(self.meta.repr_bits())
+    }
+
+    /// Sets how this string is quoted when emitted.
+    ///
+    /// Has no effect if the item is not a string.
+    #[inline]
+    pub fn set_string_repr(&mut self, repr: StringRepr) {
+        if self.tag() == TAG_STRING {
+            self.meta.set_repr_bits(repr.to_bits());
+        }
+    }
+
+    /// Returns the notation hint of a float item, if one is set.
+    ///
+    /// Parsed finite floats report the notation found in the source text.
+    #[inline]
+    pub fn float_repr(&self) -> Option<FloatRepr> {
+        if self.tag() != TAG_FLOAT {
+            return None;
+        }
+        FloatRepr::from_bits(self.meta.repr_bits())
+    }
+
+    /// Sets the notation this float is written in when emitted.
+    ///
+    /// Has no effect if the item is not a float.
+    #[inline]
+    pub fn set_float_repr(&mut self, repr: FloatRepr) {
+        if self.tag() == TAG_FLOAT {
+            self.meta.set_repr_bits(repr.to_bits());
+        }
+    }
+
     #[doc(hidden)]
     /// Used in derive macro for style attributes
     pub fn with_style_of_array_or_table(mut self, style: TableStyle) -> Item<'de> {
@@ -1153,6 +1604,67 @@ impl<'de> Item<'de> {
         }
     }
 
+    /// Moves this item into `arena`, copying the parts it borrows from
+    /// anywhere else.
+    ///
+    /// Tables, arrays, strings, and key names already allocated in `arena`
+    /// are kept in place, so only data borrowed from other memory is copied.
+    /// The result can outlive the value the item was built from: derived
+    /// [`ToToml`](crate::ToToml) implementations using `into` or `try_into`
+    /// use this to detach the item built from a temporary proxy value.
+    pub fn into_owned_in<'a>(self, arena: &'a Arena) -> Item<'a> {
+        fn own<'x>(mut item: Item<'x>, arena: &'x Arena) -> Item<'x> {
+            item.own_in(arena);
+            item
+        }
+        let item = own(self, arena);
+        // SAFETY: own_in moved every string, key name, and non-empty
+        // container of the tree into `arena`, so the item borrows nothing
+        // that lives shorter than 'a.
+        unsafe { std::mem::transmute::<Item<'_>, Item<'a>>(item) }
+    }
+
+    fn own_in(&mut self, arena: &'de Arena) {
+        match self.tag() {
+            TAG_STRING => {
+                // SAFETY: tag == TAG_STRING guarantees payload.string is the
+                // active union field.
+                let text = unsafe { self.payload.string };
+                if !arena.contains(text.as_ptr()) {
+                    self.payload.string = arena.alloc_str(text);
+                }
+            }
+            TAG_ARRAY => {
+                // SAFETY: tag == TAG_ARRAY guarantees payload.array is the
+                // active union field.
+                let array = unsafe { &mut self.payload.array };
+                let elements = array.as_slice().as_ptr().cast::<u8>();
+                if !array.is_empty() && !arena.contains(elements) {
+                    **array = array.clone_in(arena);
+                }
+                for item in array.as_mut_slice() {
+                    item.own_in(arena);
+                }
+            }
+            TAG_TABLE => {
+                // SAFETY: tag == TAG_TABLE guarantees payload.table is the
+                // active union field.
+                let table = unsafe { &mut self.payload.table };
+                let entries = table.entries().as_ptr().cast::<u8>();
+                if !table.is_empty() && !arena.contains(entries) {
+                    **table = table.clone_in(arena);
+                }
+                for (key, item) in table.entries_mut() {
+                    if !arena.contains(key.name.as_ptr()) {
+                        key.name = arena.alloc_str(key.name);
+                    }
+                    item.own_in(arena);
+                }
+            }
+            _ => (),
+        }
+    }
+
     /// Copies this item into `target`, returning a copy with `'static` lifetime.
     ///
     /// # Safety
@@ -1258,6 +1770,30 @@ impl fmt::Debug for Item<'_> {
     }
 }
 
+/// How a TOML key is quoted in source text.
+///
+/// Parsing records the quoting of every key, and emit applies it when a key
+/// is written without source text to copy from. Read it with
+/// [`Table::key_style`] and pin a choice with [`Table::set_key_style`]. A
+/// style that cannot represent the name falls back to the default choice:
+/// bare when the name allows it, quoted otherwise.
+///
+/// ```toml
+/// name = 1      # Bare
+/// "name" = 1    # Basic
+/// 'name' = 1    # Literal
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyStyle {
+    /// An unquoted key such as `name`.
+    #[default]
+    Bare,
+    /// A double-quoted key such as `"name"`.
+    Basic,
+    /// A single-quoted key such as `'name'`.
+    Literal,
+}
+
 /// A TOML table key with its source span.
 ///
 /// Keys appear as the first element in `(`[`Key`]`, `[`Item`]`)` entry pairs
@@ -4,8 +4,8 @@ mod tests;
 
 use crate::Span;
 use crate::item::{
-    FLAG_DOTTED, FLAG_FROZEN, FLAG_HEADER, FLAG_TABLE, Item, ItemMetadata, Key, MaybeItem, NONE,
-    TAG_TABLE, TableStyle,
+    FLAG_DOTTED, FLAG_FROZEN, FLAG_HEADER, FLAG_TABLE, Item, ItemMetadata, Key, KeyStyle,
+    MaybeItem, NONE, TAG_TABLE, TableStyle,
 };
 use crate::parser::KeyRef;
 use std::mem::size_of;
@@ -554,6 +554,26 @@ impl<'de> Table<'de> {
         self.value.remove_entry(name)
     }
 
+    /// Returns the [`KeyStyle`] of the key `name`, or `None` if absent.
+    ///
+    /// Parsed keys report how they were written; inserted keys report
+    /// [`KeyStyle::Bare`] unless pinned with [`Table::set_key_style`].
+    pub fn key_style(&self, name: &str) -> Option<KeyStyle> {
+        let (key, item) = self.value.get_entry(name)?;
+        Some(item.meta.key_style(key))
+    }
+
+    /// Pins the [`KeyStyle`] used when the key `name` is emitted without
+    /// source text to copy. Returns `false` if the key is absent.
+    pub fn set_key_style(&mut self, name: &str, style: KeyStyle) -> bool {
+        let Some(index) = self.value.find_index(name) else {
+            return false;
+        };
+        let (key, item) = &mut self.value.entries_mut()[index];
+        item.meta.set_key_style(key, style);
+        true
+    }
+
     /// Returns a slice of all entries.
     #[inline]
     pub fn entries(&self) -> &[TableEntry<'de>] {
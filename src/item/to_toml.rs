@@ -1,6 +1,8 @@
 use super::array::Array;
 use super::table::Table;
-use super::{FLAG_MASK, HINTS_BIT, Item, ItemMetadata, NOT_PROJECTED, TAG_MASK, TAG_SHIFT};
+use super::{
+    FLAG_SPELLED, HINTS_BIT, Item, ItemMetadata, NOT_PROJECTED, REPR_MASK, TAG_MASK, TAG_SHIFT,
+};
 
 /// Bit 30 of `end_and_flag`: marks a full match during reprojection.
 pub(crate) const FULL_MATCH_BIT: u32 = 1 << 30;
@@ -22,6 +24,10 @@ pub(crate) const EXPANDED_BIT: u32 = 1 << 25;
 /// Bit 24 of `end_and_flag`: when set in format-hints mode, prevents this
 /// item from being reprojected during format-preserving emission.
 pub(crate) const IGNORE_SOURCE_FORMATTING_RECURSIVELY_BIT: u32 = 1 << 24;
+/// Bit 23 of `end_and_flag`: when set in format-hints mode, the key of the
+/// entry holding this item was renamed during reprojection. The key keeps
+/// the span of the source entry it replaces, but not its text.
+pub(crate) const KEY_RENAMED_BIT: u32 = 1 << 23;
 
 impl ItemMetadata {
     /// Returns the projected index (bits 3-31 of `start_and_tag`).
@@ -30,20 +36,13 @@ impl ItemMetadata {
         self.start_and_tag >> TAG_SHIFT
     }
 
-    /// Branchless mask: span mode -> FLAG_MASK (clears stale span data),
-    /// hints mode -> 0xFFFFFFFF (preserves existing hint bits).
-    #[inline]
-    fn hints_preserve_mask(&self) -> u32 {
-        ((self.end_and_flag as i32) >> 31) as u32 | FLAG_MASK
-    }
-
     /// Stores a reprojected index, preserving user-set hint bits when
     /// already in hints mode. Returns `false` if the index doesn't fit.
     #[inline]
     pub(crate) fn set_reprojected_index(&mut self, index: usize) -> bool {
         if index <= (u32::MAX >> TAG_SHIFT) as usize {
+            self.end_and_flag = self.hints_end();
             self.start_and_tag = (self.start_and_tag & TAG_MASK) | ((index as u32) << TAG_SHIFT);
-            self.end_and_flag = (self.end_and_flag & self.hints_preserve_mask()) | HINTS_BIT;
             true
         } else {
             false
@@ -54,9 +53,8 @@ impl ItemMetadata {
     /// in hints mode and clearing full-match.
     #[inline]
     pub(crate) fn set_reprojected_to_none(&mut self) {
+        self.end_and_flag = self.hints_end() & !FULL_MATCH_BIT;
         self.start_and_tag |= NOT_PROJECTED;
-        self.end_and_flag =
-            (self.end_and_flag & (self.hints_preserve_mask() & !FULL_MATCH_BIT)) | HINTS_BIT;
     }
 
     #[inline]
@@ -72,7 +70,7 @@ impl ItemMetadata {
     /// Disables source-position reordering for this table's entries.
     #[inline]
     pub(crate) fn set_ignore_source_order(&mut self) {
-        self.end_and_flag |= HINTS_BIT | IGNORE_SOURCE_ORDER_BIT;
+        self.end_and_flag = self.hints_end() | IGNORE_SOURCE_ORDER_BIT;
     }
 
     /// Returns `true` if source-position reordering is disabled.
@@ -86,7 +84,7 @@ impl ItemMetadata {
     /// Marks an array element as reordered during reprojection.
     #[inline]
     pub(crate) fn set_array_reordered(&mut self) {
-        self.end_and_flag |= HINTS_BIT | ARRAY_REORDERED_BIT;
+        self.end_and_flag = self.hints_end() | ARRAY_REORDERED_BIT;
     }
 
     /// Returns `true` if this element was reordered during array reprojection.
@@ -98,7 +96,7 @@ impl ItemMetadata {
     /// Disables copying structural styles from source during reprojection.
     #[inline]
     pub(crate) fn set_ignore_source_style(&mut self) {
-        self.end_and_flag |= HINTS_BIT | IGNORE_SOURCE_STYLE_BIT;
+        self.end_and_flag = self.hints_end() | IGNORE_SOURCE_STYLE_BIT;
     }
 
     /// Returns `true` if source-style copying is disabled for this table.
@@ -110,7 +108,7 @@ impl ItemMetadata {
 
     #[inline]
     pub(crate) fn set_expanded(&mut self) {
-        self.end_and_flag |= HINTS_BIT | EXPANDED_BIT;
+        self.end_and_flag = self.hints_end() | EXPANDED_BIT;
     }
 
     #[inline]
@@ -123,10 +121,28 @@ impl ItemMetadata {
         self.end_and_flag &= !EXPANDED_BIT;
     }
 
-    /// Prevents this item from being reprojected during format-preserving emission.
+    /// Prevents this item from being reprojected during format-preserving
+    /// emission. A spelling recorded by the parser is source formatting too,
+    /// so it is dropped rather than kept as a hint.
     #[inline]
     pub(crate) fn set_ignore_source_formatting_recursively(&mut self) {
-        self.end_and_flag |= HINTS_BIT | IGNORE_SOURCE_FORMATTING_RECURSIVELY_BIT;
+        let mut end = self.hints_end();
+        if self.flag() == FLAG_SPELLED && self.is_span_mode() {
+            end &= !REPR_MASK;
+        }
+        self.end_and_flag = end | IGNORE_SOURCE_FORMATTING_RECURSIVELY_BIT;
+    }
+
+    /// Marks the key of the entry holding this item as renamed.
+    #[inline]
+    pub(crate) fn set_key_renamed(&mut self) {
+        self.end_and_flag = self.hints_end() | KEY_RENAMED_BIT;
+    }
+
+    /// Returns `true` if the key of the entry holding this item was renamed.
+    #[inline]
+    pub(crate) fn key_renamed(&self) -> bool {
+        self.end_and_flag & (HINTS_BIT | KEY_RENAMED_BIT) == (HINTS_BIT | KEY_RENAMED_BIT)
     }
 
     /// Returns `true` if this item should skip reprojection and use formatted output.
@@ -0,0 +1,240 @@
+use crate::error::PathComponent;
+use crate::{Context, Failed, FromToml, Item, Key, Span, TomlPath};
+
+/// A value read from one configuration layer, with where it was found.
+///
+/// The field type of the partial structs generated by
+/// `#[toml(partial = Name)]`. Values are read with layer `0`, use
+/// [`Partial::with_layer`] to assign the layer of a whole partial.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layered<T> {
+    /// The inner value.
+    pub value: T,
+    /// The span of the value in the source of its layer.
+    pub span: Span,
+    /// The layer the value was read from.
+    pub layer: u32,
+}
+
+impl<T> Layered<T> {
+    /// Assigns the value to `layer`.
+    pub fn set_layer(&mut self, layer: u32) {
+        self.layer = layer;
+    }
+
+    #[doc(hidden)]
+    /// Used in derive macro: returns the value of a required field, recording
+    /// a [`MissingField`] when no layer set it.
+    pub fn required(
+        field: Option<Self>,
+        key: &'static str,
+        path: &[&'static str],
+        table: Option<Layered<()>>,
+        missing: &mut Vec<MissingField>,
+    ) -> Option<T> {
+        match field {
+            Some(field) => Some(field.value),
+            None => {
+                missing.push(MissingField::new(path, key, table));
+                None
+            }
+        }
+    }
+}
+
+impl<P: Partial> Layered<P> {
+    /// Merges a nested partial table, the entries of `upper` taking
+    /// precedence. The merged table takes the span and layer of `upper`.
+    pub fn merge(lower: Option<Self>, upper: Option<Self>) -> Option<Self> {
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => Some(Layered {
+                value: lower.value.merge(upper.value),
+                span: upper.span,
+                layer: upper.layer,
+            }),
+            (lower, None) => lower,
+            (None, upper) => upper,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Used in derive macro: resolves a nested partial table under `key`.
+    ///
+    /// A table no layer defined resolves as empty, so its fields fall back to
+    /// their defaults and missing ones are reported against `table`.
+    pub fn resolve_in(
+        field: Option<Self>,
+        key: &'static str,
+        path: &mut Vec<&'static str>,
+        table: Option<Layered<()>>,
+        missing: &mut Vec<MissingField>,
+    ) -> Option<P::Full> {
+        path.push(key);
+        let resolved = match field {
+            Some(field) => {
+                let table = Layered {
+                    value: (),
+                    span: field.span,
+                    layer: field.layer,
+                };
+                field.value.resolve_in(path, Some(table), missing)
+            }
+            None => P::default().resolve_in(path, table, missing),
+        };
+        path.pop();
+        resolved
+    }
+}
+
+impl<'de, T: FromToml<'de>> FromToml<'de> for Layered<T> {
+    fn from_toml(ctx: &mut Context<'de>, item: &Item<'de>) -> Result<Self, Failed> {
+        Self::from_toml_at_key(ctx, Span::default(), item)
+    }
+
+    fn from_toml_at_key(
+        ctx: &mut Context<'de>,
+        key_span: Span,
+        item: &Item<'de>,
+    ) -> Result<Self, Failed> {
+        let value = T::from_toml_at_key(ctx, key_span, item)?;
+        Ok(Self {
+            value,
+            span: item.span(),
+            layer: 0,
+        })
+    }
+
+    fn claims_key(key: &str) -> bool {
+        T::claims_key(key)
+    }
+
+    fn expected_keys(keys: &mut Vec<&'static str>) {
+        T::expected_keys(keys)
+    }
+}
+
+/// A companion of a struct with every field optional, for merging
+/// configuration layers.
+///
+/// Generated by `#[toml(partial = Name)]`. Read each layer into the partial
+/// type, stack them with [`merge`](Self::merge), and [`resolve`](Self::resolve)
+/// the result into the full struct.
+pub trait Partial: Default {
+    /// The struct this partial resolves into.
+    type Full;
+
+    /// Overlays `upper` onto `self`. Fields set in `upper` win, along with
+    /// their span and layer, and nested partial tables merge recursively.
+    fn merge(self, upper: Self) -> Self;
+
+    /// Assigns `layer` to every value set in this partial.
+    fn set_layer(&mut self, layer: u32);
+
+    /// Returns the highest layer of the values set directly in this
+    /// partial, or `None` when none is set.
+    fn top_layer(&self) -> Option<u32>;
+
+    /// Builds the full struct for the table at `path`, defined by `table`,
+    /// recording every required field no layer set.
+    ///
+    /// Called by [`resolve`](Self::resolve) and by the partials of
+    /// enclosing structs. Returns `None` when a field is missing.
+    fn resolve_in(
+        self,
+        path: &mut Vec<&'static str>,
+        table: Option<Layered<()>>,
+        missing: &mut Vec<MissingField>,
+    ) -> Option<Self::Full>;
+
+    /// Returns this partial with every value assigned to `layer`.
+    fn with_layer(mut self, layer: u32) -> Self {
+        self.set_layer(layer);
+        self
+    }
+
+    /// Builds the full struct, filling unset fields with their defaults.
+    ///
+    /// Required fields missing from the root table are reported against the
+    /// highest layer that set any of its keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns every required field that no layer set.
+    fn resolve(self) -> Result<Self::Full, ResolveError> {
+        let mut missing = Vec::new();
+        let root = self.top_layer().map(|layer| Layered {
+            value: (),
+            span: Span::default(),
+            layer,
+        });
+        match self.resolve_in(&mut Vec::new(), root, &mut missing) {
+            Some(full) if missing.is_empty() => Ok(full),
+            _ => Err(ResolveError { missing }),
+        }
+    }
+}
+
+/// Links a struct to the partial type generated for it, so partial
+/// tables can nest.
+pub trait HasPartial {
+    /// The generated partial type.
+    type Partial: Partial<Full = Self>;
+}
+
+/// A required field that no layer set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissingField {
+    path: Box<[PathComponent<'static>]>,
+    /// The table the field was expected in: the span and layer of the
+    /// highest layer defining it, or `None` for a table no layer defined.
+    /// The root table has an empty span.
+    pub table: Option<Layered<()>>,
+}
+
+impl MissingField {
+    fn new(path: &[&'static str], key: &'static str, table: Option<Layered<()>>) -> Self {
+        let path = path
+            .iter()
+            .chain([&key])
+            .map(|name| PathComponent::Key(Key::new(name)))
+            .collect();
+        MissingField { path, table }
+    }
+
+    /// Returns the keys leading to the field, displayed as `server.port`.
+    pub fn path(&self) -> &TomlPath<'static> {
+        TomlPath::new(&self.path)
+    }
+}
+
+impl std::fmt::Display for MissingField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "missing field '{}'", self.path())?;
+        if let Some(table) = &self.table {
+            write!(f, " in layer {}", table.layer)?;
+        }
+        Ok(())
+    }
+}
+
+/// The required fields left unset by every layer, returned by
+/// [`Partial::resolve`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolveError {
+    /// The missing fields, in declaration order.
+    pub missing: Vec<MissingField>,
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, field) in self.missing.iter().enumerate() {
+            if i != 0 {
+                f.write_str("\n")?;
+            }
+            field.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ResolveError {}
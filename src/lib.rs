@@ -185,12 +185,19 @@ mod error;
 #[cfg(feature = "from-toml")]
 pub mod helper;
 mod item;
+#[cfg(feature = "from-toml")]
+mod layer;
+#[cfg(feature = "from-toml")]
+mod migrate;
 
 mod parser;
+pub mod schema;
 #[cfg(feature = "to-toml")]
 mod ser;
 mod span;
 mod time;
+#[cfg(feature = "from-toml")]
+mod update;
 
 /// Error sentinel indicating a failure.
 ///
@@ -202,27 +209,37 @@ pub use arena::Arena;
 #[cfg(feature = "from-toml")]
 pub use de::FromTomlError;
 #[cfg(feature = "from-toml")]
-pub use de::{Context, FromFlattened, FromToml, TableHelper};
+pub use de::{Context, FromFlattened, FromToml, KeyMatch, TableHelper};
 #[cfg(feature = "to-toml")]
-pub use emit::Indent;
+pub use emit::{AutoStyle, Indent};
 #[cfg(feature = "to-toml")]
-use emit::{EmitConfig, emit_with_config};
+use emit::{DocComments, EmitConfig, emit_with_config};
 #[cfg(feature = "to-toml")]
-use emit::{reproject, reproject_with_span_identity};
+use emit::{ReprojectOptions, reproject_with};
 pub use error::{Error, ErrorKind, TomlPath};
 pub use item::array::Array;
 pub use item::owned::{OwnedItem, OwnedTable};
 pub use item::table::Table;
-pub use item::{ArrayStyle, Integer, Item, Key, Kind, MaybeItem, TableStyle, Value, ValueMut};
+pub use item::{
+    ArrayStyle, FloatRepr, Integer, IntegerRadix, IntegerRepr, Item, Key, KeyStyle, Kind,
+    MaybeItem, StringRepr, TableStyle, Value, ValueMut,
+};
+#[cfg(feature = "from-toml")]
+pub use layer::{HasPartial, Layered, MissingField, Partial, ResolveError};
+#[cfg(feature = "from-toml")]
+pub use migrate::{Migrated, Migration, Migrator, Versioned};
 #[cfg(feature = "from-toml")]
 pub use parser::parse_recoverable;
 pub use parser::{Document, parse};
+pub use schema::TomlSchema;
 #[cfg(feature = "to-toml")]
 pub use ser::ToTomlError;
 #[cfg(feature = "to-toml")]
 pub use ser::{ToFlattened, ToToml};
 pub use span::{Span, Spanned};
 pub use time::{Date, DateTime, Time, TimeOffset};
+#[cfg(feature = "from-toml")]
+pub use update::{ChangeSet, UpdateFromToml};
 
 #[cfg(feature = "derive")]
 pub use toml_spanner_macros::Toml;
@@ -302,6 +319,78 @@ pub fn to_string(value: &dyn ToToml) -> Result<String, ToTomlError> {
     Formatting::default().format(value)
 }
 
+/// Serializes a [`ToToml`] value into a commented TOML template.
+///
+/// The output is the document [`to_string`] would produce, annotated with
+/// the fields described by the [`TomlSchema`] of `T`, which the derive
+/// macro implements for types marked `#[toml(Schema)]`:
+///
+/// - each field's doc comment becomes a leading `#` comment,
+/// - optional fields are commented out, keeping the value they were
+///   serialized with, or written as `# key =` when they were omitted,
+/// - nested structs are written as `[sections]`,
+/// - `skip_if_default` fields are written even when equal to their default.
+///
+/// Passing `T::default()` yields an example config listing every default.
+/// Types whose schema is not a struct are written as by [`to_string`].
+///
+/// # Errors
+///
+/// Returns [`ToTomlError`] if serialization fails or the top-level value
+/// is not a table.
+///
+/// # Examples
+///
+/// ```
+/// use toml_spanner::Toml;
+///
+/// #[derive(Toml, Default)]
+/// #[toml(ToToml, Schema)]
+/// struct Config {
+///     /// Name of the service.
+///     name: String,
+///     /// Worker threads.
+///     #[toml(default)]
+///     workers: u32,
+///     /// Port to listen on.
+///     port: Option<u16>,
+/// }
+///
+/// let template = toml_spanner::to_template(&Config::default()).unwrap();
+/// assert_eq!(
+///     template,
+///     "\
+/// ## Name of the service.
+/// name = \"\"
+/// ## Worker threads.
+/// ## workers = 0
+/// ## Port to listen on.
+/// ## port =
+/// ",
+/// );
+/// ```
+#[cfg(feature = "to-toml")]
+pub fn to_template<T: ToToml + TomlSchema>(value: &T) -> Result<String, ToTomlError> {
+    let arena = Arena::new();
+    let item = ser::with_expanded_defaults(true, || value.to_toml(&arena))?;
+    let Some(mut table) = item.into_table() else {
+        return Err(ToTomlError {
+            message: "Top-level item must be a table".into(),
+        });
+    };
+    let fields = emit::schema_fields(T::schema());
+    emit::promote_template_sections(&mut table, fields);
+    let buffer = Formatting::default().emit_table(table, &arena, |table| {
+        Some(DocComments::template(table, fields))
+    });
+    let Ok(text) = String::from_utf8(buffer) else {
+        return Err(ToTomlError {
+            message: "Failed to convert emitted bytes into a UTF-8 string".into(),
+        });
+    };
+    Ok(text)
+}
+
 /// Controls how TOML output is formatted when serializing.
 ///
 /// [`Formatting::preserved_from`] preserves formatting from a previously
@@ -329,6 +418,10 @@ pub struct Formatting<'a> {
     formatting_from: Option<&'a Document<'a>>,
     indent: Indent,
     span_projection_identity: bool,
+    renamed_keys: Vec<(Vec<&'a str>, &'a str)>,
+    array_identities: Vec<(Vec<&'a str>, &'a str)>,
+    auto_style: AutoStyle,
+    expand_defaults: bool,
 }
 
 #[cfg(feature = "to-toml")]
@@ -343,6 +436,10 @@ impl<'a> Formatting<'a> {
             formatting_from: Some(doc),
             indent,
             span_projection_identity: false,
+            renamed_keys: Vec::new(),
+            array_identities: Vec::new(),
+            auto_style: AutoStyle::new(),
+            expand_defaults: false,
         }
     }
 
@@ -353,29 +450,116 @@ impl<'a> Formatting<'a> {
         self
     }
 
+    /// Sets the policy used to choose between inline tables, dotted keys,
+    /// and headers for values that carry no explicit style.
+    ///
+    /// See [`AutoStyle`] for the available knobs.
+    pub fn with_auto_style(mut self, policy: AutoStyle) -> Self {
+        self.auto_style = policy;
+        self
+    }
+
+    /// Writes fields marked `skip_if_default` even when they are equal to
+    /// their default, for a fully expanded file.
+    ///
+    /// Applies to values serialized by [`format`](Self::format).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toml_spanner::{Formatting, Toml};
+    ///
+    /// #[derive(Toml)]
+    /// #[toml(ToToml, skip_if_default)]
+    /// struct Config {
+    ///     name: String,
+    ///     #[toml(default = 8080)]
+    ///     port: u16,
+    /// }
+    ///
+    /// let config = Config { name: "app".into(), port: 8080 };
+    /// assert_eq!(toml_spanner::to_string(&config).unwrap(), "name = \"app\"\n");
+    /// assert_eq!(
+    ///     Formatting::default().with_expanded_defaults().format(&config).unwrap(),
+    ///     "name = \"app\"\nport = 8080\n",
+    /// );
+    /// ```
+    pub fn with_expanded_defaults(mut self) -> Self {
+        self.expand_defaults = true;
+        self
+    }
+
+    #[doc(hidden)]
+    /// Used in derive macro: whether the [`format`](Self::format) or
+    /// [`to_template`] call in progress writes `skip_if_default` fields equal
+    /// to their default.
+    pub fn expands_defaults() -> bool {
+        ser::expands_defaults()
+    }
+
     /// Serializes a [`ToToml`] value into a TOML string.
     ///
     /// The value must serialize to a table at the top level.
     ///
+    /// For values with [doc comments](ToToml::doc_comments), each field's
+    /// doc comment is written as a leading comment on its key or section
+    /// header, and nested documented structs are written as `[sections]`.
+    /// Keys already present in the [`preserved_from`](Self::preserved_from)
+    /// source keep the comments written there instead.
+    ///
     /// # Errors
     ///
     /// Returns [`ToTomlError`] if serialization fails or the top-level value
     /// is not a table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toml_spanner::{Arena, Formatting, Toml};
+    ///
+    /// #[derive(Toml)]
+    /// #[toml(ToToml, Schema, doc_comments)]
+    /// struct Config {
+    ///     /// Name of the service.
+    ///     name: String,
+    ///     /// Port to listen on.
+    ///     port: u16,
+    /// }
+    ///
+    /// let config = Config { name: "app".into(), port: 8080 };
+    /// assert_eq!(
+    ///     toml_spanner::to_string(&config).unwrap(),
+    ///     "# Name of the service.\nname = \"app\"\n# Port to listen on.\nport = 8080\n",
+    /// );
+    ///
+    /// let arena = Arena::new();
+    /// let doc = toml_spanner::parse("# The app.\nname = \"old\"\n", &arena).unwrap();
+    /// assert_eq!(
+    ///     Formatting::preserved_from(&doc).format(&config).unwrap(),
+    ///     "# The app.\nname = \"app\"\n# Port to listen on.\nport = 8080\n",
+    /// );
+    /// ```
     pub fn format(&self, value: &dyn ToToml) -> Result<String, ToTomlError> {
         let arena = Arena::new();
-        let item = value.to_toml(&arena)?;
-        let Some(table) = item.into_table() else {
+        let item = ser::with_expanded_defaults(self.expand_defaults, || value.to_toml(&arena))?;
+        let Some(mut table) = item.into_table() else {
             return Err(ToTomlError {
                 message: "Top-level item must be a table".into(),
             });
         };
-        let buffer = self.format_table_to_bytes(table, &arena);
-        match String::from_utf8(buffer) {
-            Ok(s) => Ok(s),
-            Err(_) => Err(ToTomlError {
+        let fields = value.doc_comments().map_or(&[][..], emit::schema_fields);
+        emit::promote_template_sections(&mut table, fields);
+        let source = self.formatting_from.map(Document::table);
+        let buffer = self.emit_table(table, &arena, |table| {
+            (!fields.is_empty())
+                .then(|| DocComments::fresh(table, fields, source, &self.renamed_keys))
+        });
+        let Ok(text) = String::from_utf8(buffer) else {
+            return Err(ToTomlError {
                 message: "Failed to convert emitted bytes into a UTF-8 string".into(),
-            }),
-        }
+            });
+        };
+        Ok(text)
     }
 
     /// Formats a [`Table`] directly into bytes.
@@ -383,30 +567,49 @@ impl<'a> Formatting<'a> {
     /// Low-level primitive that normalizes and (when a source document
     /// is set) reprojects the table before emission. The provided arena
     /// is used for temporary allocations during emission.
-    pub fn format_table_to_bytes(&self, mut table: Table<'_>, arena: &Arena) -> Vec<u8> {
+    pub fn format_table_to_bytes(&self, table: Table<'_>, arena: &Arena) -> Vec<u8> {
+        self.emit_table(table, arena, |_| None)
+    }
+
+    /// [`format_table_to_bytes`](Self::format_table_to_bytes), writing the
+    /// leading comments `comments` derives from the table about to be
+    /// emitted.
+    fn emit_table(
+        &self,
+        mut table: Table<'_>,
+        arena: &Arena,
+        comments: impl FnOnce(&Table<'_>) -> Option<DocComments>,
+    ) -> Vec<u8> {
         let mut items = Vec::new();
         let mut buffer = Vec::new();
         if let Some(formatting_from) = self.formatting_from {
-            if self.span_projection_identity {
-                reproject_with_span_identity(formatting_from, &mut table, &mut items);
-            } else {
-                reproject(formatting_from, &mut table, &mut items);
-            }
+            let options = ReprojectOptions {
+                span_identity: self.span_projection_identity,
+                renames: &self.renamed_keys,
+                array_identities: &self.array_identities,
+            };
+            reproject_with(formatting_from, &mut table, &mut items, &options);
+            let normalized = table.normalize_with(&self.auto_style);
+            let comments = comments(normalized.table());
             emit_with_config(
-                table.normalize(),
+                normalized,
                 &EmitConfig {
                     projected_source_items: &items,
                     projected_source_text: formatting_from.ctx.source(),
                     indent: self.indent,
+                    comments: comments.as_ref(),
                 },
                 arena,
                 &mut buffer,
             );
         } else {
+            let normalized = table.normalize_with(&self.auto_style);
+            let comments = comments(normalized.table());
             emit_with_config(
-                table.normalize(),
+                normalized,
                 &EmitConfig {
                     indent: self.indent,
+                    comments: comments.as_ref(),
                     ..EmitConfig::default()
                 },
                 arena,
@@ -478,4 +681,121 @@ impl<'a> Formatting<'a> {
         self.span_projection_identity = true;
         self
     }
+
+    /// Treats a key named `new` as the renamed form of the key at `path` in
+    /// the formatting reference.
+    ///
+    /// Reprojection pairs table entries by key name, so migrating a config
+    /// key from one name to another normally drops the old entry's
+    /// comments and position. With a rename registered, a `new` entry in
+    /// the same table that has no counterpart in the reference takes over
+    /// the formatting of the entry at `path`: its leading comments, trailing
+    /// comment, and place in the document. The key itself is written under
+    /// its new name.
+    ///
+    /// `path` lists the keys from the root to the old entry, skipping array
+    /// indices, so a rename under `[[bin]]` applies to every element. The
+    /// rename only applies where the output has no entry under the old name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toml_spanner::{Arena, Formatting};
+    /// use std::collections::BTreeMap;
+    ///
+    /// let arena = Arena::new();
+    /// let source = "# Worker count.\nold-name = 4 # tuned for CI\nother = 1\n";
+    /// let doc = toml_spanner::parse(source, &arena).unwrap();
+    ///
+    /// let mut map = BTreeMap::new();
+    /// map.insert("other", 1);
+    /// map.insert("new-name", 4);
+    ///
+    /// let output = Formatting::preserved_from(&doc)
+    ///     .with_renamed_key(&["old-name"], "new-name")
+    ///     .format(&map)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     output,
+    ///     "# Worker count.\nnew-name = 4 # tuned for CI\nother = 1\n",
+    /// );
+    /// ```
+    pub fn with_renamed_key(mut self, path: &[&'a str], new: &'a str) -> Self {
+        self.renamed_keys.push((path.to_vec(), new));
+        self
+    }
+
+    /// Registers every key renamed by a format upgrade, as if by
+    /// [`with_renamed_key`](Self::with_renamed_key).
+    ///
+    /// Use with a formatting reference of the pre-upgrade document to write
+    /// the [`Migrated`] table back while keeping its comments. Rename paths
+    /// are taken relative to the document root, so migrate the root table.
+    /// Keys renamed inside arrays are not registered, see
+    /// [`Migrated::renamed_keys`].
+    pub fn with_migration(mut self, migrated: &Migrated<'a>) -> Self {
+        self.renamed_keys.extend_from_slice(migrated.renamed_keys());
+        self
+    }
+
+    /// Pairs the tables of an array of tables by the value of one of
+    /// their fields.
+    ///
+    /// By default, array elements are paired with the formatting reference
+    /// by content, falling back to position. When `[[package]]` entries are
+    /// added, removed, reordered, and edited at the same time, comments and
+    /// blank-line spacing can end up on the wrong table. With an identity
+    /// field, each table takes its formatting from the reference table with
+    /// an equal `field` value, and tables with no counterpart are formatted
+    /// fresh.
+    ///
+    /// `path` is the key path of the array from the document root, such as
+    /// `&["package"]` or `&["workspace", "members"]`. Arrays of tables along
+    /// the path are descended into, so `&["target", "bin"]` applies to the
+    /// `bin` array of every `[[target]]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toml_spanner::{Arena, Formatting};
+    ///
+    /// let arena = Arena::new();
+    /// let source = "\
+    /// [[package]]
+    /// name = \"a\" # first
+    /// version = 1
+    ///
+    /// [[package]]
+    /// name = \"b\" # second
+    /// version = 1
+    /// ";
+    /// let doc = toml_spanner::parse(source, &arena).unwrap();
+    ///
+    /// // Swap the packages and bump a version.
+    /// let mut table = doc.table().clone_in(&arena);
+    /// let packages = table.get_mut("package").unwrap().as_array_mut().unwrap();
+    /// packages.as_mut_slice().swap(0, 1);
+    /// let b = packages.as_mut_slice()[0].as_table_mut().unwrap();
+    /// *b.get_mut("version").unwrap() = 2i64.into();
+    ///
+    /// let bytes = Formatting::preserved_from(&doc)
+    ///     .with_array_identity(&["package"], "name")
+    ///     .format_table_to_bytes(table, &arena);
+    /// assert_eq!(
+    ///     std::str::from_utf8(&bytes).unwrap(),
+    ///     "\
+    /// [[package]]
+    /// name = \"b\" # second
+    /// version = 2
+    ///
+    /// [[package]]
+    /// name = \"a\" # first
+    /// version = 1
+    /// ",
+    /// );
+    /// ```
+    pub fn with_array_identity(mut self, path: &[&'a str], field: &'a str) -> Self {
+        self.array_identities.push((path.to_vec(), field));
+        self
+    }
 }
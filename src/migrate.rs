@@ -0,0 +1,297 @@
+use std::ptr::NonNull;
+
+use foldhash::HashSet;
+
+use crate::error::{Error, ErrorKind, MaybeTomlPath};
+use crate::parser::INDEXED_TABLE_THRESHOLD;
+use crate::{Context, Failed, Item, Key, Span, Table};
+
+/// One step in a chain of format upgrades.
+///
+/// The step at index `i` of [`Versioned::MIGRATIONS`] receives a table
+/// written in format version `i + 1` and rewrites it in place into version
+/// `i + 2`. Entries moved or renamed by a step keep their source spans, so
+/// diagnostics reported while deserializing the upgraded table point into
+/// the original document.
+pub type Migration = for<'m, 'de> fn(&mut Migrator<'m, 'de>, &mut Table<'de>) -> Result<(), Failed>;
+
+/// A type whose TOML form carries a format version and can be upgraded
+/// from older versions.
+///
+/// Implemented by `#[derive(Toml)]` for structs with a `#[toml(version)]`
+/// field. Generated [`FromToml`](crate::FromToml) implementations run
+/// [`Context::migrate`] before reading any field.
+pub trait Versioned {
+    /// The key holding the format version.
+    const VERSION_KEY: &'static str;
+    /// The upgrade steps, oldest first.
+    ///
+    /// Versions are numbered from 1, so the current version is
+    /// `MIGRATIONS.len() + 1`.
+    const MIGRATIONS: &'static [Migration];
+
+    /// Returns the format version written by this build.
+    fn current_version() -> u32 {
+        Self::MIGRATIONS.len() as u32 + 1
+    }
+}
+
+/// State handed to each [`Migration`] step.
+pub struct Migrator<'m, 'de> {
+    /// The context of the document being upgraded, for reporting errors and
+    /// allocating new items in its arena.
+    pub ctx: &'m mut Context<'de>,
+    from_version: u32,
+    /// The source span of each renamed key, with its old and new names.
+    renamed_keys: &'m mut Vec<(Span, &'static str, &'static str)>,
+}
+
+impl<'de> Migrator<'_, 'de> {
+    /// Returns the version this step upgrades from.
+    pub fn from_version(&self) -> u32 {
+        self.from_version
+    }
+
+    /// Renames the `old` key of `table` to `new`, keeping its value and
+    /// position.
+    ///
+    /// Records a non-fatal [`ErrorKind::Deprecated`] warning at the original
+    /// key, tagged with [`from_version`](Self::from_version). Returns `false`
+    /// and leaves the table untouched when `old` is absent or `new` is
+    /// already present.
+    pub fn rename_key(
+        &mut self,
+        table: &mut Table<'de>,
+        old: &'static &'static str,
+        new: &'static &'static str,
+    ) -> bool {
+        if table.contains_key(new) {
+            return false;
+        }
+        let Some((key, item)) = table
+            .entries_mut()
+            .iter_mut()
+            .find(|(key, _)| key.name == *old)
+        else {
+            return false;
+        };
+        key.name = new;
+        self.ctx.errors.push(Error::new_with_path(
+            ErrorKind::Deprecated {
+                tag: self.from_version,
+                old,
+                new,
+            },
+            key.span,
+            MaybeTomlPath::uncomputed(item),
+        ));
+        // Keys added by an earlier step have no source entry to take
+        // formatting from, and a key renamed twice keeps its first name.
+        if !key.span.is_empty() {
+            match self
+                .renamed_keys
+                .iter_mut()
+                .find(|(span, _, renamed)| *span == key.span && *renamed == *old)
+            {
+                Some(entry) => entry.2 = new,
+                None => self.renamed_keys.push((key.span, old, new)),
+            }
+        }
+        true
+    }
+}
+
+/// A table upgraded to the current format version by [`Context::migrate`].
+pub struct Migrated<'de> {
+    /// The upgraded table. Entries carried over from the original document
+    /// keep their spans.
+    ///
+    /// Deserialize it as returned: the context only knows the upgraded
+    /// entries, so keys added or removed afterwards are not seen by
+    /// [`TableHelper`](crate::TableHelper) lookups or error paths.
+    pub table: Table<'de>,
+    /// The version the original document was written in.
+    pub from_version: u32,
+    renamed_keys: Vec<(Vec<&'de str>, &'static str)>,
+}
+
+impl<'de> Migrated<'de> {
+    /// Returns the `(path, new)` key renames applied through
+    /// [`Migrator::rename_key`], where `path` leads from the upgraded table
+    /// to the key under its old name.
+    ///
+    /// Keys renamed inside arrays are left out, as a path cannot name the
+    /// element they were renamed in. Register those with
+    /// [`Formatting::with_renamed_key`](crate::Formatting::with_renamed_key)
+    /// when every element was renamed.
+    ///
+    /// Pass the upgrade to
+    /// [`Formatting::with_migration`](crate::Formatting::with_migration) to
+    /// keep the comments of renamed entries when writing the table back.
+    pub fn renamed_keys(&self) -> &[(Vec<&'de str>, &'static str)] {
+        &self.renamed_keys
+    }
+}
+
+impl<'de> Context<'de> {
+    /// Upgrades `table` to the current format version of `T`.
+    ///
+    /// Reads the version from [`T::VERSION_KEY`](Versioned::VERSION_KEY),
+    /// treating a missing key as version 1, then applies the migrations
+    /// from that version onwards to a copy of the table and sets the key to
+    /// the current version. Returns `None` when the table is already
+    /// current and no copy was needed.
+    ///
+    /// Errors found while deserializing the upgraded table are reported
+    /// at spans in the original document, and their
+    /// [`Error::path`](crate::Error::path) leads from the place of the
+    /// original table through the upgraded one, so a moved entry is named
+    /// by its new path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Failed`] when the version is not an integer, is newer than
+    /// the current version, or a migration fails.
+    pub fn migrate<T: Versioned + ?Sized>(
+        &mut self,
+        table: &Table<'de>,
+    ) -> Result<Option<Migrated<'de>>, Failed> {
+        let current = T::current_version();
+        let from_version = match table.get(T::VERSION_KEY) {
+            None => 1,
+            Some(item) => match item.as_i64() {
+                Some(version) if version == current as i64 => return Ok(None),
+                Some(version) if version >= 1 && version < current as i64 => version as u32,
+                Some(version) => {
+                    return Err(
+                        self.report_custom_error(UnsupportedVersion { version, current }, item)
+                    );
+                }
+                None => return Err(self.report_expected_but_found(&"an integer", item)),
+            },
+        };
+        let mut migrated = Migrated {
+            table: table.clone_in(self.arena),
+            from_version,
+            renamed_keys: Vec::new(),
+        };
+        let mut renamed = Vec::new();
+        for (step, migration) in T::MIGRATIONS[from_version as usize - 1..]
+            .iter()
+            .enumerate()
+        {
+            let mut migrator = Migrator {
+                ctx: self,
+                from_version: from_version + step as u32,
+                renamed_keys: &mut renamed,
+            };
+            migration(&mut migrator, &mut migrated.table)?;
+        }
+        for (span, old, new) in renamed {
+            let mut path = Vec::new();
+            if find_key(&migrated.table, span, new, &mut path) {
+                *path.last_mut().unwrap() = old;
+                migrated.renamed_keys.push((path, new));
+            }
+        }
+        migrated.table.insert(
+            Key::new(T::VERSION_KEY),
+            Item::from(current as i64),
+            self.arena,
+        );
+        self.track_migrated(table, &migrated.table);
+        Ok(Some(migrated))
+    }
+
+    #[doc(hidden)]
+    /// Used in derive macro: runs [`migrate`](Self::migrate) and returns the
+    /// table to read the fields from, the original one when it is already
+    /// current.
+    pub fn migrated_table<'t, T: Versioned + ?Sized>(
+        &mut self,
+        table: &'t Table<'de>,
+    ) -> Result<&'t Table<'de>, Failed> {
+        match self.migrate::<T>(table)? {
+            Some(_) => Ok(self.migrated.last().unwrap().1),
+            None => Ok(table),
+        }
+    }
+
+    /// Records `copy` as the upgrade of `original`.
+    ///
+    /// The copy keeps the key spans of the parsed tables it was made from,
+    /// which the span index would resolve to their entries, so its tables
+    /// are looked up linearly. Error paths pointing into the copy are
+    /// resolved through the place of `original` by `compute_paths`.
+    fn track_migrated(&mut self, original: &Table<'de>, copy: &Table<'de>) {
+        let slot: NonNull<Table<'de>> = self.arena.alloc(size_of::<Table<'de>>()).cast();
+        // SAFETY: slot is a fresh arena allocation, sized for a `Table` and
+        // aligned to 8 like every arena allocation. `Table` has no Drop impl
+        // and its entries live in the arena, so the bitwise copy shares them
+        // with `copy` for as long as `'de`.
+        let copy: &'de Table<'de> = unsafe {
+            slot.as_ptr().write(std::ptr::read(copy));
+            &*slot.as_ptr()
+        };
+        collect_unindexed(copy.as_item(), &mut self.unindexed);
+        self.migrated
+            .push((original as *const Table<'de> as usize, copy));
+    }
+}
+
+/// Collects the entry addresses of the tables in `item` large enough to be
+/// looked up through the span index.
+fn collect_unindexed(item: &Item<'_>, unindexed: &mut HashSet<usize>) {
+    if let Some(table) = item.as_table() {
+        if table.len() > INDEXED_TABLE_THRESHOLD {
+            unindexed.insert(table.entries().as_ptr() as usize);
+        }
+        for (_, item) in table {
+            collect_unindexed(item, unindexed);
+        }
+    } else if let Some(array) = item.as_array() {
+        for item in array {
+            collect_unindexed(item, unindexed);
+        }
+    }
+}
+
+/// Finds the entry named `name` whose key has `span`, pushing the key path
+/// leading to it onto `path`.
+///
+/// Arrays are not searched: rename paths carry no array indices, so a key
+/// renamed in one element would be applied to every element of the array.
+fn find_key<'de>(table: &Table<'de>, span: Span, name: &str, path: &mut Vec<&'de str>) -> bool {
+    for (key, item) in table {
+        path.push(key.name);
+        if key.span == span && key.name == name {
+            return true;
+        }
+        if let Some(table) = item.as_table()
+            && find_key(table, span, name, path)
+        {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+struct UnsupportedVersion {
+    version: i64,
+    current: u32,
+}
+
+impl std::fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.version < 1 {
+            write!(f, "invalid format version {}", self.version)
+        } else {
+            write!(
+                f,
+                "unsupported format version {}, the newest supported version is {}",
+                self.version, self.current
+            )
+        }
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
     arena::Arena,
     error::{Error, ErrorKind, PathComponent},
     item::{
-        self, Item, Key,
+        self, FloatRepr, IntegerRepr, Item, Key, StringRepr,
         table::{InnerTable, Table},
     },
     time::DateTime,
@@ -843,7 +843,7 @@ impl<'de> Parser<'de> {
                 Some(b) if is_keylike_byte(b) => {
                     let after = self.read_keylike();
                     match self.float(start, end, s, Some(after), sign) {
-                        Ok(f) => Ok(Item::float_spanned(f, Span::new(start, self.cursor as u32))),
+                        Ok(f) => Ok(self.float_item(f, start)),
                         Err(e) => Err(e),
                     }
                 }
@@ -900,7 +900,7 @@ impl<'de> Parser<'de> {
 
         if bytes.iter().any(|&b| b == b'e' || b == b'E') {
             return match self.float(start, end, s, None, sign) {
-                Ok(f) => Ok(Item::float_spanned(f, Span::new(start, self.cursor as u32))),
+                Ok(f) => Ok(self.float_item(f, start)),
                 Err(e) => Err(e),
             };
         }
@@ -979,7 +979,11 @@ impl<'de> Parser<'de> {
             } else {
                 acc as i128
             };
-            return Ok(Item::integer_spanned(val, span));
+            return Ok(Item::integer_spelled(
+                val,
+                span,
+                IntegerRepr::from_source(bytes),
+            ));
         };
         self.error_span = error_span;
         self.error_kind = Some(ErrorKind::InvalidInteger(reason));
@@ -1048,13 +1052,29 @@ impl<'de> Parser<'de> {
             if acc > i128::MAX as u128 {
                 break 'error "integer overflow";
             }
-            return Ok(Item::integer_spanned(acc as i128, span));
+            let text = &self.bytes[span.start as usize..span.end as usize];
+            return Ok(Item::integer_spelled(
+                acc as i128,
+                span,
+                IntegerRepr::from_source(text),
+            ));
         };
         self.error_span = error_span;
         self.error_kind = Some(ErrorKind::InvalidInteger(reason));
         Err(Failed)
     }
 
+    /// Wraps a parsed float with the notation of its source text, which
+    /// runs from `start` to the cursor.
+    fn float_item(&self, f: f64, start: u32) -> Item<'de> {
+        let text = &self.bytes[start as usize..self.cursor];
+        Item::float_spelled(
+            f,
+            Span::new(start, self.cursor as u32),
+            FloatRepr::from_source(text),
+        )
+    }
+
     fn float(
         &mut self,
         start: u32,
@@ -1171,7 +1191,11 @@ impl<'de> Parser<'de> {
             b'"' | b'\'' => {
                 self.cursor += 1;
                 return match self.read_string(self.cursor - 1, byte) {
-                    Ok((key, _)) => Ok(Item::string_spanned(key.name, key.span)),
+                    Ok((key, multiline)) => Ok(Item::string_spelled(
+                        key.name,
+                        key.span,
+                        StringRepr::from_delim(byte, multiline),
+                    )),
                     Err(e) => Err(e),
                 };
             }
@@ -1518,12 +1542,22 @@ impl<'de> Parser<'de> {
             unsafe { Ok(inserted.as_table_mut_unchecked()) }
         }
     }
+    /// Records on `item` when its key was written as a `'literal'` key. Bare
+    /// and basic-quoted keys are told apart by their span alone.
+    #[inline]
+    fn mark_key_quoting(&self, key: &Key<'de>, item: &mut Item<'de>) {
+        if self.bytes.get(key.span.start as usize) == Some(&b'\'') {
+            item.meta.set_key_literal();
+        }
+    }
+
     fn insert_value_known_to_be_unique<'t>(
         &mut self,
         table: &'t mut InnerTable<'de>,
         key: Key<'de>,
-        item: Item<'de>,
+        mut item: Item<'de>,
     ) -> &'t mut item::Item<'de> {
+        self.mark_key_quoting(&key, &mut item);
         let len = table.len();
         if len >= INDEXED_TABLE_THRESHOLD {
             // SAFETY: len >= INDEXED_TABLE_THRESHOLD (>= 6), so the table is non-empty.
@@ -1663,8 +1697,9 @@ impl<'de> Parser<'de> {
         &mut self,
         table: &mut InnerTable<'de>,
         key: Key<'de>,
-        item: Item<'de>,
+        mut item: Item<'de>,
     ) -> Result<(), Failed> {
+        self.mark_key_quoting(&key, &mut item);
         if table.len() < INDEXED_TABLE_THRESHOLD {
             for (existing_key, _) in table.entries() {
                 if existing_key.as_str() == key.name {
@@ -2109,7 +2144,7 @@ impl<'de> Document<'de> {
     #[doc(alias = "from_toml")]
     pub fn to<T: crate::FromToml<'de>>(&mut self) -> Result<T, crate::FromTomlError> {
         let result = T::from_toml(&mut self.ctx, self.table.as_item());
-        crate::de::compute_paths(&self.table, &mut self.ctx.errors);
+        crate::de::compute_paths(&self.table, &self.ctx.migrated, &mut self.ctx.errors);
         match result {
             Ok(v) if self.ctx.errors.is_empty() => Ok(v),
             _ => Err(crate::de::FromTomlError {
@@ -2118,6 +2153,23 @@ impl<'de> Document<'de> {
         }
     }
 
+    /// Applies the root table to an existing `value` via
+    /// [`UpdateFromToml`](crate::UpdateFromToml), returning the paths of the
+    /// fields that changed.
+    ///
+    /// The value is only modified when the conversion succeeds without
+    /// errors, so a broken reload leaves it as it was.
+    ///
+    /// # Errors
+    /// Returns [`FromTomlError`](crate::FromTomlError) containing all accumulated errors.
+    pub fn update<T: crate::UpdateFromToml<'de>>(
+        &mut self,
+        value: &mut T,
+    ) -> Result<crate::ChangeSet, crate::FromTomlError> {
+        let new = self.to::<T>()?;
+        Ok(value.update(new))
+    }
+
     /// Resolves the TOML path of every accumulated error.
     ///
     /// Errors recorded by [`Error::custom_at`](crate::Error::custom_at) or
@@ -2132,7 +2184,7 @@ impl<'de> Document<'de> {
     /// [`table_helper`](Self::table_helper) and reading errors through
     /// [`errors`](Self::errors).
     pub fn compute_error_paths(&mut self) {
-        crate::de::compute_paths(&self.table, &mut self.ctx.errors);
+        crate::de::compute_paths(&self.table, &self.ctx.migrated, &mut self.ctx.errors);
     }
 
     /// Converts the root table into a typed value `T` via [`FromToml`](crate::FromToml).
@@ -2147,7 +2199,7 @@ impl<'de> Document<'de> {
         T: crate::de::FromToml<'de>,
     {
         let result = T::from_toml(&mut self.ctx, self.table.as_item());
-        crate::de::compute_paths(&self.table, &mut self.ctx.errors);
+        crate::de::compute_paths(&self.table, &self.ctx.migrated, &mut self.ctx.errors);
         let errors = crate::de::FromTomlError {
             errors: std::mem::take(&mut self.ctx.errors),
         };
@@ -2225,6 +2277,8 @@ pub fn parse<'de>(document: &'de str, arena: &'de Arena) -> Result<Document<'de>
         ctx: crate::de::Context {
             errors: Vec::new(),
             index: parser.index,
+            unindexed: Default::default(),
+            migrated: Vec::new(),
             arena,
             source: document,
         },
@@ -2266,6 +2320,8 @@ pub fn parse_recoverable<'de>(document: &'de str, arena: &'de Arena) -> Document
             ctx: crate::de::Context {
                 errors: parser.errors,
                 index: parser.index,
+                unindexed: Default::default(),
+                migrated: Vec::new(),
                 arena,
                 source: document,
             },
@@ -2290,6 +2346,8 @@ pub fn parse_recoverable<'de>(document: &'de str, arena: &'de Arena) -> Document
         ctx: crate::de::Context {
             errors: parser.errors,
             index: parser.index,
+            unindexed: Default::default(),
+            migrated: Vec::new(),
             arena,
             source: document,
         },
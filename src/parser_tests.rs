@@ -215,6 +215,31 @@ fn inline_tables() {
     assert_eq!(v["a"][0]["x"].as_i64(), Some(1));
 }
 
+#[test]
+fn key_styles() {
+    use crate::KeyStyle;
+    let ctx = TestCtx::new();
+
+    let v = ctx.parse_ok("bare = 1\n\"basic\" = 2\n'literal' = 3\n\"esc\\t\".'x' = 4\n\"\" = 5");
+    let styles: Vec<_> = v
+        .iter()
+        .map(|(k, _)| (k.name, v.key_style(k.name).unwrap()))
+        .collect();
+    assert_eq!(
+        styles,
+        [
+            ("bare", KeyStyle::Bare),
+            ("basic", KeyStyle::Basic),
+            ("literal", KeyStyle::Literal),
+            ("esc\t", KeyStyle::Basic),
+            ("", KeyStyle::Basic),
+        ]
+    );
+    let inner = v["esc\t"].as_table().unwrap();
+    assert_eq!(inner.key_style("x"), Some(KeyStyle::Literal));
+    assert_eq!(inner.key_style("missing"), None);
+}
+
 #[test]
 fn table_headers_and_structure() {
     let ctx = TestCtx::new();
@@ -0,0 +1,490 @@
+//! Static descriptions of the TOML shape a type reads and writes.
+//!
+//! [`TomlSchema`] exposes what the derive macro knows about a type: every
+//! key with its aliases, defaults and doc comment, how enums are
+//! represented, and which fields are flattened. Tools such as documentation
+//! generators, editor completion, and migration scripts can consume this
+//! instead of parsing Rust source.
+//!
+//! The derive implements the trait for types marked `#[toml(Schema)]`:
+//!
+//! ```
+//! # #[cfg(feature = "derive")] {
+//! use toml_spanner::Toml;
+//! use toml_spanner::schema::{Schema, TomlSchema};
+//!
+//! #[derive(Toml)]
+//! #[toml(FromToml, Schema)]
+//! struct Config {
+//!     /// Port to listen on.
+//!     #[toml(default = 8080)]
+//!     port: u16,
+//! }
+//!
+//! let Schema::Struct(config) = Config::schema() else { unreachable!() };
+//! assert_eq!(config.fields[0].key, "port");
+//! assert_eq!(config.fields[0].doc, [" Port to listen on."]);
+//! # }
+//! ```
+//!
+//! Recursive references are stored as `fn() -> Schema` so that
+//! self-referential types can be described by `'static` data.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{Array, Date, DateTime, Item, OwnedItem, OwnedTable, Spanned, Table, Time};
+
+mod json;
+
+/// Types that can describe the TOML shape they are read from and written to.
+///
+/// Implemented by the [`Toml`](macro@crate::Toml) derive for types marked
+/// `#[toml(Schema)]`, and by this crate for the standard types it converts.
+pub trait TomlSchema {
+    /// Returns the description of this type's TOML representation.
+    fn schema() -> Schema;
+}
+
+/// The shape of a TOML value.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Schema {
+    /// Any value. Used where the shape is not known statically, such as
+    /// fields converted `with` a custom module.
+    Any,
+    /// A boolean.
+    Boolean,
+    /// An integer within an inclusive range.
+    Integer {
+        /// The smallest accepted value.
+        min: i128,
+        /// The largest accepted value.
+        max: i128,
+    },
+    /// A float.
+    Float,
+    /// A string.
+    String,
+    /// A date, time, or date-time.
+    DateTime,
+    /// An array whose elements all have the given shape.
+    Array(fn() -> Schema),
+    /// An array with one element of each given shape, in order.
+    Tuple(&'static [fn() -> Schema]),
+    /// A table with arbitrary keys whose values all have the given shape.
+    Map(fn() -> Schema),
+    /// A table with known fields.
+    Struct(&'static StructSchema),
+    /// An enum, see [`EnumRepr`] for the possible representations.
+    Enum(&'static EnumSchema),
+}
+
+impl Schema {
+    /// Renders this description as a JSON Schema (draft-07) document.
+    ///
+    /// Editors that validate TOML against JSON Schema, such as Even Better
+    /// TOML, can use the result for completion and diagnostics. Structs and
+    /// enums other than the root are placed under `definitions`. Doc
+    /// comments become descriptions, and custom defaults that are also TOML
+    /// literals become `default` values.
+    ///
+    /// A recursive reference back to the root type cannot be recognized
+    /// from the description alone and adds a definition for it;
+    /// [`json_schema`] refers to the document itself instead.
+    pub fn to_json_schema(self) -> String {
+        json::to_json_schema(self, 0)
+    }
+}
+
+/// Renders the JSON Schema of `T`, see [`Schema::to_json_schema`].
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use toml_spanner::Toml;
+///
+/// #[derive(Toml)]
+/// #[toml(FromToml, Schema, deny_unknown_fields)]
+/// struct Config {
+///     /// Port to listen on.
+///     #[toml(default = 8080)]
+///     port: u16,
+///     host: String,
+/// }
+///
+/// let schema = toml_spanner::schema::json_schema::<Config>();
+/// assert!(schema.contains(r#""description": "Port to listen on.""#));
+/// assert!(schema.contains(r#""default": 8080"#));
+/// assert!(schema.contains(r#""additionalProperties": false"#));
+/// # }
+/// ```
+pub fn json_schema<T: TomlSchema + ?Sized>() -> String {
+    let schema: fn() -> Schema = T::schema;
+    json::to_json_schema(schema(), schema as usize)
+}
+
+/// Description of a struct read from a table.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct StructSchema {
+    /// The Rust name of the type.
+    pub name: &'static str,
+    /// The type's doc comment, one entry per `///` line.
+    pub doc: &'static [&'static str],
+    /// The fields, in declaration order.
+    pub fields: &'static [FieldSchema],
+    /// How keys without a matching field are handled.
+    pub unknown_fields: UnknownFields,
+}
+
+impl StructSchema {
+    #[doc(hidden)]
+    /// Used in derive macro, which cannot name the fields of a non-exhaustive struct
+    pub const fn new(
+        name: &'static str,
+        doc: &'static [&'static str],
+        fields: &'static [FieldSchema],
+        unknown_fields: UnknownFields,
+    ) -> StructSchema {
+        StructSchema {
+            name,
+            doc,
+            fields,
+            unknown_fields,
+        }
+    }
+}
+
+/// Description of a single field of a struct or struct variant.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct FieldSchema {
+    /// The key the field is read from and written to, after renaming.
+    pub key: &'static str,
+    /// Additional keys accepted when reading.
+    pub aliases: &'static [&'static str],
+    /// Keys accepted when reading that report a deprecation warning.
+    pub deprecated_aliases: &'static [&'static str],
+    /// The field's doc comment, one entry per `///` line.
+    pub doc: &'static [&'static str],
+    /// The Rust type of the field, as written in the source.
+    pub rust_type: &'static str,
+    /// What happens when the key is missing.
+    pub default: FieldDefault,
+    /// Whether the field's entries are flattened into the enclosing table
+    /// rather than stored under [`key`](Self::key).
+    pub flatten: bool,
+    /// The shape of the field's value.
+    pub schema: fn() -> Schema,
+}
+
+impl FieldSchema {
+    #[doc(hidden)]
+    /// Used in derive macro, which cannot name the fields of a non-exhaustive struct
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
+        key: &'static str,
+        aliases: &'static [&'static str],
+        deprecated_aliases: &'static [&'static str],
+        doc: &'static [&'static str],
+        rust_type: &'static str,
+        default: FieldDefault,
+        flatten: bool,
+        schema: fn() -> Schema,
+    ) -> FieldSchema {
+        FieldSchema {
+            key,
+            aliases,
+            deprecated_aliases,
+            doc,
+            rust_type,
+            default,
+            flatten,
+            schema,
+        }
+    }
+
+    /// Returns `true` if the key must be present.
+    pub fn is_required(&self) -> bool {
+        matches!(self.default, FieldDefault::Required)
+    }
+}
+
+/// The value used for a field whose key is missing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FieldDefault {
+    /// The key is required.
+    Required,
+    /// `Default::default()`, which is `None` for `Option` fields.
+    Default,
+    /// The source text of a custom default expression.
+    Expr(&'static str),
+}
+
+/// How a struct handles keys that match none of its fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnknownFields {
+    /// Unknown keys are reported as errors, without failing the conversion.
+    Warn,
+    /// Unknown keys are silently ignored.
+    Ignore,
+    /// Unknown keys fail the conversion.
+    Deny,
+}
+
+/// Description of an enum.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct EnumSchema {
+    /// The Rust name of the type.
+    pub name: &'static str,
+    /// The type's doc comment, one entry per `///` line.
+    pub doc: &'static [&'static str],
+    /// How the variant is encoded.
+    pub repr: EnumRepr,
+    /// The variants, in declaration order.
+    pub variants: &'static [VariantSchema],
+}
+
+impl EnumSchema {
+    #[doc(hidden)]
+    /// Used in derive macro, which cannot name the fields of a non-exhaustive struct
+    pub const fn new(
+        name: &'static str,
+        doc: &'static [&'static str],
+        repr: EnumRepr,
+        variants: &'static [VariantSchema],
+    ) -> EnumSchema {
+        EnumSchema {
+            name,
+            doc,
+            repr,
+            variants,
+        }
+    }
+}
+
+/// How an enum's variant is encoded in TOML.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EnumRepr {
+    /// Unit variants are strings, others a table with the variant name as
+    /// its single key.
+    External,
+    /// The variant name is stored under `tag` alongside its fields.
+    Internal {
+        /// The key holding the variant name.
+        tag: &'static str,
+    },
+    /// The variant name is stored under `tag` and its data under `content`.
+    Adjacent {
+        /// The key holding the variant name.
+        tag: &'static str,
+        /// The key holding the variant data.
+        content: &'static str,
+    },
+    /// Only the variant data is stored; variants are tried in order.
+    Untagged,
+    /// Unit variants are stored as their integer value.
+    Int,
+    /// Unit variants are stored as either their name or their integer value.
+    StrOrInt,
+}
+
+/// Description of one enum variant.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct VariantSchema {
+    /// The name the variant is encoded as, after renaming.
+    pub name: &'static str,
+    /// Additional names accepted when reading.
+    pub aliases: &'static [&'static str],
+    /// Names accepted when reading that report a deprecation warning.
+    pub deprecated_aliases: &'static [&'static str],
+    /// The variant's doc comment, one entry per `///` line.
+    pub doc: &'static [&'static str],
+    /// Whether this is the catch-all `#[toml(other)]` variant.
+    pub other: bool,
+    /// The integer the variant is encoded as, for [`EnumRepr::Int`] and
+    /// [`EnumRepr::StrOrInt`].
+    pub value: Option<i64>,
+    /// The data the variant carries.
+    pub kind: VariantKind,
+}
+
+impl VariantSchema {
+    #[doc(hidden)]
+    /// Used in derive macro, which cannot name the fields of a non-exhaustive struct
+    pub const fn new(
+        name: &'static str,
+        aliases: &'static [&'static str],
+        deprecated_aliases: &'static [&'static str],
+        doc: &'static [&'static str],
+        other: bool,
+        value: Option<i64>,
+        kind: VariantKind,
+    ) -> VariantSchema {
+        VariantSchema {
+            name,
+            aliases,
+            deprecated_aliases,
+            doc,
+            other,
+            value,
+            kind,
+        }
+    }
+}
+
+/// The data carried by an enum variant.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum VariantKind {
+    /// No data.
+    Unit,
+    /// Unnamed fields of the given shapes.
+    Tuple(&'static [fn() -> Schema]),
+    /// Named fields.
+    Struct(&'static [FieldSchema]),
+}
+
+macro_rules! impl_schema {
+    ($($ty:ty => $schema:expr),+ $(,)?) => {$(
+        impl TomlSchema for $ty {
+            fn schema() -> Schema {
+                $schema
+            }
+        }
+    )+};
+}
+
+macro_rules! impl_integer_schema {
+    ($($ty:ty),+) => {$(
+        impl TomlSchema for $ty {
+            fn schema() -> Schema {
+                Schema::Integer {
+                    min: <$ty>::MIN as i128,
+                    max: <$ty>::MAX as i128,
+                }
+            }
+        }
+    )+};
+}
+
+impl_integer_schema!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+impl_schema!(
+    u128 => Schema::Integer { min: 0, max: i128::MAX },
+    bool => Schema::Boolean,
+    f32 => Schema::Float,
+    f64 => Schema::Float,
+    String => Schema::String,
+    str => Schema::String,
+    Cow<'_, str> => Schema::String,
+    PathBuf => Schema::String,
+    DateTime => Schema::DateTime,
+    Date => Schema::DateTime,
+    Time => Schema::DateTime,
+    Item<'_> => Schema::Any,
+    OwnedItem => Schema::Any,
+    Table<'_> => Schema::Map(Item::schema),
+    OwnedTable => Schema::Map(Item::schema),
+    Array<'_> => Schema::Array(Item::schema),
+);
+
+impl<T: TomlSchema + ?Sized> TomlSchema for &T {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: TomlSchema> TomlSchema for Option<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: TomlSchema> TomlSchema for Spanned<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: TomlSchema + ?Sized> TomlSchema for Box<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: TomlSchema + ?Sized> TomlSchema for Rc<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: TomlSchema + ?Sized> TomlSchema for Arc<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+impl<T: TomlSchema> TomlSchema for [T] {
+    fn schema() -> Schema {
+        Schema::Array(T::schema)
+    }
+}
+
+impl<T: TomlSchema, const N: usize> TomlSchema for [T; N] {
+    fn schema() -> Schema {
+        Schema::Array(T::schema)
+    }
+}
+
+impl<T: TomlSchema> TomlSchema for Vec<T> {
+    fn schema() -> Schema {
+        Schema::Array(T::schema)
+    }
+}
+
+impl<T: TomlSchema> TomlSchema for BTreeSet<T> {
+    fn schema() -> Schema {
+        Schema::Array(T::schema)
+    }
+}
+
+impl<T: TomlSchema, H> TomlSchema for HashSet<T, H> {
+    fn schema() -> Schema {
+        Schema::Array(T::schema)
+    }
+}
+
+impl<K, V: TomlSchema> TomlSchema for BTreeMap<K, V> {
+    fn schema() -> Schema {
+        Schema::Map(V::schema)
+    }
+}
+
+impl<K, V: TomlSchema, H> TomlSchema for HashMap<K, V, H> {
+    fn schema() -> Schema {
+        Schema::Map(V::schema)
+    }
+}
+
+macro_rules! impl_tuple_schema {
+    ($($name:ident),+) => {
+        impl<$($name: TomlSchema),+> TomlSchema for ($($name,)+) {
+            fn schema() -> Schema {
+                Schema::Tuple(&[$($name::schema as fn() -> Schema),+])
+            }
+        }
+    };
+}
+
+impl_tuple_schema!(A);
+impl_tuple_schema!(A, B);
+impl_tuple_schema!(A, B, C);
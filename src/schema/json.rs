@@ -0,0 +1,508 @@
+use super::{
+    EnumRepr, EnumSchema, FieldDefault, FieldSchema, Schema, StructSchema, UnknownFields,
+    VariantKind, VariantSchema,
+};
+use crate::item::Value;
+use crate::{Arena, Item};
+
+const DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Minimal JSON tree, only what the schema output needs.
+enum Json {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn str(text: &str) -> Json {
+        Json::Str(text.to_string())
+    }
+
+    fn object(entries: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    fn is_ref(&self) -> bool {
+        matches!(self, Json::Object(entries) if entries.iter().any(|(k, _)| k == "$ref"))
+    }
+
+    fn write(&self, indent: usize, out: &mut String) {
+        match self {
+            Json::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Json::Int(value) => out.push_str(&value.to_string()),
+            Json::Float(value) => out.push_str(&value.to_string()),
+            Json::Str(value) => write_string(value, out),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(indent + 1, out);
+                    item.write(indent + 1, out);
+                }
+                newline(indent, out);
+                out.push(']');
+            }
+            Json::Object(entries) if entries.is_empty() => out.push_str("{}"),
+            Json::Object(entries) => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(indent + 1, out);
+                    write_string(key, out);
+                    out.push_str(": ");
+                    value.write(indent + 1, out);
+                }
+                newline(indent, out);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn newline(indent: usize, out: &mut String) {
+    out.push('\n');
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_string(text: &str, out: &mut String) {
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+/// Joins doc comment lines, dropping the space rustdoc keeps after `///`.
+fn description(doc: &[&str]) -> Option<Json> {
+    let lines: Vec<&str> = doc
+        .iter()
+        .flat_map(|attr| attr.split('\n'))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
+        .collect();
+    let text = lines.join("\n");
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(Json::str(text))
+    }
+}
+
+/// Adds `annotations` to `schema`. A `$ref` is wrapped in `allOf`, since
+/// draft-07 ignores keywords next to it.
+fn annotate(schema: Json, mut annotations: Vec<(String, Json)>) -> Json {
+    if annotations.is_empty() {
+        return schema;
+    }
+    if schema.is_ref() {
+        annotations.push(("allOf".to_string(), Json::Array(vec![schema])));
+        return Json::Object(annotations);
+    }
+    match schema {
+        Json::Object(entries) => {
+            annotations.extend(entries);
+            Json::Object(annotations)
+        }
+        other => other,
+    }
+}
+
+fn with_doc(schema: Json, doc: &[&str]) -> Json {
+    match description(doc) {
+        Some(text) => annotate(schema, vec![("description".to_string(), text)]),
+        None => schema,
+    }
+}
+
+/// Converts a default expression to JSON if it is also a TOML literal,
+/// such as `8080`, `"localhost"` or `[1, 2]`.
+fn default_value(expr: &str) -> Option<Json> {
+    let arena = Arena::new();
+    let text = format!("v = {expr}");
+    let doc = crate::parse(&text, &arena).ok()?;
+    item_to_json(doc.table().get("v")?)
+}
+
+fn item_to_json(item: &Item<'_>) -> Option<Json> {
+    Some(match item.value() {
+        Value::String(value) => Json::str(value),
+        Value::Integer(value) => Json::Int(value.as_i128()),
+        Value::Float(value) if value.is_finite() => Json::Float(*value),
+        Value::Boolean(value) => Json::Bool(*value),
+        Value::Array(array) => Json::Array(array.iter().map(item_to_json).collect::<Option<_>>()?),
+        Value::Table(table) => Json::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| Some((key.name.to_string(), item_to_json(value)?)))
+                .collect::<Option<_>>()?,
+        ),
+        Value::Float(_) | Value::DateTime(_) => return None,
+    })
+}
+
+/// Accumulates the entries of an object schema.
+#[derive(Default)]
+struct Object {
+    properties: Vec<(String, Json)>,
+    required: Vec<Json>,
+    /// `anyOf` required checks for required fields that have aliases.
+    any_required: Vec<Json>,
+    additional: Option<Json>,
+}
+
+impl Object {
+    fn finish(self, deny_unknown: bool) -> Json {
+        let mut entries = vec![("type", Json::str("object"))];
+        if !self.properties.is_empty() {
+            entries.push(("properties", Json::Object(self.properties)));
+        }
+        if !self.required.is_empty() {
+            entries.push(("required", Json::Array(self.required)));
+        }
+        if !self.any_required.is_empty() {
+            entries.push(("allOf", Json::Array(self.any_required)));
+        }
+        match self.additional {
+            Some(additional) => entries.push(("additionalProperties", additional)),
+            None if deny_unknown => entries.push(("additionalProperties", Json::Bool(false))),
+            None => {}
+        }
+        Json::object(entries)
+    }
+}
+
+struct Generator {
+    definitions: Vec<(String, Json)>,
+    /// Each visited struct or enum, identified by its name and the
+    /// `schema` function that described it, and its `$ref`.
+    seen: Vec<(Identity, String)>,
+}
+
+/// The name of a struct or enum and the address of the function returning
+/// its description. Descriptions are constants, which the compiler may
+/// duplicate or merge, so their own address does not identify a type.
+type Identity = (&'static str, usize);
+
+impl Generator {
+    fn value(&mut self, schema: fn() -> Schema) -> Json {
+        self.shape(schema(), schema as usize)
+    }
+
+    /// `source` is the address of the function `schema` was returned by.
+    fn shape(&mut self, schema: Schema, source: usize) -> Json {
+        match schema {
+            Schema::Any => Json::Object(Vec::new()),
+            Schema::Boolean => Json::object(vec![("type", Json::str("boolean"))]),
+            Schema::Integer { min, max } => {
+                let mut entries = vec![("type", Json::str("integer"))];
+                if min > i64::MIN as i128 {
+                    entries.push(("minimum", Json::Int(min)));
+                }
+                if max < i64::MAX as i128 {
+                    entries.push(("maximum", Json::Int(max)));
+                }
+                Json::object(entries)
+            }
+            Schema::Float => Json::object(vec![("type", Json::str("number"))]),
+            Schema::String | Schema::DateTime => Json::object(vec![("type", Json::str("string"))]),
+            Schema::Array(items) => Json::object(vec![
+                ("type", Json::str("array")),
+                ("items", self.value(items)),
+            ]),
+            Schema::Tuple(items) => self.tuple(items),
+            Schema::Map(values) => Json::object(vec![
+                ("type", Json::str("object")),
+                ("additionalProperties", self.value(values)),
+            ]),
+            Schema::Struct(schema) => {
+                self.reference((schema.name, source), |this| this.struct_body(schema))
+            }
+            Schema::Enum(schema) => {
+                self.reference((schema.name, source), |this| this.enum_body(schema))
+            }
+        }
+    }
+
+    fn tuple(&mut self, items: &[fn() -> Schema]) -> Json {
+        let len = items.len() as i128;
+        Json::object(vec![
+            ("type", Json::str("array")),
+            (
+                "items",
+                Json::Array(items.iter().map(|&item| self.value(item)).collect()),
+            ),
+            ("minItems", Json::Int(len)),
+            ("maxItems", Json::Int(len)),
+        ])
+    }
+
+    /// Returns a `$ref` to the definition of a struct or enum, adding the
+    /// definition on first use. Distinct types sharing a name get numbered.
+    fn reference(&mut self, identity: Identity, body: impl FnOnce(&mut Self) -> Json) -> Json {
+        if let Some((_, path)) = self.seen.iter().find(|(seen, _)| *seen == identity) {
+            return Json::object(vec![("$ref", Json::str(path))]);
+        }
+        let name = identity.0;
+        let mut unique = name.to_string();
+        let mut n = 1;
+        while self.definitions.iter().any(|(key, _)| *key == unique) {
+            n += 1;
+            unique = format!("{name}{n}");
+        }
+        let path = format!("#/definitions/{unique}");
+        self.seen.push((identity, path.clone()));
+        let slot = self.definitions.len();
+        self.definitions.push((unique, Json::Object(Vec::new())));
+        self.definitions[slot].1 = body(self);
+        Json::object(vec![("$ref", Json::Str(path))])
+    }
+
+    fn struct_body(&mut self, schema: &StructSchema) -> Json {
+        let mut object = Object::default();
+        self.fields(schema.fields, &mut object);
+        let body = object.finish(schema.unknown_fields == UnknownFields::Deny);
+        with_doc(body, schema.doc)
+    }
+
+    fn fields(&mut self, fields: &[FieldSchema], object: &mut Object) {
+        for field in fields {
+            if field.flatten {
+                match (field.schema)() {
+                    Schema::Struct(inner) => self.fields(inner.fields, object),
+                    Schema::Map(values) => object.additional = Some(self.value(values)),
+                    _ => object.additional = Some(Json::Object(Vec::new())),
+                }
+                continue;
+            }
+            let schema = self.field(field);
+            object.properties.push((field.key.to_string(), schema));
+            for alias in field.aliases {
+                let alias_schema = self.field(field);
+                object.properties.push((alias.to_string(), alias_schema));
+            }
+            for alias in field.deprecated_aliases {
+                let alias_schema = annotate(
+                    self.field(field),
+                    vec![("deprecated".to_string(), Json::Bool(true))],
+                );
+                object.properties.push((alias.to_string(), alias_schema));
+            }
+
+            if !field.is_required() {
+                continue;
+            }
+            let keys = std::iter::once(&field.key)
+                .chain(field.aliases)
+                .chain(field.deprecated_aliases);
+            if field.aliases.is_empty() && field.deprecated_aliases.is_empty() {
+                object.required.push(Json::str(field.key));
+            } else {
+                let any_of = keys
+                    .map(|key| Json::object(vec![("required", Json::Array(vec![Json::str(key)]))]))
+                    .collect();
+                object
+                    .any_required
+                    .push(Json::object(vec![("anyOf", Json::Array(any_of))]));
+            }
+        }
+    }
+
+    fn field(&mut self, field: &FieldSchema) -> Json {
+        let schema = self.value(field.schema);
+        let mut annotations = Vec::new();
+        if let Some(text) = description(field.doc) {
+            annotations.push(("description".to_string(), text));
+        }
+        if let FieldDefault::Expr(expr) = field.default
+            && let Some(value) = default_value(expr)
+        {
+            annotations.push(("default".to_string(), value));
+        }
+        annotate(schema, annotations)
+    }
+
+    fn enum_body(&mut self, schema: &EnumSchema) -> Json {
+        let plain = !matches!(
+            schema.repr,
+            EnumRepr::Internal { .. } | EnumRepr::Adjacent { .. }
+        ) && schema.variants.iter().all(|variant| {
+            matches!(variant.kind, VariantKind::Unit)
+                && !variant.other
+                && description(variant.doc).is_none()
+        });
+        let body = if plain {
+            let names = schema
+                .variants
+                .iter()
+                .flat_map(variant_names)
+                .map(Json::str);
+            let values = schema
+                .variants
+                .iter()
+                .filter_map(|variant| variant.value)
+                .map(|value| Json::Int(value.into()));
+            let (ty, values) = match schema.repr {
+                EnumRepr::Int => (Json::str("integer"), values.collect()),
+                EnumRepr::StrOrInt => (
+                    Json::Array(vec![Json::str("string"), Json::str("integer")]),
+                    names.chain(values).collect(),
+                ),
+                _ => (Json::str("string"), names.collect()),
+            };
+            Json::object(vec![("type", ty), ("enum", Json::Array(values))])
+        } else {
+            let variants = schema
+                .variants
+                .iter()
+                .map(|variant| {
+                    let body = self.variant(schema.repr, variant);
+                    with_doc(body, variant.doc)
+                })
+                .collect();
+            Json::object(vec![("anyOf", Json::Array(variants))])
+        };
+        with_doc(body, schema.doc)
+    }
+
+    fn variant(&mut self, repr: EnumRepr, variant: &VariantSchema) -> Json {
+        let name = if variant.other {
+            Json::object(vec![("type", Json::str("string"))])
+        } else if variant.aliases.is_empty() && variant.deprecated_aliases.is_empty() {
+            Json::object(vec![("const", Json::str(variant.name))])
+        } else {
+            let names = variant_names(variant).map(Json::str).collect();
+            Json::object(vec![("enum", Json::Array(names))])
+        };
+        let value = match variant.value {
+            Some(value) if !variant.other => Json::object(vec![("const", Json::Int(value.into()))]),
+            _ => Json::object(vec![("type", Json::str("integer"))]),
+        };
+        match repr {
+            EnumRepr::Int => value,
+            EnumRepr::StrOrInt => Json::object(vec![("anyOf", Json::Array(vec![name, value]))]),
+            EnumRepr::External | EnumRepr::Untagged
+                if matches!(variant.kind, VariantKind::Unit) =>
+            {
+                name
+            }
+            EnumRepr::External => {
+                let mut object = Object::default();
+                let data = self.variant_data(variant.kind);
+                object.properties.push((variant.name.to_string(), data));
+                object.required.push(Json::str(variant.name));
+                object.finish(true)
+            }
+            EnumRepr::Untagged => self.variant_data(variant.kind),
+            EnumRepr::Internal { tag } => {
+                let mut object = Object::default();
+                object.properties.push((tag.to_string(), name));
+                object.required.push(Json::str(tag));
+                match variant.kind {
+                    VariantKind::Unit => object.finish(false),
+                    VariantKind::Struct(fields) => {
+                        self.fields(fields, &mut object);
+                        object.finish(false)
+                    }
+                    VariantKind::Tuple(_) => {
+                        let tag = object.finish(false);
+                        let data = self.variant_data(variant.kind);
+                        Json::object(vec![("allOf", Json::Array(vec![tag, data]))])
+                    }
+                }
+            }
+            EnumRepr::Adjacent { tag, content } => {
+                let mut object = Object::default();
+                object.properties.push((tag.to_string(), name));
+                object.required.push(Json::str(tag));
+                if !matches!(variant.kind, VariantKind::Unit) {
+                    let data = self.variant_data(variant.kind);
+                    object.properties.push((content.to_string(), data));
+                    object.required.push(Json::str(content));
+                }
+                object.finish(false)
+            }
+        }
+    }
+
+    fn variant_data(&mut self, kind: VariantKind) -> Json {
+        match kind {
+            VariantKind::Unit => Json::Object(Vec::new()),
+            VariantKind::Tuple([single]) => self.value(*single),
+            VariantKind::Tuple(items) => self.tuple(items),
+            VariantKind::Struct(fields) => {
+                let mut object = Object::default();
+                self.fields(fields, &mut object);
+                object.finish(false)
+            }
+        }
+    }
+}
+
+/// The name of `variant` followed by every name it is read from.
+fn variant_names(variant: &VariantSchema) -> impl Iterator<Item = &'static str> {
+    std::iter::once(variant.name)
+        .chain(variant.aliases.iter().copied())
+        .chain(variant.deprecated_aliases.iter().copied())
+}
+
+/// See [`Schema::to_json_schema`]. `source` is the address of the function
+/// `schema` was returned by, or 0 when unknown.
+pub(super) fn to_json_schema(schema: Schema, source: usize) -> String {
+    let mut generator = Generator {
+        definitions: Vec::new(),
+        seen: Vec::new(),
+    };
+    let mut entries = vec![("$schema".to_string(), Json::str(DRAFT))];
+    // The root struct or enum is written inline and referenced as `#`.
+    let (title, body) = match schema {
+        Schema::Struct(root) => {
+            generator.seen.push(((root.name, source), "#".to_string()));
+            (Some(root.name), generator.struct_body(root))
+        }
+        Schema::Enum(root) => {
+            generator.seen.push(((root.name, source), "#".to_string()));
+            (Some(root.name), generator.enum_body(root))
+        }
+        other => (None, generator.shape(other, source)),
+    };
+    if let Some(title) = title {
+        entries.push(("title".to_string(), Json::str(title)));
+    }
+    if let Json::Object(body) = body {
+        entries.extend(body);
+    }
+    if !generator.definitions.is_empty() {
+        entries.push((
+            "definitions".to_string(),
+            Json::Object(generator.definitions),
+        ));
+    }
+    let mut out = String::new();
+    Json::Object(entries).write(0, &mut out);
+    out.push('\n');
+    out
+}
@@ -6,8 +6,31 @@ use std::{
     sync::Arc,
 };
 
+use crate::schema::Schema;
 use crate::{Arena, Array, DateTime, Item, Key, Table, item::Value};
 
+std::thread_local! {
+    static EXPAND_DEFAULTS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Runs `f` with [`expands_defaults`] returning `expand`, restoring the
+/// previous value afterwards, so the flag never outlives the serialization
+/// it was set for.
+pub(crate) fn with_expanded_defaults<R>(expand: bool, f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            EXPAND_DEFAULTS.set(self.0);
+        }
+    }
+    let _restore = Restore(EXPAND_DEFAULTS.replace(expand));
+    f()
+}
+
+pub(crate) fn expands_defaults() -> bool {
+    EXPAND_DEFAULTS.get()
+}
+
 /// extracted out to avoid code bloat
 fn optional_to_required<'a>(
     optional: Result<Option<Item<'a>>, ToTomlError>,
@@ -79,6 +102,18 @@ pub trait ToToml {
     fn to_optional_toml<'a>(&'a self, arena: &'a Arena) -> Result<Option<Item<'a>>, ToTomlError> {
         required_to_optional(self.to_toml(arena))
     }
+    /// Returns the schema whose field doc comments
+    /// [`to_string`](crate::to_string) and
+    /// [`Formatting::format`](crate::Formatting::format) write as leading
+    /// comments.
+    ///
+    /// Returns `None` by default. The derive macro overrides it for structs
+    /// marked `#[toml(doc_comments)]`, returning their [`TomlSchema`].
+    ///
+    /// [`TomlSchema`]: crate::schema::TomlSchema
+    fn doc_comments(&self) -> Option<Schema> {
+        None
+    }
 }
 
 impl<K: ToToml> ToToml for BTreeSet<K> {
@@ -156,6 +191,9 @@ impl<T: ToToml> ToToml for Box<T> {
     fn to_toml<'a>(&'a self, arena: &'a Arena) -> Result<Item<'a>, ToTomlError> {
         <T as ToToml>::to_toml(self, arena)
     }
+    fn doc_comments(&self) -> Option<Schema> {
+        <T as ToToml>::doc_comments(self)
+    }
 }
 
 impl<T: ToToml> ToToml for [T] {
@@ -204,30 +242,45 @@ impl<T: ToToml + ?Sized> ToToml for &T {
     fn to_toml<'a>(&'a self, arena: &'a Arena) -> Result<Item<'a>, ToTomlError> {
         <T as ToToml>::to_toml(self, arena)
     }
+    fn doc_comments(&self) -> Option<Schema> {
+        <T as ToToml>::doc_comments(self)
+    }
 }
 
 impl<T: ToToml + ?Sized> ToToml for &mut T {
     fn to_toml<'a>(&'a self, arena: &'a Arena) -> Result<Item<'a>, ToTomlError> {
         <T as ToToml>::to_toml(self, arena)
     }
+    fn doc_comments(&self) -> Option<Schema> {
+        <T as ToToml>::doc_comments(self)
+    }
 }
 
 impl<T: ToToml> ToToml for Rc<T> {
     fn to_toml<'a>(&'a self, arena: &'a Arena) -> Result<Item<'a>, ToTomlError> {
         <T as ToToml>::to_toml(self, arena)
     }
+    fn doc_comments(&self) -> Option<Schema> {
+        <T as ToToml>::doc_comments(self)
+    }
 }
 
 impl<T: ToToml> ToToml for Arc<T> {
     fn to_toml<'a>(&'a self, arena: &'a Arena) -> Result<Item<'a>, ToTomlError> {
         <T as ToToml>::to_toml(self, arena)
     }
+    fn doc_comments(&self) -> Option<Schema> {
+        <T as ToToml>::doc_comments(self)
+    }
 }
 
 impl<'b, T: ToToml + Clone> ToToml for Cow<'b, T> {
     fn to_toml<'a>(&'a self, arena: &'a Arena) -> Result<Item<'a>, ToTomlError> {
         <T as ToToml>::to_toml(self, arena)
     }
+    fn doc_comments(&self) -> Option<Schema> {
+        <T as ToToml>::doc_comments(self)
+    }
 }
 
 impl ToToml for char {
@@ -291,9 +291,28 @@ where
     fn from_toml(
         ctx: &mut crate::de::Context<'de>,
         value: &crate::item::Item<'de>,
+    ) -> Result<Self, crate::de::Failed> {
+        Self::from_toml_at_key(ctx, Span::default(), value)
+    }
+
+    #[inline]
+    fn from_toml_at_key(
+        ctx: &mut crate::de::Context<'de>,
+        key_span: Span,
+        value: &crate::item::Item<'de>,
     ) -> Result<Self, crate::de::Failed> {
         let span = value.span_unchecked();
-        let inner = T::from_toml(ctx, value)?;
+        let inner = T::from_toml_at_key(ctx, key_span, value)?;
         Ok(Self { span, value: inner })
     }
+
+    #[inline]
+    fn claims_key(key: &str) -> bool {
+        T::claims_key(key)
+    }
+
+    #[inline]
+    fn expected_keys(keys: &mut Vec<&'static str>) {
+        T::expected_keys(keys)
+    }
 }
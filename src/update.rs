@@ -0,0 +1,152 @@
+use crate::error::{MaybeTomlPath, PathComponent};
+use crate::{Context, Failed, FromToml, Item, Key, TomlPath};
+
+/// The fields changed by an in-place update, returned by
+/// [`UpdateFromToml::update`].
+///
+/// Each entry is the path of a value that was replaced, relative to the
+/// updated value. Values under a changed path are not listed separately.
+/// An empty path means the updated value was replaced as a whole.
+#[derive(Default)]
+pub struct ChangeSet {
+    paths: Vec<MaybeTomlPath>,
+}
+
+impl ChangeSet {
+    /// Creates an empty change set.
+    pub fn new() -> Self {
+        ChangeSet { paths: Vec::new() }
+    }
+
+    /// Creates a change set recording that the whole value was replaced.
+    pub fn replaced() -> Self {
+        ChangeSet {
+            paths: vec![MaybeTomlPath::empty()],
+        }
+    }
+
+    /// Returns `true` if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Returns the number of changed paths.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns an iterator over the changed paths, in field order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &TomlPath<'_>> {
+        self.paths.iter().map(|path| match path.as_toml_path() {
+            Some(path) => path,
+            None => TomlPath::empty(),
+        })
+    }
+
+    /// Returns `true` if the value at the dotted `keys` changed, either
+    /// because it or something inside it was replaced, or because a table
+    /// containing it was.
+    ///
+    /// ```
+    /// # use toml_spanner::ChangeSet;
+    /// let mut server = ChangeSet::new();
+    /// server.nest("port", ChangeSet::replaced());
+    /// let mut changes = ChangeSet::new();
+    /// changes.nest("server", server);
+    ///
+    /// assert!(changes.affects(&["server"]));
+    /// assert!(changes.affects(&["server", "port"]));
+    /// assert!(!changes.affects(&["server", "host"]));
+    /// ```
+    pub fn affects(&self, keys: &[&str]) -> bool {
+        self.iter().any(|path| {
+            path.iter()
+                .zip(keys)
+                .all(|(component, key)| match component {
+                    PathComponent::Key(changed) => changed.name == *key,
+                    PathComponent::Index(_) => false,
+                })
+        })
+    }
+
+    /// Records the changes of the value under `key`, prefixing each of the
+    /// `nested` paths with it.
+    pub fn nest(&mut self, key: &str, nested: ChangeSet) {
+        let mut components = vec![PathComponent::Key(Key::new(key))];
+        for path in nested.iter() {
+            components.truncate(1);
+            components.extend_from_slice(path);
+            self.paths.push(MaybeTomlPath::from_components(&components));
+        }
+    }
+}
+
+impl std::fmt::Debug for ChangeSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Updates an existing value in place from TOML, reporting what changed.
+///
+/// Implemented by `#[derive(Toml)]` with `#[toml(Update)]`, for reloading
+/// configuration without rebuilding the parts that stayed the same.
+///
+/// ```
+/// # #[cfg(not(feature = "derive"))]
+/// # fn main() {}
+/// # #[cfg(feature = "derive")]
+/// # fn main() {
+/// # use toml_spanner::Arena;
+/// #[derive(toml_spanner::Toml, PartialEq)]
+/// #[toml(FromToml, Update)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let arena = Arena::new();
+/// let mut doc = toml_spanner::parse("name = 'a'\nport = 80", &arena).unwrap();
+/// let mut config = doc.to::<Config>().unwrap();
+///
+/// let mut doc = toml_spanner::parse("name = 'a'\nport = 8080", &arena).unwrap();
+/// let changes = doc.update(&mut config).unwrap();
+/// assert_eq!(config.port, 8080);
+/// assert!(changes.affects(&["port"]));
+/// assert_eq!(changes.len(), 1);
+/// # }
+/// ```
+pub trait UpdateFromToml<'de>: FromToml<'de> {
+    /// Moves the fields of `new` that differ into `self`, leaving equal
+    /// fields untouched, and returns the paths of those that changed.
+    fn update(&mut self, new: Self) -> ChangeSet;
+
+    /// Reads a new value from `item` and applies it with
+    /// [`update`](Self::update).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Failed`] when `item` cannot be converted, in which case
+    /// `self` is left unchanged.
+    fn update_from_toml(
+        &mut self,
+        ctx: &mut Context<'de>,
+        item: &Item<'de>,
+    ) -> Result<ChangeSet, Failed> {
+        let new = Self::from_toml(ctx, item)?;
+        Ok(self.update(new))
+    }
+}
+
+impl<'de, T: UpdateFromToml<'de>> UpdateFromToml<'de> for Option<T> {
+    fn update(&mut self, new: Self) -> ChangeSet {
+        match (self, new) {
+            (Some(value), Some(new)) => value.update(new),
+            (None, None) => ChangeSet::new(),
+            (value, new) => {
+                *value = new;
+                ChangeSet::replaced()
+            }
+        }
+    }
+}
@@ -318,6 +318,8 @@ fn expect_helpers() {
     let mut ctx = crate::de::Context {
         arena: &arena,
         index: Default::default(),
+        unindexed: Default::default(),
+        migrated: Vec::new(),
         errors: Vec::new(),
         source: "",
     };
@@ -921,6 +923,48 @@ fn clone_in_preserves_flags() {
     assert!(cloned.is_aot());
 }
 
+#[test]
+fn into_owned_in_outlives_source() {
+    let arena = Arena::new();
+    let owned = {
+        let source = String::from("items = [{ name = 'a' }, 1.5]\n[t]\nkey = true");
+        let src_arena = Arena::new();
+        let doc = crate::parse(&source, &src_arena).unwrap();
+        doc.table()
+            .as_item()
+            .clone_in(&src_arena)
+            .into_owned_in(&arena)
+    };
+    assert_eq!(owned["items"][0]["name"].as_str(), Some("a"));
+    assert_eq!(owned["items"][1].as_f64(), Some(1.5));
+    assert_eq!(owned["t"]["key"].as_bool(), Some(true));
+    let (key, _) = owned.as_table().unwrap().get_key_value("t").unwrap();
+    assert_eq!(key.span, sp(31, 32));
+    assert!(owned["t"].as_table().unwrap().style() == TableStyle::Header);
+}
+
+#[test]
+fn into_owned_in_keeps_data_already_in_arena() {
+    let arena = Arena::new();
+    let name = arena.alloc_str("name");
+    let value = arena.alloc_str("value");
+    let borrowed = String::from("borrowed");
+    let mut table = crate::Table::new();
+    table.insert(Key::new(name), Item::string(value), &arena);
+    table.insert(Key::new("other"), Item::string(&borrowed), &arena);
+    let entries = table.entries().as_ptr();
+
+    let owned = table.into_item().into_owned_in(&arena);
+    let table = owned.as_table().unwrap();
+    assert!(std::ptr::eq(table.entries().as_ptr(), entries));
+    let (key, item) = &table.entries()[0];
+    assert!(std::ptr::eq(key.name, name));
+    assert!(std::ptr::eq(item.as_str().unwrap(), value));
+    let copied = table["other"].as_str().unwrap();
+    assert_eq!(copied, "borrowed");
+    assert!(!std::ptr::eq(copied, borrowed.as_str()));
+}
+
 #[test]
 fn clone_in_empty_containers() {
     let arena = Arena::new();
@@ -2688,6 +2688,132 @@ fn from_attribute_enum() {
     assert_eq!(v, Color::Red);
 }
 
+#[test]
+fn into_attribute_roundtrip() {
+    #[derive(Toml, Debug, PartialEq)]
+    #[toml(Toml)]
+    struct RawEndpoint {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Toml, Debug, PartialEq, Clone)]
+    #[toml(Toml, from = RawEndpoint, into = RawEndpoint)]
+    struct Endpoint(String);
+
+    impl From<RawEndpoint> for Endpoint {
+        fn from(raw: RawEndpoint) -> Self {
+            Endpoint(format!("{}:{}", raw.host, raw.port))
+        }
+    }
+
+    impl From<Endpoint> for RawEndpoint {
+        fn from(endpoint: Endpoint) -> Self {
+            let (host, port) = endpoint.0.split_once(':').unwrap();
+            RawEndpoint {
+                host: host.to_string(),
+                port: port.parse().unwrap(),
+            }
+        }
+    }
+
+    let endpoint = Endpoint("localhost:8080".to_string());
+    let out = toml_spanner::to_string(&endpoint).unwrap();
+    assert_eq!(out, "host = \"localhost\"\nport = 8080\n");
+    let back: Endpoint = toml_spanner::from_str(&out).unwrap();
+    assert_eq!(back, endpoint);
+}
+
+#[test]
+fn try_into_attribute() {
+    #[derive(Toml)]
+    #[toml(ToToml)]
+    struct Positive {
+        value: u64,
+    }
+
+    #[derive(Toml, Clone)]
+    #[toml(ToToml, try_into = Positive)]
+    struct Signed {
+        value: i64,
+    }
+
+    impl TryFrom<Signed> for Positive {
+        type Error = String;
+        fn try_from(signed: Signed) -> Result<Self, Self::Error> {
+            match u64::try_from(signed.value) {
+                Ok(value) => Ok(Positive { value }),
+                Err(_) => Err(format!("{} is negative", signed.value)),
+            }
+        }
+    }
+
+    let out = toml_spanner::to_string(&Signed { value: 3 }).unwrap();
+    assert_eq!(out, "value = 3\n");
+    let err = toml_spanner::to_string(&Signed { value: -3 }).unwrap_err();
+    assert_eq!(err.to_string(), "-3 is negative");
+}
+
+mod foreign {
+    #[derive(Debug, PartialEq)]
+    pub struct Window {
+        pub width: u32,
+        pub height: u32,
+        pub title: String,
+    }
+}
+
+#[derive(Toml)]
+#[toml(Toml, remote = "foreign::Window")]
+struct WindowDef {
+    width: u32,
+    height: u32,
+    #[toml(default = String::from("untitled"))]
+    title: String,
+}
+
+#[test]
+fn remote_attribute_roundtrip() {
+    #[derive(Toml, Debug, PartialEq)]
+    #[toml(Toml)]
+    struct Config {
+        #[toml(with = WindowDef)]
+        window: foreign::Window,
+    }
+
+    let config: Config = toml_spanner::from_str("[window]\nwidth = 800\nheight = 600\n").unwrap();
+    assert_eq!(
+        config.window,
+        foreign::Window {
+            width: 800,
+            height: 600,
+            title: "untitled".to_string(),
+        }
+    );
+
+    let out = toml_spanner::to_string(&config).unwrap();
+    assert_eq!(
+        out,
+        "[window]\nwidth = 800\nheight = 600\ntitle = \"untitled\"\n"
+    );
+    let back: Config = toml_spanner::from_str(&out).unwrap();
+    assert_eq!(back, config);
+}
+
+#[test]
+fn remote_attribute_errors() {
+    #[derive(Toml, Debug)]
+    #[toml(FromToml)]
+    struct Config {
+        #[toml(with = WindowDef)]
+        #[allow(dead_code)]
+        window: foreign::Window,
+    }
+
+    let err = toml_spanner::from_str::<Config>("[window]\nwidth = 800\n").unwrap_err();
+    assert_eq!(err.to_string(), "missing required key 'height' at `window`");
+}
+
 // String enum with other
 #[derive(Toml, Debug, PartialEq)]
 #[toml(FromToml, ToToml)]
@@ -3027,6 +3153,67 @@ fn deny_unknown_fields_with_tag() {
     ));
 }
 
+#[test]
+fn unknown_field_suggests_closest_key() {
+    #[derive(Toml, Debug)]
+    #[toml(FromToml, rename_all = "kebab-case")]
+    #[allow(dead_code)]
+    struct Server {
+        host: String,
+        port: u16,
+        max_connections: Option<u32>,
+    }
+
+    let src = "host = 'a'\nport = 1\nprot = 80\nmax-conections = 5\nzzz = 0";
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse(src, &arena).unwrap();
+    let (_, errors) = doc.to_allowing_errors::<Server>().unwrap();
+    assert!(
+        errors
+            .errors
+            .iter()
+            .all(|e| matches!(e.kind(), toml_spanner::ErrorKind::UnexpectedKey { .. }))
+    );
+    let suggestions: Vec<_> = errors.errors.iter().map(|e| e.suggestion()).collect();
+    assert_eq!(suggestions, [Some("port"), Some("max-connections"), None]);
+    let help: Vec<_> = errors.errors.iter().map(|e| e.help()).collect();
+    assert_eq!(
+        help,
+        [
+            Some("did you mean `port`?".into()),
+            Some("did you mean `max-connections`?".into()),
+            None
+        ]
+    );
+    assert_eq!(
+        errors.errors[0].message(src),
+        "unexpected key\nhelp: did you mean `port`?"
+    );
+    assert_eq!(
+        errors.errors[0].to_string(),
+        "unexpected key at `prot`, did you mean `port`?"
+    );
+    assert_eq!(errors.errors[2].help(), None);
+}
+
+#[test]
+fn unknown_short_field_gets_no_suggestion() {
+    #[derive(Toml, Debug)]
+    #[toml(FromToml)]
+    #[allow(dead_code)]
+    struct Point {
+        w: Option<u32>,
+        ab: Option<u32>,
+    }
+
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("x = 1\nac = 2\nAB = 3", &arena).unwrap();
+    let (_, errors) = doc.to_allowing_errors::<Point>().unwrap();
+    let suggestions: Vec<_> = errors.errors.iter().map(|e| e.suggestion()).collect();
+    // Only a difference in case is close enough for names this short.
+    assert_eq!(suggestions, [None, None, Some("ab")]);
+}
+
 #[derive(Toml, Debug, PartialEq)]
 struct WithDeprecatedAlias {
     #[toml(deprecated_alias = "old_name")]
@@ -3180,6 +3367,106 @@ fn deprecated_alias_with_rename_all_old_key() {
     }
 }
 
+#[derive(Toml, Debug, PartialEq)]
+#[toml(rename_all = "kebab-case", key_match = "ignore_case_and_separators")]
+struct LooseKeys {
+    default_features: bool,
+    #[toml(alias = "pkg")]
+    package_name: String,
+}
+
+#[test]
+fn key_match_canonical_spelling_has_no_warnings() {
+    let v: LooseKeys = toml_spanner::from_str("default-features = true\npkg = \"a\"").unwrap();
+    assert!(v.default_features);
+    assert_eq!(v.package_name, "a");
+}
+
+#[test]
+fn key_match_accepts_other_spellings_with_warning() {
+    let src = "defaultFeatures = true\nPACKAGE_NAME = \"a\"\nunknown_key = 1";
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse(src, &arena).unwrap();
+    let (val, errors) = doc
+        .to_allowing_errors::<LooseKeys>()
+        .expect("should deserialize");
+    assert!(val.default_features);
+    assert_eq!(val.package_name, "a");
+
+    let kinds: Vec<_> = errors.errors.iter().map(|e| e.kind()).collect();
+    assert!(matches!(
+        kinds[..],
+        [
+            toml_spanner::ErrorKind::NonCanonicalKey {
+                canonical: &"default-features"
+            },
+            toml_spanner::ErrorKind::NonCanonicalKey {
+                canonical: &"package-name"
+            },
+            toml_spanner::ErrorKind::UnexpectedKey { .. },
+        ]
+    ));
+    assert_eq!(
+        errors.errors[0].message(src),
+        "key 'defaultFeatures' should be spelled 'default-features'"
+    );
+    assert_eq!(
+        errors.errors[1].to_string(),
+        "key should be spelled 'package-name'"
+    );
+}
+
+#[test]
+fn key_match_ignore_case_keeps_separators() {
+    #[derive(Toml, Debug)]
+    #[toml(key_match = "ignore_case", deny_unknown_fields)]
+    struct CaseOnly {
+        max_size: u32,
+    }
+
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("Max_Size = 3", &arena).unwrap();
+    let (val, errors) = doc.to_allowing_errors::<CaseOnly>().unwrap();
+    assert_eq!(val.max_size, 3);
+    assert_eq!(errors.errors.len(), 1);
+
+    assert!(toml_spanner::from_str::<CaseOnly>("max-size = 3").is_err());
+}
+
+#[test]
+fn key_match_rejects_two_spellings_of_one_field() {
+    let src = "default_features = true\ndefault-features = false\npkg = \"a\"";
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse(src, &arena).unwrap();
+    let err = doc.to_allowing_errors::<LooseKeys>().unwrap_err();
+    let duplicate = err
+        .errors
+        .iter()
+        .find(|e| matches!(e.kind(), toml_spanner::ErrorKind::DuplicateField { .. }))
+        .expect("second spelling should be a duplicate");
+    assert_eq!(&src[duplicate.span().range()], "default-features");
+}
+
+#[test]
+fn key_match_in_struct_variant() {
+    #[derive(Toml, Debug, PartialEq)]
+    #[toml(tag = "kind", key_match = "ignore_separators")]
+    enum Source {
+        Git { repo_url: String },
+    }
+
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("kind = \"Git\"\nrepo-url = \"x\"", &arena).unwrap();
+    let (val, errors) = doc.to_allowing_errors::<Source>().unwrap();
+    assert_eq!(
+        val,
+        Source::Git {
+            repo_url: "x".into()
+        }
+    );
+    assert_eq!(errors.errors.len(), 1);
+}
+
 #[derive(Toml, Debug, PartialEq)]
 #[toml(Toml)]
 struct WithTuples {
@@ -4088,3 +4375,2110 @@ fn recoverable_66_fields_missing_within_64_with_prior_error() {
         "f00 type error + f01 missing both reported: {errors:?}"
     );
 }
+
+fn nonzero_port(port: &u16) -> Result<(), &'static str> {
+    if *port == 0 {
+        Err("port must be non-zero")
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, validate = Listen::check_range)]
+struct Listen {
+    #[toml(validate = nonzero_port)]
+    port: u16,
+    #[toml(validate = |host: &String| if host.is_empty() { Err("host is empty") } else { Ok(()) })]
+    host: Option<String>,
+    #[toml(default)]
+    min: u32,
+    #[toml(default = 10)]
+    max: u32,
+}
+
+impl Listen {
+    fn check_range(&self, ctx: &mut toml_spanner::Context<'_>) -> Result<(), toml_spanner::Failed> {
+        if self.min > self.max {
+            return Err(ctx.report_error_at("min exceeds max", toml_spanner::Span::new(0, 0)));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn validate_field_passes() {
+    let v: Listen = toml_spanner::from_str("port = 80\nhost = \"a\"").unwrap();
+    assert_eq!(
+        v,
+        Listen {
+            port: 80,
+            host: Some("a".into()),
+            min: 0,
+            max: 10
+        }
+    );
+}
+
+#[test]
+fn validate_field_reports_span_and_path() {
+    #[derive(Toml, Debug)]
+    #[allow(dead_code)]
+    struct Outer {
+        server: Listen,
+    }
+    let arena = Arena::new();
+    let input = "[server]\nport = 0\n";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let err = doc.to::<Outer>().unwrap_err();
+    assert_eq!(err.errors.len(), 1);
+    let error = &err.errors[0];
+    assert_eq!(&input[error.span().range()], "0");
+    assert_eq!(error.path().unwrap().to_string(), "server.port");
+    assert!(error.to_string().contains("port must be non-zero"));
+}
+
+#[test]
+fn validate_optional_field() {
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("port = 1\nhost = \"\"", &arena).unwrap();
+    let err = doc.to::<Listen>().unwrap_err();
+    assert_eq!(err.errors.len(), 1);
+    assert!(err.errors[0].to_string().contains("host is empty"));
+
+    // Like a value that fails to deserialize, a rejected optional value
+    // leaves the field unset while the error is still recorded.
+    for input in ["port = 1\nhost = \"\"", "port = 1\nhost = 5"] {
+        let mut doc = toml_spanner::parse(input, &arena).unwrap();
+        let (ctx, table) = doc.split();
+        let listen = Listen::from_toml(ctx, table.as_item()).unwrap();
+        assert_eq!(listen.host, None);
+        assert_eq!(doc.errors().len(), 1);
+    }
+}
+
+#[test]
+fn validate_container_runs_after_construction() {
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("port = 1\nmin = 20", &arena).unwrap();
+    let err = doc.to::<Listen>().unwrap_err();
+    assert_eq!(err.errors.len(), 1);
+    assert!(err.errors[0].to_string().contains("min exceeds max"));
+
+    // Container validation is skipped when a field already failed.
+    let mut doc = toml_spanner::parse("port = 0\nmin = 20", &arena).unwrap();
+    let err = doc.to::<Listen>().unwrap_err();
+    assert_eq!(err.errors.len(), 1);
+    assert!(err.errors[0].to_string().contains("port must be non-zero"));
+}
+
+#[test]
+fn validate_recoverable_collects_all_violations() {
+    #[derive(Toml, Debug)]
+    #[allow(dead_code)]
+    #[toml(FromToml, recoverable)]
+    struct Limits {
+        #[toml(validate = nonzero_port)]
+        a: u16,
+        #[toml(validate = nonzero_port)]
+        b: u16,
+        c: u16,
+    }
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("a = 0\nb = 0", &arena).unwrap();
+    let err = doc.to::<Limits>().unwrap_err();
+    assert_eq!(
+        err.errors.len(),
+        3,
+        "expected 2 violations + 1 missing field, got: {:?}",
+        err.errors
+    );
+}
+
+#[test]
+fn validate_enum_container() {
+    fn not_stop(
+        action: &Action,
+        ctx: &mut toml_spanner::Context<'_>,
+    ) -> Result<(), toml_spanner::Failed> {
+        if *action == Action::Stop {
+            return Err(ctx.report_error_at("stop is disabled", toml_spanner::Span::new(0, 0)));
+        }
+        Ok(())
+    }
+    #[derive(Toml, Debug, PartialEq)]
+    #[toml(FromToml, tag = "type", validate = not_stop)]
+    enum Action {
+        Run {
+            #[toml(validate = nonzero_port)]
+            speed: u16,
+        },
+        Stop,
+    }
+    let v: Action = toml_spanner::from_str("type = \"Run\"\nspeed = 3").unwrap();
+    assert_eq!(v, Action::Run { speed: 3 });
+    assert!(toml_spanner::from_str::<Action>("type = \"Run\"\nspeed = 0").is_err());
+    assert!(toml_spanner::from_str::<Action>("type = \"Stop\"").is_err());
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml)]
+struct Constrained {
+    #[toml(range = 1..=65535)]
+    port: u32,
+    #[toml(non_empty)]
+    name: String,
+    #[toml(min_len = 1, max_len = 3)]
+    tags: Vec<String>,
+    #[toml(one_of = ["debug", "info", "warn"])]
+    level: Option<String>,
+    #[toml(default = 1, one_of = [-1, 1])]
+    sign: i64,
+}
+
+fn constraint_error(input: &str) -> toml_spanner::Error {
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let mut err = doc.to::<Constrained>().unwrap_err();
+    assert_eq!(err.errors.len(), 1, "{:?}", err.errors);
+    err.errors.remove(0)
+}
+
+#[test]
+fn constraints_accept_valid_values() {
+    let v: Constrained = toml_spanner::from_str(
+        "port = 80\nname = \"a\"\ntags = [\"x\"]\nlevel = \"info\"\nsign = -1",
+    )
+    .unwrap();
+    assert_eq!(
+        v,
+        Constrained {
+            port: 80,
+            name: "a".into(),
+            tags: vec!["x".into()],
+            level: Some("info".into()),
+            sign: -1,
+        }
+    );
+}
+
+#[test]
+fn constraint_range_reports_out_of_range() {
+    let input = "port = 70000\nname = \"a\"\ntags = [\"x\"]";
+    let error = constraint_error(input);
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::OutOfRange {
+            ty: &"u32",
+            range: &"1..=65535"
+        }
+    ));
+    assert_eq!(&input[error.span().range()], "70000");
+    assert_eq!(error.path().unwrap().to_string(), "port");
+}
+
+#[test]
+fn constraint_lengths() {
+    let error = constraint_error("port = 1\nname = \"\"\ntags = [\"x\"]");
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::OutOfRange {
+            ty: &"String",
+            range: &"non-empty"
+        }
+    ));
+
+    let input = "port = 1\nname = \"a\"\ntags = [\"a\", \"b\", \"c\", \"d\"]";
+    let error = constraint_error(input);
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::OutOfRange {
+            ty: &"Vec<String>",
+            range: &"length 1..=3"
+        }
+    ));
+    assert_eq!(&input[error.span().range()], "[\"a\", \"b\", \"c\", \"d\"]");
+
+    let error = constraint_error("port = 1\nname = \"a\"\ntags = []");
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::OutOfRange {
+            range: &"length 1..=3",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn constraint_one_of_reports_unexpected_value() {
+    let input = "port = 1\nname = \"a\"\ntags = [\"x\"]\nlevel = \"trace\"";
+    let error = constraint_error(input);
+    let toml_spanner::ErrorKind::UnexpectedValue { expected } = error.kind() else {
+        panic!("unexpected error kind: {error:?}");
+    };
+    assert_eq!(expected, &["debug", "info", "warn"]);
+    assert_eq!(&input[error.span().range()], "\"trace\"");
+
+    let error = constraint_error("port = 1\nname = \"a\"\ntags = [\"x\"]\nsign = 0");
+    let toml_spanner::ErrorKind::UnexpectedValue { expected } = error.kind() else {
+        panic!("unexpected error kind: {error:?}");
+    };
+    assert_eq!(expected, &["-1", "1"]);
+}
+
+#[test]
+fn constraints_recoverable() {
+    #[derive(Toml, Debug)]
+    #[allow(dead_code)]
+    #[toml(FromToml, recoverable)]
+    struct Limits {
+        #[toml(range = 1..=10)]
+        a: u8,
+        #[toml(max_len = 2)]
+        b: String,
+    }
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("a = 11\nb = \"abc\"", &arena).unwrap();
+    let err = doc.to::<Limits>().unwrap_err();
+    assert_eq!(err.errors.len(), 2, "{:?}", err.errors);
+}
+
+#[derive(Toml, Debug)]
+#[allow(dead_code)]
+#[toml(FromToml, exactly_one_of = [git, path, version])]
+struct Dependency {
+    #[toml(conflicts_with = path)]
+    git: Option<String>,
+    #[toml(requires = git)]
+    branch: Option<String>,
+    path: Option<String>,
+    version: Option<String>,
+    #[toml(conflicts_with = [git, path])]
+    registry: Option<String>,
+}
+
+fn relation_error(input: &str) -> toml_spanner::Error {
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let mut err = doc.to::<Dependency>().unwrap_err();
+    assert_eq!(err.errors.len(), 1, "{:?}", err.errors);
+    err.errors.remove(0)
+}
+
+#[test]
+fn field_relations_accept_valid_tables() {
+    let v: Dependency = toml_spanner::from_str("git = \"url\"\nbranch = \"main\"").unwrap();
+    assert_eq!(v.git.as_deref(), Some("url"));
+    assert_eq!(v.branch.as_deref(), Some("main"));
+    let v: Dependency = toml_spanner::from_str("version = \"1\"\nregistry = \"crates\"").unwrap();
+    assert_eq!(v.version.as_deref(), Some("1"));
+    assert_eq!(v.registry.as_deref(), Some("crates"));
+}
+
+#[test]
+fn conflicts_with_reports_later_key() {
+    let input = "path = \"a\"\ngit = \"b\"";
+    let error = relation_error(input);
+    let toml_spanner::ErrorKind::ConflictingFields {
+        field,
+        other,
+        first,
+    } = error.kind()
+    else {
+        panic!("unexpected error kind: {error:?}");
+    };
+    assert_eq!((field, other), ("git", "path"));
+    assert_eq!(&input[error.span().range()], "git");
+    assert_eq!(&input[first.range()], "path");
+    assert_eq!(
+        error.to_string(),
+        "key 'git' cannot be used together with 'path'"
+    );
+
+    let input = "version = \"1\"\nregistry = \"x\"\ngit = \"b\"";
+    let error = relation_error(input);
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::ConflictingFields {
+            field: "git",
+            other: "registry",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn requires_reports_at_requiring_key() {
+    let input = "version = \"1\"\nbranch = \"main\"";
+    let error = relation_error(input);
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::MissingRequiredBy {
+            field: "git",
+            by: "branch"
+        }
+    ));
+    assert_eq!(&input[error.span().range()], "branch");
+    assert_eq!(error.to_string(), "missing key 'git', required by 'branch'");
+}
+
+#[test]
+fn exactly_one_of_reports_missing_and_extra() {
+    let error = relation_error("branch = \"main\"\ngit = \"x\"\nregistry = \"r\"");
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::ConflictingFields { .. }
+    ));
+
+    let error = relation_error("registry = \"r\"");
+    let toml_spanner::ErrorKind::MissingOneOf { fields } = error.kind() else {
+        panic!("unexpected error kind: {error:?}");
+    };
+    assert_eq!(fields, &["git", "path", "version"]);
+
+    let input = "version = \"1\"\npath = \"p\"";
+    let error = relation_error(input);
+    assert!(matches!(
+        error.kind(),
+        toml_spanner::ErrorKind::ConflictingFields {
+            field: "path",
+            other: "version",
+            ..
+        }
+    ));
+    assert_eq!(&input[error.span().range()], "path");
+}
+
+#[test]
+fn field_relations_recoverable() {
+    #[derive(Toml, Debug)]
+    #[allow(dead_code)]
+    #[toml(FromToml, recoverable, exactly_one_of = [a, b])]
+    struct Source {
+        a: Option<u32>,
+        b: Option<u32>,
+        #[toml(rename = "c-flag", requires = a)]
+        c: bool,
+    }
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse("a = 1\nb = 2\nc-flag = true", &arena).unwrap();
+    let err = doc.to::<Source>().unwrap_err();
+    assert_eq!(err.errors.len(), 1, "{:?}", err.errors);
+
+    let mut doc = toml_spanner::parse("c-flag = true", &arena).unwrap();
+    let err = doc.to::<Source>().unwrap_err();
+    assert_eq!(err.errors.len(), 2, "{:?}", err.errors);
+    assert!(matches!(
+        err.errors[0].kind(),
+        toml_spanner::ErrorKind::MissingRequiredBy {
+            field: "a",
+            by: "c-flag"
+        }
+    ));
+}
+
+#[derive(Toml, Debug)]
+#[toml(Toml)]
+struct Located {
+    name: String,
+    #[toml(span)]
+    span: toml_spanner::Span,
+    #[toml(key_span)]
+    key_span: toml_spanner::Span,
+}
+
+#[derive(Toml, Debug)]
+#[toml(FromToml)]
+struct LocatedParent {
+    server: Located,
+    inline: Located,
+    many: Vec<Located>,
+    by_name: BTreeMap<String, Located>,
+    boxed: Option<Box<Located>>,
+}
+
+#[test]
+fn span_fields_capture_table_and_key() {
+    let input = "\
+inline = { name = 'i' }
+[server]
+name = 's'
+[[many]]
+name = 'a'
+[[many]]
+name = 'b'
+[by_name.first]
+name = 'f'
+[boxed]
+name = 'b'
+";
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let parent = doc.to::<LocatedParent>().unwrap();
+
+    assert_eq!(&input[parent.inline.key_span.range()], "inline");
+    assert_eq!(&input[parent.inline.span.range()], "{ name = 'i' }");
+    assert_eq!(&input[parent.server.key_span.range()], "server");
+    assert_eq!(parent.server.span, doc["server"].span());
+    assert_eq!(parent.many.len(), 2);
+    for located in &parent.many {
+        assert_eq!(&input[located.key_span.range()], "many");
+    }
+    assert_eq!(&input[parent.by_name["first"].key_span.range()], "first");
+    assert_eq!(&input[parent.boxed.unwrap().key_span.range()], "boxed");
+
+    let located: Located = toml_spanner::from_str("name = 'x'").unwrap();
+    assert!(located.key_span.is_empty());
+}
+
+#[test]
+fn span_fields_are_skipped_when_serializing() {
+    let located = Located {
+        name: "x".into(),
+        span: toml_spanner::Span::new(1, 2),
+        key_span: toml_spanner::Span::new(3, 4),
+    };
+    let out = toml_spanner::to_string(&located).unwrap();
+    assert_eq!(out.trim(), "name = \"x\"");
+}
+
+#[test]
+fn key_span_through_table_helper() {
+    struct Manual {
+        inner: Located,
+    }
+    impl<'de> FromToml<'de> for Manual {
+        fn from_toml(
+            ctx: &mut toml_spanner::Context<'de>,
+            item: &toml_spanner::Item<'de>,
+        ) -> Result<Self, toml_spanner::Failed> {
+            let mut th = item.table_helper(ctx)?;
+            let inner = th.required("inner")?;
+            th.require_empty()?;
+            Ok(Manual { inner })
+        }
+    }
+    let input = "[inner]\nname = 'n'";
+    let arena = Arena::new();
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let manual = doc.to::<Manual>().unwrap();
+    assert_eq!(&input[manual.inner.key_span.range()], "inner");
+}
+
+#[derive(Toml, Default)]
+#[toml(ToToml, Schema)]
+struct TemplateServer {
+    /// Address to bind.
+    host: String,
+    /// Port to listen on.
+    #[toml(default)]
+    port: u16,
+    /// Connection limits.
+    limits: Option<TemplateLimits>,
+}
+
+#[derive(Toml, Default)]
+#[toml(ToToml, Schema)]
+struct TemplateLimits {
+    /// Maximum open connections.
+    max: u32,
+}
+
+#[derive(Toml, Default)]
+#[toml(ToToml, Schema)]
+struct TemplateConfig {
+    /// Name of the service.
+    ///
+    /// Shown in logs.
+    name: String,
+    /// Optional motto.
+    motto: Option<String>,
+    /// The server.
+    server: TemplateServer,
+    /// Worker threads.
+    #[toml(default)]
+    workers: u32,
+}
+
+#[test]
+fn template_from_doc_comments() {
+    let mut config = TemplateConfig::default();
+    config.server.limits = Some(TemplateLimits { max: 64 });
+    let out = toml_spanner::to_template(&config).unwrap();
+    assert_eq!(
+        out,
+        "\
+# Name of the service.
+#
+# Shown in logs.
+name = \"\"
+# Optional motto.
+# motto =
+# Worker threads.
+# workers = 0
+
+# The server.
+[server]
+# Address to bind.
+host = \"\"
+# Port to listen on.
+# port = 0
+
+# Connection limits.
+# [server.limits]
+# Maximum open connections.
+# max = 64
+"
+    );
+}
+
+#[test]
+fn template_comments_out_arrays_of_tables() {
+    #[derive(Toml, Default)]
+    #[toml(ToToml, Schema)]
+    struct Mirror {
+        /// Mirror address.
+        url: String,
+    }
+
+    #[derive(Toml, Default)]
+    #[toml(ToToml, Schema)]
+    struct Sources {
+        /// Fallback mirrors.
+        mirrors: Option<Vec<Mirror>>,
+        /// Cache directory.
+        cache: Option<String>,
+    }
+
+    let sources = Sources {
+        mirrors: Some(vec![Mirror { url: "a".into() }, Mirror { url: "b".into() }]),
+        cache: None,
+    };
+    let out = toml_spanner::to_template(&sources).unwrap();
+    assert_eq!(
+        out,
+        "\
+# Cache directory.
+# cache =
+
+# Fallback mirrors.
+# [[mirrors]]
+# Mirror address.
+# url = \"a\"
+
+# [[mirrors]]
+# Mirror address.
+# url = \"b\"
+"
+    );
+}
+
+#[test]
+fn template_flatten_and_rename() {
+    #[derive(Toml, Default)]
+    #[toml(ToToml, Schema, rename_all = "kebab-case")]
+    struct Logging {
+        /// Minimum level.
+        log_level: String,
+    }
+
+    #[derive(Toml, Default)]
+    #[toml(ToToml, Schema, rename_all = "kebab-case")]
+    struct App {
+        /// Data directory.
+        data_dir: String,
+        #[toml(flatten, with = flatten_any)]
+        logging: Logging,
+        /// Undocumented types are written as-is.
+        extra: BTreeMap<String, u32>,
+    }
+
+    let mut app = App::default();
+    app.extra.insert("a".into(), 1);
+    let out = toml_spanner::to_template(&app).unwrap();
+    assert_eq!(
+        out,
+        "\
+# Data directory.
+data-dir = \"\"
+# Undocumented types are written as-is.
+extra = { a = 1 }
+# Minimum level.
+log-level = \"\"
+"
+    );
+}
+
+/// A deployment target.
+#[derive(Toml)]
+#[toml(FromToml, Schema, rename_all = "kebab-case", deny_unknown_fields)]
+#[allow(dead_code)]
+struct SchemaTarget {
+    /// Host name.
+    #[toml(alias = "hostname", FromToml deprecated_alias = "server")]
+    host_name: String,
+    #[toml(default = 22)]
+    port: u16,
+    user: Option<String>,
+    tags: Vec<String>,
+    mode: SchemaMode,
+    #[toml(skip)]
+    cached: bool,
+    #[toml(with = toml_spanner::helper::parse_string)]
+    addr: IpAddr,
+    #[toml(flatten)]
+    extra: BTreeMap<String, i64>,
+}
+
+#[derive(Toml)]
+#[toml(FromToml, Schema, tag = "kind")]
+#[allow(dead_code)]
+enum SchemaMode {
+    /// Copy files over.
+    Copy,
+    #[toml(rename = "sync")]
+    Sync { delete: bool },
+}
+
+#[test]
+fn schema_struct_fields() {
+    use toml_spanner::schema::{
+        EnumRepr, FieldDefault, Schema, TomlSchema, UnknownFields, VariantKind,
+    };
+
+    let Schema::Struct(target) = SchemaTarget::schema() else {
+        panic!("expected a struct schema");
+    };
+    assert_eq!(target.name, "SchemaTarget");
+    assert_eq!(target.doc, [" A deployment target."]);
+    assert_eq!(target.unknown_fields, UnknownFields::Deny);
+
+    let keys: Vec<&str> = target.fields.iter().map(|f| f.key).collect();
+    assert_eq!(
+        keys,
+        ["host-name", "port", "user", "tags", "mode", "addr", "extra"]
+    );
+
+    let host = &target.fields[0];
+    assert_eq!(host.aliases, ["hostname"]);
+    assert_eq!(host.deprecated_aliases, ["server"]);
+    assert_eq!(host.doc, [" Host name."]);
+    assert!(host.is_required());
+    assert!(matches!((host.schema)(), Schema::String));
+
+    let port = &target.fields[1];
+    assert_eq!(port.default, FieldDefault::Expr("22"));
+    assert!(matches!(
+        (port.schema)(),
+        Schema::Integer { min: 0, max: 65535 }
+    ));
+
+    assert_eq!(target.fields[2].default, FieldDefault::Default);
+    assert_eq!(target.fields[2].rust_type, "Option<String>");
+    assert!(matches!((target.fields[2].schema)(), Schema::String));
+
+    let Schema::Array(element) = (target.fields[3].schema)() else {
+        panic!("expected an array schema");
+    };
+    assert!(matches!(element(), Schema::String));
+
+    assert!(matches!((target.fields[5].schema)(), Schema::Any));
+    assert!(target.fields[6].flatten);
+    assert!(matches!((target.fields[6].schema)(), Schema::Map(_)));
+
+    let Schema::Enum(mode) = (target.fields[4].schema)() else {
+        panic!("expected an enum schema");
+    };
+    assert_eq!(mode.repr, EnumRepr::Internal { tag: "kind" });
+    assert_eq!(mode.variants[0].name, "Copy");
+    assert_eq!(mode.variants[0].doc, [" Copy files over."]);
+    assert!(matches!(mode.variants[0].kind, VariantKind::Unit));
+    assert_eq!(mode.variants[1].name, "sync");
+    let VariantKind::Struct(fields) = mode.variants[1].kind else {
+        panic!("expected a struct variant");
+    };
+    assert_eq!(fields[0].key, "delete");
+    assert!(matches!((fields[0].schema)(), Schema::Boolean));
+}
+
+#[test]
+fn schema_newtype_and_generic() {
+    use toml_spanner::schema::{Schema, TomlSchema};
+
+    #[derive(Toml)]
+    #[toml(FromToml, Schema)]
+    #[allow(dead_code)]
+    struct Meters(f64);
+
+    #[derive(Toml)]
+    #[toml(FromToml, Schema)]
+    #[allow(dead_code)]
+    struct Wrapper<T> {
+        inner: T,
+    }
+
+    assert!(matches!(Meters::schema(), Schema::Float));
+
+    let Schema::Struct(wrapper) = Wrapper::<Meters>::schema() else {
+        panic!("expected a struct schema");
+    };
+    assert_eq!(wrapper.fields[0].rust_type, "T");
+    assert!(matches!((wrapper.fields[0].schema)(), Schema::Float));
+}
+
+#[test]
+fn json_schema_struct() {
+    /// Service settings.
+    #[derive(Toml)]
+    #[toml(FromToml, Schema, rename_all = "kebab-case", deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Service<'a> {
+        /// Address to bind.
+        #[toml(default = "0.0.0.0")]
+        bind_addr: &'a str,
+        #[toml(default = 8080)]
+        port: u16,
+        #[toml(alias = "workers")]
+        thread_count: u32,
+        log: Option<SchemaMode>,
+    }
+
+    let schema = toml_spanner::schema::json_schema::<Service<'_>>();
+    assert_eq!(
+        schema,
+        r##"{
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "title": "Service",
+  "description": "Service settings.",
+  "type": "object",
+  "properties": {
+    "bind-addr": {
+      "description": "Address to bind.",
+      "default": "0.0.0.0",
+      "type": "string"
+    },
+    "port": {
+      "default": 8080,
+      "type": "integer",
+      "minimum": 0,
+      "maximum": 65535
+    },
+    "thread-count": {
+      "type": "integer",
+      "minimum": 0,
+      "maximum": 4294967295
+    },
+    "workers": {
+      "type": "integer",
+      "minimum": 0,
+      "maximum": 4294967295
+    },
+    "log": {
+      "$ref": "#/definitions/SchemaMode"
+    }
+  },
+  "allOf": [
+    {
+      "anyOf": [
+        {
+          "required": [
+            "thread-count"
+          ]
+        },
+        {
+          "required": [
+            "workers"
+          ]
+        }
+      ]
+    }
+  ],
+  "additionalProperties": false,
+  "definitions": {
+    "SchemaMode": {
+      "anyOf": [
+        {
+          "description": "Copy files over.",
+          "type": "object",
+          "properties": {
+            "kind": {
+              "const": "Copy"
+            }
+          },
+          "required": [
+            "kind"
+          ]
+        },
+        {
+          "type": "object",
+          "properties": {
+            "kind": {
+              "const": "sync"
+            },
+            "delete": {
+              "type": "boolean"
+            }
+          },
+          "required": [
+            "kind",
+            "delete"
+          ]
+        }
+      ]
+    }
+  }
+}
+"##
+    );
+}
+
+#[test]
+fn json_schema_enum_representations() {
+    #[derive(Toml)]
+    #[toml(FromToml, Schema)]
+    #[allow(dead_code)]
+    enum Level {
+        Low,
+        High,
+    }
+
+    #[derive(Toml)]
+    #[toml(FromToml, Schema, tag = "type", content = "value")]
+    #[allow(dead_code)]
+    enum Adjacent {
+        Off,
+        Level(Level),
+    }
+
+    #[derive(Toml)]
+    #[toml(FromToml, Schema, untagged)]
+    #[allow(dead_code)]
+    enum Untagged {
+        Count(i64),
+        Name(String),
+    }
+
+    #[derive(Toml)]
+    #[toml(FromToml, Schema)]
+    #[allow(dead_code)]
+    enum External {
+        /// Nothing at all.
+        Empty,
+        Tree {
+            children: Vec<External>,
+        },
+    }
+
+    let level = toml_spanner::schema::json_schema::<Level>();
+    assert!(level.contains("\"enum\": [\n    \"Low\",\n    \"High\"\n  ]"));
+
+    let adjacent = toml_spanner::schema::json_schema::<Adjacent>();
+    assert!(adjacent.contains("\"required\": [\n        \"type\",\n        \"value\"\n      ]"));
+    assert!(adjacent.contains("\"value\": {\n          \"$ref\": \"#/definitions/Level\""));
+
+    let untagged = toml_spanner::schema::json_schema::<Untagged>();
+    assert!(untagged.contains("\"anyOf\": [\n    {\n      \"type\": \"integer\"\n    },"));
+
+    // Recursion back to the root is a reference to the document itself.
+    let external = toml_spanner::schema::json_schema::<External>();
+    assert!(external.contains("\"description\": \"Nothing at all.\",\n      \"const\": \"Empty\""));
+    assert!(external.contains("\"items\": {\n                \"$ref\": \"#\""));
+    assert!(external.contains("\"additionalProperties\": false"));
+    assert!(!external.contains("definitions"));
+}
+
+#[test]
+fn json_schema_generic_instantiations() {
+    #[derive(Toml)]
+    #[toml(FromToml, Schema)]
+    #[allow(dead_code)]
+    struct Wrapper<T> {
+        value: T,
+    }
+
+    #[derive(Toml)]
+    #[toml(FromToml, Schema)]
+    #[allow(dead_code)]
+    struct Pair {
+        first: Wrapper<u8>,
+        second: Wrapper<String>,
+        third: Wrapper<u8>,
+    }
+
+    // Each instantiation gets its own definition, shared by every use.
+    let schema = toml_spanner::schema::json_schema::<Pair>();
+    assert!(schema.contains("\"first\": {\n      \"$ref\": \"#/definitions/Wrapper\""));
+    assert!(schema.contains("\"second\": {\n      \"$ref\": \"#/definitions/Wrapper2\""));
+    assert!(schema.contains("\"third\": {\n      \"$ref\": \"#/definitions/Wrapper\""));
+    assert!(!schema.contains("Wrapper3"));
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, migrations = [service_v1_to_v2, service_v2_to_v3])]
+struct Service {
+    #[toml(version)]
+    version: u32,
+    threads: u32,
+    server: ServiceServer,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+struct ServiceServer {
+    port: u16,
+}
+
+#[derive(Toml, Debug)]
+#[toml(FromToml)]
+struct ServiceHolder {
+    #[allow(dead_code)]
+    nested: Service,
+}
+
+// Version 2 renamed `workers` to `threads`.
+fn service_v1_to_v2<'de>(
+    m: &mut toml_spanner::Migrator<'_, 'de>,
+    table: &mut toml_spanner::Table<'de>,
+) -> Result<(), toml_spanner::Failed> {
+    m.rename_key(table, &"workers", &"threads");
+    Ok(())
+}
+
+// Version 3 moved `port` into a `[server]` table.
+fn service_v2_to_v3<'de>(
+    m: &mut toml_spanner::Migrator<'_, 'de>,
+    table: &mut toml_spanner::Table<'de>,
+) -> Result<(), toml_spanner::Failed> {
+    if let Some((key, port)) = table.remove_entry("port") {
+        let mut server = toml_spanner::Table::new();
+        server.set_style(toml_spanner::TableStyle::Header);
+        server.insert(key, port, m.ctx.arena);
+        table.insert(
+            toml_spanner::Key::new("server"),
+            server.into_item(),
+            m.ctx.arena,
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn versioned_struct_migrates_old_formats() {
+    use toml_spanner::{ErrorKind, Versioned};
+
+    assert_eq!(Service::current_version(), 3);
+    let expected = Service {
+        version: 3,
+        threads: 4,
+        server: ServiceServer { port: 80 },
+    };
+
+    let arena = Arena::new();
+    let source = "workers = 4\nport = 80\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let (service, warnings) = doc.to_allowing_errors::<Service>().unwrap();
+    assert_eq!(service, expected);
+    // The deprecation points at the key in the original document.
+    let [warning] = &warnings.errors[..] else {
+        panic!("expected one warning, got {:?}", warnings.errors)
+    };
+    assert!(matches!(
+        warning.kind(),
+        ErrorKind::Deprecated {
+            tag: 1,
+            old: &"workers",
+            new: &"threads"
+        }
+    ));
+    assert_eq!(&source[warning.span().range()], "workers");
+
+    let source = "version = 2\nthreads = 4\nport = 80\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    assert_eq!(doc.to::<Service>().unwrap(), expected);
+
+    let source = "version = 3\nthreads = 4\n[server]\nport = 80\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    assert_eq!(doc.to::<Service>().unwrap(), expected);
+
+    // Errors in upgraded tables still carry spans into the original source.
+    let source = "version = 1\nworkers = 4\nport = \"eighty\"\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let err = doc.to_allowing_errors::<Service>().err().unwrap();
+    let wanted = err
+        .errors
+        .iter()
+        .find(|e| matches!(e.kind(), ErrorKind::Wanted { .. }))
+        .unwrap();
+    assert_eq!(&source[wanted.span().range()], "\"eighty\"");
+    // Paths follow the upgraded layout.
+    assert_eq!(wanted.path().unwrap().to_string(), "server.port");
+    let [deprecated] = &err.errors[..1] else {
+        unreachable!()
+    };
+    assert_eq!(deprecated.path().unwrap().to_string(), "threads");
+
+    // A table too large to scan linearly is looked up without the parser's
+    // index once upgraded, where moved and added keys are found by name.
+    let source = "workers = 4\nport = 80\na = 1\nb = 2\nc = 3\nd = 4\ne = 5\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let err = doc.to::<Service>().unwrap_err();
+    let mut unexpected: Vec<String> = err
+        .errors
+        .iter()
+        .filter(|e| matches!(e.kind(), ErrorKind::UnexpectedKey { .. }))
+        .map(|e| e.path().unwrap().to_string())
+        .collect();
+    unexpected.sort();
+    assert_eq!(unexpected, ["a", "b", "c", "d", "e"]);
+
+    // Missing fields of the upgraded table itself are reported at its place.
+    let source = "[nested]\nworkers = 4\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let err = doc.to::<ServiceHolder>().unwrap_err();
+    assert_eq!(
+        err.errors[1].to_string(),
+        "missing required key 'server' at `nested`"
+    );
+
+    let source = "version = 4\nthreads = 4\n[server]\nport = 80\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let err = doc.to::<Service>().unwrap_err();
+    assert_eq!(
+        err.errors[0].to_string(),
+        "unsupported format version 4, the newest supported version is 3 at `version`"
+    );
+
+    let source = "version = \"3\"\nthreads = 4\n[server]\nport = 80\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let err = doc.to::<Service>().unwrap_err();
+    assert!(matches!(err.errors[0].kind(), ErrorKind::Wanted { .. }));
+}
+
+#[test]
+fn versioned_struct_upgrade_written_back() {
+    let arena = Arena::new();
+    let source = "\
+# Service settings.
+version = 1
+# Worker pool size.
+workers = 4 # tuned for CI
+port = 80
+";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let (ctx, table) = doc.split();
+    let migrated = ctx.migrate::<Service>(table).unwrap().unwrap();
+    assert_eq!(migrated.from_version, 1);
+    assert_eq!(migrated.renamed_keys(), &[(vec!["workers"], "threads")]);
+
+    let bytes = Formatting::preserved_from(&doc)
+        .with_migration(&migrated)
+        .format_table_to_bytes(migrated.table, &arena);
+    let output = String::from_utf8(bytes).unwrap();
+    assert_eq!(
+        output,
+        "\
+# Service settings.
+version = 3
+# Worker pool size.
+threads = 4 # tuned for CI
+
+[server]
+port = 80
+"
+    );
+    let service: Service = toml_spanner::from_str(&output).unwrap();
+    assert_eq!(service.threads, 4);
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, migrations = [fleet_v1_to_v2])]
+struct Fleet {
+    #[toml(version)]
+    version: u32,
+    hosts: Vec<FleetHost>,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+struct FleetHost {
+    name: String,
+    threads: u32,
+}
+
+// Version 2 renamed `workers` to `threads` in each host.
+fn fleet_v1_to_v2<'de>(
+    m: &mut toml_spanner::Migrator<'_, 'de>,
+    table: &mut toml_spanner::Table<'de>,
+) -> Result<(), toml_spanner::Failed> {
+    if let Some(hosts) = table.get_mut("hosts").and_then(|h| h.as_array_mut()) {
+        for host in hosts.as_mut_slice() {
+            if let Some(host) = host.as_table_mut() {
+                m.rename_key(host, &"workers", &"threads");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn versioned_rename_inside_array_of_tables() {
+    let arena = Arena::new();
+    let source = "\
+[[hosts]]
+name = \"a\"
+threads = 2 # kept
+
+[[hosts]]
+name = \"b\"
+# Renamed.
+workers = 4
+";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let (ctx, table) = doc.split();
+    let migrated = ctx.migrate::<Fleet>(table).unwrap().unwrap();
+    // The rename happened in one element only, which a path cannot name.
+    assert!(migrated.renamed_keys().is_empty());
+
+    let bytes = Formatting::preserved_from(&doc)
+        .with_migration(&migrated)
+        .format_table_to_bytes(migrated.table, &arena);
+    let output = String::from_utf8(bytes).unwrap();
+    assert_eq!(
+        output,
+        "\
+version = 2
+[[hosts]]
+name = \"a\"
+threads = 2 # kept
+
+[[hosts]]
+name = \"b\"
+threads = 4
+"
+    );
+    let fleet: Fleet = toml_spanner::from_str(&output).unwrap();
+    assert_eq!(
+        fleet.hosts,
+        [
+            FleetHost {
+                name: "a".into(),
+                threads: 2
+            },
+            FleetHost {
+                name: "b".into(),
+                threads: 4
+            },
+        ]
+    );
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, partial = AppLayer, deny_unknown_fields)]
+struct App {
+    name: String,
+    #[toml(default = 8080)]
+    port: u16,
+    log_file: Option<String>,
+    #[toml(partial)]
+    server: AppServer,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, partial = AppServerLayer)]
+struct AppServer {
+    host: String,
+    #[toml(default)]
+    workers: u32,
+}
+
+fn app_layer<'a>(arena: &'a Arena, source: &'a str, layer: u32) -> AppLayer {
+    use toml_spanner::Partial;
+    let mut doc = toml_spanner::parse(source, arena).unwrap();
+    doc.to::<AppLayer>().unwrap().with_layer(layer)
+}
+
+#[test]
+fn partial_layers_merge_and_resolve() {
+    use toml_spanner::Partial;
+
+    let arena = Arena::new();
+    let defaults = "name = \"app\"\n[server]\nhost = \"localhost\"\n";
+    let user = "port = 9000\n[server]\nhost = \"example.com\"\n";
+    let cli = "log_file = \"app.log\"\n";
+
+    let merged = app_layer(&arena, defaults, 0)
+        .merge(app_layer(&arena, user, 1))
+        .merge(app_layer(&arena, cli, 2));
+    // Each value keeps the layer and span it was read from.
+    let port = merged.port.unwrap();
+    assert_eq!((port.value, port.layer), (9000, 1));
+    assert_eq!(&user[port.span.range()], "9000");
+    let server = merged.server.as_ref().unwrap();
+    assert_eq!(server.layer, 1);
+    let host = server.value.host.as_ref().unwrap();
+    assert_eq!((host.value.as_str(), host.layer), ("example.com", 1));
+    assert_eq!(merged.name.as_ref().unwrap().layer, 0);
+
+    assert_eq!(
+        merged.resolve().unwrap(),
+        App {
+            name: "app".into(),
+            port: 9000,
+            log_file: Some("app.log".into()),
+            server: AppServer {
+                host: "example.com".into(),
+                workers: 0,
+            },
+        }
+    );
+
+    // Defaults fill in fields no layer set.
+    let app = app_layer(&arena, "name = \"app\"\n[server]\nhost = \"h\"\n", 0)
+        .resolve()
+        .unwrap();
+    assert_eq!((app.port, app.log_file), (8080, None));
+
+    // The partial type keeps the unknown field policy of the struct.
+    let mut doc = toml_spanner::parse("nmae = \"app\"\n", &arena).unwrap();
+    assert!(doc.to::<AppLayer>().is_err());
+}
+
+#[test]
+fn partial_resolve_reports_missing_fields() {
+    use toml_spanner::Partial;
+
+    let arena = Arena::new();
+    let system = "[server]\nworkers = 2\n";
+    let merged = app_layer(&arena, "port = 1\n", 0).merge(app_layer(&arena, system, 1));
+    let err = merged.resolve().unwrap_err();
+    let [name, host] = &err.missing[..] else {
+        panic!("expected two missing fields, got {:?}", err.missing)
+    };
+    assert_eq!(name.path().to_string(), "name");
+    assert_eq!(name.table.unwrap().layer, 1);
+    assert_eq!(host.path().to_string(), "server.host");
+    let table = host.table.unwrap();
+    assert_eq!(table.layer, 1);
+    assert_eq!(&system[table.span.range()], "[server]\nworkers = 2");
+    assert_eq!(
+        err.to_string(),
+        "missing field 'name' in layer 1\nmissing field 'server.host' in layer 1"
+    );
+
+    // A nested table no layer defined is reported against its parent.
+    let err = AppLayer::default().resolve().unwrap_err();
+    let paths: Vec<_> = err.missing.iter().map(|m| m.path().to_string()).collect();
+    assert_eq!(paths, ["name", "server.host"]);
+}
+
+#[test]
+fn partial_resolve_reports_missing_root_fields_in_top_layer() {
+    use toml_spanner::Partial;
+
+    let arena = Arena::new();
+    let merged = app_layer(&arena, "port = 1\n[server]\nhost = \"h\"\n", 0).merge(app_layer(
+        &arena,
+        "log_file = \"a.log\"\n",
+        2,
+    ));
+    let err = merged.resolve().unwrap_err();
+    let [name] = &err.missing[..] else {
+        panic!("expected one missing field, got {:?}", err.missing)
+    };
+    assert_eq!(name.path().to_string(), "name");
+    let table = name.table.unwrap();
+    assert_eq!(table.layer, 2);
+    assert!(table.span.is_empty());
+    assert_eq!(err.to_string(), "missing field 'name' in layer 2");
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, Update)]
+struct Daemon {
+    name: String,
+    #[toml(rename = "worker-threads", default)]
+    threads: u32,
+    #[toml(update)]
+    server: DaemonServer,
+    #[toml(update)]
+    log: Option<DaemonLog>,
+    #[toml(skip)]
+    reloads: u32,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, Update)]
+struct DaemonServer {
+    host: String,
+    port: u16,
+    #[toml(span)]
+    span: toml_spanner::Span,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, Update)]
+struct DaemonLog {
+    level: String,
+}
+
+#[test]
+fn update_reports_changed_fields() {
+    let arena = Arena::new();
+    let source = "name = \"d\"\n[server]\nhost = \"a\"\nport = 1\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let mut daemon = doc.to::<Daemon>().unwrap();
+    daemon.reloads = 3;
+
+    // Re-applying the same configuration changes nothing.
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let changes = doc.update(&mut daemon).unwrap();
+    assert!(changes.is_empty());
+
+    let source = "name = \"d\"\nworker-threads = 4\n[server]\nhost = \"a\"\nport = 2\n[log]\nlevel = \"info\"\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let changes = doc.update(&mut daemon).unwrap();
+    let paths: Vec<String> = changes.iter().map(|path| path.to_string()).collect();
+    assert_eq!(paths, ["worker-threads", "server.port", "log"]);
+    assert!(changes.affects(&["server"]));
+    assert!(changes.affects(&["log", "level"]));
+    assert!(!changes.affects(&["name"]));
+    assert!(!changes.affects(&["server", "host"]));
+
+    assert_eq!(daemon.threads, 4);
+    assert_eq!(daemon.server.port, 2);
+    assert_eq!(
+        daemon.log,
+        Some(DaemonLog {
+            level: "info".into()
+        })
+    );
+    // Skipped fields keep their runtime state and spans follow the new document.
+    assert_eq!(daemon.reloads, 3);
+    assert_eq!(
+        &source[daemon.server.span.range()],
+        "[server]\nhost = \"a\"\nport = 2"
+    );
+
+    let source = "name = \"d\"\nworker-threads = 4\n[server]\nhost = \"a\"\nport = 2\n[log]\nlevel = \"warn\"\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let changes = doc.update(&mut daemon).unwrap();
+    assert_eq!(format!("{changes:?}"), "[\"log.level\"]");
+}
+
+#[test]
+fn update_with_errors_leaves_value_unchanged() {
+    use toml_spanner::UpdateFromToml;
+
+    let arena = Arena::new();
+    let source = "name = \"d\"\n[server]\nhost = \"a\"\nport = 1\n";
+    let mut doc = toml_spanner::parse(source, &arena).unwrap();
+    let mut daemon = doc.to::<Daemon>().unwrap();
+
+    let mut doc = toml_spanner::parse("name = \"e\"\n[server]\nhost = \"a\"\n", &arena).unwrap();
+    let err = doc.update(&mut daemon).unwrap_err();
+    assert_eq!(err.errors[0].path().unwrap().to_string(), "server");
+    assert_eq!(daemon.name, "d");
+
+    // The trait method works on any item, such as a nested table.
+    let mut doc = toml_spanner::parse("[server]\nhost = \"b\"\nport = 1\n", &arena).unwrap();
+    let (ctx, table) = doc.split();
+    let changes = daemon
+        .server
+        .update_from_toml(ctx, table.get("server").unwrap())
+        .unwrap();
+    assert_eq!(format!("{changes:?}"), "[\"host\"]");
+    assert_eq!(daemon.server.host, "b");
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml, Schema, skip_if_default)]
+struct Elided {
+    name: String,
+    #[toml(default = 8080)]
+    port: u16,
+    #[toml(default)]
+    tags: Vec<String>,
+    #[toml(default = "info".to_string())]
+    level: String,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml)]
+enum ElidedShape {
+    Circle {
+        #[toml(default = 1.0, skip_if_default)]
+        radius: f64,
+        #[toml(default)]
+        filled: bool,
+    },
+}
+
+#[test]
+fn skip_if_default_elides_default_fields() {
+    let value = Elided {
+        name: "app".into(),
+        port: 8080,
+        tags: Vec::new(),
+        level: "info".into(),
+    };
+    let output = toml_spanner::to_string(&value).unwrap();
+    assert_eq!(output, "name = \"app\"\n");
+    assert_eq!(toml_spanner::from_str::<Elided>(&output).unwrap(), value);
+
+    let value = Elided {
+        port: 9000,
+        level: "debug".into(),
+        ..value
+    };
+    let output = toml_spanner::to_string(&value).unwrap();
+    assert_eq!(output, "name = \"app\"\nport = 9000\nlevel = \"debug\"\n");
+
+    // Only the marked field is elided in variants.
+    let shape = ElidedShape::Circle {
+        radius: 1.0,
+        filled: false,
+    };
+    let output = toml_spanner::to_string(&shape).unwrap();
+    assert_eq!(output, "Circle = { filled = false }\n");
+}
+
+#[test]
+fn skip_if_default_expanded_by_formatting() {
+    let value = Elided {
+        name: "app".into(),
+        port: 8080,
+        tags: Vec::new(),
+        level: "info".into(),
+    };
+    let output = Formatting::default()
+        .with_expanded_defaults()
+        .format(&value)
+        .unwrap();
+    assert_eq!(
+        output,
+        "name = \"app\"\nport = 8080\ntags = []\nlevel = \"info\"\n"
+    );
+    // The toggle only applies to that call.
+    let output = toml_spanner::to_string(&value).unwrap();
+    assert_eq!(output, "name = \"app\"\n");
+
+    // Templates list every default.
+    let output = toml_spanner::to_template(&value).unwrap();
+    assert!(output.contains("port = 8080"), "{output}");
+
+    // Nor does it carry over to values serialized afterwards into an arena.
+    let arena = Arena::new();
+    let table = toml_spanner::ToToml::to_toml(&value, &arena)
+        .unwrap()
+        .into_table()
+        .unwrap();
+    assert_eq!(table.len(), 1);
+}
+
+#[derive(Toml, Debug, PartialEq, Clone, Copy)]
+#[toml(FromToml, ToToml, Schema, repr = "int")]
+enum Level {
+    Debug = 1,
+    Info,
+    Warn,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, ToToml, repr = "str_or_int", rename_all = "lowercase")]
+enum Signal {
+    Hup = 1,
+    Int = 2,
+    Kill = 9,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, ToToml)]
+struct Levels {
+    level: Level,
+    signal: Signal,
+}
+
+#[test]
+fn repr_int_round_trips() {
+    let value: Levels = toml_spanner::from_str("level = 3\nsignal = \"kill\"").unwrap();
+    assert_eq!(
+        value,
+        Levels {
+            level: Level::Warn,
+            signal: Signal::Kill
+        }
+    );
+    let value: Levels = toml_spanner::from_str("level = 1\nsignal = 2").unwrap();
+    assert_eq!(value.signal, Signal::Int);
+
+    let output = toml_spanner::to_string(&value).unwrap();
+    assert_eq!(output, "level = 1\nsignal = \"int\"\n");
+    assert_eq!(toml_spanner::from_str::<Levels>(&output).unwrap(), value);
+
+    let schema = toml_spanner::schema::json_schema::<Level>();
+    assert!(schema.contains(r#""type": "integer""#), "{schema}");
+}
+
+#[test]
+fn repr_int_reports_unknown_values() {
+    let arena = Arena::new();
+    let input = "level = 4\nsignal = 3";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let err = doc.to::<Levels>().unwrap_err();
+    assert!(matches!(
+        err.errors[0].kind(),
+        toml_spanner::ErrorKind::OutOfRange {
+            ty: &"Level",
+            range: &"1..=3"
+        }
+    ));
+    assert_eq!(&input[err.errors[0].span().range()], "4");
+
+    let input = "level = 1\nsignal = 3";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let err = doc.to::<Levels>().unwrap_err();
+    assert!(matches!(
+        err.errors[0].kind(),
+        toml_spanner::ErrorKind::UnexpectedValue {
+            expected: &["1", "2", "9"]
+        }
+    ));
+    assert_eq!(&input[err.errors[0].span().range()], "3");
+
+    let mut doc = toml_spanner::parse("level = \"warn\"\nsignal = 1", &arena).unwrap();
+    let err = doc.to::<Levels>().unwrap_err();
+    assert!(matches!(
+        err.errors[0].kind(),
+        toml_spanner::ErrorKind::Wanted { .. }
+    ));
+
+    let mut doc = toml_spanner::parse("level = 1\nsignal = true", &arena).unwrap();
+    let err = doc.to::<Levels>().unwrap_err();
+    assert!(matches!(
+        err.errors[0].kind(),
+        toml_spanner::ErrorKind::Wanted {
+            expected: &"a string or integer",
+            ..
+        }
+    ));
+}
+
+#[derive(Toml, Debug)]
+#[toml(ToToml, Schema, doc_comments)]
+struct Commented {
+    /// Name of the service.
+    name: String,
+    /// Worker threads.
+    ///
+    /// Zero picks one per core.
+    workers: u32,
+    undocumented: bool,
+    /// Listener settings.
+    listen: CommentedListen,
+}
+
+#[derive(Toml, Debug)]
+#[toml(ToToml, Schema, doc_comments)]
+struct CommentedListen {
+    /// Port to listen on.
+    port: u16,
+}
+
+fn commented() -> Commented {
+    Commented {
+        name: "app".into(),
+        workers: 0,
+        undocumented: true,
+        listen: CommentedListen { port: 8080 },
+    }
+}
+
+#[test]
+fn doc_comments_on_fresh_output() {
+    let output = toml_spanner::to_string(&commented()).unwrap();
+    assert_eq!(
+        output,
+        "\
+# Name of the service.
+name = \"app\"
+# Worker threads.
+#
+# Zero picks one per core.
+workers = 0
+undocumented = true
+
+# Listener settings.
+[listen]
+# Port to listen on.
+port = 8080
+"
+    );
+}
+
+#[test]
+fn doc_comments_through_smart_pointers() {
+    let expected = toml_spanner::to_string(&commented()).unwrap();
+    let rc = std::rc::Rc::new(commented());
+    assert_eq!(toml_spanner::to_string(&rc).unwrap(), expected);
+    let arc = std::sync::Arc::new(commented());
+    assert_eq!(toml_spanner::to_string(&arc).unwrap(), expected);
+    let boxed = Box::new(commented());
+    assert_eq!(toml_spanner::to_string(&boxed).unwrap(), expected);
+}
+
+#[test]
+fn doc_comments_defer_to_preserved_source() {
+    let arena = Arena::new();
+    let source = "\
+# Edited by hand.
+name = \"old\"
+workers = 4
+
+[listen]
+port = 80
+";
+    let doc = toml_spanner::parse(source, &arena).unwrap();
+    let output = Formatting::preserved_from(&doc)
+        .format(&commented())
+        .unwrap();
+    assert_eq!(
+        output,
+        "\
+# Edited by hand.
+name = \"app\"
+workers = 0
+undocumented = true
+
+[listen]
+port = 8080
+"
+    );
+}
+
+#[test]
+fn doc_comments_on_array_of_tables_elements() {
+    #[derive(Toml)]
+    #[toml(ToToml, Schema, doc_comments)]
+    struct Host {
+        /// Host name.
+        name: String,
+        /// Worker threads.
+        threads: u32,
+    }
+
+    #[derive(Toml)]
+    #[toml(ToToml, Schema, doc_comments)]
+    struct Cluster {
+        /// Cluster hosts.
+        hosts: Vec<Host>,
+    }
+
+    let cluster = Cluster {
+        hosts: vec![
+            Host {
+                name: "a".into(),
+                threads: 2,
+            },
+            Host {
+                name: "b".into(),
+                threads: 4,
+            },
+        ],
+    };
+    let output = toml_spanner::to_string(&cluster).unwrap();
+    assert_eq!(
+        output,
+        "\
+# Cluster hosts.
+[[hosts]]
+# Host name.
+name = \"a\"
+# Worker threads.
+threads = 2
+
+[[hosts]]
+# Host name.
+name = \"b\"
+# Worker threads.
+threads = 4
+"
+    );
+
+    // Keys of the element at the same position in the reference keep its
+    // comments, the rest are documented.
+    let arena = Arena::new();
+    let source = "\
+[[hosts]]
+# Edited by hand.
+name = \"a\"
+";
+    let doc = toml_spanner::parse(source, &arena).unwrap();
+    let output = Formatting::preserved_from(&doc).format(&cluster).unwrap();
+    assert_eq!(
+        output,
+        "\
+[[hosts]]
+# Edited by hand.
+name = \"a\"
+# Worker threads.
+threads = 2
+
+[[hosts]]
+# Host name.
+name = \"b\"
+# Worker threads.
+threads = 4
+"
+    );
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml, rename_all = "lowercase")]
+enum Speed {
+    Slow,
+    #[toml(alias = "quick", deprecated_alias[TEST_TAG] = "fast")]
+    Turbo,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml, tag = "mode")]
+enum TaggedSpeed {
+    #[toml(deprecated_alias = "fast")]
+    Turbo {
+        level: u32,
+    },
+    Slow,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml, tag = "mode", content = "with")]
+enum AdjacentSpeed {
+    #[toml(alias = "quick", deprecated_alias = "fast")]
+    Turbo(u32),
+    Slow,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml)]
+struct Speeds {
+    speed: Speed,
+    tagged: TaggedSpeed,
+    adjacent: AdjacentSpeed,
+}
+
+#[test]
+fn variant_aliases() {
+    let value: Speeds = toml_spanner::from_str(
+        "speed = \"quick\"\ntagged = { mode = \"Slow\" }\nadjacent = { mode = \"quick\", with = 2 }",
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        Speeds {
+            speed: Speed::Turbo,
+            tagged: TaggedSpeed::Slow,
+            adjacent: AdjacentSpeed::Turbo(2),
+        }
+    );
+    let output = toml_spanner::to_string(&value).unwrap();
+    assert!(output.contains("speed = \"turbo\""), "{output}");
+    assert!(output.contains("mode = \"Turbo\""), "{output}");
+}
+
+#[test]
+fn variant_deprecated_aliases() {
+    let arena = Arena::new();
+    let input = "\
+speed = \"fast\"
+tagged = { mode = \"fast\", level = 3 }
+adjacent = { mode = \"fast\", with = 1 }";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let (value, errors) = doc.to_allowing_errors::<Speeds>().unwrap();
+    assert_eq!(
+        value,
+        Speeds {
+            speed: Speed::Turbo,
+            tagged: TaggedSpeed::Turbo { level: 3 },
+            adjacent: AdjacentSpeed::Turbo(1),
+        }
+    );
+    assert_eq!(errors.errors.len(), 3, "{:?}", errors.errors);
+    let mut tags = Vec::new();
+    for error in &errors.errors {
+        let toml_spanner::ErrorKind::DeprecatedVariant { tag, old, new } = error.kind() else {
+            panic!("expected DeprecatedVariant error, got {:?}", error.kind());
+        };
+        assert_eq!(*old, "fast");
+        assert!(*new == "turbo" || *new == "Turbo");
+        assert_eq!(&input[error.span().range()], "\"fast\"");
+        tags.push(tag);
+    }
+    assert_eq!(tags, [TEST_TAG, 0, 0]);
+    assert_eq!(
+        errors.errors[0].to_string(),
+        "variant 'fast' is deprecated, use 'turbo' instead at `speed`"
+    );
+    assert_eq!(
+        errors.errors[1].to_string(),
+        "variant 'fast' is deprecated, use 'Turbo' instead at `tagged.mode`"
+    );
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml)]
+struct LoggingSettings {
+    #[toml(default)]
+    log_level: String,
+    #[toml(default, alias = "log_path")]
+    log_file: Option<String>,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml)]
+struct TlsSettings {
+    cert: String,
+    #[toml(default)]
+    verify: bool,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml)]
+struct SharedSettings {
+    port: u16,
+    #[toml(flatten, with = flatten_any)]
+    logging: LoggingSettings,
+    #[toml(flatten, with = flatten_any)]
+    tls: TlsSettings,
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(Toml)]
+struct SharedSettingsWithExtras {
+    port: u16,
+    #[toml(flatten, with = flatten_any)]
+    logging: LoggingSettings,
+    #[toml(flatten)]
+    extras: BTreeMap<String, String>,
+    #[toml(flatten, with = flatten_any)]
+    tls: TlsSettings,
+}
+
+#[test]
+fn multiple_flatten_fields() {
+    let arena = Arena::new();
+    let input =
+        "port = 443\nlog_level = \"info\"\nlog_path = \"a.log\"\ncert = \"c.pem\"\nmode = \"x\"";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let (value, errors) = doc.to_allowing_errors::<SharedSettings>().unwrap();
+    let expected = SharedSettings {
+        port: 443,
+        logging: LoggingSettings {
+            log_level: "info".into(),
+            log_file: Some("a.log".into()),
+        },
+        tls: TlsSettings {
+            cert: "c.pem".into(),
+            verify: false,
+        },
+    };
+    assert_eq!(value, expected);
+    assert_eq!(errors.errors.len(), 1, "{:?}", errors.errors);
+    assert!(matches!(
+        errors.errors[0].kind(),
+        toml_spanner::ErrorKind::UnexpectedKey { .. }
+    ));
+    assert_eq!(&input[errors.errors[0].span().range()], "mode");
+
+    let output = toml_spanner::to_string(&expected).unwrap();
+    let mut doc = toml_spanner::parse(&output, &arena).unwrap();
+    assert_eq!(doc.to::<SharedSettings>().unwrap(), expected);
+}
+
+#[test]
+fn multiple_flatten_fields_suggest_flattened_keys() {
+    let arena = Arena::new();
+    let input = "port = 443\nlog_levle = \"info\"\ncert = \"c.pem\"\nverfy = true\nprot = 1";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let (_, warnings) = doc.to_allowing_errors::<SharedSettings>().unwrap();
+    let suggestions: Vec<_> = warnings
+        .errors
+        .iter()
+        .filter(|e| matches!(e.kind(), toml_spanner::ErrorKind::UnexpectedKey { .. }))
+        .map(|e| (&input[e.span().range()], e.suggestion()))
+        .collect();
+    assert_eq!(
+        suggestions,
+        [
+            ("log_levle", Some("log_level")),
+            ("verfy", Some("verify")),
+            ("prot", Some("port")),
+        ]
+    );
+}
+
+#[test]
+fn multiple_flatten_fields_with_catch_all() {
+    let arena = Arena::new();
+    let input = "port = 80\ncert = \"c.pem\"\nverify = true\nmode = \"x\"";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let value = doc.to::<SharedSettingsWithExtras>().unwrap();
+    assert_eq!(value.logging.log_level, "");
+    assert_eq!(
+        value.tls,
+        TlsSettings {
+            cert: "c.pem".into(),
+            verify: true,
+        }
+    );
+    assert_eq!(value.extras.len(), 1);
+    assert_eq!(value.extras["mode"], "x");
+}
+
+#[test]
+fn multiple_flatten_fields_through_wrappers() {
+    #[derive(Toml, Debug, PartialEq)]
+    #[toml(FromToml)]
+    struct Wrapped {
+        #[toml(flatten, with = flatten_any)]
+        logging: Box<LoggingSettings>,
+        #[toml(flatten, with = flatten_any)]
+        tls: Option<TlsSettings>,
+    }
+
+    let arena = Arena::new();
+    let input = "log_level = \"info\"\ncert = \"c.pem\"";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let value = doc.to::<Wrapped>().unwrap();
+    assert_eq!(value.logging.log_level, "info");
+    assert_eq!(value.tls.unwrap().cert, "c.pem");
+}
+
+#[derive(Toml, Debug, PartialEq)]
+#[toml(FromToml, untagged)]
+enum DependencySpec {
+    #[toml(expecting = "string (a version)")]
+    Version(String),
+    Source {
+        git: String,
+        path: Option<String>,
+    },
+}
+
+#[test]
+fn untagged_no_match_explains_each_variant() {
+    let arena = Arena::new();
+    let input = "dep = { git = 1 }";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let err = doc.to::<HashMap<String, DependencySpec>>().unwrap_err();
+    assert_eq!(err.errors.len(), 1, "{:?}", err.errors);
+    let error = &err.errors[0];
+    let toml_spanner::ErrorKind::NoMatchingVariant { expected } = error.kind() else {
+        panic!("expected NoMatchingVariant, got {:?}", error.kind());
+    };
+    assert_eq!(
+        expected,
+        ["string (a version)", "table with `git` or `path`"]
+    );
+    assert_eq!(
+        error.message(input),
+        "expected one of: string (a version), table with `git` or `path`"
+    );
+    assert_eq!(&input[error.span().range()], "{ git = 1 }");
+
+    let notes = error.notes();
+    assert_eq!(notes.len(), 2);
+    assert_eq!(&input[notes[0].0.range()], "{ git = 1 }");
+    assert!(
+        notes[0].1.starts_with("as string (a version): "),
+        "{}",
+        notes[0].1
+    );
+    assert_eq!(&input[notes[1].0.range()], "1");
+    assert!(
+        notes[1].1.starts_with("as table with `git` or `path`: ") && notes[1].1.contains("dep.git"),
+        "{}",
+        notes[1].1
+    );
+}
+
+#[test]
+fn untagged_final_if_reports_own_errors() {
+    #[derive(Toml, Debug, PartialEq)]
+    #[toml(FromToml, untagged)]
+    enum Limit {
+        #[toml(final_if = |_ctx, item| item.kind() == toml_spanner::Kind::Table)]
+        Range {
+            max: i64,
+        },
+        Fixed(i64),
+    }
+
+    let arena = Arena::new();
+    // The predicate holds for tables, so `Range` reports its own error.
+    let input = "val = { min = 1 }";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let err = doc.to::<HashMap<String, Limit>>().unwrap_err();
+    assert!(
+        matches!(
+            err.errors[1].kind(),
+            toml_spanner::ErrorKind::MissingField("max")
+        ),
+        "{:?}",
+        err.errors
+    );
+
+    // Otherwise `Range` is skipped and `Fixed`, the only variant left to
+    // attempt, reports its own error.
+    let input = "val = \"x\"";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let err = doc.to::<HashMap<String, Limit>>().unwrap_err();
+    assert_eq!(err.errors.len(), 1, "{:?}", err.errors);
+    assert!(
+        matches!(err.errors[0].kind(), toml_spanner::ErrorKind::Wanted { .. }),
+        "{:?}",
+        err.errors
+    );
+}
+
+#[test]
+fn untagged_no_match_keeps_every_error_of_each_variant() {
+    #[derive(Toml, Debug, PartialEq)]
+    #[toml(FromToml, untagged)]
+    enum Endpoint {
+        Port(u16),
+        Address {
+            #[toml(deprecated_alias = "hostname")]
+            host: String,
+            port: u16,
+        },
+    }
+
+    let arena = Arena::new();
+    let input = "val = { hostname = \"a\", port = \"x\" }";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let err = doc.to::<HashMap<String, Endpoint>>().unwrap_err();
+    assert_eq!(err.errors.len(), 1, "{:?}", err.errors);
+    let notes = err.errors[0].notes();
+    let spans: Vec<_> = notes.iter().map(|(span, _)| &input[span.range()]).collect();
+    assert_eq!(spans, [&input[6..], "hostname", "\"x\""]);
+    assert!(notes[0].1.starts_with("as `u16`: "), "{}", notes[0].1);
+    assert!(notes[1].1.starts_with("as table with `host` or `port`: "));
+    assert!(notes[2].1.starts_with("as table with `host` or `port`: "));
+
+    // A match after a failed attempt keeps only its own warnings.
+    let input = "val = { hostname = \"a\", port = 1 }";
+    let mut doc = toml_spanner::parse(input, &arena).unwrap();
+    let (value, errors) = doc
+        .to_allowing_errors::<HashMap<String, Endpoint>>()
+        .unwrap();
+    assert_eq!(errors.errors.len(), 1, "{:?}", errors.errors);
+    assert!(matches!(
+        errors.errors[0].kind(),
+        toml_spanner::ErrorKind::Deprecated { .. }
+    ));
+    assert_eq!(
+        value["val"],
+        Endpoint::Address {
+            host: "a".into(),
+            port: 1
+        }
+    );
+}